@@ -0,0 +1,228 @@
+//! Summaries of the changes between two versions of a generated file.
+//!
+//! Reviewing the diff of a regenerated file is tedious when a specification changes, so the
+//! items of both versions are compared to list the ones that were added, removed or changed.
+
+use std::collections::BTreeMap;
+use std::io;
+
+use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
+use quote::ToTokens;
+
+/// The items added, removed or changed between two versions of a generated file.
+///
+/// Items are named after their kind and path, such as `struct v0_7::Block` or
+/// `impl Serialize for Block`. The impl blocks of a type for the same trait, or without a trait,
+/// are compared as one item.
+#[derive(Debug, Clone, Default)]
+pub struct Changes {
+    /// The items that only exist in the new version.
+    pub added: Vec<String>,
+    /// The items that only exist in the old version.
+    pub removed: Vec<String>,
+    /// The items whose definition differs between the two versions.
+    pub changed: Vec<String>,
+}
+
+impl Changes {
+    /// Compares two versions of a generated file.
+    ///
+    /// Items are compared without their documentation, so only the changes to their signature
+    /// or contents are reported. An error is returned if a version isn't valid Rust.
+    pub fn between(old: &str, new: &str) -> syn::Result<Self> {
+        let old = items(old)?;
+        let new = items(new)?;
+
+        let mut changes = Self::default();
+        for (name, definition) in &new {
+            match old.get(name) {
+                None => changes.added.push(name.clone()),
+                Some(old) if old != definition => changes.changed.push(name.clone()),
+                Some(_) => (),
+            }
+        }
+        changes.removed = old
+            .into_keys()
+            .filter(|name| !new.contains_key(name))
+            .collect();
+        Ok(changes)
+    }
+
+    /// Returns whether no item changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Writes the changes as Markdown, in a section titled `title`.
+    pub fn write_markdown(&self, w: &mut dyn io::Write, title: &str) -> io::Result<()> {
+        writeln!(w, "## {title}")?;
+        writeln!(w)?;
+        for (heading, names) in [
+            ("Added", &self.added),
+            ("Removed", &self.removed),
+            ("Changed", &self.changed),
+        ] {
+            if names.is_empty() {
+                continue;
+            }
+            writeln!(w, "### {heading}")?;
+            writeln!(w)?;
+            for name in names {
+                writeln!(w, "- `{name}`")?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// Maps the name of every item of `code` to its definition, without documentation.
+fn items(code: &str) -> syn::Result<BTreeMap<String, String>> {
+    let file = syn::parse_file(code)?;
+    let mut output = BTreeMap::new();
+    collect(&file.items, "", &mut output);
+    // The blocks sharing a name are sorted, so that moving them around changes nothing.
+    Ok(output
+        .into_iter()
+        .map(|(name, mut definitions)| {
+            definitions.sort();
+            (name, definitions.join("\n"))
+        })
+        .collect())
+}
+
+/// Adds the definitions of the provided items, defined in the module at `prefix`, to `output`.
+fn collect(items: &[syn::Item], prefix: &str, output: &mut BTreeMap<String, Vec<String>>) {
+    for item in items {
+        let name = match item {
+            syn::Item::Struct(item) => format!("struct {prefix}{}", item.ident),
+            syn::Item::Enum(item) => format!("enum {prefix}{}", item.ident),
+            syn::Item::Union(item) => format!("union {prefix}{}", item.ident),
+            syn::Item::Type(item) => format!("type {prefix}{}", item.ident),
+            syn::Item::Const(item) => format!("const {prefix}{}", item.ident),
+            syn::Item::Static(item) => format!("static {prefix}{}", item.ident),
+            syn::Item::Fn(item) => format!("fn {prefix}{}", item.sig.ident),
+            syn::Item::Trait(item) => format!("trait {prefix}{}", item.ident),
+            syn::Item::Impl(item) => {
+                let ty = tokens_to_string(item.self_ty.to_token_stream());
+                match &item.trait_ {
+                    Some((_, path, _)) => {
+                        let path = tokens_to_string(path.to_token_stream());
+                        format!("impl {path} for {prefix}{ty}")
+                    }
+                    None => format!("impl {prefix}{ty}"),
+                }
+            }
+            syn::Item::Mod(item) => match &item.content {
+                Some((_, items)) => {
+                    collect(items, &format!("{prefix}{}::", item.ident), output);
+                    continue;
+                }
+                None => format!("mod {prefix}{}", item.ident),
+            },
+            _ => continue,
+        };
+
+        let definition = strip_docs(item.to_token_stream()).to_string();
+        output.entry(name).or_default().push(definition);
+    }
+}
+
+/// Removes the `#[doc]` attributes, which doc comments are turned into, from `tokens`.
+fn strip_docs(tokens: TokenStream) -> TokenStream {
+    let mut output = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                // Inner attributes have a `!` between the `#` and the brackets.
+                let mut lookahead = tokens.clone();
+                let bang =
+                    matches!(lookahead.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '!');
+                if bang {
+                    lookahead.next();
+                }
+                let is_doc = matches!(
+                    lookahead.next(),
+                    Some(TokenTree::Group(group)) if group.stream().into_iter().next().is_some_and(
+                        |first| matches!(first, TokenTree::Ident(ident) if ident == "doc")
+                    )
+                );
+                if is_doc {
+                    tokens = lookahead;
+                } else {
+                    output.push(TokenTree::Punct(punct));
+                }
+            }
+            TokenTree::Group(group) => {
+                let mut stripped = Group::new(group.delimiter(), strip_docs(group.stream()));
+                stripped.set_span(group.span());
+                output.push(TokenTree::Group(stripped));
+            }
+            token => output.push(token),
+        }
+    }
+    output.into_iter().collect()
+}
+
+/// Converts `tokens` to a string, without the spaces `TokenStream::to_string` puts around
+/// punctuation.
+fn tokens_to_string(tokens: TokenStream) -> String {
+    let mut output = String::new();
+    // Whether the last token was an identifier or a literal, which must be separated from
+    // the next one.
+    let mut word = false;
+    for token in tokens {
+        match token {
+            TokenTree::Punct(punct) => {
+                output.push(punct.as_char());
+                if punct.as_char() == ',' {
+                    output.push(' ');
+                }
+                word = false;
+            }
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::None => ("", ""),
+                };
+                output.push_str(open);
+                output.push_str(&tokens_to_string(group.stream()));
+                output.push_str(close);
+                word = false;
+            }
+            token => {
+                if word {
+                    output.push(' ');
+                }
+                output.push_str(&token.to_string());
+                word = true;
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impl_blocks_are_compared_as_one_item() {
+        let old = "struct A; impl A { fn a() {} } impl A { fn b() {} }";
+        let new = "struct A; impl A { fn b() {} } impl A { fn a() {} }";
+        assert!(Changes::between(old, new).unwrap().is_empty());
+
+        let new = "struct A; impl A { fn a() {} } impl A { fn c() {} }";
+        let changes = Changes::between(old, new).unwrap();
+        assert_eq!(changes.changed, ["impl A"]);
+        assert!(changes.added.is_empty() && changes.removed.is_empty());
+    }
+
+    #[test]
+    fn invalid_code_is_an_error() {
+        assert!(Changes::between("struct A;", "struct A").is_err());
+    }
+}
@@ -4,22 +4,297 @@ use std::path::PathBuf;
 
 /// A CLI tool to parse OpenRPC documents and generate Rust types from them.
 #[derive(Debug, Clone, clap::Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct Cli {
+    /// The command to run instead of generating the outputs.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// The arguments of the generator, present when no command is provided.
+    #[command(flatten)]
+    pub generate: Option<CommandLineArgs>,
+    #[command(flatten)]
+    pub reporting: Reporting,
+}
+
+/// The commands of `openrpc-gen`, besides generating the outputs.
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum Command {
+    /// Checks OpenRPC documents for likely mistakes without generating anything.
+    ///
+    /// Broken references, duplicate methods and names colliding once converted to Rust are
+    /// reported as errors. Unused schemas and methods without a result or a description are
+    /// reported as warnings, along with the warnings of the generator.
+    Lint(LintArgs),
+    /// Generates the outputs, then keeps running, regenerating them whenever the configuration
+    /// file or one of the input documents changes.
+    ///
+    /// This takes the same arguments as the generator, except for `--check`.
+    Watch(CommandLineArgs),
+}
+
+/// The arguments of the `lint` command.
+#[derive(Debug, Clone, clap::Args)]
+pub struct LintArgs {
+    /// The path to the configuration file to check the documents against.
+    ///
+    /// The default configuration is used when omitted.
+    #[clap(short, long)]
+    pub config: Option<PathBuf>,
+    /// The OpenRPC documents to check, as paths or HTTP(S) URLs.
+    #[clap(short, long = "input", alias = "document", required = true)]
+    pub inputs: Vec<PathBuf>,
+    /// Copied from [`Cli::reporting`] by [`from_env`].
+    #[clap(skip)]
+    pub reporting: Reporting,
+}
+
+/// The arguments controlling how diagnostics are reported, accepted by all the commands.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct Reporting {
+    /// Reports the warnings as errors, exiting with a non-zero status when any is emitted.
+    #[clap(long, global = true)]
+    pub deny_warnings: bool,
+    /// How to print the errors and warnings to the standard error stream.
+    #[clap(long, global = true, value_enum, default_value_t = MessageFormat::Human)]
+    pub message_format: MessageFormat,
+}
+
+/// The arguments of the generator.
+#[derive(Debug, Clone, clap::Args)]
 pub struct CommandLineArgs {
     /// The path to the configuration file to use.
     #[clap(short, long)]
     pub config: PathBuf,
-    /// The OpenRPC document to be parsed.
-    #[clap(short, long)]
-    pub document: PathBuf,
-    /// The path to the output file.
-    #[clap(short, long)]
-    pub output: PathBuf,
+    /// The OpenRPC documents to be parsed, as paths or HTTP(S) URLs.
+    ///
+    /// This flag may be repeated to generate multiple documents in one run. Each input is
+    /// paired with the `--output` at the same position.
+    #[clap(short, long = "input", alias = "document", required = true)]
+    pub inputs: Vec<PathBuf>,
+    /// The paths to the output files.
+    ///
+    /// This flag must be repeated as many times as `--input`, unless `--merge` or
+    /// `--versioned` is used. It can't be used with `--crate`.
+    #[clap(short, long = "output", required_unless_present = "krate")]
+    pub outputs: Vec<PathBuf>,
+    /// Checks that the output files are up to date instead of writing them.
+    ///
+    /// The differences are printed as a unified diff, and the program exits with a non-zero
+    /// status if any output is out of date.
+    #[clap(long)]
+    pub check: bool,
+    /// Writes a Markdown summary of the items added, removed or changed in the outputs by this
+    /// run to the provided path.
+    #[clap(long, value_name = "PATH", conflicts_with = "check")]
+    pub changes: Option<PathBuf>,
+    /// What to write to the output files.
+    #[clap(long, value_enum, default_value_t = Emit::Rust)]
+    pub emit: Emit,
+    /// Prints every change made by the fixes requested in the configuration file.
+    #[clap(long)]
+    pub trace_fixes: bool,
+    /// Copied from [`Cli::reporting`] by [`from_env`].
+    #[clap(skip)]
+    pub reporting: Reporting,
+    /// The path of a file to write the types shared between the input documents to.
+    ///
+    /// The types defined identically in several documents are written once to this file,
+    /// and the other outputs re-export them from the module named by the `common-module`
+    /// option of the configuration file.
+    #[clap(long)]
+    pub common: Option<PathBuf>,
+    /// Merges all the input documents into a single one, generated to the only `--output`.
+    ///
+    /// Components defined differently by several documents are renamed after the file they
+    /// come from.
+    #[clap(long, conflicts_with = "common")]
+    pub merge: bool,
+    /// Generates all the input documents, usually several versions of the same API, to the
+    /// only `--output`.
+    ///
+    /// Each document is generated to its own module, named after its `info.version`, such as
+    /// `v0_7` for version `0.7.1`.
+    #[clap(long, conflicts_with_all = ["common", "merge"])]
+    pub versioned: bool,
+    /// The path of a directory to generate a complete Cargo crate in, instead of writing each
+    /// document to an `--output`.
+    ///
+    /// Each input document becomes a module of the crate, named after its file. When several
+    /// documents are provided, the types they share are moved into a `common` module.
+    #[clap(
+        long = "crate",
+        value_name = "DIR",
+        conflicts_with_all = ["common", "merge", "versioned"]
+    )]
+    pub krate: Option<PathBuf>,
+    /// The path of a directory to generate a cargo-fuzz crate in, along with the outputs.
+    ///
+    /// The crate has one fuzz target per generated struct and enum, deserializing arbitrary
+    /// bytes into it. It is described by the `[fuzz]` section of the configuration file.
+    #[clap(long, value_name = "DIR", conflicts_with_all = ["merge", "versioned"])]
+    pub fuzz: Option<PathBuf>,
+    /// Compiles the outputs with `cargo check` once they are generated.
+    ///
+    /// The outputs are compiled as the modules of a scratch crate described by the `[verify]`
+    /// section of the configuration file, or as-is with `--crate`. The errors are reported
+    /// along with the schema they come from when `debug-path` is enabled.
+    #[clap(long)]
+    pub verify: bool,
 }
 
-/// Loads an instance of [`CommandLineArgs`] from the environment.
+/// The kinds of output `openrpc-gen` can produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Emit {
+    /// The generated Rust code.
+    Rust,
+    /// The intermediate representation of the file after fixes were applied, as JSON.
+    ///
+    /// This is useful to find the paths to use in the configuration file.
+    Ir,
+}
+
+/// The formats in which `openrpc-gen` can print its diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum MessageFormat {
+    /// Colored messages, meant to be read by humans.
+    #[default]
+    Human,
+    /// One JSON object per line, with the `severity`, `message`, and optional `path` and
+    /// `suggestion` of the diagnostic.
+    ///
+    /// This is meant to be read by editors and other tools.
+    Json,
+}
+
+impl CommandLineArgs {
+    /// Returns the paths of the files the outputs are generated from.
+    pub fn sources(&self) -> impl Iterator<Item = &PathBuf> {
+        std::iter::once(&self.config).chain(self.inputs.iter())
+    }
+
+    /// Returns the paths of the Rust files written by the generator.
+    ///
+    /// With `--crate`, those are the files found in the `src` directory of the crate when this
+    /// function is called.
+    pub fn written_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self.outputs.iter().chain(&self.common).cloned().collect();
+        if let Some(dir) = &self.krate {
+            let entries = std::fs::read_dir(dir.join("src")).into_iter().flatten();
+            files.extend(
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "rs")),
+            );
+        }
+        files
+    }
+
+    /// Returns an iterator over the `(input, output)` pairs provided on the command line.
+    pub fn documents(&self) -> impl Iterator<Item = (&PathBuf, &PathBuf)> {
+        self.inputs.iter().zip(self.outputs.iter())
+    }
+}
+
+/// Loads an instance of [`Cli`] from the environment.
 ///
 /// If an error occurs or if the user requests help, the program will exit, eventually leaking
 /// memory if some destructors are not run.
-pub fn from_env() -> CommandLineArgs {
-    clap::Parser::parse()
+pub fn from_env() -> Cli {
+    let mut cli: Cli = clap::Parser::parse();
+    if let Some(args) = &mut cli.generate {
+        validate(args);
+        args.reporting = cli.reporting.clone();
+    }
+    match &mut cli.command {
+        Some(Command::Lint(args)) => args.reporting = cli.reporting.clone(),
+        Some(Command::Watch(args)) => {
+            validate(args);
+            if args.check {
+                let mut cmd = <Cli as clap::CommandFactory>::command();
+                cmd.error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "`--check` can't be used with `watch`, which writes the outputs",
+                )
+                .exit();
+            }
+            args.reporting = cli.reporting.clone();
+        }
+        None => (),
+    }
+    cli
+}
+
+/// Checks the constraints between the arguments of the generator that clap can't express,
+/// exiting with an error if one is not met.
+fn validate(args: &CommandLineArgs) {
+    if args.krate.is_some() && !args.outputs.is_empty() {
+        let mut cmd = <Cli as clap::CommandFactory>::command();
+        cmd.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "`--output` can't be used with `--crate`, which names the outputs after the inputs",
+        )
+        .exit();
+    }
+    if (args.merge || args.versioned) && args.outputs.len() != 1 {
+        let flag = if args.merge { "--merge" } else { "--versioned" };
+        let mut cmd = <Cli as clap::CommandFactory>::command();
+        cmd.error(
+            clap::error::ErrorKind::WrongNumberOfValues,
+            format!(
+                "got {} `--output` but `{flag}` generates exactly one",
+                args.outputs.len(),
+            ),
+        )
+        .exit();
+    }
+    if !args.merge
+        && !args.versioned
+        && args.krate.is_none()
+        && args.inputs.len() != args.outputs.len()
+    {
+        let mut cmd = <Cli as clap::CommandFactory>::command();
+        cmd.error(
+            clap::error::ErrorKind::WrongNumberOfValues,
+            format!(
+                "got {} `--input` but {} `--output`; each input needs exactly one output",
+                args.inputs.len(),
+                args.outputs.len(),
+            ),
+        )
+        .exit();
+    }
+    if args.check && args.emit != Emit::Rust {
+        let mut cmd = <Cli as clap::CommandFactory>::command();
+        cmd.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "`--check` can only be used with `--emit rust`",
+        )
+        .exit();
+    }
+    if args.changes.is_some() && args.emit != Emit::Rust {
+        let mut cmd = <Cli as clap::CommandFactory>::command();
+        cmd.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "`--changes` can only be used with `--emit rust`",
+        )
+        .exit();
+    }
+    if args.verify && args.emit != Emit::Rust {
+        let mut cmd = <Cli as clap::CommandFactory>::command();
+        cmd.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "`--verify` can only be used with `--emit rust`",
+        )
+        .exit();
+    }
+    let multi = args.common.is_some() || args.merge || args.versioned || args.krate.is_some();
+    if multi && args.emit != Emit::Rust {
+        let mut cmd = <Cli as clap::CommandFactory>::command();
+        cmd.error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "`--common`, `--merge`, `--versioned` and `--crate` can only be used with `--emit rust`",
+        )
+        .exit();
+    }
 }
@@ -0,0 +1,135 @@
+//! Extraction of the types shared between several documents.
+//!
+//! When generating multiple documents at once, the types that are defined identically in
+//! several of them can be moved into a common file, which the other outputs import.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::deps::TypeDeps;
+use crate::fix::{self, Log};
+use crate::parse::{File, Info, TypeDef, TypeKind, TypeRef};
+
+/// Removes the types shared between the provided files, returning a file holding them.
+///
+/// A type is shared when it is defined identically in at least two of the files, and when all
+/// the types it depends on in the combined dependency graph are shared as well. References to
+/// the removed types are replaced by references to their names, which the generated files are
+/// expected to import from the common module.
+pub fn extract(files: &mut [File], config: &crate::config::Config) -> File {
+    // Maps the name of each type to its definitions, along with the files they come from.
+    let mut definitions: BTreeMap<&str, Vec<&TypeDef>> = BTreeMap::new();
+    for file in files.iter() {
+        for ty in file.types.values() {
+            definitions.entry(&ty.name).or_default().push(ty);
+        }
+    }
+
+    let mut shared: BTreeMap<String, TypeDef> = definitions
+        .iter()
+        .filter(|(_, defs)| defs.len() >= 2)
+        .filter(|(_, defs)| {
+            let first = serde_json::to_value(defs[0]).ok();
+            defs[1..]
+                .iter()
+                .all(|def| serde_json::to_value(def).ok() == first)
+        })
+        .map(|(name, defs)| (name.to_string(), defs[0].clone()))
+        .collect();
+
+    let mut deps = TypeDeps::default();
+    for file in files.iter() {
+        deps.merge(&TypeDeps::new(file, config));
+    }
+
+    // Shared types can't depend on types that are only defined in some of the files.
+    loop {
+        let removed: BTreeSet<String> = shared
+            .keys()
+            .filter(|name| {
+                deps.dependencies(name)
+                    .any(|dep| definitions.contains_key(dep) && !shared.contains_key(dep))
+            })
+            .cloned()
+            .collect();
+        if removed.is_empty() {
+            break;
+        }
+        shared.retain(|name, _| !removed.contains(name));
+    }
+
+    let mut scratch = Log::default();
+    for file in files.iter_mut() {
+        let paths: Vec<_> = file
+            .types
+            .values()
+            .filter(|ty| shared.contains_key(&ty.name))
+            .map(|ty| (ty.path.clone(), ty.name.clone()))
+            .collect();
+        for (path, name) in paths {
+            fix::replace_type(file, &path, &name, &mut scratch);
+        }
+    }
+
+    File {
+        info: Info::default(),
+        methods: Vec::new(),
+        types: shared
+            .into_values()
+            .map(|ty| (ty.path.clone(), ty))
+            .collect(),
+        errors: BTreeMap::new(),
+    }
+}
+
+/// Returns the names of the types of `shared` that `file` refers to.
+pub fn imports<'a>(file: &File, shared: &'a File) -> Vec<&'a str> {
+    fn visit<'b>(ty: &'b TypeRef, names: &mut BTreeSet<&'b str>) {
+        match ty {
+            TypeRef::ExternalRef(name) => {
+                names.insert(name);
+            }
+            TypeRef::Array(inner)
+            | TypeRef::Set(inner)
+            | TypeRef::Map(inner)
+            | TypeRef::Boxed(_, inner)
+            | TypeRef::Nullable(inner) => visit(inner, names),
+            TypeRef::Tuple(items) => {
+                for item in items {
+                    visit(item, names);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let mut names = BTreeSet::new();
+    for ty in file.types.values() {
+        match &ty.kind {
+            TypeKind::Struct(s) => s.fields.values().for_each(|f| visit(&f.ty, &mut names)),
+            TypeKind::Enum(e) => e
+                .variants
+                .values()
+                .filter_map(|v| v.ty.as_ref())
+                .for_each(|ty| visit(ty, &mut names)),
+            TypeKind::Alias(a) => visit(&a.ty, &mut names),
+        }
+    }
+    for method in &file.methods {
+        method.params.iter().for_each(|p| visit(&p.ty, &mut names));
+        if let Some(result) = &method.result {
+            visit(&result.ty, &mut names);
+        }
+    }
+    for error in file.errors.values() {
+        if let Some(data) = &error.data {
+            visit(data, &mut names);
+        }
+    }
+
+    shared
+        .types
+        .values()
+        .map(|ty| ty.name.as_str())
+        .filter(|name| names.contains(name))
+        .collect()
+}
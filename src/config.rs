@@ -1,9 +1,11 @@
 //! The configuration file for `openrpc-gen`.
 
 use std::collections::{BTreeMap, BTreeSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
 
 /// Contains information how primitives should be represented.
 #[derive(Debug, Clone, Deserialize)]
@@ -14,6 +16,33 @@ pub struct Primitives {
     /// **Default:** `i64`
     #[serde(default = "defaults::integer")]
     pub integer: String,
+    /// The name of the type that should be used to represent integers whose `minimum` is not
+    /// negative, when they have no `format`, such as `u64`.
+    ///
+    /// An integer whose `maximum` fits in a narrower type is represented by the narrowest of
+    /// `u8`, `u16` and `u32` holding it instead. When this is not set, these integers use
+    /// `integer` like the others.
+    ///
+    /// **Default:** none
+    #[serde(default)]
+    pub unsigned_integer: Option<String>,
+    /// The types that should be used to represent integers with a given `format`, keyed by the
+    /// name of the format, such as `{ int32 = "i32", uint64 = "u64" }`.
+    ///
+    /// Integers whose format is not listed here use `integer`, or `unsigned-integer`.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub integer_formats: BTreeMap<String, String>,
+    /// The name of the type that should be used to represent integers too large for `integer`,
+    /// such as `primitive_types::U256`.
+    ///
+    /// When set, strings whose `pattern` only accepts hexadecimal numbers of more than 16 digits
+    /// are generated with this type, (de)serialized with the `big-num-as-hex` formatter.
+    ///
+    /// **Default:** none
+    #[serde(default)]
+    pub big_integer: Option<String>,
     /// The name of the type that should be used to represent numbers.
     ///
     /// **Default:** `f64`
@@ -26,6 +55,24 @@ pub struct Primitives {
     /// **Default:** `Vec<{}>`
     #[serde(default = "defaults::array")]
     pub array: String,
+    /// The name of the type that should be used to represent arrays whose `uniqueItems` is
+    /// `true`, such as `std::collections::BTreeSet<{}>` or `std::collections::HashSet<{}>`.
+    ///
+    /// The string `{}` is replaced by the type of the set's items, which must implement `Ord`
+    /// or `Hash` as required by the set (see the `derive-eq` option of the `[generation]`
+    /// section). Arrays of numbers, maps and hexadecimal integers keep using `array`.
+    ///
+    /// **Default:** none, unique arrays use `array`
+    #[serde(default)]
+    pub set: Option<String>,
+    /// The name of the type that should be used to represent maps from strings to values,
+    /// described by the `additionalProperties` keyword.
+    ///
+    /// The string `{}` is replaced by the type of the map's values.
+    ///
+    /// **Default:** `std::collections::BTreeMap<String, {}>`
+    #[serde(default = "defaults::map")]
+    pub map: String,
     /// The name of the type that should be used to represent strings.
     ///
     /// **Default:** `String`
@@ -41,23 +88,46 @@ pub struct Primitives {
     /// **Default:** `bool`
     #[serde(default = "defaults::boolean")]
     pub boolean: String,
+    /// The name of the type that should be used to represent values of any type, described by
+    /// schemas without constraints such as `{}` or `true`.
+    ///
+    /// **Default:** `serde_json::Value`
+    #[serde(default = "defaults::any")]
+    pub any: String,
     /// The name of the type that should be used to represent optional values.
     ///
     /// The string `{}` is replaced by the type of the optional value.
     #[serde(default = "defaults::optional")]
     pub optional: String,
+    /// The types that should be used to represent strings with a given `format`, keyed by the
+    /// name of the format, such as `uuid` or `date-time`.
+    ///
+    /// The value is either the name of the Rust type, or a table with a `type` key and an
+    /// optional `with` key naming a module used to (de)serialize it, as in
+    /// `#[serde(with = "...")]`. Strings whose format is not listed here use `string`.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub formats: BTreeMap<String, FieldType>,
 }
 
 impl Default for Primitives {
     fn default() -> Self {
         Self {
             integer: defaults::integer(),
+            unsigned_integer: None,
+            integer_formats: BTreeMap::new(),
+            big_integer: None,
             number: defaults::number(),
             array: defaults::array(),
+            set: None,
+            map: defaults::map(),
             string: defaults::string(),
             null: defaults::null(),
             boolean: defaults::boolean(),
+            any: defaults::any(),
             optional: defaults::optional(),
+            formats: BTreeMap::new(),
         }
     }
 }
@@ -69,17 +139,223 @@ pub struct Formatters {
     /// The name of a module that should be used when formatting integers as hexadecimal strings.
     #[serde(default = "defaults::num_as_hex")]
     pub num_as_hex: String,
+    /// The name of a module that should be used when formatting big integers as hexadecimal
+    /// strings.
+    ///
+    /// **Default:** `big_num_as_hex`
+    #[serde(default = "defaults::big_num_as_hex")]
+    pub big_num_as_hex: String,
+    /// Per-path overrides of `num-as-hex` and `big-num-as-hex`, keyed by the path of the fields
+    /// or aliases holding integers formatted as hexadecimal strings.
+    ///
+    /// Paths may be glob patterns. When several patterns match an item, the last one in
+    /// lexicographic order wins. The override of a field takes precedence over the one of the
+    /// alias it refers to.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub num_as_hex_types: BTreeMap<String, String>,
+}
+
+impl Formatters {
+    /// Returns the module overriding the hexadecimal formatter of the item at `path`, if any.
+    pub fn num_as_hex_override(&self, path: &str) -> Option<&str> {
+        self.num_as_hex_types
+            .iter()
+            .rfind(|(pattern, _)| crate::glob::matches(pattern, path))
+            .map(|(_, module)| module.as_str())
+    }
 }
 
 impl Default for Formatters {
     fn default() -> Self {
         Self {
             num_as_hex: defaults::num_as_hex(),
+            big_num_as_hex: defaults::big_num_as_hex(),
+            num_as_hex_types: BTreeMap::new(),
+        }
+    }
+}
+
+/// Describes the crate generated with `--crate`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Crate {
+    /// The name of the crate.
+    ///
+    /// **Default:** the name of the directory the crate is generated in
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The version of the crate.
+    ///
+    /// **Default:** `0.1.0`
+    #[serde(default = "defaults::crate_version")]
+    pub version: String,
+    /// A short description of the crate.
+    ///
+    /// **Default:** none
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The SPDX license expression of the crate.
+    ///
+    /// **Default:** none
+    #[serde(default)]
+    pub license: Option<String>,
+    /// The version requirement used for the `jsonrpsee` dependency, which is only added when
+    /// `jsonrpsee` generation is enabled.
+    ///
+    /// **Default:** `0.24`
+    #[serde(default = "defaults::jsonrpsee_version")]
+    pub jsonrpsee_version: String,
+}
+
+impl Default for Crate {
+    fn default() -> Self {
+        Self {
+            name: None,
+            version: defaults::crate_version(),
+            description: None,
+            license: None,
+            jsonrpsee_version: defaults::jsonrpsee_version(),
+        }
+    }
+}
+
+/// Describes the cargo-fuzz crate generated with `--fuzz`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Fuzz {
+    /// The path of the crate holding the generated code, relative to the fuzz crate.
+    ///
+    /// The name of that crate is the `name` of the `[crate]` section when set, and is read
+    /// from its `Cargo.toml` file otherwise.
+    ///
+    /// **Default:** `..`
+    #[serde(default = "defaults::fuzz_crate_path")]
+    pub crate_path: String,
+    /// The path of the module holding the generated types, relative to the root of the crate.
+    ///
+    /// The string `{}` is replaced by the snake-cased name of the input document.
+    ///
+    /// **Default:** `{}` with `--crate`, which generates each document to its own module,
+    /// and the root of the crate otherwise
+    #[serde(default)]
+    pub module: Option<String>,
+}
+
+impl Default for Fuzz {
+    fn default() -> Self {
+        Self {
+            crate_path: defaults::fuzz_crate_path(),
+            module: None,
+        }
+    }
+}
+
+/// Describes the scratch crate in which the generated code is compiled with `--verify`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Verify {
+    /// The dependencies of the scratch crate, written as in a `Cargo.toml` file.
+    ///
+    /// Ignored with `--crate`, which checks the generated crate itself.
+    ///
+    /// **Default:** `serde` with the `derive` feature, and `serde_json`
+    #[serde(default = "defaults::verify_dependencies")]
+    pub dependencies: toml::Table,
+    /// Rust code written at the root of the scratch crate, before the generated modules.
+    ///
+    /// This is where the items the generated code expects to find, such as the formatters,
+    /// should be defined or imported. Ignored with `--crate`.
+    ///
+    /// **Default:** empty
+    #[serde(default)]
+    pub preamble: String,
+    /// Additional arguments passed to `cargo check`, such as `--offline`.
+    ///
+    /// **Default:** none
+    #[serde(default)]
+    pub cargo_args: Vec<String>,
+}
+
+impl Default for Verify {
+    fn default() -> Self {
+        Self {
+            dependencies: defaults::verify_dependencies(),
+            preamble: String::new(),
+            cargo_args: Vec::new(),
         }
     }
 }
 
+/// Configures the resolution of `$ref`s pointing outside of the document, and the download of
+/// documents given as URLs.
+///
+/// References to other files are resolved relative to the file holding them, and the items
+/// they point to are bundled into the `components` of the document.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Refs {
+    /// The prefixes of the URLs that references may be fetched from, such as
+    /// `https://raw.githubusercontent.com/starkware-libs/starknet-specs/`.
+    ///
+    /// Fetching a URL that isn't under one of these prefixes is an error, except for the URLs
+    /// next to an input document that was itself given as a URL. A URL is under a prefix when
+    /// it has the same scheme, host and port, and its path starts with the whole segments of
+    /// the path of the prefix once `.` and `..` are resolved, so `https://host/org` allows
+    /// `https://host/org/spec.json` but not `https://host/org-evil/spec.json`.
+
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub allowed_urls: Vec<String>,
+    /// A directory to cache downloaded documents in.
+    ///
+    /// Cached documents are revalidated using their `ETag`, and used as is when the download
+    /// fails, which allows working offline.
+    ///
+    /// **Default:** none, documents are downloaded on every run
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+/// Configures the dependency graph between the generated types.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Deps {
+    /// Additional `[from, to]` edges to add to the dependency graph, stating that the type
+    /// named `from` depends on the type named `to`.
+    ///
+    /// This is useful when a dependency cannot be seen in the OpenRPC document, for example
+    /// when a type is replaced with an external type that itself depends on other types.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub extra_edges: Vec<(String, String)>,
+    /// The names of the external types known to implement `Default`.
+    ///
+    /// Those are the starting point of the analysis finding which types can implement
+    /// `Default`. Primitive types and optional values are always assumed to.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub default_types: Vec<String>,
+    /// The names of the external types known to implement `PartialEq`, `Eq` and `Hash`.
+    ///
+    /// This is used by the `derive-eq` option of the `[generation]` section.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub eq_types: Vec<String>,
+}
+
 /// A list of fixes that should be applied to the parsed file.
+///
+/// Some fixes accept glob patterns instead of exact paths, in which case they apply to every
+/// symbol matching the pattern. In a pattern, `*` matches any sequence of characters within a
+/// path segment, `?` matches a single character, and a `**` segment matches any number of
+/// segments. For example, `#/components/schemas/*_TXN/version` matches the `version` field of
+/// every type whose name ends with `_TXN`.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct Fixes {
@@ -94,6 +370,8 @@ pub struct Fixes {
     ///
     /// When this refers to a type, all fields of that type will be flattened.
     ///
+    /// Paths may be glob patterns.
+    ///
     /// **Default:** `[]`
     #[serde(default)]
     pub flatten: Vec<String>,
@@ -112,6 +390,8 @@ pub struct Fixes {
     /// Be careful, removed symbols are not replaced in the generated code, meaning that
     /// this might create broken references.
     ///
+    /// Paths may be glob patterns.
+    ///
     /// **Default:** `[]`
     #[serde(default)]
     pub remove: Vec<String>,
@@ -119,17 +399,60 @@ pub struct Fixes {
     ///
     /// References to those symbols will be automatically updated.
     ///
+    /// This also overrides the names of the types defined inline, which are otherwise named
+    /// after the items enclosing them, such as `GetEventsResult` for the result of
+    /// `getEvents`, or `BlockHeader` for the `header` field of `BLOCK`.
+    ///
+    /// Paths may be glob patterns.
+    ///
     /// **Default:** `{}`
     #[serde(default)]
     pub rename: BTreeMap<String, String>,
+    /// Types defined inline to turn into named types, as if they were declared in the
+    /// `components` of the document.
+    ///
+    /// The key is the path of the type, such as `#/methods/getEvents/result/_anon`, and the
+    /// value is either its name, or a table with its `name` and its `doc`, which replaces the
+    /// description of the schema. The types defined inside it are renamed after it.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub name_anonymous: BTreeMap<String, AnonymousName>,
+    /// Regular expression substitutions applied to the names of all types, fields and variants.
+    ///
+    /// The key is the expression, using the syntax of the `regex` crate, and the value is its
+    /// replacement, in which `$1` or `${1}` refers to a captured group. Every match in a name is
+    /// replaced. Substitutions are applied in the lexicographic order of their expressions,
+    /// before the explicit renames of `rename`.
+    ///
+    /// Expressions are matched against the Rust names, not the names of the document: the
+    /// schema `INVOKE_TXN` is named `InvokeTxn`, so `Txn$ = "Transaction"` renames it to
+    /// `InvokeTransaction`.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub rename_regex: BTreeMap<String, String>,
     /// A list of types to replace with an external type.
     ///
     /// The symbol will be removed from the generated file, and references to it will be replaced
     /// with the provided type.
     ///
+    /// Paths may be glob patterns.
+    ///
     /// **Default:** `{}`
     #[serde(default)]
     pub replace: BTreeMap<String, String>,
+    /// A list of fields whose type should be overridden with an external type.
+    ///
+    /// The value is either the name of the Rust type, or a table with a `type` key and an
+    /// optional `with` key naming a module used to (de)serialize the field, as in
+    /// `#[serde(with = "...")]`. Optional fields are still wrapped in an `Option<T>`.
+    ///
+    /// Paths may be glob patterns.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub field_type: BTreeMap<String, FieldType>,
     /// Whether stray types should be removed from the generated file.
     ///
     /// Stray types are types that are not referenced by any other type AND that were not
@@ -138,6 +461,52 @@ pub struct Fixes {
     /// **Default:** `true`
     #[serde(default = "defaults::yes")]
     pub remove_stray_types: bool,
+    /// A list of fields, variants or aliases whose type should be boxed.
+    ///
+    /// Recursive types are detected automatically, and one of the fields of each recursion
+    /// chain is boxed with a warning. Listing a field here chooses which one is boxed instead.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub boxed: Vec<String>,
+    /// Fields, variants or aliases whose type should be stored behind a pointer, keyed by their
+    /// path.
+    ///
+    /// The pointer is either `Box`, `Arc` or `Rc`. Sharing large values makes them cheaper to
+    /// clone, and storing them on the heap shrinks the enums holding them. `Arc` and `Rc`
+    /// require the `rc` feature of serde. Paths may be glob patterns.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub pointers: BTreeMap<String, Pointer>,
+    /// A list of fields, variants or aliases whose integers should use `primitives.big-integer`.
+    ///
+    /// This is needed for the big integers that are not detected from their pattern. Paths may
+    /// be glob patterns.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub big_integers: Vec<String>,
+    /// The enums that should accept values that match none of their variants.
+    ///
+    /// This is either a boolean selecting all the enums, or a list of paths, which may be glob
+    /// patterns. An `Unknown` variant is added to the selected enums. It is marked with
+    /// `#[serde(other)]` when the enum only has unit variants, and holds the raw
+    /// `serde_json::Value` otherwise.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub non_exhaustive_enums: Selection,
+    /// The aliases whose chains of aliases should be collapsed.
+    ///
+    /// This is either a boolean selecting all the aliases, or a list of paths, which may be
+    /// glob patterns. The aliases referenced by a selected alias are inlined transitively, so
+    /// that `TXN_HASH -> FELT -> string` becomes `TXN_HASH -> string`. Inlining stops at types
+    /// that are not aliases, such as structs and enums.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub collapse_aliases: Selection,
     /// A list of enums that should be tagged.
     ///
     /// The key is the name of the enum, and the value is the name of the tag.
@@ -145,6 +514,33 @@ pub struct Fixes {
     /// **Default:** `{}`
     #[serde(default)]
     pub tagged_enums: BTreeMap<String, String>,
+    /// Automatically tag the enums whose schemas declare a `discriminator` with its
+    /// `propertyName`, unless they are listed in `tagged-enums`.
+    ///
+    /// The tag of a variant is the value the `mapping` of the discriminator associates with its
+    /// schema, or the value of its keyword field, or else the name of its schema.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub auto_tag_discriminators: bool,
+    /// A list of enums that should be tagged adjacently, holding the name of the variant in the
+    /// `tag` property and its content in the `content` property.
+    ///
+    /// The key is the path of the enum. Variants whose name in JSON is unknown are named after
+    /// their Rust names, which can be changed with `rename`.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub adjacently_tagged_enums: BTreeMap<String, AdjacentTag>,
+    /// A list of enums that should be tagged externally, wrapping the content of each variant
+    /// in an object whose only property is the name of the variant.
+    ///
+    /// Variants whose name in JSON is unknown are named after their Rust names, which can be
+    /// changed with `rename`.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub externally_tagged_enums: Vec<String>,
     /// Make a specific field a keyword with the specified value.
     ///
     /// This is useful if you have a field which is a String but the specification doesn't
@@ -161,6 +557,26 @@ pub struct Fixes {
     /// **Default:** `[]`
     #[serde(default)]
     pub preserve: BTreeSet<String>,
+    /// Splits the structs with `readOnly` or `writeOnly` fields into a request-side version,
+    /// without the read-only fields, and a response-side version, without the write-only
+    /// fields.
+    ///
+    /// The types that hold such structs are split as well. Method parameters use the
+    /// request-side versions, while method results and error data use the response-side ones.
+    ///
+    /// **Default:** none
+    #[serde(default)]
+    pub split_read_write: Option<SplitReadWrite>,
+    /// Whether symbols whose names collide once converted to Rust, such as the schemas
+    /// `BLOCK_ID` and `BlockId`, should be renamed with a numeric suffix.
+    ///
+    /// Collisions are checked among the types, the fields of a struct, the variants of an enum
+    /// and the errors. They are reported as errors when this is disabled. Methods whose
+    /// identifiers collide are always reported as errors, as their names can't be changed.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub disambiguate_names: bool,
 }
 
 impl Default for Fixes {
@@ -170,13 +586,248 @@ impl Default for Fixes {
             flatten: Vec::new(),
             remove: Vec::new(),
             rename: BTreeMap::new(),
+            name_anonymous: BTreeMap::new(),
+            rename_regex: BTreeMap::new(),
             replace: BTreeMap::new(),
+            field_type: BTreeMap::new(),
             remove_stray_types: true,
+            boxed: Vec::new(),
+            pointers: BTreeMap::new(),
+            big_integers: Vec::new(),
             auto_flatten_one_fields: true,
+            non_exhaustive_enums: Selection::default(),
+            collapse_aliases: Selection::default(),
             tagged_enums: BTreeMap::new(),
+            auto_tag_discriminators: false,
+            adjacently_tagged_enums: BTreeMap::new(),
+            externally_tagged_enums: Vec::new(),
             auto_flatten_one_ref: true,
             set_tags: BTreeMap::new(),
             preserve: BTreeSet::new(),
+            split_read_write: None,
+            disambiguate_names: false,
+        }
+    }
+}
+
+/// Names the two versions of the types split by `fixes.split-read-write`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SplitReadWrite {
+    /// The name of the request-side versions, without the read-only fields.
+    ///
+    /// The string `{}` is replaced by the name of the original type.
+    ///
+    /// **Default:** `{}Request`
+    #[serde(default = "defaults::split_request")]
+    pub request: String,
+    /// The name of the response-side versions, without the write-only fields.
+    ///
+    /// The string `{}` is replaced by the name of the original type.
+    ///
+    /// **Default:** `{}`
+    #[serde(default = "defaults::split_response")]
+    pub response: String,
+}
+
+/// A selection of items of the generated file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Selection {
+    /// Whether all the items are selected.
+    All(bool),
+    /// The paths of the selected items, which may be glob patterns.
+    Paths(Vec<String>),
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Selection::All(false)
+    }
+}
+
+/// The pointer a value is stored behind, as chosen by the `pointers` fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pointer {
+    /// `Box<T>`, owning the value.
+    Box,
+    /// `Arc<T>`, sharing the value between threads.
+    Arc,
+    /// `Rc<T>`, sharing the value within a thread.
+    Rc,
+}
+
+/// The type forced on a field by the `field-type` fix, or used for a string format.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum FieldType {
+    /// The name of the Rust type.
+    Name(String),
+    /// A Rust type, along with the module used to (de)serialize it.
+    Detailed {
+        /// The name of the Rust type.
+        #[serde(rename = "type")]
+        ty: String,
+        /// The module used to (de)serialize the field.
+        #[serde(default)]
+        with: Option<String>,
+    },
+}
+
+impl FieldType {
+    /// Returns the name of the Rust type.
+    pub fn ty(&self) -> &str {
+        match self {
+            FieldType::Name(ty) | FieldType::Detailed { ty, .. } => ty,
+        }
+    }
+
+    /// Returns the module used to (de)serialize the field, if any.
+    pub fn with(&self) -> Option<&str> {
+        match self {
+            FieldType::Name(_) => None,
+            FieldType::Detailed { with, .. } => with.as_deref(),
+        }
+    }
+}
+
+/// The properties of an enum tagged by the `adjacently-tagged-enums` fix.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AdjacentTag {
+    /// The property holding the name of the variant.
+    pub tag: String,
+    /// The property holding the content of the variant.
+    pub content: String,
+}
+
+/// The name given to a type defined inline by the `name-anonymous` fix.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum AnonymousName {
+    /// The name of the type.
+    Name(String),
+    /// The name of the type, along with its documentation.
+    Detailed {
+        /// The name of the type.
+        name: String,
+        /// The documentation of the type.
+        #[serde(default)]
+        doc: Option<String>,
+    },
+}
+
+impl AnonymousName {
+    /// Returns the name of the type.
+    pub fn name(&self) -> &str {
+        match self {
+            AnonymousName::Name(name) | AnonymousName::Detailed { name, .. } => name,
+        }
+    }
+
+    /// Returns the documentation of the type, if any.
+    pub fn doc(&self) -> Option<&str> {
+        match self {
+            AnonymousName::Name(_) => None,
+            AnonymousName::Detailed { doc, .. } => doc.as_deref(),
+        }
+    }
+}
+
+/// Describes how subscription methods are recognized in the OpenRPC document.
+///
+/// A subscription is made of a method used to subscribe, a method used to unsubscribe, and a
+/// notification method through which the server sends the subscribed items.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Subscriptions {
+    /// The name of the methods used to subscribe.
+    ///
+    /// The string `{}` is replaced by the name of the subscription.
+    pub subscribe: String,
+    /// The name of the methods used to unsubscribe.
+    ///
+    /// The string `{}` is replaced by the name of the subscription. When it is not present, the
+    /// same method is used to cancel every subscription.
+    pub unsubscribe: String,
+    /// The name of the notification methods sent by the server.
+    ///
+    /// The string `{}` is replaced by the name of the subscription.
+    pub notification: String,
+    /// The name of the notification parameter that carries the subscribed item.
+    ///
+    /// Notifications with a single parameter always use that parameter.
+    ///
+    /// **Default:** `result`
+    #[serde(default = "defaults::subscription_payload")]
+    pub payload: String,
+}
+
+/// Describes the directory of JSON samples that the generated types are tested against.
+///
+/// Every subdirectory is named after a generated type, and holds `.json` files that should
+/// deserialize into that type, such as `Block/mainnet_1000.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Fixtures {
+    /// The directory holding the samples, read when generating the code.
+    pub dir: PathBuf,
+    /// The path of the directory relative to the root of the crate the generated code is
+    /// compiled in, which the tests read the samples from.
+    ///
+    /// **Default:** the value of `dir`
+    #[serde(default)]
+    pub crate_path: Option<String>,
+}
+
+/// Describes which enums are generated from the errors declared in the OpenRPC document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorEnums {
+    /// No error enum is generated.
+    #[default]
+    None,
+    /// A single `ApiError` enum is generated, listing every error of the document.
+    Document,
+    /// An `<Method>Error` enum is generated for every method that declares errors, listing
+    /// only the errors that this specific method might return.
+    Method,
+}
+
+/// Serde container attributes written on the generated types.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SerdeContainer {
+    /// The value of `#[serde(rename_all = "...")]`.
+    ///
+    /// This also renames the fields and variants whose Rust name already matches their JSON
+    /// name, so it is mostly useful along with the `rename` fix.
+    ///
+    /// **Default:** `None`
+    #[serde(default)]
+    pub rename_all: Option<String>,
+    /// Whether to write `#[serde(deny_unknown_fields)]`.
+    ///
+    /// Serde does not support this on types with flattened fields, unless `manual-flatten`
+    /// is enabled.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub deny_unknown_fields: Option<bool>,
+    /// Whether to write `#[serde(default)]` on structs, which must then implement `Default`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub default: Option<bool>,
+}
+
+impl SerdeContainer {
+    /// Returns these options, overridden by the ones set in `other`.
+    pub fn merge(&self, other: &SerdeContainer) -> SerdeContainer {
+        SerdeContainer {
+            rename_all: other.rename_all.clone().or_else(|| self.rename_all.clone()),
+            deny_unknown_fields: other.deny_unknown_fields.or(self.deny_unknown_fields),
+            default: other.default.or(self.default),
         }
     }
 }
@@ -190,22 +841,251 @@ pub struct Generation {
     /// **Default:** `false`
     #[serde(default)]
     pub use_core: bool,
+    /// Whether the generated code should only depend on `core` and `alloc`.
+    ///
+    /// This implies `use-core`. `String`, `Vec`, `Box` and `vec!` are imported from `alloc`,
+    /// and `std::collections` in the `map` primitive is replaced by `alloc::collections`.
+    /// The crate including the generated file must declare `extern crate alloc`. This cannot
+    /// be combined with `jsonrpsee`, `validation`, `from-json` or pattern newtypes, which
+    /// require `std`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub no_std: bool,
+    /// The name of a cargo feature gating serde support in the generated code.
+    ///
+    /// When set, serde derives and attributes are wrapped in
+    /// `#[cfg_attr(feature = "...", ...)]`, and the items that cannot exist without serde
+    /// are put behind `#[cfg(feature = "...")]`. The generated types can then be used
+    /// without serde when the feature is disabled. This cannot be combined with the client,
+    /// server, `jsonrpsee`, envelope or batch generation, which are built on serde.
+    ///
+    /// Default values that cannot be written as Rust literals are still deserialized at
+    /// runtime, and require serde regardless of the feature.
+    ///
+    /// **Default:** none
+    #[serde(default)]
+    pub serde_feature: Option<String>,
     /// A collection of additional `use` statements.
     ///
     /// **Default:** `[]`
     #[serde(default)]
     pub additional_imports: Vec<String>,
+    /// The path of the module holding the types shared between documents, as seen from the
+    /// generated files.
+    ///
+    /// This is only used when generating several documents with a common file, in which case
+    /// each generated file re-exports the shared types it uses from this module.
+    ///
+    /// **Default:** `super::common`
+    #[serde(default = "defaults::common_module")]
+    pub common_module: String,
     /// An optional prefix to remove from method names before generating Rust identifiers
     /// from them.
     ///
     /// **Default:** `None`
     #[serde(default)]
     pub method_name_prefix: Option<String>,
+    /// The identifiers to use for the fields, variants, types, parameters and methods named
+    /// after a Rust keyword.
+    ///
+    /// The key is the keyword and the value is the identifier replacing it. Keywords missing
+    /// from this map are written as raw identifiers, such as `r#match`. The name used in JSON
+    /// is kept with `#[serde(rename)]`.
+    ///
+    /// **Default:** `{ type = "ty" }`
+    #[serde(default = "defaults::keyword_renames")]
+    pub keyword_renames: BTreeMap<String, String>,
+    /// The acronyms to keep as-is in the PascalCase names of types, variants and errors, such
+    /// as `L1`, `DA` or `ERC20`.
+    ///
+    /// Words matching one of them without regard to case are written as listed, so `DA_MODE`
+    /// becomes `DAMode` instead of `DaMode` when `DA` is listed.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub acronyms: Vec<String>,
     /// Whether to generate constants for method names.
     ///
     /// **Default:** `false`
     #[serde(default)]
     pub method_name_constants: bool,
+    /// Whether to document the generated file with the title, description and version of the
+    /// OpenRPC document, and to generate the `SPEC_TITLE` and `SPEC_VERSION` constants.
+    ///
+    /// The documentation is written as inner doc comments (`//!`), so the generated file must
+    /// be used as a module rather than included.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub spec_info: bool,
+    /// The version the OpenRPC document is expected to have.
+    ///
+    /// Generation fails when the `info.version` of the document is different, which catches
+    /// specifications updated without their configuration being reviewed.
+    ///
+    /// **Default:** `None`, any version is accepted
+    #[serde(default)]
+    pub expected_spec_version: Option<String>,
+    /// Whether methods and the types they use should be grouped into one module per tag.
+    ///
+    /// A method goes into the module of its first tag, along with the types only its tag
+    /// reaches. Types shared between tags stay at the top of the file. Every module is
+    /// re-exported with `pub use`, so the generated items keep their paths.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub tag_modules: bool,
+    /// Whether the module named by `formatters.num-as-hex` should be generated, instead of being
+    /// written by hand.
+    ///
+    /// The module (de)serializes the integer types as hexadecimal strings, as well as `Option`s
+    /// and `Vec`s of them, so arrays of such integers use it too. `formatters.num-as-hex` must
+    /// then be the name of a module rather than a path.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub hex_helpers: bool,
+    /// Whether a `Validate` trait should be generated and implemented by the structs, enums and
+    /// newtypes, checking the `minLength`, `maxLength`, `pattern`, `minItems`, `maxItems`,
+    /// `minimum` and `maximum` constraints of the document.
+    ///
+    /// The constraints of aliases are checked where they are used. Checking patterns requires
+    /// the `regex` crate, and the patterns it does not support, such as those with look-arounds
+    /// or backreferences, are skipped with a warning.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub validation: bool,
+    /// Whether object schemas without properties should be generated as unit structs, such as
+    /// `pub struct Empty;`, rather than as structs without fields. They are still (de)serialized
+    /// as `{}`.
+    ///
+    /// The methods without parameters then share a single `NoParams` unit struct instead of
+    /// having their own empty parameters struct. It accepts `{}`, `[]` and `null`, and is
+    /// serialized as `[]` when all those methods take their parameters by position, as `{}`
+    /// otherwise.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub unit_structs: bool,
+    /// Whether the enums tagged with a property, the untagged enums and the enums mixing
+    /// string variants with untagged ones should implement `Deserialize` by hand instead of
+    /// deriving it.
+    ///
+    /// The derived implementations buffer the value in serde's internal representation, and
+    /// untagged ones only report that the data did not match any variant. The generated ones
+    /// read the tag of tagged enums first and deserialize the variant it names from the other
+    /// entries, and read the value of untagged ones as a `serde_json::Value` to try the
+    /// variants in order, reporting the error of every variant they tried. Generic enums keep
+    /// the derived implementation.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub manual_deserialize: bool,
+    /// Whether the structs flattening other structs should implement `Serialize` and
+    /// `Deserialize` by hand, reading and writing the fields of the flattened structs as if
+    /// they were their own.
+    ///
+    /// serde buffers the fields of a struct with flattened fields in an intermediate map, and
+    /// does not support `deny_unknown_fields` on it. The generated implementations match the
+    /// keys of the object directly, and reject unknown ones when `deny-unknown-fields` is set.
+    /// Structs flattening anything else than structs, holding fields with a `with` module,
+    /// collecting extra fields, or using `rename-all` or `default` keep the derived
+    /// implementations, as do generic structs.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub manual_flatten: bool,
+    /// Whether a `FromJson` trait should be generated and implemented by every type that can be
+    /// deserialized, providing `from_json_str` and `from_value` functions.
+    ///
+    /// Their errors report the path of the value that failed to deserialize, such as
+    /// `transactions[3].type`, which serde_json omits. The generated code relies on the
+    /// `serde_path_to_error` crate and on `std`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub from_json: bool,
+    /// Whether strings should borrow from the deserialized input, as `Cow<'a, str>`, instead
+    /// of being represented with `primitives.string`.
+    ///
+    /// The types holding strings, directly or transitively, then take a lifetime parameter
+    /// `'a`, and the fields and variants mentioning it are marked `#[serde(borrow)]`. serde
+    /// only borrows the strings held directly by fields that contain no escape sequences;
+    /// the others are still allocated. Arrays remain `primitives.array`, as serde can't borrow
+    /// sequences of values other than bytes. This can't be combined with `arbitrary` or with
+    /// pattern newtypes, and the method-level items that don't support generic parameters
+    /// don't support it either.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub borrowed: bool,
+    /// Whether structs should get a `new` function taking their required fields, along with a
+    /// `with_*` method setting each of their optional fields.
+    ///
+    /// The fields that are not passed to `new` take their default value, or are left empty.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub constructors: bool,
+    /// The structs that should get a builder, returned by their `builder` function, which only
+    /// implements `build` once all of their required fields are set.
+    ///
+    /// Each required field is tracked by a type parameter of the builder, so forgetting one is
+    /// caught at compile time. The other fields keep their default value unless set. The
+    /// builders of generic structs take the same parameters, followed by those of the required
+    /// fields. Paths may be glob patterns.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub builders: Vec<String>,
+    /// Whether enums should get an `is_*` method per variant, along with `as_*` and `into_*`
+    /// methods returning the value held by each variant, if any.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub enum_accessors: bool,
+    /// Whether the values of the tags removed from structs by `tagged-enums` should be exposed,
+    /// as constants of those structs such as `pub const TYPE: &str = "INVOKE";`.
+    ///
+    /// The enums tagged with a property or adjacently also get a `tag` method returning the tag
+    /// of their variants. It returns an `Option`, which is `None` for fallback variants, when
+    /// the enum has any.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub tag_constants: bool,
+    /// Whether the enums of strings should implement `Display` and `FromStr`, along with an
+    /// `as_str` method returning their JSON value.
+    ///
+    /// Parsing a string matching none of the variants returns an `UnknownVariantError`, unless
+    /// the enum has a fallback variant.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub enum_strings: bool,
+    /// Whether the generated types, fields and variants should have a `#[doc(alias = "...")]`
+    /// attribute holding their name in the document, when it differs from their Rust name.
+    ///
+    /// Searching the generated documentation for `BLOCK_WITH_TX_HASHES` then finds
+    /// `BlockWithTxHashes`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub doc_aliases: bool,
+    /// Whether to generate a `SCHEMA_NAMES` constant mapping the names of the schemas of the
+    /// document to the names of the generated types.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub schema_names: bool,
+    /// Whether the documentation of the generated types should end with the path of the
+    /// schema they were generated from, such as `#/components/schemas/BLOCK_HEADER`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub doc_paths: bool,
     /// Whether to generate type aliases for method result types.
     ///
     /// **Default:** `false`
@@ -216,35 +1096,433 @@ pub struct Generation {
     /// **Default:** `false`
     #[serde(default)]
     pub param_types: bool,
+    /// Whether to generate a zero-sized marker type per method, implementing the generated
+    /// `RpcMethod` trait to link the name of the method to its parameter and result types.
+    ///
+    /// This implies `param-types`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub method_markers: bool,
+    /// Whether to generate `to_positional` and `to_named` methods on parameter structs,
+    /// converting them to a JSON array or object regardless of their `paramStructure`.
+    ///
+    /// This implies `param-types`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub param_helpers: bool,
+    /// Whether to generate a `#[cfg(test)]` module checking that the examples of the methods
+    /// deserialize into the generated types, and serialize back to an equivalent JSON.
+    ///
+    /// The parameters of an example are checked together against the parameter type of the
+    /// method when `param-types` is enabled, and one by one otherwise. Values whose encoding
+    /// depends on a formatter, such as hexadecimal integers, are not checked. The tests use
+    /// `serde_json`, which must be a dependency of the crate.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub example_tests: bool,
+    /// Whether to generate a `mocks` module listing the examples of the methods, with a
+    /// `respond` function answering JSON-RPC requests with the result of the matching example.
+    ///
+    /// The module uses `serde_json`, which must be a dependency of the crate.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub mocks: bool,
+    /// A directory of JSON samples to generate a `#[cfg(test)]` module from, with one test
+    /// deserializing each sample into the type named after its subdirectory.
+    ///
+    /// Samples that are complete JSON-RPC responses are checked against their `result`. The
+    /// tests use `serde_json`, which must be a dependency of the crate.
+    ///
+    /// **Default:** `None`
+    #[serde(default)]
+    pub fixtures: Option<Fixtures>,
+    /// Whether to implement `proptest`'s `Arbitrary` trait for the generated structs and enums,
+    /// so that property-based tests can produce values of any generated type.
+    ///
+    /// Keyword fields always hold their keyword, and the fallback variants of enums are only
+    /// produced when an enum has no other variant. External types must implement `Arbitrary`
+    /// themselves.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub arbitrary: bool,
+    /// The name of a Cargo feature gating the `Arbitrary` implementations.
+    ///
+    /// **Default:** `None`, the implementations are always compiled
+    #[serde(default)]
+    pub arbitrary_feature: Option<String>,
+    /// Whether to generate a `Client` trait with one async method per OpenRPC method, along
+    /// with a blanket implementation for any type implementing the generated `Transport` trait.
+    ///
+    /// This implies `param-types`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub client_trait: bool,
+    /// Describes how subscription methods are recognized.
+    ///
+    /// When set along with `client-trait`, a `Subscriber` trait is generated with one method
+    /// per subscription, returning a stream of the subscribed items.
+    ///
+    /// **Default:** `None`
+    #[serde(default)]
+    pub subscriptions: Option<Subscriptions>,
+    /// Whether to generate an `RpcServer` trait with one async method per OpenRPC method, along
+    /// with a `dispatch` function calling the right method for a raw JSON-RPC request.
+    ///
+    /// This implies `param-types`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub server_trait: bool,
+    /// Whether to generate an `Api` trait annotated with jsonrpsee's `#[rpc(server, client)]`
+    /// attribute, which jsonrpsee expands into `ApiServer` and `ApiClient` traits.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub jsonrpsee: bool,
+    /// Whether to generate the JSON-RPC 2.0 envelope types (`JsonRpcRequest<T>`,
+    /// `JsonRpcResponse<T>` and `JsonRpcError`), along with a constructor per method.
+    ///
+    /// The constructors are named after the methods, except for the methods named `new` and
+    /// `notification`, whose constructors are suffixed with `_method` so as not to collide
+    /// with the constructors of every request.
+    ///
+    /// This implies `param-types`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub envelopes: bool,
+    /// Whether to generate `BatchRequest` and `BatchResponse` types, used to send any of the
+    /// generated calls as a single JSON-RPC batch and retrieve their typed results by id.
+    ///
+    /// This implies `envelopes` and `method-markers`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub batch: bool,
+    /// Which enums should be generated from the errors declared in the OpenRPC document.
+    ///
+    /// **Default:** `none`
+    #[serde(default)]
+    pub error_enums: ErrorEnums,
     /// A list of types to derive globally.
     ///
     /// **Default:** `[Clone, Debug]`
     #[serde(default = "defaults::global_derives")]
     pub global_derives: Vec<String>,
+    /// Whether `Default` should be derived on the structs that can soundly implement it.
+    ///
+    /// A struct can derive `Default` when all of its required fields hold types implementing
+    /// `Default`. Enums never do, and external types are assumed not to, unless they are
+    /// listed in `[deps]` `default-types`. Structs whose fields declare default values get a
+    /// manual implementation instead.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub derive_default: bool,
+    /// Whether `PartialEq`, `Eq` and `Hash` should be derived on the types that can soundly
+    /// implement them.
+    ///
+    /// A type can derive them when it holds no floats, maps or sets, either directly or
+    /// transitively. External types are assumed not to implement them, unless they are
+    /// listed in `[deps]` `eq-types`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub derive_eq: bool,
+    /// Whether `Copy`, `PartialEq`, `Eq` and `Hash` should be derived on fieldless enums.
+    ///
+    /// This covers string enums as well as any other enum whose variants all carry no
+    /// value. Traits already listed in `global-derives` or `derives` are not repeated.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub copy_enums: bool,
     /// A list of types associated with traits to derive automatically on them.
     ///
     /// **Default:** `{}`
     #[serde(default)]
     pub derives: BTreeMap<String, Vec<String>>,
+    /// Attributes to write verbatim on the types, fields and variants at the given paths,
+    /// such as `#[serde(deny_unknown_fields)]` or `#[cfg(feature = "trace")]`.
+    ///
+    /// Paths may be glob patterns, in which case the attributes are written on every item
+    /// matching them.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub attributes: BTreeMap<String, Vec<String>>,
+    /// Whether optional fields should be omitted when serialized as `None`, rather than being
+    /// written as `null`.
+    ///
+    /// This adds `skip_serializing_if = "Option::is_none"` to the fields, and thus assumes that
+    /// `primitives.optional` is an `Option<T>`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub skip_serializing_none: bool,
+    /// Per-field overrides of `skip-serializing-none`, keyed by the path of the fields.
+    ///
+    /// Paths may be glob patterns. When several patterns match a field, the last one in
+    /// lexicographic order wins.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub skip_serializing_none_fields: BTreeMap<String, bool>,
+    /// A list of optional fields that should distinguish a missing value from an explicit
+    /// `null`.
+    ///
+    /// Those fields are generated as a `Maybe<T>`, which is either `Absent`, `Null` or holds a
+    /// `Value`. Required fields are not affected, and fields using a serde `with` module are
+    /// not supported.
+    ///
+    /// Paths may be glob patterns.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub tri_state_fields: Vec<String>,
+    /// Whether the generated structs and enums should be marked `#[non_exhaustive]`.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub non_exhaustive: bool,
+    /// Per-type overrides of `non-exhaustive`, keyed by the path of the types.
+    ///
+    /// Paths may be glob patterns. When several patterns match a type, the last one in
+    /// lexicographic order wins.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub non_exhaustive_types: BTreeMap<String, bool>,
+    /// Whether aliases should be generated as newtypes instead of type aliases.
+    ///
+    /// A newtype is a tuple struct wrapping the aliased type, such as
+    /// `pub struct TxnHash(pub Felt);`, and is (de)serialized transparently. Unlike a type
+    /// alias, it can't be mixed up with another type aliasing the same one.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub newtypes: bool,
+    /// Per-type overrides of `newtypes`, keyed by the path of the aliases.
+    ///
+    /// Paths may be glob patterns. When several patterns match a type, the last one in
+    /// lexicographic order wins.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub newtype_types: BTreeMap<String, bool>,
+    /// Whether aliases of strings constrained by a `pattern` should be generated as newtypes
+    /// validating the pattern.
+    ///
+    /// The value of such a newtype can only be created through its fallible `new` function, or
+    /// its `FromStr` and `TryFrom` implementations, which check the value against the pattern.
+    /// Deserialization goes through the same check. The generated code relies on the `regex`
+    /// crate and on `std`. Generation fails for the patterns the `regex` crate does not support,
+    /// such as those with look-arounds or backreferences.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub pattern_newtypes: bool,
+    /// Per-type overrides of `pattern-newtypes`, keyed by the path of the aliases.
+    ///
+    /// Paths may be glob patterns. When several patterns match a type, the last one in
+    /// lexicographic order wins.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub pattern_newtype_types: BTreeMap<String, bool>,
+    /// Whether the object schemas combined by `allOf` should be merged into a single struct,
+    /// combining their `properties` and `required` keywords, instead of a struct flattening a
+    /// field for each schema.
+    ///
+    /// Referenced schemas and nested `allOf` are merged as well. When a property is declared by
+    /// several schemas, the last declaration wins. An `allOf` that combines schemas other than
+    /// objects is always generated as a flatten struct.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub merge_all_of: bool,
+    /// Per-type overrides of `merge-all-of`, keyed by the path of the schemas using `allOf`.
+    ///
+    /// Paths may be glob patterns. When several patterns match a type, the last one in
+    /// lexicographic order wins.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub merge_all_of_types: BTreeMap<String, bool>,
+    /// Whether the schemas combined by `anyOf` should be generated as an untagged enum, like
+    /// the ones combined by `oneOf`, instead of a struct flattening an optional field for each
+    /// schema.
+    ///
+    /// A value matching several schemas is deserialized into the first matching variant.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub any_of_enums: bool,
+    /// Per-type overrides of `any-of-enums`, keyed by the path of the schemas using `anyOf`.
+    ///
+    /// Paths may be glob patterns. When several patterns match a type, the last one in
+    /// lexicographic order wins.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub any_of_enum_types: BTreeMap<String, bool>,
+    /// A list of structs that should keep the fields they don't know about, in a flattened
+    /// `extra` map of `serde_json::Value`s.
+    ///
+    /// The map is represented with `primitives.map`. Paths may be glob patterns.
+    ///
+    /// **Default:** `[]`
+    #[serde(default)]
+    pub extra_fields: Vec<String>,
+    /// The serde container attributes written on every generated struct and enum.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub serde: SerdeContainer,
+    /// Per-type overrides of the `serde` options, keyed by the path of the types.
+    ///
+    /// Only the options set in an override replace the global ones. Paths may be glob
+    /// patterns, in which case the overrides of the matching patterns are applied in
+    /// lexicographic order.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub type_serde: BTreeMap<String, SerdeContainer>,
+    /// Generic parameters to propagate onto the generated types.
+    ///
+    /// The key is the name of the parameter, and the value its bounds (e.g.
+    /// `Default + Serialize`), which may be empty. Every type that depends on a type named
+    /// after the parameter, either directly or transitively, is made generic over it. Types
+    /// usually depend on the parameter through the `replace` fix or through `[deps]`
+    /// `extra-edges`.
+    ///
+    /// Method parameter structs and result types are made generic as well. The other
+    /// method-level items (markers, traits, envelopes and error enums) are not supported for
+    /// methods that depend on a generic parameter.
+    ///
+    /// Note that serde requires the parameters of types with optional fields to implement
+    /// `Default`.
+    ///
+    /// **Default:** `{}`
+    #[serde(default)]
+    pub generics: BTreeMap<String, String>,
 }
 
 impl Default for Generation {
     fn default() -> Self {
         Self {
             use_core: false,
+            no_std: false,
+            serde_feature: None,
             additional_imports: Vec::new(),
+            common_module: defaults::common_module(),
             method_name_prefix: None,
+            keyword_renames: defaults::keyword_renames(),
+            acronyms: Vec::new(),
             method_name_constants: false,
+            spec_info: false,
+            expected_spec_version: None,
+            tag_modules: false,
+            hex_helpers: false,
+            validation: false,
+            unit_structs: false,
+            manual_deserialize: false,
+            manual_flatten: false,
+            from_json: false,
+            borrowed: false,
+            constructors: false,
+            builders: Vec::new(),
+            enum_accessors: false,
+            tag_constants: false,
+            enum_strings: false,
+            doc_aliases: false,
+            schema_names: false,
+            doc_paths: false,
             result_types: false,
             param_types: false,
+            param_helpers: false,
+            example_tests: false,
+            mocks: false,
+            fixtures: None,
+            arbitrary: false,
+            arbitrary_feature: None,
+            method_markers: false,
+            client_trait: false,
+            subscriptions: None,
+            server_trait: false,
+            jsonrpsee: false,
+            envelopes: false,
+            batch: false,
+            error_enums: ErrorEnums::None,
             global_derives: defaults::global_derives(),
+            derive_default: false,
+            derive_eq: false,
+            copy_enums: false,
             derives: BTreeMap::new(),
+            attributes: BTreeMap::new(),
+            skip_serializing_none: false,
+            skip_serializing_none_fields: BTreeMap::new(),
+            tri_state_fields: Vec::new(),
+            non_exhaustive: false,
+            non_exhaustive_types: BTreeMap::new(),
+            newtypes: false,
+            newtype_types: BTreeMap::new(),
+            pattern_newtypes: false,
+            pattern_newtype_types: BTreeMap::new(),
+            merge_all_of: false,
+            merge_all_of_types: BTreeMap::new(),
+            any_of_enums: false,
+            any_of_enum_types: BTreeMap::new(),
+            extra_fields: Vec::new(),
+            serde: SerdeContainer::default(),
+            type_serde: BTreeMap::new(),
+            generics: BTreeMap::new(),
         }
     }
 }
 
+impl Generation {
+    /// Returns whether the alias at `path` should be generated as a newtype.
+    pub fn is_newtype(&self, path: &str) -> bool {
+        self.newtype_types
+            .iter()
+            .rfind(|(pattern, _)| crate::glob::matches(pattern, path))
+            .map_or(self.newtypes, |(_, yes)| *yes)
+    }
+
+    /// Returns whether the alias at `path` should validate its pattern, if it has one.
+    pub fn is_pattern_newtype(&self, path: &str) -> bool {
+        self.pattern_newtype_types
+            .iter()
+            .rfind(|(pattern, _)| crate::glob::matches(pattern, path))
+            .map_or(self.pattern_newtypes, |(_, yes)| *yes)
+    }
+
+    /// Returns whether the `allOf` schema at `path` should be merged into a single struct.
+    pub fn is_merged_all_of(&self, path: &str) -> bool {
+        self.merge_all_of_types
+            .iter()
+            .rfind(|(pattern, _)| crate::glob::matches(pattern, path))
+            .map_or(self.merge_all_of, |(_, yes)| *yes)
+    }
+
+    /// Returns whether the `anyOf` schema at `path` should be generated as an enum.
+    pub fn is_any_of_enum(&self, path: &str) -> bool {
+        self.any_of_enum_types
+            .iter()
+            .rfind(|(pattern, _)| crate::glob::matches(pattern, path))
+            .map_or(self.any_of_enums, |(_, yes)| *yes)
+    }
+}
+
 /// The configuration file of `openrpc-gen`. Should be parsed from a TOML file.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct Config {
     /// The type to use for integers.
@@ -259,23 +1537,58 @@ pub struct Config {
     /// The formatters that should be used for types with special encoding.
     #[serde(default)]
     pub formatters: Formatters,
+    /// The configuration of the dependency graph between types.
+    #[serde(default)]
+    pub deps: Deps,
     /// Whether the path of symbols should be written as comments in the generated code.
     ///
     /// **Default:** `false`
     #[serde(default)]
     pub debug_path: bool,
-    /// Whether to automatically run `rustfmt` on the generated code.
+    /// Whether the schema features that can't be generated faithfully should be reported as
+    /// errors instead of being approximated.
+    ///
+    /// This covers unknown string formats, the `not`, `if`, `then` and `else` keywords,
+    /// `additionalProperties: false`, `const` values that are not strings, booleans or numbers,
+    /// the items following `prefixItems`, `uniqueItems` when `primitives.set` is not set,
+    /// properties declared differently by the schemas merged by `generation.merge-all-of`, and
+    /// references to schemas that don't exist once the fixes are applied.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub strict: bool,
+    /// Whether to format the generated code with `rustfmt` rather than with `prettyplease`.
+    ///
+    /// When `rustfmt` is not installed, the code is formatted with `prettyplease` and a warning
+    /// is emitted.
+    ///
+    /// **Default:** `false`
     #[serde(default)]
     pub run_rustfmt: bool,
+    /// The description of the crate generated with `--crate`.
+    #[serde(default, rename = "crate")]
+    pub krate: Crate,
+    /// The resolution of external references.
+    #[serde(default)]
+    pub refs: Refs,
+    /// The description of the fuzz crate generated with `--fuzz`.
+    #[serde(default)]
+    pub fuzz: Fuzz,
+    /// The scratch crate used by `--verify`.
+    #[serde(default)]
+    pub verify: Verify,
 }
 
 /// Loads the configuration file from the provided path.
-///
-/// Errors are simply returned as strings.
-pub fn load(path: &Path) -> Result<Config, String> {
-    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let config = toml::from_str(&contents).map_err(|e| e.to_string())?;
-    Ok(config)
+pub fn load(path: &Path) -> Result<Config, Error> {
+    let contents = std::fs::read_to_string(path).map_err(|error| Error::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    toml::from_str(&contents).map_err(|error| Error::Config {
+        path: path.to_path_buf(),
+        error,
+    })
 }
 
 /// Contains the default values for the configuration.
@@ -292,6 +1605,10 @@ mod defaults {
         "Vec<{}>".into()
     }
 
+    pub fn map() -> String {
+        "std::collections::BTreeMap<String, {}>".into()
+    }
+
     pub fn string() -> String {
         "String".into()
     }
@@ -304,6 +1621,18 @@ mod defaults {
         "bool".into()
     }
 
+    pub fn any() -> String {
+        "serde_json::Value".into()
+    }
+
+    pub fn split_request() -> String {
+        "{}Request".into()
+    }
+
+    pub fn split_response() -> String {
+        "{}".into()
+    }
+
     pub fn optional() -> String {
         "Option<{}>".into()
     }
@@ -316,6 +1645,41 @@ mod defaults {
         "num_as_hex".into()
     }
 
+    pub fn big_num_as_hex() -> String {
+        "big_num_as_hex".into()
+    }
+
+    pub fn crate_version() -> String {
+        "0.1.0".into()
+    }
+
+    pub fn fuzz_crate_path() -> String {
+        "..".into()
+    }
+
+    pub fn verify_dependencies() -> toml::Table {
+        toml::toml! {
+            serde = { version = "1", features = ["derive"] }
+            serde_json = "1"
+        }
+    }
+
+    pub fn jsonrpsee_version() -> String {
+        "0.24".into()
+    }
+
+    pub fn keyword_renames() -> std::collections::BTreeMap<String, String> {
+        std::collections::BTreeMap::from([("type".into(), "ty".into())])
+    }
+
+    pub fn common_module() -> String {
+        "super::common".into()
+    }
+
+    pub fn subscription_payload() -> String {
+        "result".into()
+    }
+
     pub fn global_derives() -> Vec<String> {
         vec![String::from("Clone"), String::from("Debug")]
     }
@@ -0,0 +1,458 @@
+//! The dependency graph between the types of a parsed file.
+//!
+//! Nodes of the graph are type names: the names of the types defined in the file, and the
+//! names of the external types they reference (for example, types introduced by the `replace`
+//! fix). An edge `A -> B` means that the definition of `A` mentions `B`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::config::Config;
+use crate::parse::{File, Path, TypeKind, TypeRef};
+
+/// The dependency graph between the types of a [`File`].
+#[derive(Debug, Clone, Default)]
+pub struct TypeDeps {
+    /// Maps the name of a type to the names of the types it directly depends on.
+    edges: BTreeMap<String, BTreeSet<String>>,
+    /// Maps the name of a type defined in the file to the types its `Default` implementation
+    /// would require, or `None` if the type can never implement `Default`.
+    default_requirements: BTreeMap<String, Option<BTreeSet<String>>>,
+    /// Maps the name of a type defined in the file to the types its `PartialEq`, `Eq` and
+    /// `Hash` implementations would require, or `None` if the type holds floats, maps, sets or
+    /// arbitrary values and can never implement them.
+    eq_requirements: BTreeMap<String, Option<BTreeSet<String>>>,
+    /// Maps the name of a type defined in the file to the types it holds by value, along with
+    /// the path of the field, variant or alias holding them.
+    ///
+    /// A cycle among those edges makes the generated types infinitely sized.
+    values: BTreeMap<String, Vec<(String, Path)>>,
+}
+
+/// An edge through which a type holds another by value.
+#[derive(Debug, Clone)]
+pub struct ValueEdge {
+    /// The name of the type holding the value.
+    pub from: String,
+    /// The name of the held type.
+    pub to: String,
+    /// The path of the field, variant or alias holding the value.
+    pub path: Path,
+}
+
+impl TypeDeps {
+    /// Builds the dependency graph of the provided file.
+    ///
+    /// The extra edges declared in the `[deps]` section of the configuration are added to the
+    /// edges found in the file.
+    pub fn new(file: &File, config: &Config) -> Self {
+        let mut deps = Self::default();
+
+        for ty in file.types.values() {
+            deps.edges.entry(ty.name.clone()).or_default();
+            let mut eq_requirements = Some(BTreeSet::new());
+            let requirements = match &ty.kind {
+                TypeKind::Struct(s) => {
+                    let mut requirements = BTreeSet::new();
+                    for field in s.fields.values() {
+                        add_eq_requirements(file, &field.ty, &mut eq_requirements);
+                        deps.add_type_ref(file, &ty.name, &field.ty);
+                        deps.add_value(file, &ty.name, &field.ty, &field.path);
+                        // Optional fields default to `None` regardless of their type, and
+                        // other fields may declare their own default value.
+                        if field.required && field.default.is_none() {
+                            requirements.extend(direct_names(file, &field.ty));
+                        }
+                    }
+                    Some(requirements)
+                }
+                TypeKind::Enum(e) => {
+                    for variant in e.variants.values() {
+                        if let Some(inner) = &variant.ty {
+                            add_eq_requirements(file, inner, &mut eq_requirements);
+                            deps.add_type_ref(file, &ty.name, inner);
+                            deps.add_value(file, &ty.name, inner, &variant.path);
+                        }
+                    }
+                    None
+                }
+                TypeKind::Alias(a) => {
+                    add_eq_requirements(file, &a.ty, &mut eq_requirements);
+                    deps.add_type_ref(file, &ty.name, &a.ty);
+                    deps.add_value(file, &ty.name, &a.ty, &ty.path);
+                    // An empty string may not match the pattern of a validated newtype.
+                    if a.constraints.pattern.is_some()
+                        && config.generation.is_pattern_newtype(&ty.path)
+                    {
+                        None
+                    } else {
+                        Some(direct_names(file, &a.ty).into_iter().collect())
+                    }
+                }
+            };
+            deps.default_requirements
+                .insert(ty.name.clone(), requirements);
+            deps.eq_requirements
+                .insert(ty.name.clone(), eq_requirements);
+        }
+
+        for (from, to) in &config.deps.extra_edges {
+            deps.add_edge(from, to);
+        }
+
+        deps
+    }
+
+    /// Adds the edges from `from` to the types mentioned by `ty`.
+    fn add_type_ref(&mut self, file: &File, from: &str, ty: &TypeRef) {
+        match ty {
+            TypeRef::Ref(path) => {
+                if let Some(target) = file.types.get(path) {
+                    self.add_edge(from, &target.name);
+                }
+            }
+            TypeRef::ExternalRef(name) => self.add_edge(from, name),
+            TypeRef::Array(inner)
+            | TypeRef::Set(inner)
+            | TypeRef::Map(inner)
+            | TypeRef::Boxed(_, inner)
+            | TypeRef::Nullable(inner) => self.add_type_ref(file, from, inner),
+            TypeRef::Tuple(items) => {
+                for item in items {
+                    self.add_type_ref(file, from, item);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Records that `from` holds the type referenced by `ty` by value, at `path`.
+    ///
+    /// Arrays and boxes store their content on the heap and don't count.
+    fn add_value(&mut self, file: &File, from: &str, ty: &TypeRef, path: &Path) {
+        let target = match ty {
+            TypeRef::Ref(target) => target,
+            TypeRef::Nullable(inner) => return self.add_value(file, from, inner, path),
+            TypeRef::Tuple(items) => {
+                for item in items {
+                    self.add_value(file, from, item, path);
+                }
+                return;
+            }
+            _ => return,
+        };
+        if let Some(target) = file.types.get(target) {
+            self.values
+                .entry(from.to_owned())
+                .or_default()
+                .push((target.name.clone(), path.clone()));
+        }
+    }
+
+    /// Adds an edge stating that `from` depends on `to`.
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        self.edges
+            .entry(from.to_owned())
+            .or_default()
+            .insert(to.to_owned());
+        self.edges.entry(to.to_owned()).or_default();
+    }
+
+    /// Adds the nodes and edges of `other` to this graph.
+    ///
+    /// Only the edges are merged: the analyses of the types defined in `other`, such as
+    /// [`TypeDeps::default_types`], are not carried over.
+    pub fn merge(&mut self, other: &TypeDeps) {
+        for (from, to) in &other.edges {
+            self.edges.entry(from.clone()).or_default();
+            for to in to {
+                self.add_edge(from, to);
+            }
+        }
+    }
+
+    /// Returns the names of all the nodes of the graph.
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.edges.keys().map(String::as_str)
+    }
+
+    /// Returns the names of the types `name` directly depends on.
+    pub fn dependencies<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a str> {
+        self.edges
+            .get(name)
+            .into_iter()
+            .flat_map(|deps| deps.iter().map(String::as_str))
+    }
+
+    /// Returns whether `from` depends on `to`, either directly or transitively.
+    pub fn depends_on(&self, from: &str, to: &str) -> bool {
+        let mut seen = BTreeSet::new();
+        let mut stack = vec![from];
+        while let Some(node) = stack.pop() {
+            for dep in self.dependencies(node) {
+                if dep == to {
+                    return true;
+                }
+                if seen.insert(dep) {
+                    stack.push(dep);
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns the names of the types that depend on `name`, either directly or transitively.
+    pub fn dependents(&self, name: &str) -> BTreeSet<&str> {
+        self.nodes()
+            .filter(|node| self.depends_on(node, name))
+            .collect()
+    }
+
+    /// Returns the names of the types reachable from `roots`, including `roots` themselves.
+    pub fn reachable<'a>(&'a self, roots: impl IntoIterator<Item = &'a str>) -> BTreeSet<&'a str> {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<&str> = roots.into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if seen.insert(node) {
+                stack.extend(self.dependencies(node));
+            }
+        }
+        seen
+    }
+
+    /// Maps the name of the types defined in `file` to the tag they belong to.
+    ///
+    /// A method belongs to its first tag. A type belongs to a tag when it is only reachable
+    /// from the methods of that tag. Types that are shared between several tags, reachable
+    /// from an untagged method or not reachable from any method belong to no tag, and are
+    /// missing from the returned map.
+    pub fn tag_owners<'a>(&'a self, file: &'a File) -> BTreeMap<&'a str, &'a str> {
+        let mut owners: BTreeMap<&str, Option<&str>> = BTreeMap::new();
+        for method in &file.methods {
+            let tag = method.tags.first().map(|tag| tag.name.as_str());
+            let mut roots = BTreeSet::new();
+            for param in &method.params {
+                type_ref_names(file, &param.ty, &mut roots);
+            }
+            if let Some(result) = &method.result {
+                type_ref_names(file, &result.ty, &mut roots);
+            }
+            for error in method
+                .errors
+                .iter()
+                .filter_map(|path| file.errors.get(path))
+            {
+                if let Some(data) = &error.data {
+                    type_ref_names(file, data, &mut roots);
+                }
+            }
+            for name in self.reachable(roots) {
+                owners
+                    .entry(name)
+                    .and_modify(|owner| {
+                        if *owner != tag {
+                            *owner = None;
+                        }
+                    })
+                    .or_insert(tag);
+            }
+        }
+
+        // The dependencies of the types no method reaches must stay visible to them.
+        let orphans = file
+            .types
+            .values()
+            .map(|ty| ty.name.as_str())
+            .filter(|name| !owners.contains_key(name));
+        for name in self.reachable(orphans) {
+            owners.insert(name, None);
+        }
+
+        owners
+            .into_iter()
+            .filter_map(|(name, owner)| Some((name, owner?)))
+            .collect()
+    }
+
+    /// Finds a cycle among the types holding each other by value, if any.
+    ///
+    /// The edges of the cycle are returned in order, the last one leading back to the type
+    /// the first one starts from.
+    pub fn find_value_cycle(&self) -> Option<Vec<ValueEdge>> {
+        let mut done = BTreeSet::new();
+        for start in self.values.keys() {
+            let mut stack = Vec::new();
+            if let Some(cycle) = self.visit_values(start, &mut stack, &mut done) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    /// Visits the types held by value by `node` in depth-first order, `stack` being the edges
+    /// leading to `node`.
+    fn visit_values<'a>(
+        &'a self,
+        node: &'a str,
+        stack: &mut Vec<(&'a str, &'a str, &'a Path)>,
+        done: &mut BTreeSet<&'a str>,
+    ) -> Option<Vec<ValueEdge>> {
+        if done.contains(node) {
+            return None;
+        }
+        if let Some(start) = stack.iter().position(|(from, _, _)| *from == node) {
+            let cycle = stack[start..]
+                .iter()
+                .map(|(from, to, path)| ValueEdge {
+                    from: (*from).to_owned(),
+                    to: (*to).to_owned(),
+                    path: (*path).clone(),
+                })
+                .collect();
+            return Some(cycle);
+        }
+        for (to, path) in self.values.get(node).into_iter().flatten() {
+            stack.push((node, to, path));
+            if let Some(cycle) = self.visit_values(to, stack, done) {
+                return Some(cycle);
+            }
+            stack.pop();
+        }
+        done.insert(node);
+        None
+    }
+
+    /// Returns the names of the types that can implement `Default`.
+    ///
+    /// The provided `markers` are the names of the external types known to implement `Default`
+    /// (see the `default-types` option of the `[deps]` section). Other external types are
+    /// assumed not to. A type defined in the file can implement `Default` when all the types
+    /// it holds by value (through required fields, or as an alias) can; enums never can.
+    pub fn default_types<'a>(&'a self, markers: &'a [String]) -> BTreeSet<&'a str> {
+        let mut ret: BTreeSet<&str> = markers.iter().map(String::as_str).collect();
+
+        // Iterate until a fixed point is reached. Only types whose requirements are already
+        // known to implement `Default` are added, making recursive types never implement it.
+        loop {
+            let mut changed = false;
+            for (name, requirements) in &self.default_requirements {
+                let Some(requirements) = requirements else {
+                    continue;
+                };
+                if ret.contains(name.as_str()) {
+                    continue;
+                }
+                if requirements.iter().all(|r| ret.contains(r.as_str())) {
+                    ret.insert(name);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        ret
+    }
+
+    /// Returns the names of the types that can implement `PartialEq`, `Eq` and `Hash`.
+    ///
+    /// The provided `markers` are the names of the external types known to implement those
+    /// traits (see the `eq-types` option of the `[deps]` section). Other external types are
+    /// assumed not to. A type defined in the file can implement them when it holds no floats,
+    /// maps or sets, and all the types it mentions can implement them as well.
+    pub fn eq_types<'a>(&'a self, markers: &'a [String]) -> BTreeSet<&'a str> {
+        let mut ret: BTreeSet<&str> = markers.iter().map(String::as_str).collect();
+        ret.extend(
+            self.eq_requirements
+                .iter()
+                .filter(|(_, requirements)| requirements.is_some())
+                .map(|(name, _)| name.as_str()),
+        );
+
+        // Unlike `Default`, recursive types may implement those traits, so candidates are
+        // removed until a fixed point is reached rather than added.
+        loop {
+            let mut changed = false;
+            for (name, requirements) in &self.eq_requirements {
+                let Some(requirements) = requirements else {
+                    continue;
+                };
+                if ret.contains(name.as_str())
+                    && !requirements.iter().all(|r| ret.contains(r.as_str()))
+                {
+                    ret.remove(name.as_str());
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        ret
+    }
+}
+
+/// Adds the names of the types mentioned by `ty` to `requirements`, or sets it to `None` if
+/// `ty` holds floats, maps, sets or arbitrary values.
+fn add_eq_requirements(file: &File, ty: &TypeRef, requirements: &mut Option<BTreeSet<String>>) {
+    let Some(set) = requirements else {
+        return;
+    };
+    match ty {
+        TypeRef::Number | TypeRef::Map(_) | TypeRef::Set(_) | TypeRef::Any => *requirements = None,
+        TypeRef::Constant(value) if value.is_f64() => *requirements = None,
+        TypeRef::Array(inner) | TypeRef::Boxed(_, inner) | TypeRef::Nullable(inner) => {
+            add_eq_requirements(file, inner, requirements)
+        }
+        TypeRef::Tuple(items) => {
+            for item in items {
+                add_eq_requirements(file, item, requirements);
+            }
+        }
+        TypeRef::Ref(_) | TypeRef::ExternalRef(_) => {
+            set.extend(direct_names(file, ty));
+        }
+        _ => (),
+    }
+}
+
+/// Adds the names of the types mentioned by `ty` to `names`.
+fn type_ref_names<'a>(file: &'a File, ty: &'a TypeRef, names: &mut BTreeSet<&'a str>) {
+    match ty {
+        TypeRef::Ref(path) => names.extend(file.types.get(path).map(|ty| ty.name.as_str())),
+        TypeRef::ExternalRef(name) => {
+            names.insert(name);
+        }
+        TypeRef::Array(inner)
+        | TypeRef::Set(inner)
+        | TypeRef::Map(inner)
+        | TypeRef::Boxed(_, inner)
+        | TypeRef::Nullable(inner) => type_ref_names(file, inner, names),
+        TypeRef::Tuple(items) => {
+            for item in items {
+                type_ref_names(file, item, names);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Returns the names of the non-primitive types held by value by `ty`.
+///
+/// Arrays and maps are not considered, as they implement `Default` regardless of their items.
+fn direct_names(file: &File, ty: &TypeRef) -> Vec<String> {
+    match ty {
+        TypeRef::Ref(path) => vec![file
+            .types
+            .get(path)
+            .map_or_else(|| path.to_string(), |ty| ty.name.clone())],
+        TypeRef::ExternalRef(name) => vec![name.clone()],
+        TypeRef::Boxed(_, inner) => direct_names(file, inner),
+        TypeRef::Tuple(items) => items
+            .iter()
+            .flat_map(|item| direct_names(file, item))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,168 @@
+//! A minimal line-based unified diff, used to report out-of-date generated files.
+
+/// The number of unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// An operation transforming the old text into the new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    /// The line is present in both texts.
+    Keep,
+    /// The line is only present in the old text.
+    Delete,
+    /// The line is only present in the new text.
+    Insert,
+}
+
+/// Returns a unified diff from `old` to `new`, or `None` if they are identical.
+pub fn unified(old: &str, new: &str, old_name: &str, new_name: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+
+    let a = old.lines().collect::<Vec<_>>();
+    let b = new.lines().collect::<Vec<_>>();
+    let ops = edits(&a, &b);
+
+    let mut ret = format!("--- {old_name}\n+++ {new_name}\n");
+
+    let changes = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| **op != Op::Keep)
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        // Only the line endings differ.
+        ret.push_str("@@ line endings differ @@\n");
+        return Some(ret);
+    }
+
+    // Group the changes that are close enough to share their context.
+    let mut groups = Vec::<(usize, usize)>::new();
+    for &i in &changes {
+        match groups.last_mut() {
+            Some((_, last)) if i - *last <= 2 * CONTEXT => *last = i,
+            _ => groups.push((i, i)),
+        }
+    }
+
+    for (first, last) in groups {
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + 1 + CONTEXT).min(ops.len());
+
+        // Positions of the first line of the hunk in both texts.
+        let old_start = ops[..start].iter().filter(|op| **op != Op::Insert).count();
+        let new_start = ops[..start].iter().filter(|op| **op != Op::Delete).count();
+        let old_len = ops[start..end]
+            .iter()
+            .filter(|op| **op != Op::Insert)
+            .count();
+        let new_len = ops[start..end]
+            .iter()
+            .filter(|op| **op != Op::Delete)
+            .count();
+
+        ret.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            hunk_start(old_start, old_len),
+            old_len,
+            hunk_start(new_start, new_len),
+            new_len,
+        ));
+
+        let (mut x, mut y) = (old_start, new_start);
+        for op in &ops[start..end] {
+            match op {
+                Op::Keep => {
+                    ret.push(' ');
+                    ret.push_str(a[x]);
+                    x += 1;
+                    y += 1;
+                }
+                Op::Delete => {
+                    ret.push('-');
+                    ret.push_str(a[x]);
+                    x += 1;
+                }
+                Op::Insert => {
+                    ret.push('+');
+                    ret.push_str(b[y]);
+                    y += 1;
+                }
+            }
+            ret.push('\n');
+        }
+    }
+
+    Some(ret)
+}
+
+/// Returns the line number starting a hunk, as written in its header.
+///
+/// Empty ranges are numbered after the line preceding them.
+fn hunk_start(start: usize, len: usize) -> usize {
+    if len == 0 {
+        start
+    } else {
+        start + 1
+    }
+}
+
+/// Computes the shortest edit script from `a` to `b`, using Myers' algorithm.
+fn edits(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = n + m;
+    let index = |k: isize| (k + max) as usize;
+
+    // `v[k]` is the furthest `x` reached on diagonal `k`. A copy is kept for every step to
+    // backtrack the path afterwards.
+    let mut v = vec![0isize; 2 * max as usize + 2];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[index(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            ops.push(if x == prev_x { Op::Insert } else { Op::Delete });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    ops
+}
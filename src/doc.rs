@@ -0,0 +1,85 @@
+//! Rendering of the descriptions of the document as rustdoc comments.
+//!
+//! Descriptions are Markdown, but rustdoc reads some of their constructs differently: brackets
+//! are taken as intra-doc links, angle brackets as HTML tags, and bare URLs are not linked.
+//! Those are escaped outside of code spans and code blocks, which are kept as is.
+
+/// Returns the lines of the rustdoc comment documenting an item with `doc`.
+///
+/// The lines don't include the leading `///`.
+pub fn lines(doc: &str) -> Vec<String> {
+    let mut ret = Vec::new();
+    let mut fenced = false;
+    for line in doc.trim().lines() {
+        let line = line.trim_end();
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+            ret.push(line.to_owned());
+        } else if fenced {
+            ret.push(line.to_owned());
+        } else {
+            ret.push(escape_line(line));
+        }
+    }
+    // An unclosed code block would swallow the lines appended after it.
+    if fenced {
+        ret.push(String::from("```"));
+    }
+    ret
+}
+
+/// Escapes the constructs of `line` that rustdoc misreads, outside of its code spans.
+fn escape_line(line: &str) -> String {
+    let mut ret = String::with_capacity(line.len());
+    let mut rest = line;
+    while !rest.is_empty() {
+        // Code spans end at the next run of as many backticks.
+        if rest.starts_with('`') {
+            let ticks = rest.len() - rest.trim_start_matches('`').len();
+            let fence = &rest[..ticks];
+            match rest[ticks..].find(fence) {
+                Some(end) => {
+                    let span = ticks + end + ticks;
+                    ret.push_str(&rest[..span]);
+                    rest = &rest[span..];
+                }
+                None => {
+                    ret.push_str(fence);
+                    rest = &rest[ticks..];
+                }
+            }
+            continue;
+        }
+        if let Some(len) = link_len(rest) {
+            ret.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        let at_word_start = ret.is_empty() || ret.ends_with(char::is_whitespace);
+        if at_word_start && (rest.starts_with("http://") || rest.starts_with("https://")) {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let url = rest[..end].trim_end_matches(['.', ',', ';', ':', ')']);
+            ret.push('<');
+            ret.push_str(url);
+            ret.push('>');
+            rest = &rest[url.len()..];
+            continue;
+        }
+        let c = rest.chars().next().unwrap();
+        if matches!(c, '[' | ']' | '<' | '>') {
+            ret.push('\\');
+        }
+        ret.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    ret
+}
+
+/// Returns the length of the Markdown link `[text](url)` starting `s`, if any.
+fn link_len(s: &str) -> Option<usize> {
+    let text = s.strip_prefix('[')?;
+    let close = text.find(']')?;
+    let target = text[close + 1..].strip_prefix('(')?;
+    let end = target.find(')')?;
+    Some(1 + close + 2 + end + 1)
+}
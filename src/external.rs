@@ -0,0 +1,532 @@
+//! Resolution of the `$ref`s pointing outside of a document.
+//!
+//! The items referenced in other files or at URLs are copied into the `components` of the
+//! document, and the references are rewritten to point to the copies, so that the rest of the
+//! pipeline only ever deals with internal references.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+use url::Url;
+
+use crate::config::Refs;
+use crate::diagnostics::Diagnostic;
+use crate::Error;
+
+/// Where a document was loaded from.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Location {
+    /// A local file.
+    File(PathBuf),
+    /// A document fetched over HTTP(S).
+    Url(String),
+}
+
+impl Location {
+    /// Returns the location of `reference`, resolved relative to `self`.
+    fn join(&self, reference: &str) -> Result<Self, Error> {
+        if is_url(reference) {
+            return Ok(Self::Url(reference.into()));
+        }
+        match self {
+            Self::File(path) => {
+                let dir = path.parent().unwrap_or(Path::new(""));
+                let path = dir.join(reference);
+                Ok(Self::File(std::fs::canonicalize(&path).unwrap_or(path)))
+            }
+            Self::Url(url) => {
+                join_url(url, reference)
+                    .map(Self::Url)
+                    .map_err(|error| Error::Reference {
+                        reference: reference.into(),
+                        message: format!("can't be resolved relative to `{url}`: {error}"),
+                    })
+            }
+        }
+    }
+
+    /// Returns a short name for the location, used to rename conflicting components.
+    fn label(&self) -> String {
+        let name = match self {
+            Self::File(path) => path.file_name().unwrap_or_default().to_string_lossy(),
+            Self::Url(url) => url.rsplit('/').next().unwrap_or_default().into(),
+        };
+        match name.split_once('.') {
+            Some((stem, _)) => stem.into(),
+            None => name.into(),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Url(url) => f.write_str(url),
+        }
+    }
+}
+
+/// Loads the JSON of the document at `path`, and resolves its external references. The text
+/// of the document is returned along with it, and with the warnings emitted while loading it.
+///
+/// The path may also be an HTTP(S) URL, in which case the document is downloaded.
+pub fn load(path: &Path, refs: &Refs) -> Result<(Value, String, Vec<Diagnostic>), Error> {
+    let root = match path.to_str() {
+        Some(url) if is_url(url) => Location::Url(url.into()),
+        _ => Location::File(std::fs::canonicalize(path).unwrap_or_else(|_| path.into())),
+    };
+
+    let mut bundler = Bundler {
+        refs,
+        root: root.clone(),
+        documents: BTreeMap::new(),
+        imported: BTreeMap::new(),
+        taken: BTreeSet::new(),
+        added: Vec::new(),
+        text: String::new(),
+        warnings: Vec::new(),
+    };
+    let mut raw = bundler.load(&root)?.clone();
+    if let Some(Value::Object(kinds)) = raw.get("components") {
+        bundler.taken = kinds
+            .iter()
+            .filter_map(|(kind, entries)| Some((kind, entries.as_object()?)))
+            .flat_map(|(kind, entries)| entries.keys().map(|name| (kind.clone(), name.clone())))
+            .collect();
+    }
+    bundler.bundle(&mut raw)?;
+    Ok((raw, bundler.text, bundler.warnings))
+}
+
+/// The state of the resolution of the external references of a document.
+struct Bundler<'a> {
+    /// The configuration of the resolution.
+    refs: &'a Refs,
+    /// The location of the document being resolved.
+    root: Location,
+    /// The documents loaded so far.
+    documents: BTreeMap<Location, Value>,
+    /// Maps the items imported so far to the internal references that replace them.
+    imported: BTreeMap<(Location, String), String>,
+    /// The components of the document, as `(kind, name)` pairs.
+    taken: BTreeSet<(String, String)>,
+    /// The components imported into the document, as `(kind, name, value)`.
+    added: Vec<(String, String, Value)>,
+    /// The text of the root document.
+    text: String,
+    /// The warnings emitted so far.
+    warnings: Vec<Diagnostic>,
+}
+
+impl Bundler<'_> {
+    /// Resolves the external references of `raw`, the JSON of the root document.
+    fn bundle(&mut self, raw: &mut Value) -> Result<(), Error> {
+        let root = self.root.clone();
+
+        // Methods have no section in `components` to be imported into, so the external ones are
+        // inlined instead.
+        if let Some(Value::Array(methods)) = raw.get_mut("methods") {
+            for method in methods {
+                let Some(Value::String(reference)) = method.get("$ref") else {
+                    continue;
+                };
+                let (document, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+                let target = root.join(document)?;
+                if document.is_empty() || target == root {
+                    continue;
+                }
+                let pointer = pointer.to_string();
+                let mut value = self.item(&target, &pointer)?;
+                self.rewrite(&mut value, &target, false)?;
+                *method = value;
+            }
+        }
+
+        self.rewrite(raw, &root, true)?;
+
+        if self.added.is_empty() {
+            return Ok(());
+        }
+        let Value::Object(document) = raw else {
+            return Ok(());
+        };
+        let components = document
+            .entry("components")
+            .or_insert_with(|| Value::Object(Map::new()));
+        for (kind, name, value) in std::mem::take(&mut self.added) {
+            if let Some(Value::Object(entries)) = components.as_object_mut().map(|kinds| {
+                kinds
+                    .entry(kind)
+                    .or_insert_with(|| Value::Object(Map::new()))
+            }) {
+                entries.insert(name, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrites the references held by `value`, which comes from the document at `location`.
+    ///
+    /// When `internal` is set, references within the document at `location` are kept as is.
+    fn rewrite(
+        &mut self,
+        value: &mut Value,
+        location: &Location,
+        internal: bool,
+    ) -> Result<(), Error> {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get_mut("$ref") {
+                    let (document, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+                    if !(internal && document.is_empty()) {
+                        let target = match document {
+                            "" => location.clone(),
+                            document => location.join(document)?,
+                        };
+                        *reference = if target == self.root {
+                            format!("#{pointer}")
+                        } else {
+                            self.import(target, pointer)?
+                        };
+                    }
+                }
+                for value in map.values_mut() {
+                    self.rewrite(value, location, internal)?;
+                }
+            }
+            Value::Array(values) => {
+                for value in values {
+                    self.rewrite(value, location, internal)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Copies the item at `pointer` in the document at `location` into the components of the
+    /// document, returning the internal reference to the copy.
+    fn import(&mut self, location: Location, pointer: &str) -> Result<String, Error> {
+        let key = (location, pointer.to_string());
+        if let Some(reference) = self.imported.get(&key) {
+            return Ok(reference.clone());
+        }
+        let (location, _) = &key;
+        let mut value = self.item(location, pointer)?;
+
+        let segments: Vec<String> = pointer
+            .split('/')
+            .skip(1)
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect();
+        let (kind, name) = match segments.as_slice() {
+            [components, kind, name] if components == "components" => (kind.clone(), name.clone()),
+            [.., name] => (kind_of(&value).into(), name.clone()),
+            [] => (kind_of(&value).into(), location.label()),
+        };
+        let name = if self.taken.contains(&(kind.clone(), name.clone())) {
+            let taken = |candidate: &str| self.taken.contains(&(kind.clone(), candidate.into()));
+            crate::merge::fresh_name(taken, &location.label(), &name)
+        } else {
+            name
+        };
+
+        // The item is registered before its own references are resolved, which may lead back
+        // to it.
+        let local = format!("#/components/{kind}/{name}");
+        self.imported.insert(key.clone(), local.clone());
+        self.taken.insert((kind.clone(), name.clone()));
+        self.rewrite(&mut value, &key.0, false)?;
+        self.added.push((kind, name, value));
+        Ok(local)
+    }
+
+    /// Returns the item at `pointer` in the document at `location`, whose references are left
+    /// untouched.
+    fn item(&mut self, location: &Location, pointer: &str) -> Result<Value, Error> {
+        let document = self.load(location)?;
+        match document.pointer(pointer) {
+            Some(value) if pointer.is_empty() || pointer.starts_with('/') => Ok(value.clone()),
+            _ => Err(Error::Reference {
+                reference: format!("{location}#{pointer}"),
+                message: String::from("does not point to anything"),
+            }),
+        }
+    }
+
+    /// Returns whether the document at `url` may be downloaded.
+    fn allowed(&self, url: &str) -> bool {
+        let Ok(url) = Url::parse(url) else {
+            return false;
+        };
+        let next_to_root = match &self.root {
+            Location::Url(root) => Url::parse(root)
+                .and_then(|root| root.join("."))
+                .is_ok_and(|dir| within(&url, &dir)),
+            Location::File(_) => false,
+        };
+        next_to_root
+            || self
+                .refs
+                .allowed_urls
+                .iter()
+                .filter_map(|prefix| Url::parse(prefix).ok())
+                .any(|prefix| within(&url, &prefix))
+    }
+
+    /// Returns the JSON of the document at `location`, loading it if needed.
+    fn load(&mut self, location: &Location) -> Result<&Value, Error> {
+        if !self.documents.contains_key(location) {
+            let (contents, path) = match location {
+                Location::File(path) => {
+                    let contents = std::fs::read(path).map_err(|error| Error::Io {
+                        path: path.clone(),
+                        error,
+                    })?;
+                    (contents, path.clone())
+                }
+                Location::Url(url) => {
+                    if !self.allowed(url) {
+                        return Err(Error::Reference {
+                            reference: url.clone(),
+                            message: String::from("is not allowed by `refs.allowed-urls`"),
+                        });
+                    }
+                    let cache = self.refs.cache_dir.as_deref();
+                    let contents = fetch(url, cache, &mut self.warnings).map_err(|error| {
+                        Error::Reference {
+                            reference: url.clone(),
+                            message: error.to_string(),
+                        }
+                    })?;
+                    (contents, PathBuf::from(url))
+                }
+            };
+            let document = serde_json::from_slice(&contents)
+                .map_err(|error| Error::Document { path, error })?;
+            if *location == self.root {
+                self.text = String::from_utf8_lossy(&contents).into_owned();
+            }
+            self.documents.insert(location.clone(), document);
+        }
+        Ok(&self.documents[location])
+    }
+}
+
+/// Returns the section of `components` the item `value` belongs to, guessed from its
+/// shape, for items that are not referenced from the `components` of their document.
+fn kind_of(value: &Value) -> &'static str {
+    let has = |key: &str| value.get(key).is_some();
+    if has("name") && has("schema") {
+        "contentDescriptors"
+    } else if has("code") && has("message") {
+        "errors"
+    } else {
+        "schemas"
+    }
+}
+
+/// Returns whether `reference` is an absolute HTTP(S) URL.
+fn is_url(reference: &str) -> bool {
+    reference.starts_with("http://") || reference.starts_with("https://")
+}
+
+/// Returns whether `url` is on the same origin as `prefix`, with a path made of the segments of
+/// the path of `prefix` followed by any others.
+///
+/// Both URLs have their `.` and `..` segments resolved when parsed, so a path can't escape
+/// `prefix` with them.
+fn within(url: &Url, prefix: &Url) -> bool {
+    let segments = |url: &Url| -> Vec<String> {
+        let mut segments: Vec<String> = url
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .map(Into::into)
+            .collect();
+        if segments.last().is_some_and(String::is_empty) {
+            segments.pop();
+        }
+        segments
+    };
+    url.scheme() == prefix.scheme()
+        && url.host() == prefix.host()
+        && url.port_or_known_default() == prefix.port_or_known_default()
+        && segments(url).starts_with(&segments(prefix))
+}
+
+/// Resolves the relative URL `reference` against `base`.
+fn join_url(base: &str, reference: &str) -> Result<String, url::ParseError> {
+    Ok(Url::parse(base)?.join(reference)?.into())
+}
+
+/// Downloads the document at `url`, caching it in `cache` when provided.
+///
+/// A cached document is revalidated with its `ETag`, and used as is when the download fails, in
+/// which case a warning is pushed to `warnings`.
+fn fetch(url: &str, cache: Option<&Path>, warnings: &mut Vec<Diagnostic>) -> io::Result<Vec<u8>> {
+    let Some(cache) = cache else {
+        return match download(url, None)? {
+            Download::Modified { body, .. } => Ok(body),
+            Download::NotModified => Err(io::Error::other("unexpected `304 Not Modified`")),
+        };
+    };
+
+    let key = format!("{:016x}", fnv1a(url.as_bytes()));
+    let body_path = cache.join(format!("{key}.json"));
+    let etag_path = cache.join(format!("{key}.etag"));
+    let cached = std::fs::read(&body_path).ok();
+    let etag = match cached {
+        Some(_) => std::fs::read_to_string(&etag_path).ok(),
+        None => None,
+    };
+
+    match (download(url, etag.as_deref()), cached) {
+        (Ok(Download::NotModified), Some(cached)) => Ok(cached),
+        (Ok(Download::NotModified), None) => Err(io::Error::other("unexpected `304 Not Modified`")),
+        (Ok(Download::Modified { body, etag }), _) => {
+            std::fs::create_dir_all(cache)?;
+            std::fs::write(&body_path, &body)?;
+            match etag {
+                Some(etag) => std::fs::write(&etag_path, etag)?,
+                None => {
+                    let _ = std::fs::remove_file(&etag_path);
+                }
+            }
+            Ok(body)
+        }
+        (Err(error), Some(cached)) => {
+            warnings.push(Diagnostic::warning(format!(
+                "failed to download `{url}`, its cached copy is used instead\n- error = {error}"
+            )));
+            Ok(cached)
+        }
+        (Err(error), None) => Err(error),
+    }
+}
+
+/// The outcome of a download.
+enum Download {
+    /// The document was downloaded.
+    Modified {
+        /// The contents of the document.
+        body: Vec<u8>,
+        /// The `ETag` of the document, if the server provided one.
+        etag: Option<String>,
+    },
+    /// The document didn't change since it was downloaded with the provided `ETag`.
+    NotModified,
+}
+
+/// Downloads the document at `url`, unless its `ETag` still is `etag`.
+fn download(url: &str, etag: Option<&str>) -> io::Result<Download> {
+    let mut request = ureq::get(url);
+    if let Some(etag) = etag {
+        request = request.set("If-None-Match", etag);
+    }
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(status, _)) => {
+            return Err(io::Error::other(format!("HTTP status {status}")))
+        }
+        Err(error) => return Err(io::Error::other(error)),
+    };
+    if response.status() == 304 {
+        return Ok(Download::NotModified);
+    }
+    let etag = response.header("ETag").map(String::from);
+    let mut body = Vec::new();
+    response.into_reader().read_to_end(&mut body)?;
+    Ok(Download::Modified { body, etag })
+}
+
+/// Hashes `bytes` with the 64-bit FNV-1a function, which unlike the hasher of the standard
+/// library is stable across runs and releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn within(url: &str, prefix: &str) -> bool {
+        super::within(&Url::parse(url).unwrap(), &Url::parse(prefix).unwrap())
+    }
+
+    #[test]
+    fn within_compares_whole_segments() {
+        assert!(within("https://host/org/spec.json", "https://host/org"));
+        assert!(within("https://host/org/spec.json", "https://host/org/"));
+        assert!(within("https://host/org/a/b.json", "https://host/org/"));
+        assert!(!within(
+            "https://host/org-evil/spec.json",
+            "https://host/org"
+        ));
+        assert!(!within("https://host/organization", "https://host/org/"));
+    }
+
+    #[test]
+    fn within_resolves_dot_segments() {
+        assert!(!within(
+            "https://host/org/../other/spec.json",
+            "https://host/org/"
+        ));
+        assert!(!within(
+            "https://host/org/%2e%2e/other.json",
+            "https://host/org/"
+        ));
+        assert!(within(
+            "https://host/other/../org/./spec.json",
+            "https://host/org/"
+        ));
+    }
+
+    #[test]
+    fn join_url_resolves_references() {
+        let base = "https://host/org/spec.json";
+        assert_eq!(
+            join_url(base, "other.json").unwrap(),
+            "https://host/org/other.json"
+        );
+        assert_eq!(
+            join_url(base, "../a/b.json").unwrap(),
+            "https://host/a/b.json"
+        );
+        assert_eq!(
+            join_url(base, "/root.json").unwrap(),
+            "https://host/root.json"
+        );
+        assert_eq!(
+            join_url(base, "//cdn/x.json").unwrap(),
+            "https://cdn/x.json"
+        );
+        assert_eq!(
+            join_url("http://host:8080/spec.json", "//cdn/x.json").unwrap(),
+            "http://cdn/x.json"
+        );
+    }
+
+    #[test]
+    fn within_compares_origins() {
+        assert!(!within("http://host/org/spec.json", "https://host/org/"));
+        assert!(!within(
+            "https://host.evil/org/spec.json",
+            "https://host/org/"
+        ));
+        assert!(!within(
+            "https://host:8443/org/spec.json",
+            "https://host/org/"
+        ));
+        assert!(within(
+            "https://host:443/org/spec.json",
+            "https://host/org/"
+        ));
+    }
+}
@@ -1,30 +1,109 @@
+//! Fixes applied to the parsed file before generating code.
+
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::config::Config;
-use crate::parse::{EnumTag, EnumVariant, File, Path, TypeKind, TypeRef, TypeSource};
+use regex::Regex;
+
+use crate::config::{
+    AdjacentTag, AnonymousName, Config, FieldType, Pointer, Selection, SplitReadWrite,
+};
+use crate::deps::TypeDeps;
+use crate::diagnostics::Diagnostic;
+use crate::glob;
+use crate::ident;
+use crate::parse::{
+    Access, Discriminator, EnumTag, EnumVariant, File, Path, TypeKind, TypeRef, TypeSource,
+};
+
+/// Collects the messages emitted while fixing a file.
+#[derive(Debug, Clone, Default)]
+pub struct Log {
+    /// Warnings about changes that were not explicitly requested.
+    pub warnings: Vec<Diagnostic>,
+    /// Whether the changes made by the fixes should be recorded in `trace`.
+    pub tracing: bool,
+    /// A description of every change made by the fixes, in order, prefixed with the name of
+    /// the fix that made it.
+    ///
+    /// This is only filled when `tracing` is set.
+    pub trace: Vec<String>,
+}
+
+impl Log {
+    /// Records a change made by `fix`, if tracing is enabled.
+    fn record(&mut self, fix: &str, message: impl FnOnce() -> String) {
+        if self.tracing {
+            self.trace.push(format!("{fix}: {}", message()));
+        }
+    }
+}
 
 /// Fixes the provided file according to the provided configuration.
-pub fn fix(file: &mut File, config: &Config) -> Result<(), Vec<String>> {
+///
+/// Warnings about changes that were not explicitly requested, as well as the trace of the
+/// changes when requested, are pushed to `log`.
+pub fn fix(file: &mut File, config: &Config, log: &mut Log) -> Result<(), Vec<Diagnostic>> {
     let mut errs = Vec::new();
 
     if config.fixes.strip_enum_variants {
-        strip_enum_variants(file);
-    }
-    set_tags(file, &config.fixes.set_tags, &mut errs);
-    tag_enums(file, &config.fixes.tagged_enums, &mut errs);
-    remove_things(file, &config.fixes.remove, &mut errs);
-    replace_types(file, &config.fixes.replace, &mut errs);
-    rename_things(file, &config.fixes.rename, &mut errs);
-    flatten_fields(file, &config.fixes.flatten, &mut errs);
+        strip_enum_variants(file, log);
+    }
+    set_tags(file, &config.fixes.set_tags, &mut errs, log);
+    tag_enums(file, &config.fixes.tagged_enums, &mut errs, log);
+    wrap_enums(
+        file,
+        &config.fixes.adjacently_tagged_enums,
+        &config.fixes.externally_tagged_enums,
+        &mut errs,
+        log,
+    );
+    if config.fixes.auto_tag_discriminators {
+        tag_discriminated_enums(file, &mut errs, log);
+    }
+    let remove = expand_paths(file, &config.fixes.remove, "remove", &mut errs);
+    remove_things(file, &remove, &mut errs, log);
+    let replace = expand_path_map(file, &config.fixes.replace, "replace", &mut errs);
+    replace_types(file, &replace, &mut errs, log);
+    let field_type = expand_entries(
+        file,
+        config.fixes.field_type.iter().map(|(p, t)| (p, t.clone())),
+        "set field type",
+        &mut errs,
+    );
+    set_field_types(file, &field_type, &mut errs, log);
+    let big_integers = expand_paths(file, &config.fixes.big_integers, "big integer", &mut errs);
+    make_big_integers(file, config, &big_integers, &mut errs, log);
+    rename_regex(file, &config.fixes.rename_regex, &mut errs, log);
+    let rename = expand_path_map(file, &config.fixes.rename, "rename", &mut errs);
+    rename_things(file, &rename, &mut errs, log);
+    name_anonymous(file, &config.fixes.name_anonymous, &mut errs, log);
+    let flatten = expand_paths(file, &config.fixes.flatten, "flatten", &mut errs);
+    flatten_fields(file, &flatten, &mut errs, log);
     if config.fixes.auto_flatten_one_fields {
-        flatten_one_fields(file, &mut errs);
+        flatten_one_fields(file, &mut errs, log);
     }
     if config.fixes.auto_flatten_one_ref {
-        flatten_one_refs(file, &mut errs);
+        flatten_one_refs(file, &mut errs, log);
     }
+    if let Some(split) = &config.fixes.split_read_write {
+        split_read_write(file, split, log);
+    }
+    collapse_aliases(file, &config.fixes.collapse_aliases, &mut errs, log);
     if config.fixes.remove_stray_types {
-        remove_stray_types(file, &config.fixes.preserve);
+        remove_stray_types(file, &config.fixes.preserve, log);
     }
+    box_things(file, &config.fixes.boxed, &mut errs, log);
+    let pointers = expand_entries(
+        file,
+        config.fixes.pointers.iter().map(|(p, t)| (p, *t)),
+        "store behind a pointer",
+        &mut errs,
+    );
+    wrap_things(file, &pointers, &mut errs, log);
+    box_cycles(file, config, log);
+    add_fallback_variants(file, &config.fixes.non_exhaustive_enums, &mut errs, log);
+    escape_keywords(file, config, log);
+    check_collisions(file, config, &mut errs, log);
 
     if !errs.is_empty() {
         return Err(errs);
@@ -33,15 +112,149 @@ pub fn fix(file: &mut File, config: &Config) -> Result<(), Vec<String>> {
     Ok(())
 }
 
-fn strip_enum_variants(file: &mut File) {
+/// Returns an error for every reference to a type that does not exist, which would be
+/// generated as a placeholder.
+pub fn broken_references(file: &File) -> Vec<Diagnostic> {
+    let mut refs: Vec<(String, &TypeRef)> = Vec::new();
+    for ty in file.types.values() {
+        match &ty.kind {
+            TypeKind::Struct(s) => {
+                refs.extend(s.fields.values().map(|f| (f.path.to_string(), &f.ty)));
+            }
+            TypeKind::Enum(e) => refs.extend(
+                e.variants
+                    .values()
+                    .filter_map(|v| Some((v.path.to_string(), v.ty.as_ref()?))),
+            ),
+            TypeKind::Alias(a) => refs.push((ty.path.to_string(), &a.ty)),
+        }
+    }
+    for error in file.errors.values() {
+        refs.extend(
+            error
+                .data
+                .as_ref()
+                .map(|data| (error.path.to_string(), data)),
+        );
+    }
+    for method in &file.methods {
+        for param in &method.params {
+            let path = format!("#/methods/{}/params/{}", method.name, param.name_in_json);
+            refs.push((path, &param.ty));
+        }
+        if let Some(result) = &method.result {
+            refs.push((format!("#/methods/{}/result", method.name), &result.ty));
+        }
+    }
+
+    refs.into_iter()
+        .filter_map(|(path, ty)| {
+            let target = get_inner_ref(ty)?;
+            if file.types.contains_key(target) {
+                return None;
+            }
+            let message = format!("reference `{target}` points to no schema");
+            Some(
+                Diagnostic::error(message)
+                    .at(path)
+                    .suggest("define the schema, or replace it with `fixes.replace`"),
+            )
+        })
+        .collect()
+}
+
+/// Returns the paths of all the symbols of the file: types, fields, variants and errors.
+fn symbol_paths(file: &File) -> Vec<Path> {
+    let mut ret = Vec::new();
+    for ty in file.types.values() {
+        ret.push(ty.path.clone());
+        match &ty.kind {
+            TypeKind::Struct(s) => ret.extend(s.fields.keys().cloned()),
+            TypeKind::Enum(e) => ret.extend(e.variants.keys().cloned()),
+            TypeKind::Alias(_) => (),
+        }
+    }
+    ret.extend(file.errors.keys().cloned());
+    ret
+}
+
+/// Returns an error about `path` matching no symbol of the file, suggesting the closest one.
+fn not_found(file: &File, message: &str, path: &str) -> Diagnostic {
+    let symbols = symbol_paths(file);
+    Diagnostic::error(message)
+        .at(path)
+        .did_you_mean(path, symbols.iter().map(|symbol| &**symbol))
+}
+
+/// Expands the glob patterns found in `paths` into the paths of the symbols matching them.
+///
+/// Paths without wildcards are kept as-is, and an error is reported for patterns matching no
+/// symbol.
+fn expand_paths(
+    file: &File,
+    paths: &[String],
+    fix: &str,
+    errs: &mut Vec<Diagnostic>,
+) -> Vec<String> {
+    let entries = paths.iter().map(|path| (path, ()));
+    expand_entries(file, entries, fix, errs)
+        .into_iter()
+        .map(|(path, ())| path)
+        .collect()
+}
+
+/// Like [`expand_paths`], but for maps keyed by paths. The value associated with a pattern is
+/// associated with every path it matches.
+fn expand_path_map(
+    file: &File,
+    map: &BTreeMap<String, String>,
+    fix: &str,
+    errs: &mut Vec<Diagnostic>,
+) -> BTreeMap<String, String> {
+    let entries = map.iter().map(|(path, value)| (path, value.clone()));
+    expand_entries(file, entries, fix, errs)
+        .into_iter()
+        .collect()
+}
+
+fn expand_entries<'a, T: Clone>(
+    file: &File,
+    entries: impl Iterator<Item = (&'a String, T)>,
+    fix: &str,
+    errs: &mut Vec<Diagnostic>,
+) -> Vec<(String, T)> {
+    let mut symbols = None;
+    let mut ret = Vec::new();
+    for (pattern, value) in entries {
+        if !glob::is_pattern(pattern) {
+            ret.push((pattern.clone(), value));
+            continue;
+        }
+        let symbols = symbols.get_or_insert_with(|| symbol_paths(file));
+        let before = ret.len();
+        for path in symbols.iter().filter(|path| glob::matches(pattern, path)) {
+            ret.push((path.to_string(), value.clone()));
+        }
+        if ret.len() == before {
+            errs.push(
+                Diagnostic::error(format!("can't {fix}: no path matches the pattern"))
+                    .at(pattern.as_str())
+                    .suggest("run with `--emit ir` to list the paths of the symbols"),
+            );
+        }
+    }
+    ret
+}
+
+fn strip_enum_variants(file: &mut File, log: &mut Log) {
     for ty in file.types.values_mut() {
         if let TypeKind::Enum(en) = &mut ty.kind {
-            fixup_variants(&mut en.variants);
+            fixup_variants(&mut en.variants, log);
         }
     }
 }
 
-fn fixup_variants(variants: &mut BTreeMap<Path, EnumVariant>) {
+fn fixup_variants(variants: &mut BTreeMap<Path, EnumVariant>, log: &mut Log) {
     // Fast path: only one variant.
     if variants.len() <= 1 {
         return;
@@ -56,11 +269,21 @@ fn fixup_variants(variants: &mut BTreeMap<Path, EnumVariant>) {
     }
 
     for variant in variants.values_mut() {
-        variant.name = variant.name[common_prefix..variant.name.len() - common_suffix].into();
+        let name: String = variant.name[common_prefix..variant.name.len() - common_suffix].into();
+        if name == variant.name {
+            continue;
+        }
+        log.record("strip-enum-variants", || {
+            format!(
+                "renamed `{}` from `{}` to `{name}`",
+                variant.path, variant.name
+            )
+        });
+        variant.name = name;
     }
 }
 
-fn flatten_fields(file: &mut File, paths: &[String], errs: &mut Vec<String>) {
+fn flatten_fields(file: &mut File, paths: &[String], errs: &mut Vec<Diagnostic>, log: &mut Log) {
     // The list of paths in `paths` that area types instead of fields.
     // Those must be filtered.
     let mut types = BTreeSet::new();
@@ -104,14 +327,14 @@ fn flatten_fields(file: &mut File, paths: &[String], errs: &mut Vec<String>) {
         .filter(|x| !types.contains(*x))
         .chain(paths2.iter().map(|x| &**x))
     {
-        match flatten_field(file, path) {
+        match flatten_field(file, path, "flatten", log) {
             Ok(()) => (),
             Err(err) => errs.push(err),
         }
     }
 }
 
-fn flatten_one_fields(file: &mut File, errs: &mut Vec<String>) {
+fn flatten_one_fields(file: &mut File, errs: &mut Vec<Diagnostic>, log: &mut Log) {
     let mut fields = Vec::new();
 
     for ty in file.types.values() {
@@ -139,13 +362,13 @@ fn flatten_one_fields(file: &mut File, errs: &mut Vec<String>) {
     }
 
     for field in fields {
-        if let Err(err) = flatten_field(file, &field) {
+        if let Err(err) = flatten_field(file, &field, "auto-flatten-one-fields", log) {
             errs.push(err);
         }
     }
 }
 
-fn flatten_one_refs(file: &mut File, errs: &mut Vec<String>) {
+fn flatten_one_refs(file: &mut File, errs: &mut Vec<Diagnostic>, log: &mut Log) {
     let mut fields = Vec::new();
     let mut aliases = Vec::new();
 
@@ -183,7 +406,7 @@ fn flatten_one_refs(file: &mut File, errs: &mut Vec<String>) {
 
     // For fields, we already have a working function.
     for field in fields {
-        if let Err(err) = flatten_field(file, &field) {
+        if let Err(err) = flatten_field(file, &field, "auto-flatten-one-ref", log) {
             errs.push(err);
         }
     }
@@ -201,14 +424,12 @@ fn flatten_one_refs(file: &mut File, errs: &mut Vec<String>) {
         };
         let replaced_type_path = r.clone();
         let Some(replaced_type) = file.types.remove(&replaced_type_path) else {
-            errs.push(format!(
-                "\
-                can't flatten alias: broken reference found
-                - type = {}
-                - ref = {}
-                ",
-                ty_path, replaced_type_path,
-            ));
+            errs.push(
+                Diagnostic::error(format!(
+                    "can't flatten alias: broken reference found\n- ref = {replaced_type_path}"
+                ))
+                .at(&*ty_path),
+            );
             continue;
         };
         let alias = file.types.get_mut(&alias).unwrap();
@@ -217,6 +438,9 @@ fn flatten_one_refs(file: &mut File, errs: &mut Vec<String>) {
         // perserve the alias's path.
         let og_path = alias.path.clone();
         let og_name = alias.name.clone();
+        log.record("auto-flatten-one-ref", || {
+            format!("merged alias `{og_path}` with `{replaced_type_path}`")
+        });
         *alias = replaced_type;
         alias.name = og_name;
         alias.path = og_path;
@@ -226,7 +450,12 @@ fn flatten_one_refs(file: &mut File, errs: &mut Vec<String>) {
 fn get_inner_ref(r: &TypeRef) -> Option<&Path> {
     match r {
         TypeRef::Ref(r) => Some(r),
-        TypeRef::Array(r) => get_inner_ref(r),
+        TypeRef::Array(r)
+        | TypeRef::Set(r)
+        | TypeRef::Map(r)
+        | TypeRef::Boxed(_, r)
+        | TypeRef::Nullable(r) => get_inner_ref(r),
+        TypeRef::Tuple(items) => items.iter().find_map(get_inner_ref),
         _ => None,
     }
 }
@@ -260,6 +489,14 @@ fn count_refs(file: &File, type_path: &str) -> usize {
         }
     }
 
+    for error in file.errors.values() {
+        if let Some(data) = &error.data {
+            if get_inner_ref(data).is_some_and(|p| &**p == type_path) {
+                count += 1;
+            }
+        }
+    }
+
     for method in &file.methods {
         if let Some(result) = &method.result {
             if get_inner_ref(&result.ty).is_some_and(|p| &**p == type_path) {
@@ -277,7 +514,7 @@ fn count_refs(file: &File, type_path: &str) -> usize {
     count
 }
 
-fn flatten_field(file: &mut File, path: &str) -> Result<(), String> {
+fn flatten_field(file: &mut File, path: &str, fix: &str, log: &mut Log) -> Result<(), Diagnostic> {
     let mut found = None;
 
     for ty in file.types.values() {
@@ -290,13 +527,10 @@ fn flatten_field(file: &mut File, path: &str) -> Result<(), String> {
         let target_path = match &field.ty {
             TypeRef::Ref(ok) => ok,
             other => {
-                return Err(format!(
-                    "\
-                    can't flatten: field is a primitive:\n\
-                    - field = {path}\n\
-                    - type  = {other:?}\n\
-                    ",
-                ));
+                return Err(Diagnostic::error(format!(
+                    "can't flatten: field is a primitive\n- type = {other:?}"
+                ))
+                .at(path));
             }
         };
         found = Some((field.flatten, target_path.clone(), ty.path.clone()));
@@ -304,11 +538,10 @@ fn flatten_field(file: &mut File, path: &str) -> Result<(), String> {
     }
 
     let Some((field_is_flatten, target_type, into_type)) = found else {
-        return Err(format!(
-            "\
-            can't flatten: field or type not found:\n
-            - field = {path}\n\
-            ",
+        return Err(not_found(
+            file,
+            "can't flatten: field or type not found",
+            path,
         ));
     };
 
@@ -324,15 +557,13 @@ fn flatten_field(file: &mut File, path: &str) -> Result<(), String> {
             };
 
             s.fields.get_mut(path).unwrap().ty = r;
+            log.record(fix, || {
+                format!("replaced the type of `{path}` with the one aliased by `{target_type}`")
+            });
         }
         TypeKind::Struct(target_s) => {
             if !field_is_flatten {
-                return Err(format!(
-                    "\
-                can't flatten: field is not flatten:\n\
-                - field = {path}\n\
-                ",
-                ));
+                return Err(Diagnostic::error("can't flatten: field is not flatten").at(path));
             }
 
             let mut fields_to_add = target_s.fields.clone();
@@ -343,31 +574,30 @@ fn flatten_field(file: &mut File, path: &str) -> Result<(), String> {
             };
 
             s.fields.remove(path);
+            log.record(fix, || {
+                format!(
+                    "flattened `{path}` into `{into_type}`, adding the fields of `{target_type}`"
+                )
+            });
             s.fields.append(&mut fields_to_add);
         }
         TypeKind::Enum(_) => {
-            return Err(format!(
-                "\
-            can't flatten: target type is not a struct:\n\
-            - field       = {path}\n\
-            - target_type = {target_type}\n\
-            ",
+            return Err(Diagnostic::error(format!(
+                "can't flatten: target type is not a struct\n- target_type = {target_type}"
             ))
+            .at(path))
         }
     }
 
     Ok(())
 }
 
-fn remove_things(file: &mut File, paths: &[String], errs: &mut Vec<String>) {
+fn remove_things(file: &mut File, paths: &[String], errs: &mut Vec<Diagnostic>, log: &mut Log) {
     for path in paths {
-        if !remove_thing(file, path) {
-            errs.push(format!(
-                "\
-                can't remove: path not found:\n\
-                - path = {path}\n\
-                ",
-            ));
+        if remove_thing(file, path) {
+            log.record("remove", || format!("removed `{path}`"));
+        } else {
+            errs.push(not_found(file, "can't remove: path not found", path));
         }
     }
 }
@@ -377,6 +607,13 @@ fn remove_thing(file: &mut File, path: &str) -> bool {
         return true;
     }
 
+    if file.errors.remove(path).is_some() {
+        for method in &mut file.methods {
+            method.errors.retain(|e| &**e != path);
+        }
+        return true;
+    }
+
     for ty in file.types.values_mut() {
         match &mut ty.kind {
             TypeKind::Struct(s) => {
@@ -396,68 +633,691 @@ fn remove_thing(file: &mut File, path: &str) -> bool {
     false
 }
 
-fn rename_things(file: &mut File, replacements: &BTreeMap<String, String>, errs: &mut Vec<String>) {
+fn rename_things(
+    file: &mut File,
+    replacements: &BTreeMap<String, String>,
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
     for (path, by) in replacements {
-        if let Err(err) = rename_thing(file, path, by) {
-            errs.push(err);
+        match rename_thing(file, path, by) {
+            Ok(old) => log.record("rename", || {
+                format!("renamed `{path}` from `{old}` to `{by}`")
+            }),
+            Err(err) => errs.push(err),
+        }
+    }
+}
+
+/// Turns the selected types defined inline into named types, renaming the types defined
+/// inside them after their new names.
+fn name_anonymous(
+    file: &mut File,
+    names: &BTreeMap<String, AnonymousName>,
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
+    for (path, name) in names {
+        let Some(ty) = file.types.get_mut(path.as_str()) else {
+            errs.push(not_found(
+                file,
+                "can't name anonymous type: type not found",
+                path,
+            ));
+            continue;
+        };
+        if ty.source == TypeSource::Declared {
+            errs.push(
+                Diagnostic::error("can't name anonymous type: type is already named")
+                    .at(path)
+                    .suggest("use `fixes.rename` to rename it"),
+            );
+            continue;
+        }
+        let old = std::mem::replace(&mut ty.name, name.name().into());
+        ty.source = TypeSource::Declared;
+        if let Some(doc) = name.doc() {
+            ty.documentation = Some(doc.into());
+        }
+        log.record("name-anonymous", || {
+            format!("named `{path}` `{}` instead of `{old}`", name.name())
+        });
+
+        let prefix = format!("{path}/");
+        for inner in file.types.values_mut() {
+            if inner.source == TypeSource::Declared || !inner.path.starts_with(&prefix) {
+                continue;
+            }
+            if let Some(rest) = inner.name.strip_prefix(&old) {
+                let new = format!("{}{rest}", name.name());
+                log.record("name-anonymous", || {
+                    format!("renamed `{}` from `{}` to `{new}`", inner.path, inner.name)
+                });
+                inner.name = new;
+            }
         }
     }
 }
 
-fn rename_thing(file: &mut File, path: &str, by: &str) -> Result<(), String> {
+fn set_field_types(
+    file: &mut File,
+    field_types: &[(String, FieldType)],
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
+    for (path, field_type) in field_types {
+        let field = file.types.values_mut().find_map(|ty| match &mut ty.kind {
+            TypeKind::Struct(s) => s.fields.get_mut(path.as_str()),
+            _ => None,
+        });
+        match field {
+            Some(field) => {
+                field.ty = TypeRef::ExternalRef(field_type.ty().into());
+                field.serde_with = field_type.with().map(Into::into);
+                log.record("field-type", || {
+                    format!("set the type of `{path}` to `{}`", field_type.ty())
+                });
+            }
+            None => errs.push(not_found(
+                file,
+                "can't set field type: field not found",
+                path,
+            )),
+        }
+    }
+}
+
+fn make_big_integers(
+    file: &mut File,
+    config: &Config,
+    paths: &[String],
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
+    fn make_big(ty: &mut TypeRef) {
+        match ty {
+            TypeRef::Array(inner)
+            | TypeRef::Set(inner)
+            | TypeRef::Map(inner)
+            | TypeRef::Boxed(_, inner)
+            | TypeRef::Nullable(inner) => make_big(inner),
+            _ => *ty = TypeRef::BigInteger,
+        }
+    }
+
+    if !paths.is_empty() && config.primitives.big_integer.is_none() {
+        errs.push(Diagnostic::error(
+            "can't use big integers: `primitives.big-integer` is not set",
+        ));
+        return;
+    }
+
+    for path in paths {
+        let ty = file.types.values_mut().find_map(|ty| match &mut ty.kind {
+            TypeKind::Alias(a) if &*ty.path == path => Some(&mut a.ty),
+            TypeKind::Struct(s) => s.fields.get_mut(path.as_str()).map(|f| &mut f.ty),
+            TypeKind::Enum(e) => e.variants.get_mut(path.as_str())?.ty.as_mut(),
+            _ => None,
+        });
+        match ty {
+            Some(ty) => {
+                make_big(ty);
+                log.record("big-integers", || format!("made `{path}` a big integer"));
+            }
+            None => errs.push(not_found(
+                file,
+                "can't make big integer: field, variant or alias not found",
+                path,
+            )),
+        }
+    }
+}
+
+fn rename_regex(
+    file: &mut File,
+    substitutions: &BTreeMap<String, String>,
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
+    for (pattern, replacement) in substitutions {
+        let regex = match Regex::new(pattern) {
+            Ok(regex) => regex,
+            Err(err) => {
+                errs.push(Diagnostic::error(format!(
+                    "can't rename: invalid regular expression\n- regex = {pattern}\n- error = {err}"
+                )));
+                continue;
+            }
+        };
+
+        let mut substitute = |path: &Path, name: &mut String| {
+            if !regex.is_match(name) {
+                return;
+            }
+            let new = regex.replace_all(name, replacement).into_owned();
+            if new == *name {
+                return;
+            }
+            log.record("rename-regex", || {
+                format!("renamed `{path}` from `{name}` to `{new}`")
+            });
+            *name = new;
+        };
+
+        for ty in file.types.values_mut() {
+            substitute(&ty.path, &mut ty.name);
+            match &mut ty.kind {
+                TypeKind::Struct(s) => {
+                    for field in s.fields.values_mut() {
+                        substitute(&field.path, &mut field.name);
+                    }
+                }
+                TypeKind::Enum(e) => {
+                    for variant in e.variants.values_mut() {
+                        substitute(&variant.path, &mut variant.name);
+                    }
+                }
+                TypeKind::Alias(_) => (),
+            }
+        }
+    }
+}
+
+/// Renames the symbol at `path`, returning its previous name.
+fn rename_thing(file: &mut File, path: &str, by: &str) -> Result<String, Diagnostic> {
     if let Some(ty) = file.types.get_mut(path) {
-        ty.name = by.into();
-        return Ok(());
+        return Ok(std::mem::replace(&mut ty.name, by.into()));
+    }
+
+    if let Some(error) = file.errors.get_mut(path) {
+        return Ok(std::mem::replace(&mut error.name, by.into()));
     }
 
     for ty in file.types.values_mut() {
         match &mut ty.kind {
             TypeKind::Struct(s) => {
                 if let Some(field) = s.fields.get_mut(path) {
-                    field.name = by.into();
-                    return Ok(());
+                    return Ok(std::mem::replace(&mut field.name, by.into()));
                 }
             }
             TypeKind::Enum(e) => {
                 if let Some(variant) = e.variants.get_mut(path) {
-                    variant.name = by.into();
-                    return Ok(());
+                    return Ok(std::mem::replace(&mut variant.name, by.into()));
                 }
             }
             TypeKind::Alias(_) => (),
         }
     }
 
-    Err(format!(
-        "\
-        can't rename: path not found:\n\
-        - path = {path}\n\
-        ",
-    ))
+    Err(not_found(file, "can't rename: path not found", path))
 }
 
-fn replace_types(file: &mut File, replacements: &BTreeMap<String, String>, errs: &mut Vec<String>) {
-    for (path, by) in replacements {
-        if !replace_type(file, path, by) {
-            errs.push(format!(
-                "\
-                can't replace: type not found:\n\
-                - path = {path}\n\
-                ",
+fn add_fallback_variants(
+    file: &mut File,
+    selection: &Selection,
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
+    let paths = match selection {
+        Selection::All(false) => return,
+        Selection::All(true) => file
+            .types
+            .values()
+            .filter(|ty| matches!(ty.kind, TypeKind::Enum(_)))
+            .map(|ty| ty.path.to_string())
+            .collect(),
+        Selection::Paths(paths) => expand_paths(file, paths, "make enum non-exhaustive", errs),
+    };
+
+    for path in paths {
+        let Some(TypeKind::Enum(e)) = file.types.get_mut(path.as_str()).map(|ty| &mut ty.kind)
+        else {
+            if matches!(selection, Selection::Paths(p) if p.contains(&path)) {
+                errs.push(not_found(
+                    file,
+                    "can't make enum non-exhaustive: enum not found",
+                    &path,
+                ));
+            }
+            continue;
+        };
+
+        if e.variants.values().any(|v| v.name == "Unknown") {
+            errs.push(
+                Diagnostic::error(
+                    "can't make enum non-exhaustive: it already has an `Unknown` variant",
+                )
+                .at(path),
+            );
+            continue;
+        }
+
+        let structural = e.variants.values().any(|v| v.ty.is_some());
+        let ty = match &e.tag {
+            EnumTag::Integer(ty) => Some(ty.clone()),
+            _ => structural.then(|| TypeRef::ExternalRef("serde_json::Value".into())),
+        };
+        let variant_path = Path::from(format!("{path}/unknown"));
+        e.variants.insert(
+            variant_path.clone(),
+            EnumVariant {
+                path: variant_path,
+                name: "Unknown".into(),
+                name_in_json: None,
+                documentation: Some("A value that is not part of the specification.".into()),
+                ty,
+                fallback: true,
+            },
+        );
+        log.record("non-exhaustive-enums", || {
+            format!("added an `Unknown` variant to `{path}`")
+        });
+    }
+}
+
+fn collapse_aliases(
+    file: &mut File,
+    selection: &Selection,
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
+    let paths = match selection {
+        Selection::All(false) => return,
+        Selection::All(true) => file
+            .types
+            .values()
+            .filter(|ty| matches!(ty.kind, TypeKind::Alias(_)))
+            .map(|ty| ty.path.to_string())
+            .collect(),
+        Selection::Paths(paths) => expand_paths(file, paths, "collapse alias", errs),
+    };
+
+    for path in paths {
+        let Some(TypeKind::Alias(alias)) = file.types.get(path.as_str()).map(|ty| &ty.kind) else {
+            if matches!(selection, Selection::Paths(p) if p.contains(&path)) {
+                errs.push(not_found(
+                    file,
+                    "can't collapse alias: alias not found",
+                    &path,
+                ));
+            }
+            continue;
+        };
+
+        let mut ty = alias.ty.clone();
+        let mut visited = vec![path.clone()];
+        if !inline_aliases(file, &mut ty, &mut visited)
+            || get_inner_ref(&ty).is_some_and(|r| **r == *path)
+        {
+            continue;
+        }
+
+        let Some(TypeKind::Alias(alias)) = file.types.get_mut(path.as_str()).map(|ty| &mut ty.kind)
+        else {
+            unreachable!();
+        };
+        alias.ty = ty;
+        log.record("collapse-aliases", || {
+            format!("inlined `{}` into `{path}`", visited[1..].join("`, `"))
+        });
+    }
+}
+
+/// Replaces the references to aliases found in `ty` with the types they alias, transitively.
+///
+/// `visited` holds the aliases being inlined, which are not inlined again to avoid looping on
+/// recursive aliases. Returns whether `ty` was modified.
+fn inline_aliases(file: &File, ty: &mut TypeRef, visited: &mut Vec<String>) -> bool {
+    match ty {
+        TypeRef::Ref(r) => {
+            if visited.iter().any(|v| **v == **r) {
+                return false;
+            }
+            let Some(TypeKind::Alias(alias)) = file.types.get(r).map(|ty| &ty.kind) else {
+                return false;
+            };
+            visited.push(r.to_string());
+            *ty = alias.ty.clone();
+            inline_aliases(file, ty, visited);
+            true
+        }
+        TypeRef::Array(r)
+        | TypeRef::Set(r)
+        | TypeRef::Map(r)
+        | TypeRef::Boxed(_, r)
+        | TypeRef::Nullable(r) => inline_aliases(file, r, visited),
+        TypeRef::Tuple(items) => {
+            // Siblings may inline the same aliases.
+            let mut changed = false;
+            let mut inlined = Vec::new();
+            for item in items {
+                let mut item_visited = visited.clone();
+                changed |= inline_aliases(file, item, &mut item_visited);
+                inlined.extend(item_visited.drain(visited.len()..));
+            }
+            visited.extend(inlined);
+            changed
+        }
+        _ => false,
+    }
+}
+
+fn box_things(file: &mut File, paths: &[String], errs: &mut Vec<Diagnostic>, log: &mut Log) {
+    for path in paths {
+        if box_thing(file, path, Pointer::Box) {
+            log.record("boxed", || format!("boxed `{path}`"));
+        } else {
+            errs.push(not_found(file, "can't box: path not found", path));
+        }
+    }
+}
+
+fn wrap_things(
+    file: &mut File,
+    pointers: &[(String, Pointer)],
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
+    for (path, pointer) in pointers {
+        if box_thing(file, path, *pointer) {
+            log.record("pointer", || {
+                format!("stored `{path}` behind `{pointer:?}`")
+            });
+        } else {
+            errs.push(not_found(
+                file,
+                "can't store behind a pointer: path not found",
+                path,
             ));
         }
     }
 }
 
-fn replace_type(file: &mut File, path: &str, by: &str) -> bool {
-    file.types.remove(path);
+fn box_thing(file: &mut File, path: &str, pointer: Pointer) -> bool {
+    fn make_boxed(ty: &mut TypeRef, pointer: Pointer) {
+        // `Option<Box<T>>` reads better than `Box<Option<T>>`.
+        if let TypeRef::Nullable(inner) = ty {
+            return make_boxed(inner, pointer);
+        }
+        let inner = std::mem::replace(ty, TypeRef::Null);
+        *ty = TypeRef::Boxed(pointer, Box::new(inner));
+    }
+
+    for ty in file.types.values_mut() {
+        if &*ty.path == path {
+            let TypeKind::Alias(a) = &mut ty.kind else {
+                return false;
+            };
+            make_boxed(&mut a.ty, pointer);
+            return true;
+        }
+        match &mut ty.kind {
+            TypeKind::Struct(s) => {
+                if let Some(field) = s.fields.get_mut(path) {
+                    make_boxed(&mut field.ty, pointer);
+                    return true;
+                }
+            }
+            TypeKind::Enum(e) => {
+                if let Some(variant) = e.variants.get_mut(path) {
+                    let Some(ty) = &mut variant.ty else {
+                        return false;
+                    };
+                    make_boxed(ty, pointer);
+                    return true;
+                }
+            }
+            TypeKind::Alias(_) => (),
+        }
+    }
+
+    false
+}
+
+/// Boxes one of the values of every recursion chain, which would otherwise generate infinitely
+/// sized types.
+fn box_cycles(file: &mut File, config: &Config, log: &mut Log) {
+    while let Some(cycle) = TypeDeps::new(file, config).find_value_cycle() {
+        // Box the edge closing the cycle.
+        let edge = cycle.last().unwrap();
+        let mut chain = cycle.iter().map(|e| e.from.as_str()).collect::<Vec<_>>();
+        chain.push(&edge.to);
+        log.warnings.push(
+            Diagnostic::warning(format!(
+                "recursive type found, boxing one of its values\n- chain = {}",
+                chain.join(" -> "),
+            ))
+            .at(&*edge.path)
+            .suggest("add one of the values of the chain to `fixes.boxed` to choose which one"),
+        );
+        let path = edge.path.clone();
+        if !box_thing(file, &path, Pointer::Box) {
+            break;
+        }
+    }
+}
+
+/// Replaces the names that are Rust keywords with valid identifiers, as configured by
+/// `generation.keyword-renames`.
+fn escape_keywords(file: &mut File, config: &Config, log: &mut Log) {
+    let renames = &config.generation.keyword_renames;
+    let mut escape = |path: &str, name: &mut String| {
+        let new = ident::escape(name, renames);
+        if new != *name {
+            log.record("keyword", || {
+                format!("renamed `{path}` from `{name}` to `{new}`")
+            });
+            *name = new;
+        }
+    };
+
+    for ty in file.types.values_mut() {
+        escape(&ty.path, &mut ty.name);
+        match &mut ty.kind {
+            TypeKind::Struct(s) => {
+                for field in s.fields.values_mut() {
+                    escape(&field.path, &mut field.name);
+                }
+            }
+            TypeKind::Enum(e) => {
+                for variant in e.variants.values_mut() {
+                    escape(&variant.path, &mut variant.name);
+                }
+            }
+            TypeKind::Alias(_) => (),
+        }
+    }
+    for error in file.errors.values_mut() {
+        escape(&error.path, &mut error.name);
+    }
+    for method in &mut file.methods {
+        for param in &mut method.params {
+            let path = format!("#/methods/{}/params/{}", method.name, param.name_in_json);
+            escape(&path, &mut param.name);
+        }
+    }
+}
+
+/// Reports the symbols whose names collide with a previous symbol of the same scope, or
+/// renames them with a numeric suffix when `fixes.disambiguate-names` is enabled.
+///
+/// Types defined inline are always renamed, as their names were made up by the parser.
+fn check_collisions(file: &mut File, config: &Config, errs: &mut Vec<Diagnostic>, log: &mut Log) {
+    let disambiguate = config.fixes.disambiguate_names;
+    // The third item of the symbols tells whether they were named by the parser.
+    let mut resolve = |scope: &mut Vec<(&Path, &mut String, bool)>, separator: &str| {
+        // The symbols named in the document keep their names.
+        scope.sort_by_key(|(_, _, made_up)| *made_up);
+        let mut taken: BTreeMap<String, Path> = BTreeMap::new();
+        let mut colliding = Vec::new();
+        for (i, (path, name, _)) in scope.iter().enumerate() {
+            match taken.get(name.as_str()) {
+                Some(first) => colliding.push((i, first.clone())),
+                None => {
+                    taken.insert(name.to_string(), (*path).clone());
+                }
+            }
+        }
+        for (i, first) in colliding {
+            let (path, name, made_up) = &mut scope[i];
+            if !disambiguate && !*made_up {
+                errs.push(
+                    Diagnostic::error(format!("`{name}` is already the name of `{first}`"))
+                        .at(path.to_string())
+                        .suggest("rename one of them with `fixes.rename`, or enable `fixes.disambiguate-names`"),
+                );
+                continue;
+            }
+            // A number appended to one would read as part of it.
+            let separator = if name.ends_with(|c: char| c.is_ascii_digit()) {
+                "_"
+            } else {
+                separator
+            };
+            let new = (2..)
+                .map(|n| format!("{name}{separator}{n}"))
+                .find(|new| !taken.contains_key(new))
+                .unwrap();
+            log.warnings.push(
+                Diagnostic::warning(format!(
+                    "`{name}` is already the name of `{first}`, renamed to `{new}`"
+                ))
+                .at(path.to_string()),
+            );
+            taken.insert(new.clone(), (*path).clone());
+            **name = new;
+        }
+    };
+
+    let mut types: Vec<_> = file
+        .types
+        .values_mut()
+        .map(|ty| {
+            let made_up = !matches!(ty.source, TypeSource::Declared);
+            (&ty.path, &mut ty.name, made_up)
+        })
+        .collect();
+    resolve(&mut types, "");
+    // The errors defined inline in methods are named after their codes, which they may share.
+    let mut errors: Vec<_> = file
+        .errors
+        .values_mut()
+        .map(|error| {
+            let made_up = !error.path.starts_with("#/components/errors/");
+            (&error.path, &mut error.name, made_up)
+        })
+        .collect();
+    resolve(&mut errors, "");
+    for ty in file.types.values_mut() {
+        match &mut ty.kind {
+            TypeKind::Struct(s) => {
+                let mut fields: Vec<_> = s
+                    .fields
+                    .values_mut()
+                    .map(|field| (&field.path, &mut field.name, false))
+                    .collect();
+                resolve(&mut fields, "_");
+            }
+            TypeKind::Enum(e) => {
+                let mut variants: Vec<_> = e
+                    .variants
+                    .values_mut()
+                    .map(|variant| (&variant.path, &mut variant.name, false))
+                    .collect();
+                resolve(&mut variants, "");
+            }
+            TypeKind::Alias(_) => (),
+        }
+    }
+
+    let prefix = config
+        .generation
+        .method_name_prefix
+        .as_deref()
+        .unwrap_or("");
+    let mut methods: BTreeMap<String, &str> = BTreeMap::new();
+    for method in &file.methods {
+        let base = method.name.strip_prefix(prefix).unwrap_or(&method.name);
+        let ident = ident::pascal(base, &config.generation.acronyms);
+        let message = match methods.get(&ident) {
+            Some(first) if *first == method.name => "method defined several times".into(),
+            Some(first) => format!("method identifier `{ident}` is already used by `{first}`"),
+            None => {
+                methods.insert(ident, &method.name);
+                continue;
+            }
+        };
+        errs.push(Diagnostic::error(message).at(format!("#/methods/{}", method.name)));
+    }
+
+    if config.generation.tag_modules {
+        let renames = &config.generation.keyword_renames;
+        let mut modules: BTreeMap<String, &str> = BTreeMap::new();
+        for method in &file.methods {
+            let Some(tag) = method.tags.first() else {
+                continue;
+            };
+            let module = ident::escape(&ident::snake(&tag.name), renames);
+            let message = match modules.get(&module) {
+                Some(first) if *first == tag.name => continue,
+                Some(first) => format!(
+                    "the module `{module}` of tag `{}` is already the module of tag `{first}`",
+                    tag.name
+                ),
+                None => {
+                    let valid = syn::parse_str::<syn::Ident>(&module).is_ok();
+                    modules.insert(module.clone(), &tag.name);
+                    if valid {
+                        continue;
+                    }
+                    format!(
+                        "the tag `{}` would be written to the module `{module}`, which is not a valid identifier",
+                        tag.name
+                    )
+                }
+            };
+            errs.push(
+                Diagnostic::error(message)
+                    .at(format!("#/methods/{}", method.name))
+                    .suggest("rename the tag in the document, or disable `generation.tag-modules`"),
+            );
+        }
+    }
+}
+
+fn replace_types(
+    file: &mut File,
+    replacements: &BTreeMap<String, String>,
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
+    for (path, by) in replacements {
+        if !replace_type(file, path, by, log) {
+            errs.push(not_found(file, "can't replace: type not found", path));
+        }
+    }
+}
+
+pub(crate) fn replace_type(file: &mut File, path: &str, by: &str, log: &mut Log) -> bool {
+    if file.types.remove(path).is_some() {
+        log.record("replace", || format!("replaced `{path}` with `{by}`"));
+    }
 
     fn replace_ref(ty: &mut TypeRef, src: &str, dst: String) {
         match ty {
             TypeRef::Ref(p) if &**p == src => {
                 *ty = TypeRef::ExternalRef(dst);
             }
-            TypeRef::Array(inner) => replace_ref(&mut *inner, src, dst),
+            TypeRef::Array(inner)
+            | TypeRef::Set(inner)
+            | TypeRef::Map(inner)
+            | TypeRef::Boxed(_, inner)
+            | TypeRef::Nullable(inner) => replace_ref(&mut *inner, src, dst),
+            TypeRef::Tuple(items) => {
+                for item in items {
+                    replace_ref(item, src, dst.clone());
+                }
+            }
             _ => (),
         }
     }
@@ -482,6 +1342,12 @@ fn replace_type(file: &mut File, path: &str, by: &str) -> bool {
         }
     }
 
+    for error in file.errors.values_mut() {
+        if let Some(data) = &mut error.data {
+            replace_ref(data, path, by.into());
+        }
+    }
+
     for method in &mut file.methods {
         if let Some(result) = &mut method.result {
             replace_ref(&mut result.ty, path, by.into());
@@ -495,7 +1361,120 @@ fn replace_type(file: &mut File, path: &str, by: &str) -> bool {
     true
 }
 
-fn remove_stray_types(file: &mut File, preserve: &BTreeSet<String>) {
+/// Splits the types holding read-only or write-only fields, directly or through other types,
+/// into a request-side version and a response-side version.
+///
+/// The response-side versions keep the paths of the original types, and the request-side
+/// versions are added next to them. Method parameters are then redirected to the request-side
+/// versions.
+fn split_read_write(file: &mut File, split: &SplitReadWrite, log: &mut Log) {
+    fn kind_refs(kind: &TypeKind) -> Vec<&TypeRef> {
+        match kind {
+            TypeKind::Struct(s) => s.fields.values().map(|f| &f.ty).collect(),
+            TypeKind::Enum(e) => e.variants.values().filter_map(|v| v.ty.as_ref()).collect(),
+            TypeKind::Alias(a) => vec![&a.ty],
+        }
+    }
+
+    fn redirect_ref(ty: &mut TypeRef, split_paths: &BTreeSet<Path>) {
+        match ty {
+            TypeRef::Ref(p) if split_paths.contains(p) => {
+                *p = request_path(p);
+            }
+            TypeRef::Array(inner)
+            | TypeRef::Set(inner)
+            | TypeRef::Map(inner)
+            | TypeRef::Boxed(_, inner)
+            | TypeRef::Nullable(inner) => redirect_ref(inner, split_paths),
+            TypeRef::Tuple(items) => {
+                for item in items {
+                    redirect_ref(item, split_paths);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn request_path(path: &str) -> Path {
+        format!("{path}/request").into()
+    }
+
+    let mut split_paths: BTreeSet<Path> = file
+        .types
+        .values()
+        .filter(|ty| match &ty.kind {
+            TypeKind::Struct(s) => s.fields.values().any(|f| f.access != Access::ReadWrite),
+            _ => false,
+        })
+        .map(|ty| ty.path.clone())
+        .collect();
+    loop {
+        let holders: Vec<Path> = file
+            .types
+            .values()
+            .filter(|ty| !split_paths.contains(&ty.path))
+            .filter(|ty| {
+                kind_refs(&ty.kind)
+                    .iter()
+                    .flat_map(|r| r.inner_paths())
+                    .any(|p| split_paths.contains(p))
+            })
+            .map(|ty| ty.path.clone())
+            .collect();
+        if holders.is_empty() {
+            break;
+        }
+        split_paths.extend(holders);
+    }
+
+    for path in &split_paths {
+        let Some(response) = file.types.get_mut(path) else {
+            continue;
+        };
+        let mut request = response.clone();
+        let name = std::mem::take(&mut response.name);
+
+        response.name = split.response.replace("{}", &name);
+        if let TypeKind::Struct(s) = &mut response.kind {
+            s.fields.retain(|_, f| f.access != Access::WriteOnly);
+        }
+
+        request.path = request_path(path);
+        request.name = split.request.replace("{}", &name);
+        if let TypeKind::Struct(s) = &mut request.kind {
+            s.fields.retain(|_, f| f.access != Access::ReadOnly);
+        }
+        match &mut request.kind {
+            TypeKind::Struct(s) => {
+                for field in s.fields.values_mut() {
+                    redirect_ref(&mut field.ty, &split_paths);
+                }
+            }
+            TypeKind::Enum(e) => {
+                for ty in e.variants.values_mut().filter_map(|v| v.ty.as_mut()) {
+                    redirect_ref(ty, &split_paths);
+                }
+            }
+            TypeKind::Alias(a) => redirect_ref(&mut a.ty, &split_paths),
+        }
+
+        log.record("split-read-write", || {
+            format!(
+                "split `{path}` into `{}` and `{}`",
+                request.name, response.name
+            )
+        });
+        file.types.insert(request.path.clone(), request);
+    }
+
+    for method in &mut file.methods {
+        for param in &mut method.params {
+            redirect_ref(&mut param.ty, &split_paths);
+        }
+    }
+}
+
+fn remove_stray_types(file: &mut File, preserve: &BTreeSet<String>, log: &mut Log) {
     // The set of all nodes that are known not be stray types.
     let mut not_stray = BTreeSet::new();
     // Nodes to visit next.
@@ -507,19 +1486,27 @@ fn remove_stray_types(file: &mut File, preserve: &BTreeSet<String>) {
         .chain(
             file.methods
                 .iter()
-                .filter_map(|m| m.result.as_ref().and_then(|r| r.ty.inner_path()).cloned()),
+                .filter_map(|m| m.result.as_ref())
+                .flat_map(|r| r.ty.inner_paths())
+                .cloned(),
         )
         .chain(
             file.methods
                 .iter()
-                .flat_map(|m| m.params.iter().filter_map(|p| p.ty.inner_path()).cloned()),
+                .flat_map(|m| m.params.iter().flat_map(|p| p.ty.inner_paths()))
+                .cloned(),
+        )
+        .chain(
+            file.errors
+                .values()
+                .filter_map(|e| e.data.as_ref())
+                .flat_map(|d| d.inner_paths())
+                .cloned(),
         )
         .collect::<Vec<_>>();
 
     fn take_ref_into_account(r: &TypeRef, to_visit: &mut Vec<Path>) {
-        if let Some(r) = r.inner_path() {
-            to_visit.push(r.clone());
-        }
+        to_visit.extend(r.inner_paths().into_iter().cloned());
     }
 
     // Visit the graph to find all the nodes that are not stray types.
@@ -555,61 +1542,85 @@ fn remove_stray_types(file: &mut File, preserve: &BTreeSet<String>) {
         }
     }
 
-    file.types.retain(|_, ty| not_stray.contains(&ty.path));
+    file.types.retain(|_, ty| {
+        let keep = not_stray.contains(&ty.path);
+        if !keep {
+            log.record("remove-stray-types", || format!("removed `{}`", ty.path));
+        }
+        keep
+    });
 }
 
-fn tag_enums(file: &mut File, tagged: &BTreeMap<String, String>, errs: &mut Vec<String>) {
+fn tag_enums(
+    file: &mut File,
+    tagged: &BTreeMap<String, String>,
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
     for (path, tag) in tagged {
-        if let Err(err) = tag_enum(file, Path::from(&**path), tag) {
+        if let Err(err) = tag_enum(file, Path::from(&**path), tag, None, log) {
             errs.push(err);
         }
     }
 }
 
-fn tag_enum(file: &mut File, path: Path, tag: &str) -> Result<(), String> {
+/// Tags the enum at `path` with the field named `tag`, which is removed from the structs of its
+/// variants.
+///
+/// The tag of a variant is the value of its keyword field. Enums tagged after their
+/// `discriminator` first use the value its `mapping` associates with the schema of the variant,
+/// and fall back to the name of that schema when the variant has no keyword field.
+fn tag_enum(
+    file: &mut File,
+    path: Path,
+    tag: &str,
+    discriminator: Option<&Discriminator>,
+    log: &mut Log,
+) -> Result<(), Diagnostic> {
+    let fix = match discriminator {
+        Some(_) => "auto-tag-discriminators",
+        None => "tagged-enums",
+    };
     let Some(ty) = file.types.get(&path) else {
-        return Err(format!(
-            "\
-            failed to tag enum: path not found\n\
-            - path = {path}\n\
-            "
-        ));
+        return Err(not_found(file, "failed to tag enum: path not found", &path));
     };
     let TypeKind::Enum(e) = &ty.kind else {
-        return Err(format!(
-            "\
-            failed to tag enum: type is not an enum
-            - path = {path}\n\
-            "
-        ));
+        return Err(Diagnostic::error("failed to tag enum: type is not an enum").at(&*path));
     };
-    let mut to_fix = Vec::new();
+    let mut to_fix: Vec<(Path, FindKeywordResult)> = Vec::new();
     for variant in e.variants.values() {
         let Some(inner) = &variant.ty else {
-            return Err(format!(
-                "\
-                failed to tag enum: variant contains no inner value
-                - path = {path}
-                - variant = {}
-                ",
-                variant.name
-            ));
+            return Err(Diagnostic::error(format!(
+                "failed to tag enum: variant contains no inner value\n- variant = {}",
+                variant.name,
+            ))
+            .at(&*path));
         };
         let TypeRef::Ref(r) = &inner else {
-            return Err(format!(
-                "\
-                failed to tag enum: inner type is a literal
-                - path = {path}
-                - variant = {}
-                - type = {:?}
-                ",
-                variant.name, inner
-            ));
+            return Err(Diagnostic::error(format!(
+                "failed to tag enum: inner type is a literal\n- variant = {}\n- type = {:?}",
+                variant.name, inner,
+            ))
+            .at(&*path));
+        };
+        let res = match discriminator {
+            Some(discriminator) => discriminated_tag(file, r, discriminator).map_err(|err| {
+                Diagnostic::error(format!(
+                    "failed to tag enum: {err}\n- variant = {}",
+                    variant.name
+                ))
+                .at(&*path)
+            })?,
+            None => find_keyword(file, r.clone(), tag)?,
         };
-        match find_keyword(file, r.clone(), tag) {
-            Ok(res) => to_fix.push((variant.path.clone(), res)),
-            Err(err) => return Err(err),
+        if to_fix.iter().any(|(_, other)| other.value == res.value) {
+            return Err(Diagnostic::error(format!(
+                "failed to tag enum: several variants are tagged `{}`",
+                res.value
+            ))
+            .at(&*path));
         }
+        to_fix.push((variant.path.clone(), res));
     }
 
     // Fix the stuff now that we have mutable access to the file.
@@ -618,8 +1629,12 @@ fn tag_enum(file: &mut File, path: Path, tag: &str) -> Result<(), String> {
         unreachable!();
     };
     e.tag = EnumTag::Tagged(tag.to_owned());
+    log.record(fix, || format!("tagged `{path}` with `{tag}`"));
     for (var_path, res) in &to_fix {
         e.variants.get_mut(var_path).unwrap().name_in_json = Some(res.value.clone());
+        log.record(fix, || {
+            format!("variant `{var_path}` is tagged `{}`", res.value)
+        });
     }
 
     for (_, res) in &to_fix {
@@ -630,12 +1645,139 @@ fn tag_enum(file: &mut File, path: Path, tag: &str) -> Result<(), String> {
             };
             s.fields.remove(field_path);
             s.tags.insert(tag.to_owned(), res.value.clone());
+            log.record(fix, || format!("removed tag field `{field_path}`"));
         }
     }
 
     Ok(())
 }
 
+/// Tags the selected enums adjacently or externally, which wraps the content of their variants
+/// instead of mixing it with the tag.
+fn wrap_enums(
+    file: &mut File,
+    adjacent: &BTreeMap<String, AdjacentTag>,
+    external: &[String],
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
+    for (path, tag) in adjacent {
+        let (tag, content) = (&tag.tag, &tag.content);
+        let adjacent = EnumTag::Adjacent {
+            tag: tag.clone(),
+            content: content.clone(),
+        };
+        match set_enum_tag(file, path, adjacent) {
+            Ok(()) => log.record("adjacently-tagged-enums", || {
+                format!("tagged `{path}` with `{tag}` and `{content}`")
+            }),
+            Err(err) => errs.push(err),
+        }
+    }
+    for path in external {
+        match set_enum_tag(file, path, EnumTag::Normal) {
+            Ok(()) => log.record("externally-tagged-enums", || {
+                format!("tagged `{path}` externally")
+            }),
+            Err(err) => errs.push(err),
+        }
+    }
+}
+
+fn set_enum_tag(file: &mut File, path: &str, tag: EnumTag) -> Result<(), Diagnostic> {
+    match file.types.get_mut(path).map(|ty| &mut ty.kind) {
+        Some(TypeKind::Enum(e)) => {
+            e.tag = tag;
+            Ok(())
+        }
+        Some(_) => Err(Diagnostic::error("failed to tag enum: type is not an enum").at(path)),
+        None => Err(not_found(file, "failed to tag enum: path not found", path)),
+    }
+}
+
+/// Tags the enums that declare a `discriminator` and are not tagged yet.
+fn tag_discriminated_enums(file: &mut File, errs: &mut Vec<Diagnostic>, log: &mut Log) {
+    let enums: Vec<(Path, Discriminator)> = file
+        .types
+        .values()
+        .filter_map(|ty| match &ty.kind {
+            TypeKind::Enum(e) if matches!(e.tag, EnumTag::Untagged) => {
+                Some((ty.path.clone(), e.discriminator.clone()?))
+            }
+            _ => None,
+        })
+        .collect();
+    for (path, discriminator) in enums {
+        let tag = &discriminator.property;
+        if let Err(err) = tag_enum(file, path, tag, Some(&discriminator), log) {
+            errs.push(err);
+        }
+    }
+}
+
+/// Returns the tag of the variant holding the type at `path` in an enum tagged after
+/// `discriminator`, along with the fields holding it.
+///
+/// A struct that was already tagged by another enum must keep its tag.
+fn discriminated_tag(
+    file: &File,
+    path: &Path,
+    discriminator: &Discriminator,
+) -> Result<FindKeywordResult, String> {
+    let tag = &discriminator.property;
+    let keyword = find_keyword(file, path.clone(), tag).ok();
+    let mapped = discriminator
+        .mapping
+        .iter()
+        .find(|(_, target)| *target == path)
+        .map(|(value, _)| value.clone());
+    let value = mapped
+        .or_else(|| keyword.as_ref().map(|keyword| keyword.value.clone()))
+        .or_else(|| path.strip_prefix("#/components/schemas/").map(Into::into))
+        .ok_or("no value found for the variant")?;
+    if let Some(keyword) = keyword.filter(|keyword| keyword.fields.is_empty()) {
+        if keyword.value != value {
+            return Err(format!(
+                "`{path}` is already tagged `{}` by another enum, not `{value}`",
+                keyword.value
+            ));
+        }
+    }
+    let mut fields = Vec::new();
+    tag_fields(file, path, tag, &mut fields);
+    Ok(FindKeywordResult { fields, value })
+}
+
+/// Collects the `(struct, field)` paths of the fields named `name` in JSON held by the type at
+/// `path`, directly or through flattened fields and aliases.
+fn tag_fields(file: &File, path: &Path, name: &str, fields: &mut Vec<(Path, Path)>) {
+    match file.types.get(path).map(|ty| &ty.kind) {
+        Some(TypeKind::Struct(s)) => {
+            for field in s.fields.values() {
+                if field.name_in_json == name {
+                    fields.push((path.clone(), field.path.clone()));
+                } else if let (true, TypeRef::Ref(r)) = (field.flatten, &field.ty) {
+                    tag_fields(file, r, name, fields);
+                }
+            }
+        }
+        Some(TypeKind::Alias(a)) => {
+            if let TypeRef::Ref(r) = &a.ty {
+                tag_fields(file, r, name, fields);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Returns the error reported when several fields named `name` are found under `path`.
+fn multiple_keywords(path: &str, name: &str) -> Diagnostic {
+    Diagnostic::error(format!(
+        "failed to tag enum: multiple fields with the same name found\n- name = {name}"
+    ))
+    .at(path)
+}
+
 /// The result of looking for a keyword field.
 #[derive(Debug)]
 struct FindKeywordResult {
@@ -656,14 +1798,9 @@ struct FindKeywordResult {
 /// - `path`: The path to the struct to analyze. [`None`] is returned if the type is not a struct.
 ///
 /// - `name`: The name of the field.
-fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult, String> {
+fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult, Diagnostic> {
     let Some(ty) = file.types.get(&path) else {
-        return Err(format!(
-            "\
-            failed to tag enum: path not found\n\
-            - path = {path}\n\
-            "
-        ));
+        return Err(not_found(file, "failed to tag enum: path not found", &path));
     };
     match &ty.kind {
         TypeKind::Struct(s) => {
@@ -680,25 +1817,14 @@ fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult
             for field in s.fields.values() {
                 if field.name_in_json == name {
                     let TypeRef::Keyword(value) = &field.ty else {
-                        return Err(format!(
-                            "\
-                            failed to tag enum: field is not a keyword\n\
-                            - path = {path}\n\
-                            - field = {field}\n\
-                            - name = {name}\n\
-                            ",
-                            field = field.path,
-                        ));
+                        return Err(Diagnostic::error(format!(
+                            "failed to tag enum: field is not a keyword\n- name = {name}"
+                        ))
+                        .at(&*field.path));
                     };
 
                     if ret.is_some() {
-                        return Err(format!(
-                            "\
-                            failed to tag enum: multiple fields with the same name found\n\
-                            - path = {path}\n\
-                            - name = {name}\n\
-                            ",
-                        ));
+                        return Err(multiple_keywords(&path, name));
                     }
 
                     ret = Some(FindKeywordResult {
@@ -718,13 +1844,7 @@ fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult
                 let ret2 = find_keyword(file, r.clone(), name);
 
                 if ret.is_some() && ret2.is_ok() {
-                    return Err(format!(
-                        "\
-                        failed to tag enum: multiple fields with the same name found\n\
-                        - path = {path}\n\
-                        - name = {name}\n\
-                        ",
-                    ));
+                    return Err(multiple_keywords(&path, name));
                 } else if ret2.is_ok() {
                     ret = Some(ret2.unwrap());
                 }
@@ -733,23 +1853,18 @@ fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult
             if let Some(ret) = ret {
                 Ok(ret)
             } else {
-                Err(format!(
-                    "\
-                    failed to tag enum: no field with the requested name found in struct fields\n\
-                    - path = {path}\n\
-                    - name = {name}\n\
-                    ",
+                Err(Diagnostic::error(format!(
+                    "failed to tag enum: no field with the requested name found in struct fields\n\
+                    - name = {name}",
                 ))
+                .at(&*path))
             }
         }
         TypeKind::Enum(e) => {
             if matches!(e.tag, EnumTag::Normal) {
-                return Err(format!(
-                    "\
-                    failed to tag enum: enum is already tagged\n\
-                    - path = {path}\n\
-                    ",
-                ));
+                return Err(
+                    Diagnostic::error("failed to tag enum: enum is already tagged").at(&*path),
+                );
             }
 
             // For enums, all variants must have the same value.
@@ -758,26 +1873,19 @@ fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult
 
             for variant in e.variants.values() {
                 let Some(ty) = &variant.ty else {
-                    return Err(format!(
-                        "\
-                        failed to tag enum: variant contains no inner value
-                        - path = {path}
-                        - variant = {}
-                        ",
+                    return Err(Diagnostic::error(format!(
+                        "failed to tag enum: variant contains no inner value\n- variant = {}",
                         variant.name,
-                    ));
+                    ))
+                    .at(&*path));
                 };
 
                 let TypeRef::Ref(r) = ty else {
-                    return Err(format!(
-                        "\
-                        failed to tag enum: inner type is a literal
-                        - path = {path}
-                        - variant = {}
-                        - type = {:?}
-                        ",
+                    return Err(Diagnostic::error(format!(
+                        "failed to tag enum: inner type is a literal\n- variant = {}\n- type = {:?}",
                         variant.name, ty,
-                    ));
+                    ))
+                    .at(&*path));
                 };
 
                 match find_keyword(file, r.clone(), name) {
@@ -785,13 +1893,7 @@ fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult
                         match value {
                             Some(ref val) => {
                                 if val != &ret.value {
-                                    return Err(format!(
-                                        "\
-                                    failed to tag enum: multiple fields with the same name found\n\
-                                    - path = {path}\n\
-                                    - name = {name}\n\
-                                    ",
-                                    ));
+                                    return Err(multiple_keywords(&path, name));
                                 }
                             }
                             None => value = Some(ret.value.clone()),
@@ -804,13 +1906,11 @@ fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult
             }
 
             if value.is_none() {
-                return Err(format!(
-                    "\
-                    failed to tag enum: no field with the requested name found in enum variants\n\
-                    - path = {path}\n\
-                    - name = {name}\n\
-                    ",
-                ));
+                return Err(Diagnostic::error(format!(
+                    "failed to tag enum: no field with the requested name found in enum variants\n\
+                    - name = {name}",
+                ))
+                .at(&*path));
             }
 
             Ok(FindKeywordResult {
@@ -821,14 +1921,11 @@ fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult
         TypeKind::Alias(e) => {
             // For aliases, we can just check transitively.
             let TypeRef::Ref(r) = &e.ty else {
-                return Err(format!(
-                    "\
-                    failed to tag enum: inner type is a literal
-                    - path = {}
-                    - type = {:?}
-                    ",
-                    path, e.ty,
-                ));
+                return Err(Diagnostic::error(format!(
+                    "failed to tag enum: inner type is a literal\n- type = {:?}",
+                    e.ty,
+                ))
+                .at(&*path));
             };
 
             find_keyword(file, r.clone(), name)
@@ -836,15 +1933,23 @@ fn find_keyword(file: &File, path: Path, name: &str) -> Result<FindKeywordResult
     }
 }
 
-fn set_tags(file: &mut File, keywords: &BTreeMap<String, String>, errs: &mut Vec<String>) {
+fn set_tags(
+    file: &mut File,
+    keywords: &BTreeMap<String, String>,
+    errs: &mut Vec<Diagnostic>,
+    log: &mut Log,
+) {
     for (path, by) in keywords {
-        if let Err(err) = set_tag(file, path, by) {
-            errs.push(err);
+        match set_tag(file, path, by) {
+            Ok(()) => log.record("set-tags", || {
+                format!("removed `{path}`, recording it as a tag with value `{by}`")
+            }),
+            Err(err) => errs.push(err),
         }
     }
 }
 
-fn set_tag(file: &mut File, path: &str, value: &str) -> Result<(), String> {
+fn set_tag(file: &mut File, path: &str, value: &str) -> Result<(), Diagnostic> {
     for ty in file.types.values_mut() {
         let TypeKind::Struct(s) = &mut ty.kind else {
             continue;
@@ -861,12 +1966,7 @@ fn set_tag(file: &mut File, path: &str, value: &str) -> Result<(), String> {
         }
     }
 
-    Err(format!(
-        "\
-        can't make keyword: path not found:\n\
-        - path = {path}\n\
-        ",
-    ))
+    Err(not_found(file, "can't make keyword: path not found", path))
 }
 
 // The two following functions (`common_prefix` and `common_suffix`) both work on words rather
@@ -968,3 +2068,160 @@ where
         suffix = candidate;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Parses a document declaring the provided component schemas.
+    fn parse_schemas(schemas: serde_json::Value) -> File {
+        let raw = json!({
+            "openrpc": "1.2.6",
+            "info": { "title": "Test", "version": "1.0.0" },
+            "methods": [],
+            "components": { "schemas": schemas },
+        });
+        let document = crate::Document::from_json(raw).unwrap();
+        crate::parse::parse(&document, &Config::default()).unwrap()
+    }
+
+    /// Applies the provided substitutions to `file`, returning the errors and the trace.
+    fn apply(file: &mut File, substitutions: &[(&str, &str)]) -> (Vec<Diagnostic>, Vec<String>) {
+        let substitutions = substitutions
+            .iter()
+            .map(|(pattern, replacement)| (pattern.to_string(), replacement.to_string()))
+            .collect();
+        let mut errs = Vec::new();
+        let mut log = Log {
+            tracing: true,
+            ..Log::default()
+        };
+        rename_regex(file, &substitutions, &mut errs, &mut log);
+        (errs, log.trace)
+    }
+
+    /// Returns the names of the fields of the struct at `path`.
+    fn field_names(file: &File, path: &str) -> Vec<String> {
+        match &file.types[path].kind {
+            TypeKind::Struct(s) => s.fields.values().map(|f| f.name.clone()).collect(),
+            _ => panic!("`{path}` is not a struct"),
+        }
+    }
+
+    #[test]
+    fn rename_regex_matches_rust_names() {
+        let mut file = parse_schemas(json!({
+            "INVOKE_TXN": {
+                "type": "object",
+                "properties": { "txn_hash": { "type": "string" } },
+            },
+        }));
+        let (errs, _) = apply(
+            &mut file,
+            &[("Txn$", "Transaction"), ("^txn_", "transaction_")],
+        );
+        assert!(errs.is_empty());
+        let path = "#/components/schemas/INVOKE_TXN";
+        assert_eq!(file.types[path].name, "InvokeTransaction");
+        assert_eq!(field_names(&file, path), ["transaction_hash"]);
+    }
+
+    #[test]
+    fn rename_regex_ignores_document_names() {
+        let mut file = parse_schemas(json!({ "INVOKE_TXN": { "type": "string" } }));
+        let (errs, trace) = apply(&mut file, &[("TXN$", "Transaction")]);
+        assert!(errs.is_empty());
+        assert!(trace.is_empty());
+        assert_eq!(
+            file.types["#/components/schemas/INVOKE_TXN"].name,
+            "InvokeTxn"
+        );
+    }
+
+    #[test]
+    fn rename_regex_expands_groups() {
+        let mut file = parse_schemas(json!({
+            "BLOCK_HEADER": { "type": "string" },
+            "PENDING_BLOCK_HEADER": { "type": "string" },
+        }));
+        let (errs, trace) = apply(&mut file, &[("^(\\w*)BlockHeader$", "${1}Header")]);
+        assert!(errs.is_empty());
+        assert_eq!(trace.len(), 2);
+        assert_eq!(
+            file.types["#/components/schemas/BLOCK_HEADER"].name,
+            "Header"
+        );
+        assert_eq!(
+            file.types["#/components/schemas/PENDING_BLOCK_HEADER"].name,
+            "PendingHeader"
+        );
+    }
+
+    #[test]
+    fn rename_regex_reports_invalid_expressions() {
+        let mut file = parse_schemas(json!({ "BLOCK": { "type": "string" } }));
+        let (errs, _) = apply(&mut file, &[("(Block", "Header")]);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(file.types["#/components/schemas/BLOCK"].name, "Block");
+    }
+
+    #[test]
+    fn auto_tag_discriminators_reports_shared_variant_conflicts() {
+        let variant = |name: &str| json!({ "$ref": format!("#/components/schemas/{name}") });
+        let mut file = parse_schemas(json!({
+            "CAT": {
+                "type": "object",
+                "properties": { "kind": { "type": "string" } },
+            },
+            "DOG": {
+                "type": "object",
+                "properties": { "kind": { "type": "string" } },
+            },
+            "PET": {
+                "oneOf": [variant("CAT"), variant("DOG")],
+                "discriminator": {
+                    "propertyName": "kind",
+                    "mapping": { "cat": "#/components/schemas/CAT" },
+                },
+            },
+            "ANIMAL": {
+                "oneOf": [variant("CAT"), variant("DOG")],
+                "discriminator": {
+                    "propertyName": "kind",
+                    "mapping": { "feline": "#/components/schemas/CAT" },
+                },
+            },
+        }));
+        let mut errs = Vec::new();
+        tag_discriminated_enums(&mut file, &mut errs, &mut Log::default());
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].message.contains("is already tagged"));
+    }
+
+    #[test]
+    fn inline_errors_are_named_after_their_codes() {
+        let method = |name: &str, message: &str| {
+            json!({
+                "name": name,
+                "params": [],
+                "result": { "name": "result", "schema": { "type": "string" } },
+                "errors": [{ "code": -32000, "message": message }],
+            })
+        };
+        let raw = json!({
+            "openrpc": "1.2.6",
+            "info": { "title": "Test", "version": "1.0.0" },
+            "methods": [method("a", "1 block not found"), method("b", "Other")],
+        });
+        let document = crate::Document::from_json(raw).unwrap();
+        let config = Config::default();
+        let mut file = crate::parse::parse(&document, &config).unwrap();
+        let mut errs = Vec::new();
+        check_collisions(&mut file, &config, &mut errs, &mut Log::default());
+        assert!(errs.is_empty());
+        let names: Vec<_> = file.errors.values().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["ErrorMinus32000", "ErrorMinus32000_2"]);
+    }
+}
@@ -0,0 +1,63 @@
+//! The files of the cargo-fuzz crate generated with `--fuzz`.
+
+use std::io;
+
+/// Writes the `Cargo.toml` file of the fuzz crate, which fuzzes the crate named `name` at
+/// `path` through the provided targets.
+pub fn manifest(
+    w: &mut dyn io::Write,
+    name: &str,
+    path: &str,
+    targets: &[String],
+) -> io::Result<()> {
+    writeln!(w, "[package]")?;
+    writeln!(w, "name = \"{name}-fuzz\"")?;
+    writeln!(w, "version = \"0.0.0\"")?;
+    writeln!(w, "publish = false")?;
+    writeln!(w, "edition = \"2021\"")?;
+    writeln!(w)?;
+    writeln!(w, "[package.metadata]")?;
+    writeln!(w, "cargo-fuzz = true")?;
+    writeln!(w)?;
+    writeln!(w, "[dependencies]")?;
+    writeln!(w, "libfuzzer-sys = \"0.4\"")?;
+    writeln!(w, "serde_json = \"1\"")?;
+    writeln!(w)?;
+    writeln!(w, "[dependencies.{name}]")?;
+    writeln!(w, "path = {path:?}")?;
+    for target in targets {
+        writeln!(w)?;
+        writeln!(w, "[[bin]]")?;
+        writeln!(w, "name = {target:?}")?;
+        writeln!(w, "path = \"fuzz_targets/{target}.rs\"")?;
+        writeln!(w, "test = false")?;
+        writeln!(w, "doc = false")?;
+        writeln!(w, "bench = false")?;
+    }
+
+    Ok(())
+}
+
+/// Writes a fuzz target deserializing arbitrary bytes into the type at `path`, and serializing
+/// back the values it accepts.
+pub fn target(w: &mut dyn io::Write, path: &str) -> io::Result<()> {
+    writeln!(
+        w,
+        "// This file was automatically generated by openrpc-gen."
+    )?;
+    writeln!(w)?;
+    writeln!(w, "#![no_main]")?;
+    writeln!(w)?;
+    writeln!(w, "use libfuzzer_sys::fuzz_target;")?;
+    writeln!(w)?;
+    writeln!(w, "fuzz_target!(|data: &[u8]| {{")?;
+    writeln!(
+        w,
+        "    if let Ok(value) = serde_json::from_slice::<{path}>(data) {{"
+    )?;
+    writeln!(w, "        serde_json::to_vec(&value).unwrap();")?;
+    writeln!(w, "    }}")?;
+    writeln!(w, "}});")?;
+
+    Ok(())
+}
@@ -1,12 +1,23 @@
 //! Contains the code that actually generates the Rust code.
 
 use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
 
 use convert_case::{Case, Casing};
 use open_rpc::ParamStructure;
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote, ToTokens};
 
-use crate::parse::{EnumTag, TypeDef, TypeKind, TypeRef};
+use crate::config::{ErrorEnums, Pointer, SerdeContainer};
+use crate::deps::TypeDeps;
+use crate::diagnostics::Diagnostic;
+use crate::glob;
+use crate::ident;
+use crate::parse::{
+    AliasDef, Constraints, EnumDef, EnumTag, EnumVariant, ErrorDef, Method, MethodParameter,
+    StructDef, StructField, TypeDef, TypeKind, TypeRef,
+};
 
 /// Contains the state of the generator.
 struct Ctx<'a> {
@@ -16,413 +27,5712 @@ struct Ctx<'a> {
     pub file: &'a crate::parse::File,
     /// The configuration used to generate the file.
     pub config: &'a crate::config::Config,
+    /// Maps the name of the generated types to the generic parameters they take.
+    pub generics: BTreeMap<String, Vec<&'a str>>,
+    /// The names of the types that can implement `Default`.
+    ///
+    /// This is only computed when `derive-default` is enabled.
+    pub default_types: BTreeSet<String>,
+    /// The names of the types that can implement `PartialEq`, `Eq` and `Hash`.
+    ///
+    /// This is only computed when `derive-eq` is enabled.
+    pub eq_types: BTreeSet<String>,
+    /// The dependency graph between the generated types.
+    ///
+    /// This is only computed when `arbitrary` is enabled.
+    pub deps: Option<TypeDeps>,
+}
+
+impl<'a> Ctx<'a> {
+    /// Creates the state of the generator for the provided file and configuration.
+    fn new(file: &'a crate::parse::File, config: &'a crate::config::Config) -> Self {
+        let generics = type_generics(file, config);
+        let default_types = if config.generation.derive_default {
+            TypeDeps::new(file, config)
+                .default_types(&config.deps.default_types)
+                .into_iter()
+                .map(str::to_owned)
+                .collect()
+        } else {
+            BTreeSet::new()
+        };
+        let eq_types = if config.generation.derive_eq {
+            TypeDeps::new(file, config)
+                .eq_types(&config.deps.eq_types)
+                .into_iter()
+                .map(str::to_owned)
+                .collect()
+        } else {
+            BTreeSet::new()
+        };
+        let deps = config
+            .generation
+            .arbitrary
+            .then(|| TypeDeps::new(file, config));
+        Self {
+            file,
+            config,
+            generics,
+            default_types,
+            eq_types,
+            deps,
+        }
+    }
+
+    /// Returns whether `Copy`, `PartialEq`, `Eq` and `Hash` should be derived on the
+    /// provided enum.
+    pub fn is_copy(&self, e: &EnumDef) -> bool {
+        self.config.generation.copy_enums && (e.copy || e.variants.values().all(|v| v.ty.is_none()))
+    }
+
+    /// Returns the provided attribute, which is only valid when serde is available.
+    ///
+    /// `attr` is the content of the attribute, such as `serde(untagged)`. It is wrapped in a
+    /// `cfg_attr` when `serde-feature` is set.
+    pub fn serde_attr(&self, attr: TokenStream) -> TokenStream {
+        match &self.config.generation.serde_feature {
+            Some(feature) => quote!(#[cfg_attr(feature = #feature, #attr)]),
+            None => quote!(#[#attr]),
+        }
+    }
+
+    /// Returns the `#[cfg(...)]` attribute to put on items that require serde, which is empty
+    /// unless `serde-feature` is set.
+    pub fn serde_cfg(&self) -> TokenStream {
+        match &self.config.generation.serde_feature {
+            Some(feature) => quote!(#[cfg(feature = #feature)]),
+            None => TokenStream::new(),
+        }
+    }
+
+    /// Returns the serde container options of the provided type.
+    pub fn serde_options(&self, ty: &TypeDef) -> SerdeContainer {
+        let generation = &self.config.generation;
+        generation
+            .type_serde
+            .iter()
+            .filter(|(pattern, _)| glob::matches(pattern, &ty.path))
+            .fold(generation.serde.clone(), |opts, (_, o)| opts.merge(o))
+    }
+
+    /// Returns the serde container attributes of the provided type.
+    pub fn serde_container(&self, ty: &TypeDef) -> TokenStream {
+        let opts = self.serde_options(ty);
+
+        let mut ret = TokenStream::new();
+        if let Some(rename_all) = &opts.rename_all {
+            ret.extend(self.serde_attr(quote!(serde(rename_all = #rename_all))));
+        }
+        if opts.deny_unknown_fields == Some(true) {
+            ret.extend(self.serde_attr(quote!(serde(deny_unknown_fields))));
+        }
+        if opts.default == Some(true) && matches!(ty.kind, TypeKind::Struct(_)) {
+            ret.extend(self.serde_attr(quote!(serde(default))));
+        }
+        ret
+    }
+
+    /// Returns whether the optional field at `path` should be skipped when serialized as
+    /// `None`.
+    pub fn skip_serializing_none(&self, path: &str) -> bool {
+        let generation = &self.config.generation;
+        generation
+            .skip_serializing_none_fields
+            .iter()
+            .rfind(|(pattern, _)| glob::matches(pattern, path))
+            .map_or(generation.skip_serializing_none, |(_, skip)| *skip)
+    }
+
+    /// Returns whether the provided field should be generated as a `Maybe<T>`.
+    pub fn is_tri_state(&self, field: &StructField) -> bool {
+        !field.required
+            && self
+                .config
+                .generation
+                .tri_state_fields
+                .iter()
+                .any(|pattern| glob::matches(pattern, &field.path))
+    }
+
+    /// Returns the fields that should be generated as a `Maybe<T>`.
+    pub fn tri_state_fields(&self) -> impl Iterator<Item = &'a StructField> + '_ {
+        self.file
+            .types
+            .values()
+            .filter_map(|ty| match &ty.kind {
+                TypeKind::Struct(s) => Some(s.fields.values()),
+                _ => None,
+            })
+            .flatten()
+            .filter(|field| self.is_tri_state(field))
+    }
+
+    /// Returns the Rust type of the provided field.
+    pub fn field_type_name(&self, field: &'a StructField) -> Cow<'a, str> {
+        if self.is_tri_state(field) {
+            Cow::Owned(format!("Maybe<{}>", self.type_ref_name(&field.ty, true)))
+        } else {
+            self.type_ref_name(&field.ty, field.required)
+        }
+    }
+
+    /// Returns the module used to (de)serialize the provided field, if any.
+    pub fn field_with_module(&self, field: &'a StructField) -> Option<&'a str> {
+        field
+            .serde_with
+            .as_deref()
+            .or_else(|| field.ty.with_module_at(self.config, self.file, &field.path))
+    }
+
+    /// Returns a Rust expression evaluating to `value`, the default value of `field`.
+    pub fn default_expr(
+        &self,
+        field: &'a StructField,
+        value: &serde_json::Value,
+    ) -> io::Result<TokenStream> {
+        let json = value.to_string();
+        if let Some(module) = self.field_with_module(field) {
+            let module = parse_path(module)?;
+            return Ok(quote! {
+                #module::deserialize(&mut serde_json::Deserializer::from_str(#json))
+                    .expect("invalid default value")
+            });
+        }
+
+        // Literals are written directly when the type of the field is a primitive.
+        let mut ty = &field.ty;
+        while let TypeRef::Ref(path) = ty {
+            match self.file.types.get(path).map(|ty| &ty.kind) {
+                Some(TypeKind::Alias(alias))
+                    if !self.config.generation.is_newtype(path)
+                        && !self.is_validated(alias, path) =>
+                {
+                    ty = &alias.ty
+                }
+                _ => break,
+            }
+        }
+        let expr = match (ty, value) {
+            (TypeRef::Boolean, serde_json::Value::Bool(b)) => quote!(#b),
+            (TypeRef::Integer { .. }, serde_json::Value::Number(n)) if !n.is_f64() => {
+                number_literal(n)
+            }
+            (TypeRef::Number, serde_json::Value::Number(n)) => float_literal(n.as_f64().unwrap()),
+            (TypeRef::String | TypeRef::Keyword(_), serde_json::Value::String(s)) => {
+                quote!(#s.into())
+            }
+            _ => quote!(serde_json::from_str(#json).expect("invalid default value")),
+        };
+
+        Ok(if self.is_tri_state(field) {
+            quote!(Maybe::Value(#expr))
+        } else if !field.required {
+            quote!(Some(#expr))
+        } else {
+            expr
+        })
+    }
+
+    /// Returns whether the provided type is generated as a unit struct.
+    pub fn is_unit_struct(&self, ty: &TypeDef) -> bool {
+        self.config.generation.unit_structs
+            && matches!(&ty.kind, TypeKind::Struct(s) if s.fields.is_empty())
+            && !self.has_extra_fields(&ty.path)
+    }
+
+    /// Returns whether the parameters of the provided method are represented by the shared
+    /// `NoParams` unit struct.
+    pub fn has_shared_params(&self, method: &Method) -> bool {
+        self.config.generation.unit_structs && method.params.is_empty()
+    }
+
+    /// Returns whether the alias at `path` is generated as a newtype validating its pattern.
+    pub fn is_validated(&self, alias: &AliasDef, path: &str) -> bool {
+        alias.constraints.pattern.is_some() && self.config.generation.is_pattern_newtype(path)
+    }
+
+    /// Returns whether the type at `path` should be marked `#[non_exhaustive]`.
+    pub fn is_non_exhaustive(&self, path: &str) -> bool {
+        let generation = &self.config.generation;
+        generation
+            .non_exhaustive_types
+            .iter()
+            .rfind(|(pattern, _)| glob::matches(pattern, path))
+            .map_or(generation.non_exhaustive, |(_, yes)| *yes)
+    }
+
+    /// Returns the fields (de)serialized by the provided struct, including the ones of the
+    /// structs it flattens, when it should implement `Serialize` and `Deserialize` by hand.
+    ///
+    /// Returns `None` when the struct keeps the derived implementations, either because
+    /// `manual-flatten` is disabled, because it does not flatten any struct, or because
+    /// something it holds is not supported.
+    pub fn flattened_fields<'t>(&self, ty: &'t TypeDef) -> Option<Vec<FlatField<'t>>>
+    where
+        'a: 't,
+    {
+        let TypeKind::Struct(s) = &ty.kind else {
+            return None;
+        };
+        if !self.config.generation.manual_flatten || !s.fields.values().any(|f| f.flatten) {
+            return None;
+        }
+        let mut fields = Vec::new();
+        self.collect_flattened(ty, s, "", &mut vec![&*ty.path], &mut fields)?;
+        // Two fields sharing a key can't be told apart.
+        let mut keys = fields
+            .iter()
+            .map(|f| &f.field.name_in_json)
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        (keys.len() == fields.len()).then_some(fields)
+    }
+
+    /// Appends the fields of `s`, the struct `ty`, to `fields`, recursing into the flattened
+    /// ones.
+    ///
+    /// `access` is the path of the fields of `s` from the outermost struct, such as `header.`.
+    fn collect_flattened<'t>(
+        &self,
+        ty: &'t TypeDef,
+        s: &'t StructDef,
+        access: &str,
+        visited: &mut Vec<&'t str>,
+        fields: &mut Vec<FlatField<'t>>,
+    ) -> Option<()>
+    where
+        'a: 't,
+    {
+        let opts = self.serde_options(ty);
+        if self.generics.contains_key(&ty.name)
+            || self.has_extra_fields(&ty.path)
+            || self.is_unit_struct(ty)
+            || opts.rename_all.is_some()
+            || opts.default == Some(true)
+        {
+            return None;
+        }
+        for field in s.fields.values() {
+            if self.field_with_module(field).is_some() {
+                return None;
+            }
+            if !field.flatten {
+                fields.push(FlatField {
+                    owner: ty,
+                    field,
+                    access: format!("{access}{}", field.name),
+                });
+                continue;
+            }
+            let TypeRef::Ref(path) = &field.ty else {
+                return None;
+            };
+            let inner = self.file.types.get(path)?;
+            let TypeKind::Struct(inner_s) = &inner.kind else {
+                return None;
+            };
+            if !field.required || visited.contains(&&**path) {
+                return None;
+            }
+            visited.push(path);
+            let access = format!("{access}{}.", field.name);
+            self.collect_flattened(inner, inner_s, &access, visited, fields)?;
+            visited.pop();
+        }
+        Some(())
+    }
+
+    /// Returns whether the struct at `path` should get a builder.
+    pub fn has_builder(&self, path: &str) -> bool {
+        self.config
+            .generation
+            .builders
+            .iter()
+            .any(|pattern| glob::matches(pattern, path))
+    }
+
+    /// Returns whether the struct at `path` should collect its unknown fields.
+    pub fn has_extra_fields(&self, path: &str) -> bool {
+        self.config
+            .generation
+            .extra_fields
+            .iter()
+            .any(|pattern| glob::matches(pattern, path))
+    }
+
+    /// Returns the attributes configured for the item at `path`.
+    pub fn attributes(&self, path: &str) -> io::Result<Vec<syn::Attribute>> {
+        let mut ret = Vec::new();
+        for (pattern, attrs) in &self.config.generation.attributes {
+            if glob::matches(pattern, path) {
+                for attr in attrs {
+                    ret.extend(parse_with(syn::Attribute::parse_outer, attr)?);
+                }
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Returns the name of the type referenced by the provided [`TypeRef`].
+    pub fn type_ref_name(&self, r: &'a TypeRef, required: bool) -> Cow<'a, str> {
+        // Optional values that may be `null` don't distinguish a missing value from `null`.
+        if !required && !matches!(r, TypeRef::Nullable(_)) {
+            let inner = self.type_ref_name(r, true);
+            return Cow::Owned(self.config.primitives.optional.replace("{}", &inner));
+        }
+
+        match r {
+            TypeRef::Array(inner) => Cow::Owned(
+                self.config
+                    .primitives
+                    .array
+                    .replace("{}", &self.type_ref_name(inner, true)),
+            ),
+            TypeRef::Set(inner) => Cow::Owned(
+                self.config
+                    .primitives
+                    .set
+                    .as_deref()
+                    .unwrap_or(&self.config.primitives.array)
+                    .replace("{}", &self.type_ref_name(inner, true)),
+            ),
+            TypeRef::Map(inner) => Cow::Owned(
+                self.map_primitive()
+                    .replace("{}", &self.type_ref_name(inner, true)),
+            ),
+            TypeRef::Boxed(pointer, inner) => {
+                let alloc = if self.config.generation.no_std {
+                    "alloc"
+                } else {
+                    "std"
+                };
+                let pointer = match pointer {
+                    Pointer::Box => String::from("Box"),
+                    Pointer::Arc => format!("{alloc}::sync::Arc"),
+                    Pointer::Rc => format!("{alloc}::rc::Rc"),
+                };
+                Cow::Owned(format!("{pointer}<{}>", self.type_ref_name(inner, true)))
+            }
+            TypeRef::Nullable(inner) => Cow::Owned(
+                self.config
+                    .primitives
+                    .optional
+                    .replace("{}", &self.type_ref_name(inner, true)),
+            ),
+            TypeRef::Tuple(items) => {
+                let items: Vec<_> = items.iter().map(|i| self.type_ref_name(i, true)).collect();
+                match items.as_slice() {
+                    [item] => Cow::Owned(format!("({item},)")),
+                    _ => Cow::Owned(format!("({})", items.join(", "))),
+                }
+            }
+            TypeRef::Boolean => Cow::Borrowed(&self.config.primitives.boolean),
+            TypeRef::Integer {
+                format,
+                unsigned,
+                maximum,
+                ..
+            } => {
+                let primitives = &self.config.primitives;
+                let unsigned = primitives.unsigned_integer.as_ref().filter(|_| *unsigned);
+                match format
+                    .as_ref()
+                    .and_then(|f| primitives.integer_formats.get(f))
+                {
+                    Some(ty) => Cow::Borrowed(ty),
+                    None => match unsigned {
+                        Some(ty) => Cow::Borrowed(narrowest_unsigned(*maximum).unwrap_or(ty)),
+                        None => Cow::Borrowed(&primitives.integer),
+                    },
+                }
+            }
+            // Big integers are only parsed when a type is configured for them.
+            TypeRef::BigInteger => Cow::Borrowed(
+                self.config
+                    .primitives
+                    .big_integer
+                    .as_deref()
+                    .unwrap_or("BigInteger"),
+            ),
+            TypeRef::Null => Cow::Borrowed(&self.config.primitives.null),
+            TypeRef::Any => Cow::Borrowed(&self.config.primitives.any),
+            TypeRef::Number => Cow::Borrowed(&self.config.primitives.number),
+            TypeRef::String => self.string_primitive(),
+            TypeRef::Format(format) => match self.config.primitives.formats.get(format) {
+                Some(format) => Cow::Borrowed(format.ty()),
+                None => self.string_primitive(),
+            },
+            TypeRef::Keyword(_) => self.string_primitive(),
+            TypeRef::Constant(val) => {
+                let primitives = &self.config.primitives;
+                let ty = match val {
+                    serde_json::Value::Bool(_) => &primitives.boolean,
+                    serde_json::Value::Number(n) if n.is_f64() => &primitives.number,
+                    _ => &primitives.integer,
+                };
+                Cow::Borrowed(ty)
+            }
+            TypeRef::Ref(path) => match self.file.types.get(path) {
+                Some(ty) => match self.generics.get(&ty.name) {
+                    Some(params) => Cow::Owned(format!("{}<{}>", ty.name, params.join(", "))),
+                    None => Cow::Borrowed(&ty.name),
+                },
+                None => Cow::Borrowed("BrokenReference"),
+            },
+            TypeRef::ExternalRef(name) => Cow::Borrowed(name),
+        }
+    }
+
+    /// Returns the type used to represent strings.
+    ///
+    /// This is a `Cow` borrowing from the deserialized input when `borrowed` is enabled.
+    pub fn string_primitive(&self) -> Cow<'a, str> {
+        if !self.config.generation.borrowed {
+            return Cow::Borrowed(&self.config.primitives.string);
+        }
+        let alloc = if self.config.generation.no_std {
+            "alloc"
+        } else {
+            "std"
+        };
+        Cow::Owned(format!("{alloc}::borrow::Cow<'a, str>"))
+    }
+
+    /// Returns the generic parameters mentioned by the provided [`TypeRef`].
+    pub fn type_ref_generics(&self, r: &TypeRef) -> Vec<&'a str> {
+        if self.config.generation.borrowed && is_string(self.config, r) {
+            return vec![LIFETIME];
+        }
+        match r {
+            TypeRef::Ref(path) => self
+                .file
+                .types
+                .get(path)
+                .and_then(|ty| self.generics.get(&ty.name))
+                .cloned()
+                .unwrap_or_default(),
+            TypeRef::ExternalRef(name) => self
+                .config
+                .generation
+                .generics
+                .keys()
+                .filter(|param| *param == name)
+                .map(String::as_str)
+                .collect(),
+            TypeRef::Array(inner)
+            | TypeRef::Set(inner)
+            | TypeRef::Map(inner)
+            | TypeRef::Boxed(_, inner)
+            | TypeRef::Nullable(inner) => self.type_ref_generics(inner),
+            TypeRef::Tuple(items) => items
+                .iter()
+                .flat_map(|item| self.type_ref_generics(item))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the generic parameters mentioned by the parameters of the provided method, in
+    /// the order they are declared in the configuration.
+    pub fn method_generics(&self, method: &Method) -> Vec<&'a str> {
+        let mut used = Vec::new();
+        for param in &method.params {
+            used.extend(self.type_ref_generics(&param.ty));
+        }
+        let lifetime = used.contains(&LIFETIME).then_some(LIFETIME);
+        lifetime
+            .into_iter()
+            .chain(
+                self.config
+                    .generation
+                    .generics
+                    .keys()
+                    .map(String::as_str)
+                    .filter(|param| used.contains(param)),
+            )
+            .collect()
+    }
+
+    /// Returns the declaration of the provided generic parameters, along with their bounds.
+    ///
+    /// i.e. `<F: Default + Serialize>`, or nothing when `params` is empty.
+    pub fn generics_decl(&self, params: &[&str]) -> io::Result<TokenStream> {
+        self.generics_decl_with(params, &[])
+    }
+
+    /// Returns the declaration of the provided generic parameters along with their bounds,
+    /// followed by the `extra` parameters, which are written as is.
+    pub fn generics_decl_with(&self, params: &[&str], extra: &[&str]) -> io::Result<TokenStream> {
+        if params.is_empty() && extra.is_empty() {
+            return Ok(TokenStream::new());
+        }
+        let params = params
+            .iter()
+            .map(|param| match self.config.generation.generics.get(*param) {
+                Some(bounds) if !bounds.trim().is_empty() => format!("{param}: {bounds}"),
+                _ => param.to_string(),
+            })
+            .chain(extra.iter().map(|param| param.to_string()))
+            .map(|param| parse_code::<syn::GenericParam>(&param))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(quote!(<#(#params),*>))
+    }
+
+    /// Returns the name of the method, stripped from the configured prefix.
+    pub fn method_ident_base<'m>(&self, method: &'m Method) -> &'m str {
+        if let Some(ref prefix) = self.config.generation.method_name_prefix {
+            method.name.strip_prefix(prefix).unwrap_or(&method.name)
+        } else {
+            &method.name
+        }
+    }
+
+    /// Returns the PascalCase identifier of the provided method, from which the names of the
+    /// types generated for it are built.
+    pub fn method_ident(&self, method: &Method) -> String {
+        let acronyms = &self.config.generation.acronyms;
+        ident::pascal(self.method_ident_base(method), acronyms)
+    }
+
+    /// Returns the name of the functions generated for the provided method.
+    pub fn method_fn_name(&self, method: &Method) -> String {
+        let name = ident::snake(self.method_ident_base(method));
+        ident::escape(&name, &self.config.generation.keyword_renames)
+    }
+
+    /// Returns the name of the constructor of the `JsonRpcRequest` of the provided method.
+    ///
+    /// The names of the constructors shared by all the requests are suffixed with `_method`.
+    pub fn envelope_fn_name(&self, method: &Method) -> String {
+        let name = self.method_fn_name(method);
+        match name.as_str() {
+            "new" | "notification" => format!("{name}_method"),
+            _ => name,
+        }
+    }
+
+    /// Returns the name of the struct generated for the parameters of the provided method.
+    pub fn params_type_name(&self, method: &Method) -> String {
+        if self.has_shared_params(method) {
+            return String::from("NoParams");
+        }
+        let mut ident = self.method_ident(method);
+        ident.push_str("Params");
+        ident
+    }
+
+    /// Returns the name of the type alias generated for the result of the provided method.
+    pub fn result_type_name(&self, method: &Method) -> String {
+        let mut ident = self.method_ident(method);
+        ident.push_str("Result");
+        ident
+    }
+
+    /// Returns the Rust type of the result of the provided method.
+    ///
+    /// When result types are generated, their alias is used.
+    pub fn method_result_type(&self, method: &'a Method) -> Cow<'a, str> {
+        match method.result {
+            Some(_) if self.config.generation.result_types => {
+                Cow::Owned(self.result_type_name(method))
+            }
+            Some(ref result) => self.type_ref_name(&result.ty, true),
+            None => Cow::Borrowed("()"),
+        }
+    }
+
+    /// Returns the expression naming the method in the generated code.
+    ///
+    /// This is the name of the method constant when those are generated, and a string literal
+    /// otherwise.
+    pub fn method_name_expr(&self, method: &Method) -> io::Result<TokenStream> {
+        if self.config.generation.method_name_constants {
+            let name = parse_ident(&ident::screaming_snake(self.method_ident_base(method)))?;
+            Ok(quote!(#name))
+        } else {
+            let name = &method.name;
+            Ok(quote!(#name))
+        }
+    }
+
+    /// Returns the type used to represent maps, with `{}` standing for the type of the values.
+    pub fn map_primitive(&self) -> Cow<'a, str> {
+        let map = &self.config.primitives.map;
+        if self.config.generation.no_std {
+            Cow::Owned(map.replace("std::collections::", "alloc::collections::"))
+        } else {
+            Cow::Borrowed(map)
+        }
+    }
+
+    /// Whether the enum `ty` implements `Deserialize` by hand rather than deriving it.
+    pub fn is_manually_deserialized(&self, ty: &TypeDef, e: &EnumDef) -> bool {
+        self.config.generation.manual_deserialize
+            && self.generics.get(&ty.name).is_none_or(Vec::is_empty)
+            && matches!(
+                e.tag,
+                EnumTag::Tagged(_) | EnumTag::Untagged | EnumTag::Mixed
+            )
+    }
+
+    /// Returns the name of the `std` crate to use in paths.
+    pub fn std_mod(&self) -> &'static str {
+        if self.config.generation.use_core || self.config.generation.no_std {
+            "core"
+        } else {
+            "std"
+        }
+    }
+
+    /// Whether parameter structs should be generated for methods.
+    ///
+    /// Those are required by some other generated items, even when not explicitly requested.
+    pub fn param_types(&self) -> bool {
+        self.config.generation.param_types
+            || self.config.generation.param_helpers
+            || self.config.generation.client_trait
+            || self.config.generation.server_trait
+            || self.envelopes()
+            || self.method_markers()
+    }
+
+    /// Whether the JSON-RPC envelope types should be generated.
+    pub fn envelopes(&self) -> bool {
+        self.config.generation.envelopes || self.config.generation.batch
+    }
+
+    /// Whether the `RpcMethod` trait and the method marker types should be generated.
+    pub fn method_markers(&self) -> bool {
+        self.config.generation.method_markers || self.config.generation.batch
+    }
+}
+
+/// A field (de)serialized by a struct implementing `Serialize` and `Deserialize` by hand,
+/// possibly through the structs it flattens.
+struct FlatField<'a> {
+    /// The struct declaring the field.
+    owner: &'a TypeDef,
+    /// The field itself.
+    field: &'a StructField,
+    /// The path of the field from the outermost struct, such as `header.timestamp`.
+    access: String,
+}
+
+/// A subscription, recognized from the names of the methods of the document.
+struct Subscription<'a> {
+    /// The name of the subscription, extracted from the names of its methods.
+    name: &'a str,
+    /// The method used to subscribe.
+    subscribe: &'a Method,
+    /// The method used to unsubscribe.
+    unsubscribe: &'a Method,
+    /// The notification through which the server sends the subscribed items.
+    notification: &'a Method,
+    /// The parameter of the notification carrying the subscribed item.
+    payload: &'a MethodParameter,
+}
+
+impl<'a> Ctx<'a> {
+    /// Returns the subscriptions found in the document.
+    ///
+    /// Subscribe methods for which no matching unsubscribe method or notification is found are
+    /// ignored.
+    fn subscriptions(&self) -> Vec<Subscription<'a>> {
+        let Some(ref config) = self.config.generation.subscriptions else {
+            return Vec::new();
+        };
+        let methods = &self.file.methods;
+        let find = |name: &str| methods.iter().find(|m| m.name == name);
+
+        let mut ret = Vec::new();
+        for subscribe in methods {
+            let Some(name) = match_pattern(&config.subscribe, &subscribe.name) else {
+                continue;
+            };
+            let Some(unsubscribe) = find(&config.unsubscribe.replace("{}", name)) else {
+                continue;
+            };
+            let Some(notification) = find(&config.notification.replace("{}", name)) else {
+                continue;
+            };
+            let payload = match notification.params.as_slice() {
+                [single] => single,
+                params => match params.iter().find(|p| p.name_in_json == config.payload) {
+                    Some(payload) => payload,
+                    None => continue,
+                },
+            };
+            ret.push(Subscription {
+                name,
+                subscribe,
+                unsubscribe,
+                notification,
+                payload,
+            });
+        }
+        ret
+    }
+}
+
+/// Matches `name` against `pattern`, returning the part of `name` that replaces the `{}` of
+/// the pattern.
+fn match_pattern<'n>(pattern: &str, name: &'n str) -> Option<&'n str> {
+    let (prefix, suffix) = pattern.split_once("{}")?;
+    let inner = name.strip_prefix(prefix)?.strip_suffix(suffix)?;
+    (!inner.is_empty()).then_some(inner)
+}
+
+/// Returns the generic arguments `<F, G>` naming the provided parameters, or nothing when
+/// `params` is empty.
+fn generics_args(params: &[&str]) -> io::Result<TokenStream> {
+    if params.is_empty() {
+        return Ok(TokenStream::new());
+    }
+    let params = params
+        .iter()
+        .map(|param| parse_code::<syn::GenericArgument>(param))
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(quote!(<#(#params),*>))
+}
+
+/// Returns a where clause requiring all of the provided generic parameters to implement
+/// `bound`, or nothing when `params` holds no type parameter.
+///
+/// Lifetime parameters are skipped.
+fn generics_where(params: &[&str], bound: TokenStream) -> io::Result<TokenStream> {
+    let params = params
+        .iter()
+        .filter(|param| !param.starts_with('\''))
+        .map(|param| parse_ident(param))
+        .collect::<io::Result<Vec<_>>>()?;
+    if params.is_empty() {
+        return Ok(TokenStream::new());
+    }
+    Ok(quote!(where #(#params: #bound),*))
+}
+
+/// Returns the provided JSON number as an unsuffixed literal.
+fn number_literal(n: &serde_json::Number) -> TokenStream {
+    match (n.as_i64(), n.as_u64()) {
+        (Some(n), _) => int_literal(n.into()),
+        (_, Some(n)) => int_literal(n.into()),
+        _ => float_literal(n.as_f64().unwrap_or_default()),
+    }
+}
+
+/// Returns the provided integer as an unsuffixed literal, negated when needed.
+fn int_literal(n: i128) -> TokenStream {
+    let lit = Literal::u128_unsuffixed(n.unsigned_abs());
+    if n < 0 {
+        quote!(-#lit)
+    } else {
+        quote!(#lit)
+    }
+}
+
+/// Returns the provided float as an unsuffixed literal, negated when needed.
+fn float_literal(n: f64) -> TokenStream {
+    let lit = Literal::f64_unsuffixed(n.abs());
+    if n.is_sign_negative() {
+        quote!(-#lit)
+    } else {
+        quote!(#lit)
+    }
+}
+
+/// Parses a piece of Rust code built from the names and values of the document or of the
+/// configuration, such as the name of a type.
+///
+/// Those are not known to be valid, so the error names the offending code.
+fn parse_code<T: syn::parse::Parse>(code: &str) -> io::Result<T> {
+    parse_with(T::parse, code)
+}
+
+/// Parses a piece of Rust code like [`parse_code`], with the provided parser.
+fn parse_with<P: syn::parse::Parser>(parser: P, code: &str) -> io::Result<P::Output> {
+    parser.parse_str(code).map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("`{code}` is not valid Rust: {error}"),
+        )
+    })
+}
+
+/// Parses the provided identifier, which may be a raw identifier.
+fn parse_ident(name: &str) -> io::Result<syn::Ident> {
+    parse_code(name)
+}
+
+/// Parses the provided type.
+fn parse_type(ty: &str) -> io::Result<syn::Type> {
+    parse_code(ty)
+}
+
+/// Parses the provided path, such as the path of a serde `with` module.
+fn parse_path(path: &str) -> io::Result<syn::Path> {
+    parse_code(path)
+}
+
+/// Returns `doc` as `#[doc]` attributes, which are written as `///` comments.
+fn doc_attrs(doc: &str) -> TokenStream {
+    crate::doc::lines(doc)
+        .iter()
+        .map(|line| doc_line(line))
+        .collect()
+}
+
+/// Returns a single line of documentation as a `#[doc]` attribute.
+fn doc_line(line: &str) -> TokenStream {
+    let line = if line.is_empty() {
+        String::new()
+    } else {
+        format!(" {line}")
+    };
+    quote!(#[doc = #line])
+}
+
+/// The lifetime parameter taken by the types holding borrowed strings.
+const LIFETIME: &str = "'a";
+
+/// Returns whether `r` is represented by the type used for strings, rather than by a type
+/// configured for its format.
+fn is_string(config: &crate::config::Config, r: &TypeRef) -> bool {
+    match r {
+        TypeRef::String | TypeRef::Keyword(_) => true,
+        TypeRef::Format(format) => !config.primitives.formats.contains_key(format),
+        _ => false,
+    }
+}
+
+/// Returns whether `r` mentions the type used for strings, without going through other types.
+fn mentions_string(config: &crate::config::Config, r: &TypeRef) -> bool {
+    match r {
+        TypeRef::Array(inner)
+        | TypeRef::Set(inner)
+        | TypeRef::Map(inner)
+        | TypeRef::Boxed(_, inner)
+        | TypeRef::Nullable(inner) => mentions_string(config, inner),
+        TypeRef::Tuple(items) => items.iter().any(|item| mentions_string(config, item)),
+        _ => is_string(config, r),
+    }
+}
+
+/// Computes the generic parameters taken by each type of the file.
+///
+/// A type takes a parameter when it depends on it, either directly or transitively. When
+/// `borrowed` is enabled, the types holding strings take the lifetime parameter first.
+pub(crate) fn type_generics<'a>(
+    file: &crate::parse::File,
+    config: &'a crate::config::Config,
+) -> BTreeMap<String, Vec<&'a str>> {
+    let mut ret = BTreeMap::<String, Vec<&str>>::new();
+    if config.generation.generics.is_empty() && !config.generation.borrowed {
+        return ret;
+    }
+    let deps = TypeDeps::new(file, config);
+    if config.generation.borrowed {
+        let holders = file
+            .types
+            .values()
+            .filter(|ty| match &ty.kind {
+                TypeKind::Alias(alias) => mentions_string(config, &alias.ty),
+                TypeKind::Struct(s) => s.fields.values().any(|f| mentions_string(config, &f.ty)),
+                TypeKind::Enum(e) => e
+                    .variants
+                    .values()
+                    .any(|v| v.ty.as_ref().is_some_and(|ty| mentions_string(config, ty))),
+            })
+            .map(|ty| ty.name.as_str())
+            .collect::<BTreeSet<_>>();
+        for ty in file.types.values() {
+            let name = ty.name.as_str();
+            if holders.contains(name) || holders.iter().any(|h| deps.depends_on(name, h)) {
+                ret.insert(ty.name.clone(), vec![LIFETIME]);
+            }
+        }
+    }
+    for param in config.generation.generics.keys() {
+        for dependent in deps.dependents(param) {
+            if file.types.values().any(|ty| ty.name == dependent) {
+                ret.entry(dependent.to_owned()).or_default().push(param);
+            }
+        }
+    }
+    ret
+}
+
+/// Checks that the generic parameters don't reach method-level items that don't support them.
+fn check_generics(ctx: &Ctx, errs: &mut Vec<Diagnostic>) {
+    let generation = &ctx.config.generation;
+    let unsupported = [
+        (ctx.method_markers(), "method-markers"),
+        (generation.client_trait, "client-trait"),
+        (generation.server_trait, "server-trait"),
+        (generation.jsonrpsee, "jsonrpsee"),
+        (ctx.envelopes(), "envelopes"),
+    ];
+    if let Some((_, option)) = unsupported.iter().find(|(enabled, _)| *enabled) {
+        for method in &ctx.file.methods {
+            let mut generics = ctx.method_generics(method);
+            if let Some(result) = &method.result {
+                generics.extend(ctx.type_ref_generics(&result.ty));
+            }
+            if let Some(param) = generics.first() {
+                errs.push(
+                    Diagnostic::error(format!(
+                        "the method depends on the generic parameter `{param}`, which is not supported with `{option}`",
+                    ))
+                    .at(format!("#/methods/{}", method.name)),
+                );
+            }
+        }
+    }
+
+    if ctx.config.generation.error_enums != ErrorEnums::None {
+        for (path, error) in &ctx.file.errors {
+            let Some(data) = &error.data else {
+                continue;
+            };
+            if let Some(param) = ctx.type_ref_generics(data).first() {
+                errs.push(
+                    Diagnostic::error(format!(
+                        "the data of the error depends on the generic parameter `{param}`, which is not supported with `error-enums`",
+                    ))
+                    .at(&**path),
+                );
+            }
+        }
+    }
+}
+
+/// Returns the errors that prevent the code of the provided [`crate::parse::File`] from being
+/// generated with the provided configuration, such as options that don't work together, along
+/// with warnings about the code that will be generated differently than requested.
+///
+/// [`gen`] expects the file to have no errors.
+pub fn check(file: &crate::parse::File, config: &crate::config::Config) -> Vec<Diagnostic> {
+    let ctx = Ctx::new(file, config);
+    let mut errs = Vec::new();
+    check_generics(&ctx, &mut errs);
+    check_tri_state(&ctx, &mut errs);
+    check_serde_feature(&ctx, &mut errs);
+    check_no_std(&ctx, &mut errs);
+    check_hex_helpers(&ctx, &mut errs);
+    check_borrowed(&ctx, &mut errs);
+    check_spec_version(&ctx, &mut errs);
+    check_patterns(&ctx, &mut errs);
+    errs
+}
+
+/// Generates a Rust file from the provided [`crate::parse::File`] and configuration.
+pub fn gen(file: &crate::parse::File, config: &crate::config::Config) -> io::Result<syn::File> {
+    gen_with_shared(file, config, &[])
+}
+
+/// Generates a Rust file like [`gen`], re-exporting the provided types from the module named
+/// by the `common-module` option.
+///
+/// Those are the types shared with other documents, which the file refers to without
+/// defining them.
+pub fn gen_with_shared(
+    file: &crate::parse::File,
+    config: &crate::config::Config,
+    shared: &[&str],
+) -> io::Result<syn::File> {
+    let mut tokens = TokenStream::new();
+    gen_contents(&mut tokens, file, config, shared)?;
+    into_file(tokens)
+}
+
+/// Generates a Rust file holding one module per provided file, named as requested.
+///
+/// Each module is generated as if it were a file of its own, so the modules don't refer to each
+/// other.
+pub fn gen_modules(
+    files: &[(&str, &crate::parse::File)],
+    config: &crate::config::Config,
+) -> io::Result<syn::File> {
+    let mut tokens = TokenStream::new();
+    for (module, file) in files {
+        let module = parse_ident(module)?;
+        let mut content = TokenStream::new();
+        gen_contents(&mut content, file, config, &[])?;
+        tokens.extend(quote! {
+            pub mod #module {
+                #content
+            }
+        });
+    }
+    into_file(tokens)
+}
+
+/// Parses the generated tokens into a syntax tree.
+fn into_file(tokens: TokenStream) -> io::Result<syn::File> {
+    syn::parse2(tokens).map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the generated code is invalid: {error}"),
+        )
+    })
+}
+
+/// Formats the provided file, preceded by the comment at the top of every generated file.
+///
+/// The paths written as attributes when `debug-path` is enabled are turned back into regular
+/// comments, which are not part of the syntax tree.
+pub fn render(file: &syn::File) -> String {
+    let code = prettyplease::unparse(file);
+    let mut ret = String::with_capacity(HEADER.len() + 1 + code.len());
+    ret.push_str(HEADER);
+    ret.push('\n');
+    for line in code.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let path = trimmed
+            .strip_prefix("#[")
+            .and_then(|rest| rest.strip_prefix(DEBUG_PATH))
+            .and_then(|rest| rest.strip_prefix(" = "))
+            .and_then(|rest| rest.trim_end().strip_suffix(']'))
+            .and_then(|lit| syn::parse_str::<syn::LitStr>(lit).ok());
+        match path {
+            Some(path) => {
+                let indent = &line[..line.len() - trimmed.len()];
+                ret.push_str(&format!("{indent}// {}\n", path.value()));
+            }
+            None => ret.push_str(line),
+        }
+    }
+    ret
+}
+
+/// The comment at the top of every generated file.
+const HEADER: &str = "\
+    //\n\
+    // This file was automatically generated by openrpc-gen.\n\
+    //\n\
+    // Do not edit it manually and instead edit either the source OpenRPC document,\n\
+    // the configuration file, or open an issue or pull request on the openrpc-gen\n\
+    // GitHub repository.\n\
+    // \n\
+    //     https://github.com/nils-mathieu/openrpc-gen\n\
+    //\n\
+    ";
+
+/// The name of the attribute holding the path of an item when `debug-path` is enabled, which
+/// [`render`] turns into a regular comment.
+const DEBUG_PATH: &str = "openrpc_gen_debug_path";
+
+/// Returns the attribute holding `path` when `debug-path` is enabled, or nothing otherwise.
+fn debug_path(ctx: &Ctx, path: &str) -> TokenStream {
+    if !ctx.config.debug_path {
+        return TokenStream::new();
+    }
+    let name = format_ident!("{DEBUG_PATH}");
+    quote!(#[#name = #path])
+}
+
+/// Writes the contents of a generated file.
+fn gen_contents(
+    tokens: &mut TokenStream,
+    file: &crate::parse::File,
+    config: &crate::config::Config,
+    shared: &[&str],
+) -> io::Result<()> {
+    let mut ctx = Ctx::new(file, config);
+
+    if ctx.config.generation.spec_info {
+        gen_spec_docs(tokens, &ctx);
+    }
+
+    let serde_cfg = ctx.serde_cfg();
+    let mut imports = vec![quote!(serde::{Serialize, Deserialize})];
+    if ctx.param_types() {
+        let by_name = file.methods.iter().any(|m| {
+            matches!(
+                m.param_structure,
+                ParamStructure::ByName | ParamStructure::Either
+            )
+        });
+        let by_position = file
+            .methods
+            .iter()
+            .any(|m| m.param_structure == ParamStructure::ByPosition);
+        match (by_name, by_position) {
+            (true, true) => imports.push(quote!(serde::ser::{SerializeMap, SerializeSeq})),
+            (true, false) => imports.push(quote!(serde::ser::SerializeMap)),
+            (false, true) => imports.push(quote!(serde::ser::SerializeSeq)),
+            (false, false) => (),
+        }
+    }
+    for import in imports {
+        tokens.extend(quote! {
+            #serde_cfg
+            use #import;
+        });
+    }
+    if ctx.config.generation.no_std {
+        for import in [
+            quote!(boxed::Box),
+            quote!(format),
+            quote!(string::String),
+            quote!(vec),
+            quote!(vec::Vec),
+        ] {
+            tokens.extend(quote! {
+                #[allow(unused_imports)]
+                use alloc::#import;
+            });
+        }
+    }
+    for import in &ctx.config.generation.additional_imports {
+        let import = parse_code::<syn::UseTree>(import)?;
+        tokens.extend(quote!(use #import;));
+    }
+    if !shared.is_empty() {
+        let module = parse_path(&ctx.config.generation.common_module)?;
+        let shared = shared
+            .iter()
+            .map(|name| parse_ident(name))
+            .collect::<io::Result<Vec<_>>>()?;
+        tokens.extend(quote!(pub use #module::{#(#shared),*};));
+    }
+
+    if ctx.config.generation.spec_info {
+        gen_spec_constants(tokens, &ctx);
+    }
+
+    if ctx.tri_state_fields().next().is_some() {
+        gen_maybe(tokens, &ctx)?;
+    }
+    if file.types.values().any(|ty| match &ty.kind {
+        TypeKind::Alias(alias) => ctx.is_validated(alias, &ty.path),
+        _ => false,
+    }) {
+        gen_pattern_error(tokens, &ctx)?;
+    }
+    if file.types.values().any(|ty| match &ty.kind {
+        TypeKind::Enum(e) => {
+            matches!(e.tag, EnumTag::Tagged(_)) && ctx.is_manually_deserialized(ty, e)
+        }
+        _ => false,
+    }) {
+        gen_tagged_map(tokens, &ctx)?;
+    }
+    if ctx.config.generation.enum_strings
+        && file.types.values().any(|ty| match &ty.kind {
+            TypeKind::Enum(e) => is_string_enum(e),
+            _ => false,
+        })
+    {
+        gen_unknown_variant_error(tokens, &ctx)?;
+    }
+    if ctx.config.generation.hex_helpers {
+        gen_hex_helpers(tokens, &ctx)?;
+    }
+    if ctx.config.generation.validation {
+        gen_validation(tokens, &ctx)?;
+    }
+    if ctx.config.generation.from_json {
+        gen_from_json(tokens, &ctx)?;
+    }
+    if ctx.config.generation.unit_structs {
+        gen_unit_helpers(tokens, &ctx)?;
+    }
+    if ctx.config.generation.tag_modules {
+        gen_tag_modules(tokens, &mut ctx)?;
+    } else {
+        for ty in file.types.values() {
+            gen_type(tokens, &mut ctx, ty)?;
+        }
+        for method in &file.methods {
+            gen_method(tokens, &mut ctx, method)?;
+        }
+    }
+    if ctx.config.generation.schema_names {
+        gen_schema_names(tokens, &ctx);
+    }
+    if ctx.config.generation.method_name_constants
+        && file.methods.iter().any(Method::is_notification)
+    {
+        gen_notification_names(tokens, &mut ctx)?;
+    }
+    if ctx.method_markers() {
+        gen_method_markers(tokens, &mut ctx)?;
+    }
+    if ctx.config.generation.client_trait {
+        gen_client_trait(tokens, &mut ctx)?;
+    }
+    if ctx.config.generation.server_trait {
+        gen_server_trait(tokens, &mut ctx)?;
+    }
+    if ctx.config.generation.jsonrpsee {
+        gen_jsonrpsee_trait(tokens, &mut ctx)?;
+    }
+    if ctx.envelopes() {
+        gen_envelopes(tokens, &mut ctx)?;
+    }
+    if ctx.config.generation.batch {
+        gen_batch(tokens, &mut ctx);
+    }
+    if ctx.config.generation.example_tests && file.methods.iter().any(|m| !m.examples.is_empty()) {
+        gen_example_tests(tokens, &mut ctx)?;
+    }
+    if ctx.config.generation.mocks {
+        gen_mocks(tokens, &mut ctx)?;
+    }
+    if let Some(fixtures) = &ctx.config.generation.fixtures {
+        gen_fixture_tests(tokens, &mut ctx, fixtures, shared)?;
+    }
+    match ctx.config.generation.error_enums {
+        ErrorEnums::None => (),
+        ErrorEnums::Document => {
+            let errors = file.errors.values().collect::<Vec<_>>();
+            gen_error_enum(
+                tokens,
+                &mut ctx,
+                "ApiError",
+                "An error returned by the API.",
+                &errors,
+            )?;
+        }
+        ErrorEnums::Method => {
+            for method in &file.methods {
+                let errors = method
+                    .errors
+                    .iter()
+                    .filter_map(|path| file.errors.get(path))
+                    .collect::<Vec<_>>();
+                if errors.is_empty() {
+                    continue;
+                }
+                let mut ident = ctx.method_ident(method);
+                ident.push_str("Error");
+                let doc = format!("An error returned by the `{}` method.", method.name);
+                gen_error_enum(tokens, &mut ctx, &ident, &doc, &errors)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the types and methods of the file, grouping them into one module per tag.
+///
+/// Each module is re-exported at the top level, and imports everything from it in turn.
+fn gen_tag_modules(tokens: &mut TokenStream, ctx: &mut Ctx) -> io::Result<()> {
+    let file = ctx.file;
+    let deps = TypeDeps::new(file, ctx.config);
+    let owners = deps.tag_owners(file);
+
+    // Maps the name of each tag to the content of its module.
+    let mut modules: BTreeMap<&str, (Option<&str>, TokenStream)> = BTreeMap::new();
+    for tag in file.methods.iter().flat_map(|m| m.tags.first()) {
+        let module = modules.entry(&tag.name).or_default();
+        module.0 = module.0.or(tag.documentation.as_deref());
+    }
+
+    for ty in file.types.values() {
+        match owners.get(ty.name.as_str()) {
+            Some(tag) => gen_type(&mut modules.get_mut(tag).unwrap().1, ctx, ty)?,
+            None => gen_type(tokens, ctx, ty)?,
+        }
+    }
+    for method in &file.methods {
+        match method.tags.first() {
+            Some(tag) => gen_method(&mut modules.get_mut(&*tag.name).unwrap().1, ctx, method)?,
+            None => gen_method(tokens, ctx, method)?,
+        }
+    }
+
+    for (tag, (doc, content)) in modules {
+        let ident = parse_ident(&ident::escape(
+            &ident::snake(tag),
+            &ctx.config.generation.keyword_renames,
+        ))?;
+        let doc = doc.map(doc_attrs);
+        tokens.extend(quote! {
+            #doc
+            pub mod #ident {
+                #[allow(unused_imports)]
+                use super::*;
+
+                #content
+            }
+
+            pub use #ident::*;
+        });
+    }
+
+    Ok(())
+}
+
+/// Ensures that `no-std` is not combined with generated code that requires `std`.
+fn check_no_std(ctx: &Ctx, errs: &mut Vec<Diagnostic>) {
+    let generation = &ctx.config.generation;
+    if !generation.no_std {
+        return;
+    }
+    let conflicts = [
+        ("jsonrpsee", generation.jsonrpsee),
+        ("validation", generation.validation),
+        ("from-json", generation.from_json),
+    ];
+    for (option, enabled) in conflicts {
+        if enabled {
+            errs.push(Diagnostic::error(format!(
+                "`no-std` cannot be combined with `{option}`, which requires `std`"
+            )));
+        }
+    }
+    for ty in ctx.file.types.values() {
+        if let TypeKind::Alias(alias) = &ty.kind {
+            if ctx.is_validated(alias, &ty.path) {
+                errs.push(
+                    Diagnostic::error(
+                        "`no-std` cannot be combined with pattern newtypes, which require `std`",
+                    )
+                    .at(&*ty.path),
+                );
+            }
+        }
+    }
+}
+
+/// Ensures that the patterns checked by the generated code can be compiled by the `regex` crate.
+///
+/// Pattern newtypes can't be generated for the other patterns, which `validation` skips.
+fn check_patterns(ctx: &Ctx, errs: &mut Vec<Diagnostic>) {
+    for ty in ctx.file.types.values() {
+        let constraints = match &ty.kind {
+            TypeKind::Alias(alias) => vec![(&*ty.path, &alias.constraints)],
+            TypeKind::Struct(s) => s
+                .fields
+                .values()
+                .map(|field| (&*field.path, &field.constraints))
+                .collect(),
+            TypeKind::Enum(_) => Vec::new(),
+        };
+        for (path, constraints) in constraints {
+            let Some(error) = constraints.pattern.as_deref().and_then(pattern_error) else {
+                continue;
+            };
+            match &ty.kind {
+                TypeKind::Alias(alias) if ctx.is_validated(alias, path) => errs.push(
+                    Diagnostic::error(format!(
+                        "the pattern can't be checked by a pattern newtype, as the `regex` crate does not support it: {error}"
+                    ))
+                    .at(path),
+                ),
+                _ if ctx.config.generation.validation => errs.push(
+                    Diagnostic::warning(format!(
+                        "the pattern is not validated, as the `regex` crate does not support it: {error}"
+                    ))
+                    .at(path),
+                ),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Returns why `pattern` can't be compiled by the `regex` crate, which the generated code
+/// checks patterns with, or `None` if it can.
+///
+/// The crate does not support some features of the ECMA-262 patterns of JSON Schema, such as
+/// look-arounds and backreferences.
+fn pattern_error(pattern: &str) -> Option<String> {
+    let error = regex::Regex::new(pattern).err()?.to_string();
+    // Syntax errors repeat the pattern before the description of the error.
+    let message = error.lines().last().unwrap_or_default();
+    Some(message.trim_start_matches("error: ").to_owned())
+}
+
+/// Ensures that `borrowed` is not combined with generated code that requires owned strings.
+fn check_borrowed(ctx: &Ctx, errs: &mut Vec<Diagnostic>) {
+    if !ctx.config.generation.borrowed {
+        return;
+    }
+    if ctx.config.generation.arbitrary {
+        errs.push(Diagnostic::error(
+            "`borrowed` cannot be combined with `arbitrary`, which requires owned types",
+        ));
+    }
+    for ty in ctx.file.types.values() {
+        if let TypeKind::Alias(alias) = &ty.kind {
+            if ctx.is_validated(alias, &ty.path) {
+                errs.push(
+                    Diagnostic::error(
+                        "`borrowed` cannot be combined with pattern newtypes, which own their string",
+                    )
+                    .at(&*ty.path),
+                );
+            }
+        }
+    }
+}
+
+/// Ensures that the module generated by `hex-helpers` can be named as configured.
+fn check_hex_helpers(ctx: &Ctx, errs: &mut Vec<Diagnostic>) {
+    let module = &ctx.config.formatters.num_as_hex;
+    if ctx.config.generation.hex_helpers && syn::parse_str::<syn::Ident>(module).is_err() {
+        errs.push(Diagnostic::error(format!(
+            "`hex-helpers` requires `num-as-hex` to be the name of a module, not `{module}`"
+        )));
+    }
+}
+
+/// Ensures that the document has the version expected by the configuration, if any.
+fn check_spec_version(ctx: &Ctx, errs: &mut Vec<Diagnostic>) {
+    if let Some(expected) = &ctx.config.generation.expected_spec_version {
+        if *expected != ctx.file.info.version {
+            errs.push(
+                Diagnostic::error(format!(
+                    "the document has version `{}`, but `expected-spec-version` is `{expected}`",
+                    ctx.file.info.version,
+                ))
+                .at("#/info/version"),
+            );
+        }
+    }
+}
+
+/// Ensures that `serde-feature` is not combined with generated code that relies on serde.
+fn check_serde_feature(ctx: &Ctx, errs: &mut Vec<Diagnostic>) {
+    if ctx.config.generation.serde_feature.is_none() {
+        return;
+    }
+    let generation = &ctx.config.generation;
+    let conflicts = [
+        ("client-trait", generation.client_trait),
+        ("server-trait", generation.server_trait),
+        ("jsonrpsee", generation.jsonrpsee),
+        ("envelopes", generation.envelopes),
+        ("batch", generation.batch),
+    ];
+    for (option, enabled) in conflicts {
+        if enabled {
+            errs.push(Diagnostic::error(format!(
+                "`serde-feature` cannot be combined with `{option}`, which relies on serde"
+            )));
+        }
+    }
+}
+
+/// Ensures that no tri-state field relies on a serde `with` module, which would expect the
+/// field to be an `Option<T>`.
+fn check_tri_state(ctx: &Ctx, errs: &mut Vec<Diagnostic>) {
+    for field in ctx.tri_state_fields() {
+        if field.serde_with.is_some() || !field.ty.attributes(ctx.config, ctx.file).is_empty() {
+            errs.push(
+                Diagnostic::error(
+                    "the field uses a serde `with` module, which is not supported for tri-state fields",
+                )
+                .at(&*field.path),
+            );
+        }
+    }
+}
+
+/// Writes the `Maybe<T>` type used by tri-state fields.
+fn gen_maybe(tokens: &mut TokenStream, ctx: &Ctx) -> io::Result<()> {
+    let cfg = ctx.serde_cfg();
+    tokens.extend(quote! {
+        /// An optional value that distinguishes a missing field from an explicit `null`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub enum Maybe<T> {
+            /// The field is missing.
+            #[default]
+            Absent,
+            /// The field is explicitly `null`.
+            Null,
+            /// The field holds a value.
+            Value(T),
+        }
+
+        impl<T> Maybe<T> {
+            /// Returns whether the field is missing.
+            pub fn is_absent(&self) -> bool {
+                matches!(self, Self::Absent)
+            }
+
+            /// Returns whether the field is explicitly `null`.
+            pub fn is_null(&self) -> bool {
+                matches!(self, Self::Null)
+            }
+
+            /// Returns the value of the field, if any.
+            pub fn value(&self) -> Option<&T> {
+                match self {
+                    Self::Value(value) => Some(value),
+                    _ => None,
+                }
+            }
+
+            /// Converts the field into its value, if any.
+            pub fn into_value(self) -> Option<T> {
+                match self {
+                    Self::Value(value) => Some(value),
+                    _ => None,
+                }
+            }
+        }
+
+        #cfg
+        impl<T: Serialize> Serialize for Maybe<T> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Self::Value(value) => serializer.serialize_some(value),
+                    _ => serializer.serialize_none(),
+                }
+            }
+        }
+
+        #cfg
+        impl<'de, T: Deserialize<'de>> Deserialize<'de> for Maybe<T> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Option::<T>::deserialize(deserializer).map(|value| match value {
+                    Some(value) => Self::Value(value),
+                    None => Self::Null,
+                })
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the module (de)serializing integers as hexadecimal strings, named by the
+/// `num-as-hex` formatter.
+fn gen_hex_helpers(tokens: &mut TokenStream, ctx: &Ctx) -> io::Result<()> {
+    let cfg = ctx.serde_cfg();
+    let module = parse_ident(&ctx.config.formatters.num_as_hex)?;
+    tokens.extend(quote! {
+        /// (De)serializes integers as hexadecimal strings, such as `0x1f`.
+        ///
+        /// This supports the integer types, as well as `Option`s and `Vec`s of them. Negative integers
+        /// are not supported.
+        #cfg
+        pub mod #module {
+            use super::*;
+
+            /// A value that can be (de)serialized as hexadecimal strings.
+            pub trait NumAsHex: Sized {
+                /// Serializes the value as hexadecimal strings.
+                fn serialize_hex<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+
+                /// Deserializes the value from hexadecimal strings.
+                fn deserialize_hex<'de, D: serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Self, D::Error>;
+            }
+
+            macro_rules! impl_num_as_hex {
+                ($($ty:ty),*) => {$(
+                    impl NumAsHex for $ty {
+                        fn serialize_hex<S: serde::Serializer>(
+                            &self,
+                            serializer: S,
+                        ) -> Result<S::Ok, S::Error> {
+                            serializer.collect_str(&format_args!("{:#x}", self))
+                        }
+
+                        fn deserialize_hex<'de, D: serde::Deserializer<'de>>(
+                            deserializer: D,
+                        ) -> Result<Self, D::Error> {
+                            let s = String::deserialize(deserializer)?;
+                            let digits = s
+                                .strip_prefix("0x")
+                                .ok_or_else(|| serde::de::Error::custom("expected a `0x` prefix"))?;
+                            <$ty>::from_str_radix(digits, 16).map_err(serde::de::Error::custom)
+                        }
+                    }
+                )*};
+            }
+
+            impl_num_as_hex!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+            /// Serializes a value as hexadecimal strings.
+            struct Hex<'a, T>(&'a T);
+
+            impl<T: NumAsHex> Serialize for Hex<'_, T> {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    self.0.serialize_hex(serializer)
+                }
+            }
+
+            /// Deserializes a value from hexadecimal strings.
+            struct FromHex<T>(T);
+
+            impl<'de, T: NumAsHex> Deserialize<'de> for FromHex<T> {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    T::deserialize_hex(deserializer).map(FromHex)
+                }
+            }
+
+            impl<T: NumAsHex> NumAsHex for Option<T> {
+                fn serialize_hex<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    match self {
+                        Some(value) => serializer.serialize_some(&Hex(value)),
+                        None => serializer.serialize_none(),
+                    }
+                }
+
+                fn deserialize_hex<'de, D: serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Self, D::Error> {
+                    Option::<FromHex<T>>::deserialize(deserializer).map(|value| value.map(|v| v.0))
+                }
+            }
+
+            impl<T: NumAsHex> NumAsHex for Vec<T> {
+                fn serialize_hex<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_seq(self.iter().map(Hex))
+                }
+
+                fn deserialize_hex<'de, D: serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Self, D::Error> {
+                    Vec::<FromHex<T>>::deserialize(deserializer)
+                        .map(|values| values.into_iter().map(|v| v.0).collect())
+                }
+            }
+
+            /// Serializes `value` as hexadecimal strings.
+            pub fn serialize<T: NumAsHex, S: serde::Serializer>(
+                value: &T,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                value.serialize_hex(serializer)
+            }
+
+            /// Deserializes a value from hexadecimal strings.
+            pub fn deserialize<'de, T: NumAsHex, D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<T, D::Error> {
+                T::deserialize_hex(deserializer)
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the error returned when a string doesn't match the pattern of its newtype.
+fn gen_pattern_error(tokens: &mut TokenStream, ctx: &Ctx) -> io::Result<()> {
+    let core = format_ident!("{}", ctx.std_mod());
+    tokens.extend(quote! {
+        /// An error returned when a string does not match the pattern of its type.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct PatternError {
+            /// The pattern that was not matched.
+            pub pattern: &'static str,
+        }
+
+        impl #core::fmt::Display for PatternError {
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                write!(f, "the value does not match the pattern `{}`", self.pattern)
+            }
+        }
+
+        impl std::error::Error for PatternError {}
+    });
+    Ok(())
+}
+
+/// Writes the error returned when a string matches none of the variants of an enum of strings.
+fn gen_unknown_variant_error(tokens: &mut TokenStream, ctx: &Ctx) -> io::Result<()> {
+    let core = format_ident!("{}", ctx.std_mod());
+    tokens.extend(quote! {
+        /// An error returned when a string matches none of the variants of an enum.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct UnknownVariantError {
+            /// The name of the enum.
+            pub ty: &'static str,
+            /// The string that was parsed.
+            pub value: String,
+        }
+
+        impl #core::fmt::Display for UnknownVariantError {
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                write!(f, "`{}` is not a variant of `{}`", self.value, self.ty)
+            }
+        }
+    });
+    if !ctx.config.generation.no_std {
+        tokens.extend(quote!(impl std::error::Error for UnknownVariantError {}));
+    }
+    Ok(())
+}
+
+/// Writes the map access used by the `Deserialize` implementations of the enums tagged with a
+/// property, which hands the variant the entries read before the tag followed by the others.
+fn gen_tagged_map(tokens: &mut TokenStream, ctx: &Ctx) -> io::Result<()> {
+    let cfg = ctx.serde_cfg();
+    tokens.extend(quote! {
+        /// The entries of a tagged enum that follow its tag, preceded by the ones read before it.
+        #cfg
+        struct TaggedMap<A> {
+            /// The entries read before the tag.
+            buffered: <Vec<(String, serde_json::Value)> as IntoIterator>::IntoIter,
+            /// The value of the buffered entry whose key was last read.
+            value: Option<serde_json::Value>,
+            /// The entries that were not read yet.
+            map: A,
+        }
+
+        #cfg
+        impl<A> TaggedMap<A> {
+            fn new(buffered: Vec<(String, serde_json::Value)>, map: A) -> Self {
+                Self {
+                    buffered: buffered.into_iter(),
+                    value: None,
+                    map,
+                }
+            }
+        }
+
+        #cfg
+        impl<'de, A: serde::de::MapAccess<'de>> serde::de::MapAccess<'de> for TaggedMap<A> {
+            type Error = A::Error;
+
+            fn next_key_seed<K: serde::de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error> {
+                match self.buffered.next() {
+                    Some((key, value)) => {
+                        self.value = Some(value);
+                        seed.deserialize(serde::de::value::StringDeserializer::new(key)).map(Some)
+                    }
+                    None => self.map.next_key_seed(seed),
+                }
+            }
+
+            fn next_value_seed<V: serde::de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, A::Error> {
+                match self.value.take() {
+                    Some(value) => seed.deserialize(value).map_err(serde::de::Error::custom),
+                    None => self.map.next_value_seed(seed),
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the newtype of an alias of a string constrained by a pattern, checking that its values
+/// match the pattern.
+fn gen_pattern_newtype(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    alias: &AliasDef,
+) -> io::Result<()> {
+    let pattern = raw_string(alias.constraints.pattern.as_deref().unwrap_or_default());
+    let inner = parse_type(&ctx.type_ref_name(&alias.ty, true))?;
+    let name = parse_ident(&ty.name)?;
+    let core = format_ident!("{}", ctx.std_mod());
+    if ctx.config.generation.serde_feature.is_some() {
+        let derive = ctx.serde_attr(quote!(derive(Serialize, Deserialize)));
+        tokens.extend(quote! {
+            #[derive(Debug, Clone)]
+            #derive
+        });
+    } else {
+        tokens.extend(quote!(#[derive(Debug, Clone, Serialize, Deserialize)]));
+    }
+    if ctx.eq_types.contains(&ty.name) {
+        tokens.extend(quote!(#[derive(PartialEq, Eq, Hash)]));
+    }
+    let inner_name = ctx.type_ref_name(&alias.ty, true);
+    let try_from = ctx.serde_attr(quote!(serde(try_from = #inner_name)));
+    let attrs = ctx.attributes(&ty.path)?;
+    tokens.extend(quote! {
+        #try_from
+        #(#attrs)*
+        pub struct #name(#inner);
+
+        impl #name {
+            /// The regular expression the values must match.
+            pub const PATTERN: &'static str = #pattern;
+
+            /// Creates a new value, checking that it matches [`Self::PATTERN`].
+            pub fn new(value: impl Into<#inner>) -> Result<Self, PatternError> {
+                static REGEX: std::sync::OnceLock<Option<regex::Regex>> = std::sync::OnceLock::new();
+                let regex = REGEX.get_or_init(|| regex::Regex::new(Self::PATTERN).ok());
+                let value = value.into();
+                if regex.as_ref().is_some_and(|regex| regex.is_match(&value)) {
+                    Ok(Self(value))
+                } else {
+                    Err(PatternError { pattern: Self::PATTERN })
+                }
+            }
+
+            /// Returns the inner value.
+            pub fn get(&self) -> &#inner {
+                &self.0
+            }
+
+            /// Converts the value into its inner value.
+            pub fn into_inner(self) -> #inner {
+                self.0
+            }
+        }
+
+        impl #core::str::FromStr for #name {
+            type Err = PatternError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::new(s)
+            }
+        }
+
+        impl TryFrom<#inner> for #name {
+            type Error = PatternError;
+
+            fn try_from(value: #inner) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the `Validate` trait, along with the error it returns.
+fn gen_validation(tokens: &mut TokenStream, ctx: &Ctx) -> io::Result<()> {
+    let core = format_ident!("{}", ctx.std_mod());
+    tokens.extend(quote! {
+        /// An error returned when a value breaks a constraint of the document.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct ValidationError {
+            /// The location of the value within the validated one, such as `items[2].name`.
+            ///
+            /// This is empty when the validated value itself breaks the constraint.
+            pub path: String,
+            /// A description of the broken constraint.
+            pub message: String,
+        }
+
+        impl ValidationError {
+            /// Creates a new error about the value at `path`.
+            pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+                Self {
+                    path: path.into(),
+                    message: message.into(),
+                }
+            }
+
+            /// Moves the location of the error within the value at `parent`.
+            pub fn at(mut self, parent: impl Into<String>) -> Self {
+                let parent = parent.into();
+                self.path = if self.path.is_empty() {
+                    parent
+                } else if self.path.starts_with('[') {
+                    parent + &self.path
+                } else {
+                    format!("{parent}.{}", self.path)
+                };
+                self
+            }
+        }
+
+        impl #core::fmt::Display for ValidationError {
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                if self.path.is_empty() {
+                    write!(f, "the value {}", self.message)
+                } else {
+                    write!(f, "`{}` {}", self.path, self.message)
+                }
+            }
+        }
+
+        impl std::error::Error for ValidationError {}
+
+        /// Checks the constraints of the document that are not enforced by the types, such as the
+        /// length of strings.
+        pub trait Validate {
+            /// Returns an error about the first broken constraint, if any.
+            fn validate(&self) -> Result<(), ValidationError>;
+        }
+    });
+    Ok(())
+}
+
+/// Writes the `FromJson` trait, along with the error it returns and its implementation for
+/// every type that can be deserialized.
+fn gen_from_json(tokens: &mut TokenStream, ctx: &Ctx) -> io::Result<()> {
+    let cfg = ctx.serde_cfg();
+    tokens.extend(quote! {
+        /// An error returned when a value can't be deserialized from JSON.
+        #[derive(Debug)]
+        pub struct FromJsonError {
+            /// The location of the value that failed to deserialize, such as `transactions[3].type`.
+            ///
+            /// This is `.` when the error is about the whole value.
+            pub path: String,
+            /// The error returned by `serde_json`.
+            pub inner: serde_json::Error,
+        }
+
+        impl std::fmt::Display for FromJsonError {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                if self.path == "." {
+                    write!(f, "{}", self.inner)
+                } else {
+                    write!(f, "`{}`: {}", self.path, self.inner)
+                }
+            }
+        }
+
+        impl std::error::Error for FromJsonError {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(&self.inner)
+            }
+        }
+
+        #cfg
+        impl From<serde_path_to_error::Error<serde_json::Error>> for FromJsonError {
+            fn from(error: serde_path_to_error::Error<serde_json::Error>) -> Self {
+                Self {
+                    path: error.path().to_string(),
+                    inner: error.into_inner(),
+                }
+            }
+        }
+
+        /// Deserializes values from JSON, reporting the path of the value that failed to deserialize.
+        #cfg
+        pub trait FromJson: Sized {
+            /// Deserializes a value from a JSON string.
+            fn from_json_str(s: &str) -> Result<Self, FromJsonError>;
+
+            /// Deserializes a value from a `serde_json::Value`.
+            fn from_value(value: serde_json::Value) -> Result<Self, FromJsonError>;
+        }
+
+        #cfg
+        impl<T: serde::de::DeserializeOwned> FromJson for T {
+            fn from_json_str(s: &str) -> Result<Self, FromJsonError> {
+                let mut deserializer = serde_json::Deserializer::from_str(s);
+                let value = serde_path_to_error::deserialize(&mut deserializer)?;
+                deserializer.end().map_err(|inner| FromJsonError {
+                    path: ".".into(),
+                    inner,
+                })?;
+                Ok(value)
+            }
+
+            fn from_value(value: serde_json::Value) -> Result<Self, FromJsonError> {
+                Ok(serde_path_to_error::deserialize(value)?)
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the implementation of the `Validate` trait for the provided type, if it has one.
+fn gen_validate(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    generics: &[&str],
+) -> io::Result<()> {
+    if !implements_validate(ctx, &ty.path) {
+        return Ok(());
+    }
+    let value = quote!(value);
+    let mut body = TokenStream::new();
+    match &ty.kind {
+        TypeKind::Alias(alias) => {
+            let mut constraints = alias.constraints.clone();
+            // Pattern newtypes can't be built from values that don't match.
+            if ctx.is_validated(alias, &ty.path) {
+                constraints.pattern = None;
+            }
+            let mut visited = vec![&*ty.path];
+            let mut checks = TokenStream::new();
+            validation_checks(
+                ctx,
+                &alias.ty,
+                &constraints,
+                &value,
+                None,
+                0,
+                &mut visited,
+                &mut checks,
+            );
+            if !checks.is_empty() {
+                body.extend(quote! {
+                    let value = &self.0;
+                    #checks
+                });
+            }
+        }
+        TypeKind::Struct(s) => {
+            for field in s.fields.values() {
+                // The type of fields with their own module may differ from the one of the schema.
+                if field.serde_with.is_some() {
+                    continue;
+                }
+                let location = field.name_in_json.to_token_stream();
+                let mut checks = TokenStream::new();
+                validation_checks(
+                    ctx,
+                    &field.ty,
+                    &field.constraints,
+                    &value,
+                    Some(&location),
+                    0,
+                    &mut Vec::new(),
+                    &mut checks,
+                );
+                if checks.is_empty() {
+                    continue;
+                }
+                let name = parse_ident(&field.name)?;
+                body.extend(if ctx.is_tri_state(field) {
+                    quote!(if let Maybe::Value(value) = &self.#name { #checks })
+                } else if !field.required {
+                    quote!(if let Some(value) = &self.#name { #checks })
+                } else {
+                    quote!({
+                        let value = &self.#name;
+                        #checks
+                    })
+                });
+            }
+        }
+        TypeKind::Enum(e) => {
+            let mut arms = TokenStream::new();
+            for variant in e.variants.values() {
+                let Some(inner) = &variant.ty else {
+                    continue;
+                };
+                let mut checks = TokenStream::new();
+                validation_checks(
+                    ctx,
+                    inner,
+                    &Constraints::default(),
+                    &value,
+                    None,
+                    0,
+                    &mut Vec::new(),
+                    &mut checks,
+                );
+                if checks.is_empty() {
+                    continue;
+                }
+                let name = parse_ident(&variant.name)?;
+                arms.extend(quote!(Self::#name(value) => { #checks }));
+            }
+            if !arms.is_empty() {
+                body.extend(quote! {
+                    match self {
+                        #arms
+                        _ => (),
+                    }
+                });
+            }
+        }
+    }
+
+    let decl = ctx.generics_decl(generics)?;
+    let name = parse_ident(&ty.name)?;
+    let args = generics_args(generics)?;
+    tokens.extend(quote! {
+        impl #decl Validate for #name #args {
+            fn validate(&self) -> Result<(), ValidationError> {
+                #body
+                Ok(())
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Returns whether the type at `path` implements the generated `Validate` trait, which is the
+/// case of the types that are not generated as type aliases.
+fn implements_validate(ctx: &Ctx, path: &str) -> bool {
+    match ctx.file.types.get(path).map(|ty| &ty.kind) {
+        Some(TypeKind::Alias(alias)) => {
+            ctx.config.generation.is_newtype(path) || ctx.is_validated(alias, path)
+        }
+        Some(_) => true,
+        None => false,
+    }
+}
+
+/// Appends to `checks` the statements checking `value`, an expression evaluating to a reference
+/// to a value of type `ty` that must satisfy `constraints`.
+///
+/// `location` is an expression evaluating to the path of the value reported in errors, or
+/// `None` for the validated value itself. `depth` is used to name the bindings of nested
+/// values, and `visited` holds the aliases being followed.
+#[allow(clippy::too_many_arguments)]
+fn validation_checks<'a>(
+    ctx: &Ctx<'a>,
+    ty: &'a TypeRef,
+    constraints: &Constraints,
+    value: &TokenStream,
+    location: Option<&TokenStream>,
+    depth: usize,
+    visited: &mut Vec<&'a str>,
+    checks: &mut TokenStream,
+) {
+    let at = location.cloned().unwrap_or_else(|| quote!(""));
+    let mut check = |condition: TokenStream, message: String| {
+        checks.extend(quote! {
+            if #condition {
+                return Err(ValidationError::new(#at, #message));
+            }
+        });
+    };
+    let count = |n: u64, word: &str| match n {
+        1 => format!("1 {word}"),
+        _ => format!("{n} {word}s"),
+    };
+    if let Some(min) = constraints.min_length {
+        let message = format!("must be at least {} long", count(min, "character"));
+        let min = Literal::u64_unsuffixed(min);
+        check(quote!(#value.chars().count() < #min), message);
+    }
+    if let Some(max) = constraints.max_length {
+        let message = format!("must be at most {} long", count(max, "character"));
+        let max = Literal::u64_unsuffixed(max);
+        check(quote!(#value.chars().count() > #max), message);
+    }
+    if let Some(min) = constraints.min_items {
+        let message = format!("must have at least {}", count(min, "item"));
+        let min = Literal::u64_unsuffixed(min);
+        check(quote!(#value.len() < #min), message);
+    }
+    if let Some(max) = constraints.max_items {
+        let message = format!("must have at most {}", count(max, "item"));
+        let max = Literal::u64_unsuffixed(max);
+        check(quote!(#value.len() > #max), message);
+    }
+    let name = ctx.type_ref_name(ty, true);
+    if let Some(min) = &constraints.minimum {
+        if let Some(bound) = bound_literal(&name, min, false) {
+            check(quote!(*#value < #bound), format!("must be at least {min}"));
+        }
+    }
+    if let Some(max) = &constraints.maximum {
+        if let Some(bound) = bound_literal(&name, max, true) {
+            check(quote!(*#value > #bound), format!("must be at most {max}"));
+        }
+    }
+    // The patterns the `regex` crate does not support are reported by `check`.
+    if let Some(pattern) = constraints
+        .pattern
+        .as_ref()
+        .filter(|pattern| pattern_error(pattern).is_none())
+    {
+        let message = format!("must match the pattern `{pattern}`");
+        let pattern = raw_string(pattern);
+        checks.extend(quote! {
+            {
+                static REGEX: std::sync::OnceLock<Option<regex::Regex>> = std::sync::OnceLock::new();
+                let regex = REGEX.get_or_init(|| regex::Regex::new(#pattern).ok());
+                if !regex.as_ref().is_some_and(|regex| regex.is_match(#value.as_ref())) {
+                    return Err(ValidationError::new(#at, #message));
+                }
+            }
+        });
+    }
+
+    let nested = |ty: &'a TypeRef,
+                  value: &TokenStream,
+                  location: Option<&TokenStream>,
+                  visited: &mut Vec<&'a str>| {
+        let mut checks = TokenStream::new();
+        validation_checks(
+            ctx,
+            ty,
+            &Constraints::default(),
+            value,
+            location,
+            depth + 1,
+            visited,
+            &mut checks,
+        );
+        checks
+    };
+    match ty {
+        TypeRef::Ref(path) if implements_validate(ctx, path) => match location {
+            None => checks.extend(quote!(#value.validate()?;)),
+            Some(location) => {
+                checks.extend(quote!(#value.validate().map_err(|e| e.at(#location))?;))
+            }
+        },
+        TypeRef::Ref(path) => {
+            let Some(TypeKind::Alias(alias)) = ctx.file.types.get(path).map(|ty| &ty.kind) else {
+                return;
+            };
+            if visited.contains(&&**path) {
+                return;
+            }
+            visited.push(path);
+            validation_checks(
+                ctx,
+                &alias.ty,
+                &alias.constraints,
+                value,
+                location,
+                depth,
+                visited,
+                checks,
+            );
+            visited.pop();
+        }
+        TypeRef::Array(inner) | TypeRef::Set(inner) => {
+            let (index, item) = (format_ident!("i{depth}"), format_ident!("item{depth}"));
+            let location = quote!(format!("{}[{}]", #at, #index));
+            let inner_checks = nested(inner, &quote!(#item), Some(&location), visited);
+            if !inner_checks.is_empty() {
+                checks.extend(quote! {
+                    for (#index, #item) in #value.iter().enumerate() {
+                        #inner_checks
+                    }
+                });
+            }
+        }
+        TypeRef::Map(inner) => {
+            let (key, item) = (format_ident!("key{depth}"), format_ident!("item{depth}"));
+            let location = quote!(format!("{}.{}", #at, #key));
+            let inner_checks = nested(inner, &quote!(#item), Some(&location), visited);
+            if !inner_checks.is_empty() {
+                checks.extend(quote! {
+                    for (#key, #item) in #value.iter() {
+                        #inner_checks
+                    }
+                });
+            }
+        }
+        TypeRef::Nullable(inner) => {
+            let item = format_ident!("item{depth}");
+            let inner_checks = nested(inner, &quote!(#item), location, visited);
+            if !inner_checks.is_empty() {
+                checks.extend(quote! {
+                    if let Some(#item) = #value {
+                        #inner_checks
+                    }
+                });
+            }
+        }
+        TypeRef::Boxed(_, inner) => {
+            checks.extend(nested(inner, &quote!((&**#value)), location, visited));
+        }
+        TypeRef::Tuple(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let format = format!("{{}}[{i}]");
+                let location = quote!(format!(#format, #at));
+                let index = syn::Index::from(i);
+                let value = quote!((&#value.#index));
+                checks.extend(nested(item, &value, Some(&location), visited));
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Returns the literal the values of the integer or float type `name` are compared to, to check
+/// `bound`.
+///
+/// Returns `None` when no value of the type can break the bound, or when the type is not a
+/// primitive.
+fn bound_literal(name: &str, bound: &serde_json::Number, upper: bool) -> Option<TokenStream> {
+    if matches!(name, "f32" | "f64") {
+        return Some(float_literal(bound.as_f64()?));
+    }
+    let (min, max): (i128, i128) = match name {
+        "i8" => (i8::MIN.into(), i8::MAX.into()),
+        "i16" => (i16::MIN.into(), i16::MAX.into()),
+        "i32" => (i32::MIN.into(), i32::MAX.into()),
+        "i64" => (i64::MIN.into(), i64::MAX.into()),
+        "u8" => (0, u8::MAX.into()),
+        "u16" => (0, u16::MAX.into()),
+        "u32" => (0, u32::MAX.into()),
+        "u64" => (0, u64::MAX.into()),
+        _ => return None,
+    };
+    let bound: i128 = match (bound.as_i64(), bound.as_u64(), bound.as_f64()) {
+        (Some(n), _, _) => n.into(),
+        (_, Some(n), _) => n.into(),
+        // Fractional bounds are rounded towards the values they accept.
+        (_, _, Some(n)) if upper => n.floor() as i128,
+        (_, _, Some(n)) => n.ceil() as i128,
+        _ => return None,
+    };
+    let always = if upper { bound >= max } else { bound <= min };
+    (!always && bound >= min && bound <= max).then(|| int_literal(bound))
+}
+
+/// Writes the `EmptyObject` struct the unit structs are (de)serialized through, along with the
+/// `NoParams` struct shared by the methods without parameters, if any.
+fn gen_unit_helpers(tokens: &mut TokenStream, ctx: &Ctx) -> io::Result<()> {
+    let methods = ctx
+        .file
+        .methods
+        .iter()
+        .filter(|method| ctx.param_types() && ctx.has_shared_params(method))
+        .collect::<Vec<_>>();
+    if methods.is_empty() && !ctx.file.types.values().any(|ty| ctx.is_unit_struct(ty)) {
+        return Ok(());
+    }
+
+    let cfg = ctx.serde_cfg();
+    tokens.extend(quote! {
+        /// The JSON representation of the unit structs.
+        #cfg
+        #[derive(Serialize, Deserialize)]
+        struct EmptyObject {}
+    });
+
+    let Some(first) = methods.first() else {
+        return Ok(());
+    };
+    let by_position = methods
+        .iter()
+        .all(|method| method.param_structure == ParamStructure::ByPosition);
+    tokens.extend(quote! {
+        /// The parameters of the methods without parameters.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        pub struct NoParams;
+    });
+    let name = format_ident!("NoParams");
+    if ctx.config.generation.param_helpers {
+        tokens.extend(cfg);
+        gen_param_helpers(tokens, first, &name, &[], &TokenStream::new())?;
+    }
+    let repr = if by_position {
+        quote!([(); 0])
+    } else {
+        quote!(EmptyObject {})
+    };
+    // Missing parameters are deserialized from `null`.
+    gen_unit_serde(tokens, ctx, &name, repr, quote!(Option<EmptyObject>))?;
+
+    Ok(())
+}
+
+/// Writes the `Serialize` and `Deserialize` implementations of the unit struct `name`, which is
+/// serialized as the expression `repr` and deserialized from a value of type `from`.
+fn gen_unit_serde(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    name: &syn::Ident,
+    repr: TokenStream,
+    from: TokenStream,
+) -> io::Result<()> {
+    let cfg = ctx.serde_cfg();
+    tokens.extend(quote! {
+        #cfg
+        impl Serialize for #name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                #repr.serialize(serializer)
+            }
+        }
+
+        #cfg
+        impl<'de> Deserialize<'de> for #name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <#from>::deserialize(deserializer).map(|_| Self)
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Returns the integer value of a variant of an enum tagged with [`EnumTag::Integer`].
+fn integer_value(variant: &EnumVariant) -> i128 {
+    variant
+        .name_in_json
+        .as_deref()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Writes the `Serialize` and `Deserialize` implementations of an enum tagged with
+/// [`EnumTag::Integer`], whose values are integers of type `repr`.
+///
+/// Unknown values are rejected, unless the enum has a fallback variant holding them.
+fn gen_integer_enum_serde(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    e: &EnumDef,
+    repr: &str,
+) -> io::Result<()> {
+    let cfg = ctx.serde_cfg();
+    let name = parse_ident(&ty.name)?;
+    let repr = parse_type(repr)?;
+    let mut variants: Vec<_> = e.variants.values().collect();
+    variants.sort_by_key(|variant| (variant.fallback, integer_value(variant)));
+
+    let mut serialize_arms = TokenStream::new();
+    let mut deserialize_arms = TokenStream::new();
+    for variant in &variants {
+        let ident = parse_ident(&variant.name)?;
+        if variant.name_in_json.is_some() && !variant.fallback {
+            let value = int_literal(integer_value(variant));
+            serialize_arms.extend(quote!(Self::#ident => #value,));
+            deserialize_arms.extend(quote!(#value => Ok(Self::#ident),));
+        } else {
+            serialize_arms.extend(quote!(Self::#ident(value) => *value,));
+            deserialize_arms.extend(quote!(value => Ok(Self::#ident(value)),));
+        }
+    }
+    if !variants.iter().any(|variant| variant.fallback) {
+        let expected = variants
+            .iter()
+            .filter_map(|variant| variant.name_in_json.as_deref())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = format!("unknown value {{value}}, expected one of {expected}");
+        deserialize_arms.extend(quote! {
+            value => Err(serde::de::Error::custom(format_args!(#message))),
+        });
+    }
+
+    tokens.extend(quote! {
+        #cfg
+        impl Serialize for #name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let value: #repr = match self {
+                    #serialize_arms
+                };
+                value.serialize(serializer)
+            }
+        }
+
+        #cfg
+        impl<'de> Deserialize<'de> for #name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                match <#repr>::deserialize(deserializer)? {
+                    #deserialize_arms
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the `Deserialize` implementation of an enum tagged with a property, untagged, or
+/// mixing string variants with untagged ones.
+///
+/// Tagged enums read the entries of the map until the tag, and deserialize the variant it names
+/// from the remaining entries, only buffering the ones that precede the tag. The others read
+/// the value as a `serde_json::Value`, try their variants in order, and report the error of
+/// each one when none of them matches.
+fn gen_enum_deserialize(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    e: &EnumDef,
+) -> io::Result<()> {
+    let cfg = ctx.serde_cfg();
+    let name = parse_ident(&ty.name)?;
+    let (fallbacks, variants) = e
+        .variants
+        .values()
+        .partition::<Vec<_>, _>(|variant| variant.fallback);
+    let fallback = fallbacks.first();
+
+    let body = if let EnumTag::Tagged(tag) = &e.tag {
+        let core = format_ident!("{}", ctx.std_mod());
+        let expecting = format!("enum {} tagged with `{tag}`", ty.name);
+        let mut arms = TokenStream::new();
+        let mut expected = Vec::new();
+        for variant in &variants {
+            let value = variant.name_in_json.as_deref().unwrap_or(&variant.name);
+            let ident = parse_ident(&variant.name)?;
+            expected.push(value);
+            arms.extend(match &variant.ty {
+                Some(_) => quote!(#value => Deserialize::deserialize(rest).map(#name::#ident),),
+                None => quote! {
+                    #value => serde::de::IgnoredAny::deserialize(rest).map(|_| #name::#ident),
+                },
+            });
+        }
+        match fallback {
+            Some(variant) if variant.ty.is_some() => {
+                let ident = parse_ident(&variant.name)?;
+                arms.extend(quote! {
+                    _ => serde_json::Map::deserialize(rest).map(|mut object| {
+                        object.insert(#tag.into(), serde_json::Value::String(tag.clone()));
+                        #name::#ident(serde_json::Value::Object(object))
+                    }),
+                });
+            }
+            Some(variant) => {
+                let ident = parse_ident(&variant.name)?;
+                arms.extend(
+                    quote!(_ => serde::de::IgnoredAny::deserialize(rest).map(|_| #name::#ident),),
+                );
+            }
+            None => arms.extend(quote! {
+                other => return Err(A::Error::unknown_variant(other, &[#(#expected),*])),
+            }),
+        }
+        let invalid = format!("invalid `{{tag}}` variant of `{}`: {{err}}", ty.name);
+        quote! {
+            struct Visitor;
+
+            impl<'de> serde::de::Visitor<'de> for Visitor {
+                type Value = #name;
+
+                fn expecting(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                    f.write_str(#expecting)
+                }
+
+                fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<#name, A::Error> {
+                    use serde::de::value::MapAccessDeserializer;
+                    use serde::de::Error as _;
+
+                    let mut buffered = Vec::new();
+                    let tag = loop {
+                        match map.next_key::<String>()? {
+                            Some(key) if key == #tag => break map.next_value::<String>()?,
+                            Some(key) => buffered.push((key, map.next_value()?)),
+                            None => return Err(A::Error::missing_field(#tag)),
+                        }
+                    };
+                    let rest = MapAccessDeserializer::new(TaggedMap::new(buffered, map));
+                    let result = match tag.as_str() {
+                        #arms
+                    };
+                    result.map_err(|err| A::Error::custom(format_args!(#invalid)))
+                }
+            }
+
+            deserializer.deserialize_map(Visitor)
+        }
+    } else {
+        let (units, payloads) = variants
+            .iter()
+            .copied()
+            .partition::<Vec<_>, _>(|variant| variant.ty.is_none());
+        let mut body = quote! {
+            use serde::de::Error as _;
+
+            let value = serde_json::Value::deserialize(deserializer)?;
+        };
+        if matches!(e.tag, EnumTag::Mixed) {
+            let mut arms = TokenStream::new();
+            for variant in &units {
+                let value = variant.name_in_json.as_deref().unwrap_or(&variant.name);
+                let ident = parse_ident(&variant.name)?;
+                arms.extend(quote!(#value => return Ok(Self::#ident),));
+            }
+            body.extend(quote! {
+                if let Some(tag) = value.as_str() {
+                    match tag {
+                        #arms
+                        _ => (),
+                    }
+                }
+            });
+        } else if let Some(variant) = units.first() {
+            let ident = parse_ident(&variant.name)?;
+            body.extend(quote! {
+                if value.is_null() {
+                    return Ok(Self::#ident);
+                }
+            });
+        }
+        if fallback.is_none() {
+            body.extend(quote!(let mut errors: Vec<String> = Vec::new();));
+        }
+        for variant in &payloads {
+            let ident = parse_ident(&variant.name)?;
+            let error = match fallback {
+                Some(_) => quote!(Err(_) => ()),
+                None => {
+                    let message = format!("`{}`: {{err}}", variant.name);
+                    quote!(Err(err) => errors.push(format!(#message)))
+                }
+            };
+            body.extend(quote! {
+                match Deserialize::deserialize(&value) {
+                    Ok(inner) => return Ok(Self::#ident(inner)),
+                    #error,
+                }
+            });
+        }
+        body.extend(match fallback {
+            Some(variant) if variant.ty.is_some() => {
+                let ident = parse_ident(&variant.name)?;
+                quote!(Ok(Self::#ident(value)))
+            }
+            Some(variant) => {
+                let ident = parse_ident(&variant.name)?;
+                quote!(Ok(Self::#ident))
+            }
+            None => {
+                let message = format!("data did not match any variant of `{}`: {{}}", ty.name);
+                quote!(Err(D::Error::custom(
+                    format_args!(#message, errors.join("; "))
+                )))
+            }
+        });
+        body
+    };
+
+    tokens.extend(quote! {
+        #cfg
+        impl<'de> Deserialize<'de> for #name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                #body
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the `Serialize` and `Deserialize` implementations of a struct flattening other
+/// structs, whose fields, including the flattened ones, are `fields`.
+///
+/// The fields are written and matched as the entries of a single map, so that no intermediate
+/// map is needed and unknown fields can be rejected.
+fn gen_flatten_serde(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    fields: &[FlatField],
+) -> io::Result<()> {
+    let cfg = ctx.serde_cfg();
+    let core = format_ident!("{}", ctx.std_mod());
+    let name = parse_ident(&ty.name)?;
+
+    let mut entries = TokenStream::new();
+    for FlatField { field, access, .. } in fields {
+        let access = access
+            .split('.')
+            .map(parse_ident)
+            .collect::<io::Result<Vec<_>>>()?;
+        let access = quote!(self.#(#access).*);
+        let key = &field.name_in_json;
+        let entry = quote!(map.serialize_entry(#key, &#access)?;);
+        entries.extend(if ctx.is_tri_state(field) {
+            quote!(if !#access.is_absent() { #entry })
+        } else if !field.required && ctx.skip_serializing_none(&field.path) {
+            quote!(if #access.is_some() { #entry })
+        } else {
+            entry
+        });
+    }
+
+    let bindings = (0..fields.len())
+        .map(|i| format_ident!("f{i}"))
+        .collect::<Vec<_>>();
+    let keys = fields
+        .iter()
+        .map(|f| &f.field.name_in_json)
+        .collect::<Vec<_>>();
+    let types = fields
+        .iter()
+        .map(|f| parse_type(&ctx.field_type_name(f.field)))
+        .collect::<io::Result<Vec<_>>>()?;
+    let unknown = if ctx.serde_options(ty).deny_unknown_fields == Some(true) {
+        quote!(other => return Err(A::Error::unknown_field(other, &[#(#keys),*])),)
+    } else {
+        quote! {
+            _ => {
+                map.next_value::<serde::de::IgnoredAny>()?;
+            }
+        }
+    };
+    let mut unwraps = TokenStream::new();
+    for (FlatField { owner, field, .. }, binding) in fields.iter().zip(&bindings) {
+        unwraps.extend(if field.default.is_some() {
+            let default = parse_ident(&default_fn_name(owner, field))?;
+            quote!(let #binding = #binding.unwrap_or_else(#default);)
+        } else if !field.required {
+            quote!(let #binding = #binding.unwrap_or_default();)
+        } else {
+            let key = &field.name_in_json;
+            quote!(let #binding = #binding.ok_or_else(|| A::Error::missing_field(#key))?;)
+        });
+    }
+    let init = flatten_init(ctx, ty, &mut 0)?;
+    let expecting = format!("struct {}", ty.name);
+
+    tokens.extend(quote! {
+        #cfg
+        impl Serialize for #name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeMap as _;
+
+                let mut map = serializer.serialize_map(None)?;
+                #entries
+                map.end()
+            }
+        }
+
+        #cfg
+        impl<'de> Deserialize<'de> for #name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = #name;
+
+                    fn expecting(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                        f.write_str(#expecting)
+                    }
+
+                    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<#name, A::Error> {
+                        use serde::de::Error as _;
+
+                        #(let mut #bindings: Option<#types> = None;)*
+                        while let Some(key) = map.next_key::<String>()? {
+                            match key.as_str() {
+                                #(
+                                    #keys => {
+                                        if #bindings.is_some() {
+                                            return Err(A::Error::duplicate_field(#keys));
+                                        }
+                                        #bindings = Some(map.next_value()?);
+                                    }
+                                )*
+                                #unknown
+                            }
+                        }
+                        #unwraps
+                        Ok(#init)
+                    }
+                }
+
+                deserializer.deserialize_map(Visitor)
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Returns the expression building the struct `ty` from the bindings of its fields, numbered
+/// from `next` in the order of [`Ctx::flattened_fields`].
+fn flatten_init(ctx: &Ctx, ty: &TypeDef, next: &mut usize) -> io::Result<TokenStream> {
+    let TypeKind::Struct(s) = &ty.kind else {
+        return Ok(TokenStream::new());
+    };
+    let mut init = TokenStream::new();
+    for field in s.fields.values() {
+        let value = match &field.ty {
+            TypeRef::Ref(path) if field.flatten => match ctx.file.types.get(path) {
+                Some(inner) => flatten_init(ctx, inner, next)?,
+                None => TokenStream::new(),
+            },
+            _ => {
+                *next += 1;
+                format_ident!("f{}", *next - 1).into_token_stream()
+            }
+        };
+        let name = parse_ident(&field.name)?;
+        init.extend(quote!(#name: #value,));
+    }
+    let name = parse_ident(&ty.name)?;
+    Ok(quote!(#name { #init }))
+}
+
+/// Returns whether the provided enum is an enum of strings, whose variants hold no value.
+fn is_string_enum(e: &EnumDef) -> bool {
+    matches!(e.tag, EnumTag::Normal)
+        && !e.variants.is_empty()
+        && e.variants.values().all(|variant| variant.ty.is_none())
+}
+
+/// Writes the `as_str` method of the provided enum of strings, along with its `Display` and
+/// `FromStr` implementations.
+fn gen_enum_strings(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    e: &EnumDef,
+) -> io::Result<()> {
+    let core = format_ident!("{}", ctx.std_mod());
+    let name = parse_ident(&ty.name)?;
+    let value = |variant: &'_ EnumVariant| -> String {
+        variant
+            .name_in_json
+            .clone()
+            .unwrap_or_else(|| variant.name.clone())
+    };
+
+    let mut as_str = TokenStream::new();
+    let mut from_str = TokenStream::new();
+    let mut fallback = None;
+    for variant in e.variants.values() {
+        let ident = parse_ident(&variant.name)?;
+        let value = value(variant);
+        as_str.extend(quote!(Self::#ident => #value,));
+        if variant.fallback {
+            fallback = Some(ident);
+        } else {
+            from_str.extend(quote!(#value => Ok(Self::#ident),));
+        }
+    }
+    from_str.extend(match fallback {
+        Some(ident) => quote!(_ => Ok(Self::#ident),),
+        None => {
+            let ty_name = &ty.name;
+            quote!(_ => Err(UnknownVariantError { ty: #ty_name, value: s.into() }),)
+        }
+    });
+
+    tokens.extend(quote! {
+        impl #name {
+            /// Returns the JSON value of this variant.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    #as_str
+                }
+            }
+        }
+
+        impl #core::fmt::Display for #name {
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl #core::str::FromStr for #name {
+            type Err = UnknownVariantError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    #from_str
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the `tag` method of the provided enum, returning the tag of each variant, if it is
+/// tagged with a property or adjacently.
+fn gen_enum_tag(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    e: &EnumDef,
+    generics: &[&str],
+) -> io::Result<()> {
+    let tag = match &e.tag {
+        EnumTag::Tagged(tag) | EnumTag::Adjacent { tag, .. } => tag,
+        _ => return Ok(()),
+    };
+    if e.variants.is_empty() {
+        return Ok(());
+    }
+    let fallible = e.variants.values().any(|variant| variant.fallback);
+
+    let mut arms = TokenStream::new();
+    for variant in e.variants.values() {
+        let ident = parse_ident(&variant.name)?;
+        let pattern = match variant.ty {
+            Some(_) => quote!(Self::#ident(_)),
+            None => quote!(Self::#ident),
+        };
+        let value = variant.name_in_json.as_deref().unwrap_or(&variant.name);
+        arms.extend(if variant.fallback {
+            quote!(#pattern => None,)
+        } else if fallible {
+            quote!(#pattern => Some(#value),)
+        } else {
+            quote!(#pattern => #value,)
+        });
+    }
+    let doc = doc_line(&format!(
+        "Returns the value of the `{tag}` tag of this variant."
+    ));
+    let signature = if fallible {
+        quote! {
+            ///
+            /// This is `None` for the values of unknown variants.
+            pub fn tag(&self) -> Option<&'static str>
+        }
+    } else {
+        quote!(pub fn tag(&self) -> &'static str)
+    };
+
+    let decl = ctx.generics_decl(generics)?;
+    let name = parse_ident(&ty.name)?;
+    let args = generics_args(generics)?;
+    tokens.extend(quote! {
+        impl #decl #name #args {
+            #doc
+            #signature {
+                match self {
+                    #arms
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the `is_*`, `as_*` and `into_*` methods of the provided enum.
+fn gen_enum_accessors(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    e: &EnumDef,
+    generics: &[&str],
+) -> io::Result<()> {
+    // A catch-all arm would be unreachable in enums with a single variant.
+    let otherwise = if e.variants.len() > 1 {
+        quote!(_ => None,)
+    } else {
+        TokenStream::new()
+    };
+
+    let mut methods = TokenStream::new();
+    for variant in e.variants.values() {
+        let name = &variant.name;
+        let ident = parse_ident(name)?;
+        let snake = ident::snake(ident::unraw(name));
+        let pattern = match variant.ty {
+            Some(_) => quote!(Self::#ident(_)),
+            None => quote!(Self::#ident),
+        };
+        let is = parse_ident(&format!("is_{snake}"))?;
+        let doc = doc_line(&format!("Returns whether this is a [`Self::{name}`]."));
+        methods.extend(quote! {
+            #doc
+            pub fn #is(&self) -> bool {
+                matches!(self, #pattern)
+            }
+        });
+        let Some(inner) = &variant.ty else {
+            continue;
+        };
+        let inner = parse_type(&ctx.type_ref_name(inner, true))?;
+        let as_ = parse_ident(&format!("as_{snake}"))?;
+        let into = parse_ident(&format!("into_{snake}"))?;
+        let as_doc = doc_line(&format!(
+            "Returns the value held by this [`Self::{name}`], if it is one."
+        ));
+        let into_doc = doc_line(&format!(
+            "Converts this [`Self::{name}`] into the value it holds, if it is one."
+        ));
+        methods.extend(quote! {
+            #as_doc
+            pub fn #as_(&self) -> Option<&#inner> {
+                match self { Self::#ident(value) => Some(value), #otherwise }
+            }
+
+            #into_doc
+            pub fn #into(self) -> Option<#inner> {
+                match self { Self::#ident(value) => Some(value), #otherwise }
+            }
+        });
+    }
+
+    let decl = ctx.generics_decl(generics)?;
+    let name = parse_ident(&ty.name)?;
+    let args = generics_args(generics)?;
+    tokens.extend(quote! {
+        impl #decl #name #args {
+            #methods
+        }
+    });
+    Ok(())
+}
+
+/// Returns the name of the function returning the default value of `field`, a field of `ty`.
+fn default_fn_name(ty: &TypeDef, field: &StructField) -> String {
+    format!(
+        "default_{}_{}",
+        ty.name.to_case(Case::Snake),
+        ident::unraw(&field.name),
+    )
+}
+
+/// Writes the functions returning the default values of the fields of the provided struct.
+///
+/// When all of its fields are either optional or have a default value, a `Default`
+/// implementation is written for the struct as well.
+fn gen_struct_defaults(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    s: &StructDef,
+    generics: &[&str],
+) -> io::Result<()> {
+    let name = parse_ident(&ty.name)?;
+    let defaulted = s
+        .fields
+        .values()
+        .all(|f| !f.required || f.default.is_some());
+    if defaulted && s.fields.values().any(|f| f.default.is_some()) {
+        let mut init = TokenStream::new();
+        for field in s.fields.values() {
+            let ident = parse_ident(&field.name)?;
+            init.extend(match field.default {
+                Some(_) => {
+                    let default = parse_ident(&default_fn_name(ty, field))?;
+                    quote!(#ident: #default(),)
+                }
+                None => quote!(#ident: Default::default(),),
+            });
+        }
+        if ctx.has_extra_fields(&ty.path) {
+            init.extend(quote!(extra: Default::default(),));
+        }
+        let decl = ctx.generics_decl(generics)?;
+        let args = generics_args(generics)?;
+        tokens.extend(quote! {
+            impl #decl Default for #name #args {
+                fn default() -> Self {
+                    Self {
+                        #init
+                    }
+                }
+            }
+        });
+    }
+
+    for field in s.fields.values() {
+        let Some(value) = &field.default else {
+            continue;
+        };
+        // The parameters the field doesn't mention could not be inferred where the function
+        // is called.
+        let used = ctx.type_ref_generics(&field.ty);
+        let params = generics
+            .iter()
+            .copied()
+            .filter(|param| used.contains(param))
+            .collect::<Vec<_>>();
+        let default = parse_ident(&default_fn_name(ty, field))?;
+        let decl = ctx.generics_decl(&params)?;
+        let field_ty = parse_type(&ctx.field_type_name(field))?;
+        let expr = ctx.default_expr(field, value)?;
+        tokens.extend(quote! {
+            fn #default #decl() -> #field_ty {
+                #expr
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Writes the `new` function of the provided struct, taking its required fields, along with a
+/// `with_*` method for each of its other fields.
+fn gen_constructors(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    s: &StructDef,
+    generics: &[&str],
+) -> io::Result<()> {
+    let (required, others) = s
+        .fields
+        .values()
+        .partition::<Vec<_>, _>(|f| f.required && f.default.is_none());
+
+    let allow = (required.len() > 7).then(|| quote!(#[allow(clippy::too_many_arguments)]));
+    let mut params = Vec::new();
+    for field in &required {
+        let ident = parse_ident(&field.name)?;
+        let field_ty = parse_type(&ctx.field_type_name(field))?;
+        params.push(quote!(#ident: #field_ty));
+    }
+    let mut init = TokenStream::new();
+    for field in s.fields.values() {
+        let ident = parse_ident(&field.name)?;
+        init.extend(if field.required && field.default.is_none() {
+            quote!(#ident,)
+        } else if field.default.is_some() {
+            let default = parse_ident(&default_fn_name(ty, field))?;
+            quote!(#ident: #default(),)
+        } else {
+            quote!(#ident: Default::default(),)
+        });
+    }
+    if ctx.has_extra_fields(&ty.path) {
+        init.extend(quote!(extra: Default::default(),));
+    }
+    let mut setters = TokenStream::new();
+    for field in others {
+        let name = parse_ident(&field.name)?;
+        let unraw = ident::unraw(&field.name);
+        let (param, value) = if ctx.is_tri_state(field) {
+            (
+                ctx.type_ref_name(&field.ty, true),
+                quote!(Maybe::Value(#name)),
+            )
+        } else if field.required || matches!(field.ty, TypeRef::Nullable(_)) {
+            (ctx.field_type_name(field), quote!(#name))
+        } else {
+            (ctx.type_ref_name(&field.ty, true), quote!(Some(#name)))
+        };
+        let param = parse_type(&param)?;
+        let with = parse_ident(&format!("with_{unraw}"))?;
+        let doc = doc_line(&format!("Sets the `{unraw}` field."));
+        setters.extend(quote! {
+            #doc
+            pub fn #with(mut self, #name: #param) -> Self {
+                self.#name = #value;
+                self
+            }
+        });
+    }
+
+    let decl = ctx.generics_decl(generics)?;
+    let name = parse_ident(&ty.name)?;
+    let args = generics_args(generics)?;
+    tokens.extend(quote! {
+        impl #decl #name #args {
+            /// Creates a new value from its required fields, leaving the others to their default.
+            #allow
+            pub fn new(#(#params),*) -> Self {
+                Self {
+                    #init
+                }
+            }
+
+            #setters
+        }
+    });
+    Ok(())
+}
+
+/// Writes the builder of the provided struct, along with the `builder` function returning it.
+///
+/// The builder takes a type parameter per required field, which is `()` until the field is set
+/// and `(T,)` afterwards. `build` is only implemented once all of them are set.
+fn gen_builder(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    s: &StructDef,
+    generics: &[&str],
+) -> io::Result<()> {
+    let core = format_ident!("{}", ctx.std_mod());
+    let name = parse_ident(&ty.name)?;
+    let args = generics_args(generics)?;
+    let builder = parse_ident(&format!("{}Builder", ty.name))?;
+    let required = s
+        .fields
+        .values()
+        .filter(|f| f.required && f.default.is_none())
+        .collect::<Vec<_>>();
+    let params = (0..required.len())
+        .map(|i| format!("F{i}"))
+        .collect::<Vec<_>>();
+    // The arguments of the builder, which are the parameters of the struct followed by the
+    // states of its required fields.
+    let builder_args = |states: &[&str]| {
+        let args = generics.iter().chain(states).copied().collect::<Vec<_>>();
+        generics_args(&args)
+    };
+    // The arguments of the builder with the state of the `i`th required field replaced.
+    let state = |i: usize, with: &str| {
+        let states = params
+            .iter()
+            .enumerate()
+            .map(|(j, param)| if i == j { with } else { param.as_str() })
+            .collect::<Vec<_>>();
+        builder_args(&states)
+    };
+    let others = |i: usize| {
+        let params = params
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, param)| param.as_str())
+            .collect::<Vec<_>>();
+        ctx.generics_decl_with(generics, &params)
+    };
+    let extra = ctx
+        .has_extra_fields(&ty.path)
+        .then(|| quote!(extra: Default::default(),));
+
+    let defaults = params
+        .iter()
+        .map(|param| format!("{param} = ()"))
+        .collect::<Vec<_>>();
+    let defaults = defaults.iter().map(String::as_str).collect::<Vec<_>>();
+    let decl = ctx.generics_decl_with(generics, &defaults)?;
+    let mut fields = TokenStream::new();
+    let mut index = 0;
+    for field in s.fields.values() {
+        let ident = parse_ident(&field.name)?;
+        if field.required && field.default.is_none() {
+            let param = format_ident!("F{index}");
+            fields.extend(quote!(#ident: #param,));
+            index += 1;
+        } else {
+            let field_ty = parse_type(&ctx.field_type_name(field))?;
+            fields.extend(quote!(#ident: #field_ty,));
+        }
+    }
+    // The parameters of the struct are not necessarily mentioned by the fields that are not
+    // set yet.
+    if !generics.is_empty() {
+        fields.extend(quote!(_marker: #core::marker::PhantomData<fn() -> #name #args>,));
+    }
+    let marker = (!generics.is_empty()).then(|| quote!(_marker: #core::marker::PhantomData,));
+    let moved_marker = (!generics.is_empty()).then(|| quote!(_marker: self._marker,));
+    let doc = doc_line(&format!(
+        "A builder of [`{name}`], created by [`{name}::builder`]."
+    ));
+    tokens.extend(quote! {
+        #doc
+        pub struct #builder #decl {
+            #fields
+        }
+    });
+
+    let mut init = TokenStream::new();
+    for field in s.fields.values() {
+        let ident = parse_ident(&field.name)?;
+        init.extend(if field.required && field.default.is_none() {
+            quote!(#ident: (),)
+        } else if field.default.is_some() {
+            let default = parse_ident(&default_fn_name(ty, field))?;
+            quote!(#ident: #default(),)
+        } else {
+            quote!(#ident: Default::default(),)
+        });
+    }
+    let decl = ctx.generics_decl(generics)?;
+    tokens.extend(quote! {
+        impl #decl #name #args {
+            /// Returns a builder of this struct, whose required fields must all be set.
+            pub fn builder() -> #builder #args {
+                #builder {
+                    #init
+                    #marker
+                }
+            }
+        }
+    });
+
+    for (i, field) in required.iter().enumerate() {
+        let ident = parse_ident(&field.name)?;
+        let field_ty = ctx.field_type_name(field);
+        let set = state(i, &format!("({field_ty},)"))?;
+        let field_ty = parse_type(&field_ty)?;
+        let mut moved = TokenStream::new();
+        for other in s.fields.values() {
+            let other_ident = parse_ident(&other.name)?;
+            moved.extend(if other.path == field.path {
+                quote!(#other_ident: (value,),)
+            } else {
+                quote!(#other_ident: self.#other_ident,)
+            });
+        }
+        let decl = others(i)?;
+        let unset = state(i, "()")?;
+        let doc = doc_line(&format!("Sets the required `{}` field.", field.name));
+        tokens.extend(quote! {
+            impl #decl #builder #unset {
+                #doc
+                pub fn #ident(self, value: #field_ty) -> #builder #set {
+                    #builder {
+                        #moved
+                        #moved_marker
+                    }
+                }
+            }
+        });
+    }
+
+    let optional = s
+        .fields
+        .values()
+        .filter(|f| !f.required || f.default.is_some())
+        .collect::<Vec<_>>();
+    if !optional.is_empty() {
+        let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+        let decl = ctx.generics_decl_with(generics, &params)?;
+        let builder_args = builder_args(&params)?;
+        let mut setters = TokenStream::new();
+        for field in optional {
+            let ident = parse_ident(&field.name)?;
+            let (param, value) = if ctx.is_tri_state(field) {
+                (
+                    ctx.type_ref_name(&field.ty, true),
+                    quote!(Maybe::Value(value)),
+                )
+            } else if field.required || matches!(field.ty, TypeRef::Nullable(_)) {
+                (ctx.field_type_name(field), quote!(value))
+            } else {
+                (ctx.type_ref_name(&field.ty, true), quote!(Some(value)))
+            };
+            let param = parse_type(&param)?;
+            let doc = doc_line(&format!("Sets the `{}` field.", field.name));
+            setters.extend(quote! {
+                #doc
+                pub fn #ident(mut self, value: #param) -> Self {
+                    self.#ident = #value;
+                    self
+                }
+            });
+        }
+        tokens.extend(quote! {
+            impl #decl #builder #builder_args {
+                #setters
+            }
+        });
+    }
+
+    let set = required
+        .iter()
+        .map(|f| format!("({},)", ctx.field_type_name(f)))
+        .collect::<Vec<_>>();
+    let set = set.iter().map(String::as_str).collect::<Vec<_>>();
+    let set = builder_args(&set)?;
+    let mut init = TokenStream::new();
+    for field in s.fields.values() {
+        let ident = parse_ident(&field.name)?;
+        init.extend(if field.required && field.default.is_none() {
+            quote!(#ident: self.#ident.0,)
+        } else {
+            quote!(#ident: self.#ident,)
+        });
+    }
+    tokens.extend(quote! {
+        impl #decl #builder #set {
+            /// Builds the struct, once all of its required fields are set.
+            pub fn build(self) -> #name #args {
+                #name {
+                    #init
+                    #extra
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Writes the documentation of the provided type, followed by its external documentation and
+/// the path of its schema when `doc-paths` is enabled.
+fn gen_type_doc(tokens: &mut TokenStream, ctx: &Ctx, ty: &TypeDef) {
+    let mut written = false;
+    if let Some(doc) = &ty.documentation {
+        tokens.extend(doc_attrs(doc));
+        written = true;
+    }
+    if let Some(url) = &ty.external_docs {
+        if written {
+            tokens.extend(doc_line(""));
+        }
+        tokens.extend(doc_line(&format!("See also <{url}>.")));
+        written = true;
+    }
+    if ctx.config.generation.doc_paths {
+        if written {
+            tokens.extend(doc_line(""));
+        }
+        tokens.extend(doc_line(&format!("Generated from `{}`.", ty.path)));
+    }
+}
+
+/// Returns the narrowest unsigned integer type holding `maximum`, when narrower than 64 bits.
+fn narrowest_unsigned(maximum: Option<u64>) -> Option<&'static str> {
+    let maximum = maximum?;
+    [
+        ("u8", u64::from(u8::MAX)),
+        ("u16", u64::from(u16::MAX)),
+        ("u32", u64::from(u32::MAX)),
+    ]
+    .into_iter()
+    .find(|(_, max)| maximum <= *max)
+    .map(|(ty, _)| ty)
+}
+
+/// Returns the name of the schema the provided type was declared as in the `components` of
+/// the document, if any.
+fn schema_name(ty: &TypeDef) -> Option<&str> {
+    ty.path
+        .strip_prefix("#/components/schemas/")
+        .filter(|name| !name.contains('/'))
+}
+
+/// Writes the `#[doc(alias = "...")]` attribute naming an item by `original`, its name in the
+/// document, when `doc-aliases` is enabled and it differs from `name`, its Rust name.
+///
+/// Names that rustdoc rejects as aliases are skipped.
+fn gen_doc_alias(tokens: &mut TokenStream, ctx: &Ctx, original: &str, name: &str) {
+    let valid = !original.is_empty()
+        && !original
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\'));
+    if ctx.config.generation.doc_aliases && valid && original != ident::unraw(name) {
+        tokens.extend(quote!(#[doc(alias = #original)]));
+    }
+}
+
+/// Writes the `SCHEMA_NAMES` constant, mapping the names of the schemas of the document to the
+/// names of the generated types.
+fn gen_schema_names(tokens: &mut TokenStream, ctx: &Ctx) {
+    let entries = ctx
+        .file
+        .types
+        .values()
+        .filter_map(|ty| schema_name(ty).map(|name| (name, &ty.name)))
+        .map(|(name, ty)| quote!((#name, #ty)));
+    tokens.extend(quote! {
+        /// Maps the names of the schemas of the document to the names of the generated types.
+        pub const SCHEMA_NAMES: &[(&str, &str)] = &[#(#entries),*];
+    });
+}
+
+/// Returns the `#[derive(...)]` attribute for the provided traits, skipping those already
+/// derived through `existing`.
+fn missing_derives(traits: &[&str], existing: &[&str]) -> Option<TokenStream> {
+    let already = |name: &str| {
+        existing
+            .iter()
+            .flat_map(|d| d.split(','))
+            .any(|d| d.trim() == name)
+    };
+    let traits: Vec<_> = traits
+        .iter()
+        .filter(|t| !already(t))
+        .map(|t| format_ident!("{t}"))
+        .collect();
+    (!traits.is_empty()).then(|| quote!(#[derive(#(#traits),*)]))
+}
+
+/// Returns the `#[derive(...)]` attribute for `derives`, a comma-separated list of traits
+/// taken from the configuration.
+fn derive_attr(derives: &str) -> io::Result<TokenStream> {
+    let derives = parse_with(
+        syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+        derives,
+    )?;
+    Ok(quote!(#[derive(#derives)]))
+}
+
+/// Writes the provided type.
+fn gen_type(tokens: &mut TokenStream, ctx: &mut Ctx, ty: &TypeDef) -> io::Result<()> {
+    tokens.extend(debug_path(ctx, &ty.path));
+    gen_type_doc(tokens, ctx, ty);
+    if let Some(name) = schema_name(ty) {
+        gen_doc_alias(tokens, ctx, name, &ty.name);
+    }
+    let name = parse_ident(&ty.name)?;
+    let generics = ctx.generics.get(&ty.name).cloned().unwrap_or_default();
+    match &ty.kind {
+        TypeKind::Alias(alias) if ctx.is_validated(alias, &ty.path) => {
+            gen_pattern_newtype(tokens, ctx, ty, alias)?;
+        }
+        TypeKind::Alias(alias) if ctx.config.generation.is_newtype(&ty.path) => {
+            if ctx.config.generation.serde_feature.is_some() {
+                tokens.extend(quote!(#[derive(Debug, Clone)]));
+                tokens.extend(ctx.serde_attr(quote!(derive(Serialize, Deserialize))));
+            } else {
+                tokens.extend(quote!(#[derive(Debug, Clone, Serialize, Deserialize)]));
+            }
+            if ctx.default_types.contains(&ty.name) {
+                tokens.extend(quote!(#[derive(Default)]));
+            }
+            if ctx.eq_types.contains(&ty.name) {
+                tokens.extend(quote!(#[derive(PartialEq, Eq, Hash)]));
+            }
+            tokens.extend(ctx.serde_attr(quote!(serde(transparent))));
+            let attrs = ctx.attributes(&ty.path)?;
+            let with = alias
+                .ty
+                .with_module_at(ctx.config, ctx.file, &ty.path)
+                .map(|module| ctx.serde_attr(quote!(serde(with = #module))));
+            let decl = ctx.generics_decl(&generics)?;
+            let inner = parse_type(&ctx.type_ref_name(&alias.ty, true))?;
+            tokens.extend(quote! {
+                #(#attrs)*
+                pub struct #name #decl(#with pub #inner);
+            });
+        }
+        TypeKind::Alias(alias) => {
+            let attrs = ctx.attributes(&ty.path)?;
+            // Bounds are not enforced on type aliases, so only the parameters are written.
+            let args = generics_args(&generics)?;
+            let inner = parse_type(&ctx.type_ref_name(&alias.ty, true))?;
+            tokens.extend(quote! {
+                #(#attrs)*
+                pub type #name #args = #inner;
+            });
+        }
+        TypeKind::Struct(_) if ctx.is_unit_struct(ty) => {
+            let non_exhaustive = ctx
+                .is_non_exhaustive(&ty.path)
+                .then(|| quote!(#[non_exhaustive]));
+            let attrs = ctx.attributes(&ty.path)?;
+            tokens.extend(quote! {
+                #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+                #non_exhaustive
+                #(#attrs)*
+                pub struct #name;
+            });
+            gen_unit_serde(
+                tokens,
+                ctx,
+                &name,
+                quote!(EmptyObject {}),
+                quote!(EmptyObject),
+            )?;
+        }
+        TypeKind::Struct(s) => {
+            // Structs flattening other structs may implement `Serialize` and `Deserialize` by
+            // hand, in which case serde attributes are not available.
+            let flattened = ctx.flattened_fields(ty);
+            if flattened.is_some() {
+                tokens.extend(quote!(#[derive(Debug, Clone)]));
+            } else if ctx.config.generation.serde_feature.is_some() {
+                tokens.extend(quote!(#[derive(Debug, Clone)]));
+                tokens.extend(ctx.serde_attr(quote!(derive(Serialize, Deserialize))));
+            } else {
+                tokens.extend(quote!(#[derive(Debug, Clone, Serialize, Deserialize)]));
+            }
+            // Structs with default values implement `Default` manually.
+            if ctx.default_types.contains(&ty.name)
+                && s.fields.values().all(|f| f.default.is_none())
+            {
+                tokens.extend(quote!(#[derive(Default)]));
+            }
+            if ctx.eq_types.contains(&ty.name) {
+                tokens.extend(quote!(#[derive(PartialEq, Eq, Hash)]));
+            }
+            if flattened.is_none() {
+                tokens.extend(ctx.serde_container(ty));
+            }
+            if ctx.is_non_exhaustive(&ty.path) {
+                tokens.extend(quote!(#[non_exhaustive]));
+            }
+            let attrs = ctx.attributes(&ty.path)?;
+            let mut fields = TokenStream::new();
+            for field in s.fields.values() {
+                fields.extend(debug_path(ctx, &field.path));
+                if let Some(doc) = &field.documentation {
+                    fields.extend(doc_attrs(doc));
+                }
+                gen_doc_alias(&mut fields, ctx, &field.name_in_json, &field.name);
+                let default = match &field.default {
+                    Some(_) => {
+                        let default = default_fn_name(ty, field);
+                        quote!(default = #default)
+                    }
+                    None => quote!(default),
+                };
+                let mut serde = Vec::new();
+                if ctx.is_tri_state(field) {
+                    serde.push(quote!(serde(#default, skip_serializing_if = "Maybe::is_absent")));
+                } else if !field.required {
+                    if ctx.skip_serializing_none(&field.path) {
+                        serde
+                            .push(quote!(serde(#default, skip_serializing_if = "Option::is_none")));
+                    } else {
+                        serde.push(quote!(serde(#default)));
+                    }
+                } else if field.default.is_some() {
+                    serde.push(quote!(serde(#default)));
+                }
+                if field.flatten {
+                    serde.push(quote!(serde(flatten)));
+                }
+                if ctx.type_ref_generics(&field.ty).contains(&LIFETIME) {
+                    serde.push(quote!(serde(borrow)));
+                }
+                if field.name != field.name_in_json {
+                    let rename = &field.name_in_json;
+                    serde.push(quote!(serde(rename = #rename)));
+                }
+                if let Some(with) = ctx.field_with_module(field) {
+                    serde.push(quote!(serde(with = #with)));
+                }
+                if flattened.is_none() {
+                    for attr in serde {
+                        fields.extend(ctx.serde_attr(attr));
+                    }
+                }
+                let attrs = ctx.attributes(&field.path)?;
+                let ident = parse_ident(&field.name)?;
+                let field_ty = parse_type(&ctx.field_type_name(field))?;
+                fields.extend(quote! {
+                    #(#attrs)*
+                    pub #ident: #field_ty,
+                });
+            }
+            if ctx.has_extra_fields(&ty.path) {
+                let flatten = ctx.serde_attr(quote!(serde(flatten)));
+                let map = parse_type(&ctx.map_primitive().replace("{}", "serde_json::Value"))?;
+                fields.extend(quote! {
+                    /// The fields that are not part of the specification.
+                    #flatten
+                    pub extra: #map,
+                });
+            }
+            let decl = ctx.generics_decl(&generics)?;
+            tokens.extend(quote! {
+                #(#attrs)*
+                pub struct #name #decl {
+                    #fields
+                }
+            });
+            gen_struct_defaults(tokens, ctx, ty, s, &generics)?;
+            if ctx.config.generation.constructors {
+                gen_constructors(tokens, ctx, ty, s, &generics)?;
+            }
+            if ctx.config.generation.tag_constants && !s.tags.is_empty() {
+                let mut constants = TokenStream::new();
+                for (tag, value) in &s.tags {
+                    let constant = parse_ident(&ident::screaming_snake(tag))?;
+                    let doc = doc_line(&format!("The value of the `{tag}` tag of this struct."));
+                    constants.extend(quote! {
+                        #doc
+                        pub const #constant: &'static str = #value;
+                    });
+                }
+                let args = generics_args(&generics)?;
+                tokens.extend(quote! {
+                    impl #decl #name #args {
+                        #constants
+                    }
+                });
+            }
+            if ctx.has_builder(&ty.path) {
+                gen_builder(tokens, ctx, ty, s, &generics)?;
+            }
+            if let Some(fields) = &flattened {
+                gen_flatten_serde(tokens, ctx, ty, fields)?;
+            }
+        }
+        TypeKind::Enum(e) => {
+            // Integer enums are (de)serialized by hand, as serde only handles string tags.
+            let repr = match &e.tag {
+                EnumTag::Integer(int) => Some(ctx.type_ref_name(int, true)),
+                _ => None,
+            };
+            let manual = ctx.is_manually_deserialized(ty, e);
+            if manual {
+                tokens.extend(ctx.serde_attr(quote!(derive(Serialize))));
+            } else if repr.is_none() {
+                tokens.extend(ctx.serde_attr(quote!(derive(Serialize, Deserialize))));
+            }
+            let mut existing: Vec<&str> = ctx
+                .config
+                .generation
+                .global_derives
+                .iter()
+                .chain(
+                    ctx.config
+                        .generation
+                        .derives
+                        .get(&*ty.path)
+                        .into_iter()
+                        .flatten(),
+                )
+                .map(String::as_str)
+                .collect();
+            if ctx.is_copy(e) {
+                tokens.extend(missing_derives(
+                    &["Copy", "PartialEq", "Eq", "Hash"],
+                    &existing,
+                ));
+                existing.push("Copy, PartialEq, Eq, Hash");
+            }
+            for global_derive in &ctx.config.generation.global_derives {
+                tokens.extend(derive_attr(global_derive)?);
+            }
+            if let Some(derives) = ctx.config.generation.derives.get(&*ty.path) {
+                for derive in derives {
+                    tokens.extend(derive_attr(derive)?);
+                }
+            }
+            if ctx.eq_types.contains(&ty.name) {
+                tokens.extend(missing_derives(&["PartialEq", "Eq", "Hash"], &existing));
+            }
+            match &e.tag {
+                EnumTag::Normal => (),
+                EnumTag::Tagged(tag) => {
+                    tokens.extend(ctx.serde_attr(quote!(serde(tag = #tag))));
+                }
+                EnumTag::Adjacent { tag, content } => {
+                    tokens.extend(ctx.serde_attr(quote!(serde(tag = #tag, content = #content))));
+                }
+                EnumTag::Untagged => {
+                    tokens.extend(ctx.serde_attr(quote!(serde(untagged))));
+                }
+                EnumTag::Mixed => (),
+                EnumTag::Integer(int) => {
+                    let int = parse_type(&ctx.type_ref_name(int, true))?;
+                    tokens.extend(quote!(#[repr(#int)]));
+                }
+            }
+            // Integer enums don't derive the serde traits, which define the attributes.
+            if repr.is_none() {
+                tokens.extend(ctx.serde_container(ty));
+            }
+            if ctx.is_non_exhaustive(&ty.path) {
+                tokens.extend(quote!(#[non_exhaustive]));
+            }
+            let attrs = ctx.attributes(&ty.path)?;
+            // Fallback variants must come last, as serde tries the variants in order.
+            let (fallbacks, mut variants) = e
+                .variants
+                .values()
+                .partition::<Vec<_>, _>(|variant| variant.fallback);
+            // The discriminant of a fallback is the one following the largest value.
+            if repr.is_some() {
+                variants.sort_by_key(|variant| integer_value(variant));
+            }
+            // Untagged variants must come after the tagged ones.
+            if matches!(e.tag, EnumTag::Mixed) {
+                variants.sort_by_key(|variant| variant.ty.is_some());
+            }
+            let mut body = TokenStream::new();
+            for variant in variants.into_iter().chain(fallbacks) {
+                body.extend(debug_path(ctx, &variant.path));
+                if let Some(doc) = &variant.documentation {
+                    body.extend(doc_attrs(doc));
+                }
+                if let (Some(name_in_json), None) = (&variant.name_in_json, &repr) {
+                    gen_doc_alias(&mut body, ctx, name_in_json, &variant.name);
+                }
+                if let Some(name_in_json) = &variant.name_in_json {
+                    if name_in_json != &variant.name && repr.is_none() {
+                        body.extend(ctx.serde_attr(quote!(serde(rename = #name_in_json))));
+                    }
+                }
+                if let Some(inner) = &variant.ty {
+                    if ctx.type_ref_generics(inner).contains(&LIFETIME) {
+                        body.extend(ctx.serde_attr(quote!(serde(borrow))));
+                    }
+                }
+                if matches!(e.tag, EnumTag::Mixed) && variant.ty.is_some() && !variant.fallback {
+                    body.extend(ctx.serde_attr(quote!(serde(untagged))));
+                }
+                if variant.fallback && repr.is_none() {
+                    if variant.ty.is_none() {
+                        body.extend(ctx.serde_attr(quote!(serde(other))));
+                    } else if !matches!(e.tag, EnumTag::Untagged) {
+                        body.extend(ctx.serde_attr(quote!(serde(untagged))));
+                    }
+                }
+                let attrs = ctx.attributes(&variant.path)?;
+                let ident = parse_ident(&variant.name)?;
+                body.extend(if let Some(inner) = &variant.ty {
+                    let inner = parse_type(&ctx.type_ref_name(inner, true))?;
+                    quote!(#(#attrs)* #ident(#inner),)
+                } else if let (Some(_), Some(_)) = (&repr, &variant.name_in_json) {
+                    let value = int_literal(integer_value(variant));
+                    quote!(#(#attrs)* #ident = #value,)
+                } else {
+                    quote!(#(#attrs)* #ident,)
+                });
+            }
+            let decl = ctx.generics_decl(&generics)?;
+            tokens.extend(quote! {
+                #(#attrs)*
+                pub enum #name #decl {
+                    #body
+                }
+            });
+            if let Some(repr) = &repr {
+                gen_integer_enum_serde(tokens, ctx, ty, e, repr)?;
+            }
+            if manual {
+                gen_enum_deserialize(tokens, ctx, ty, e)?;
+            }
+            if ctx.config.generation.enum_accessors && !e.variants.is_empty() {
+                gen_enum_accessors(tokens, ctx, ty, e, &generics)?;
+            }
+            if ctx.config.generation.tag_constants {
+                gen_enum_tag(tokens, ctx, ty, e, &generics)?;
+            }
+            if ctx.config.generation.enum_strings && is_string_enum(e) {
+                gen_enum_strings(tokens, ctx, ty, e)?;
+            }
+        }
+    }
+    if ctx.config.generation.arbitrary {
+        gen_arbitrary(tokens, ctx, ty, &generics)?;
+    }
+    if ctx.config.generation.validation {
+        gen_validate(tokens, ctx, ty, &generics)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the `proptest::arbitrary::Arbitrary` implementation of the provided struct or enum.
+fn gen_arbitrary(
+    tokens: &mut TokenStream,
+    ctx: &Ctx,
+    ty: &TypeDef,
+    generics: &[&str],
+) -> io::Result<()> {
+    let strategy = match &ty.kind {
+        TypeKind::Alias(alias) if ctx.is_validated(alias, &ty.path) => {
+            quote!(proptest::string::string_regex(Self::PATTERN)
+                .unwrap()
+                .prop_map(Self))
+        }
+        TypeKind::Alias(alias) if ctx.config.generation.is_newtype(&ty.path) => {
+            let strategy = type_strategy(ctx, &alias.ty, true, &ty.name)?;
+            quote!(#strategy.prop_map(Self))
+        }
+        TypeKind::Alias(_) => return Ok(()),
+        TypeKind::Struct(s) => {
+            let mut fields = Vec::new();
+            for field in s.fields.values() {
+                let ident = parse_ident(&field.name)?;
+                fields.push((
+                    field_strategy(ctx, field, &ty.name)?,
+                    ident.into_token_stream(),
+                ));
+            }
+            let names = fields.iter().map(|(_, name)| name);
+            let extra = ctx
+                .has_extra_fields(&ty.path)
+                .then(|| quote!(extra: Default::default()));
+            let init = quote!(Self { #(#names,)* #extra });
+            if fields.is_empty() {
+                quote!(proptest::strategy::LazyJust::new(|| #init))
+            } else {
+                // Strategies are implemented for tuples of up to 12 elements.
+                while fields.len() > 12 {
+                    fields = fields.chunks(12).map(tuple_strategy).collect();
+                }
+                let (strategies, pattern) = tuple_strategy(&fields);
+                quote!(#strategies.prop_map(|#pattern| #init))
+            }
+        }
+        TypeKind::Enum(e) => {
+            let (fallbacks, mut variants) = e
+                .variants
+                .values()
+                .partition::<Vec<_>, _>(|variant| variant.fallback);
+            if variants.is_empty() {
+                variants = fallbacks;
+            }
+            // Recursive variants are skipped, so that producing a value terminates.
+            let (recursive, finite) = variants.into_iter().partition::<Vec<_>, _>(|variant| {
+                variant
+                    .ty
+                    .as_ref()
+                    .is_some_and(|inner| leads_back(ctx, inner, &ty.name))
+            });
+            let variants = if finite.is_empty() { recursive } else { finite };
+            let mut strategies = Vec::new();
+            for variant in variants {
+                let ident = parse_ident(&variant.name)?;
+                strategies.push(match &variant.ty {
+                    Some(inner) => {
+                        let strategy = type_strategy(ctx, inner, true, &ty.name)?;
+                        quote!(#strategy.prop_map(Self::#ident))
+                    }
+                    None => quote!(proptest::strategy::LazyJust::new(|| Self::#ident)),
+                });
+            }
+            match strategies.as_slice() {
+                // Values of an empty enum can't be produced.
+                [] => return Ok(()),
+                [strategy] => strategy.clone(),
+                _ => quote!(proptest::prop_oneof![#(#strategies),*]),
+            }
+        }
+    };
+
+    let cfg = ctx
+        .config
+        .generation
+        .arbitrary_feature
+        .as_ref()
+        .map(|feature| quote!(#[cfg(feature = #feature)]));
+    let decl = ctx.generics_decl(generics)?;
+    let name = parse_ident(&ty.name)?;
+    let args = generics_args(generics)?;
+    let bounds = generics_where(generics, quote!(proptest::arbitrary::Arbitrary + 'static))?;
+    tokens.extend(quote! {
+        #cfg
+        impl #decl proptest::arbitrary::Arbitrary for #name #args #bounds {
+            type Parameters = ();
+            type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                use proptest::strategy::Strategy;
+                #strategy.boxed()
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Returns the strategy producing the tuple of the provided `(strategy, pattern)` pairs, along
+/// with the pattern destructuring that tuple.
+fn tuple_strategy(items: &[(TokenStream, TokenStream)]) -> (TokenStream, TokenStream) {
+    let strategies = items.iter().map(|(s, _)| s);
+    let patterns = items.iter().map(|(_, p)| p);
+    // One-element tuples need a trailing comma.
+    let comma = (items.len() == 1).then(|| quote!(,));
+    (
+        quote!((#(#strategies),* #comma)),
+        quote!((#(#patterns),* #comma)),
+    )
+}
+
+/// Returns the strategy producing the values of the provided field of the type named `owner`.
+fn field_strategy(ctx: &Ctx, field: &StructField, owner: &str) -> io::Result<TokenStream> {
+    if ctx.is_tri_state(field) {
+        if leads_back(ctx, &field.ty, owner) {
+            return Ok(quote!(proptest::strategy::LazyJust::new(Maybe::default)));
+        }
+        let strategy = type_strategy(ctx, &field.ty, true, owner)?;
+        Ok(quote! {
+            proptest::prop_oneof![
+                proptest::strategy::LazyJust::new(|| Maybe::Absent),
+                proptest::strategy::LazyJust::new(|| Maybe::Null),
+                #strategy.prop_map(Maybe::Value)
+            ]
+        })
+    } else {
+        type_strategy(ctx, &field.ty, field.required, owner)
+    }
+}
+
+/// Returns the strategy producing the values of the provided type, held by the type named
+/// `owner`.
+///
+/// Keywords and constants always produce their value. Optional values, arrays and maps that
+/// lead back to `owner` are always empty, so that producing a value terminates.
+fn type_strategy(ctx: &Ctx, ty: &TypeRef, required: bool, owner: &str) -> io::Result<TokenStream> {
+    let primitives = &ctx.config.primitives;
+    let any = || -> io::Result<TokenStream> {
+        let ty = parse_type(&ctx.type_ref_name(ty, required))?;
+        Ok(quote!(proptest::arbitrary::any::<#ty>()))
+    };
+    let empty = (!required || matches!(ty, TypeRef::Array(_) | TypeRef::Set(_) | TypeRef::Map(_)))
+        && leads_back(ctx, ty, owner);
+    if empty {
+        return Ok(quote!(proptest::strategy::LazyJust::new(Default::default)));
+    }
+    if !needs_strategy(ty) {
+        return any();
+    }
+    if !required {
+        return match primitives.optional.as_str() {
+            "Option<{}>" => {
+                let inner = type_strategy(ctx, ty, true, owner)?;
+                Ok(quote!(proptest::option::of(#inner)))
+            }
+            _ => any(),
+        };
+    }
+    let inner = |inner: &TypeRef| type_strategy(ctx, inner, true, owner);
+    Ok(match ty {
+        TypeRef::Keyword(keyword) => {
+            let string = parse_type(&primitives.string)?;
+            quote!(proptest::strategy::Just(<#string>::from(#keyword)))
+        }
+        TypeRef::Constant(value) => {
+            let primitive = match value {
+                serde_json::Value::Bool(_) => &primitives.boolean,
+                serde_json::Value::Number(n) if n.is_f64() => &primitives.number,
+                _ => &primitives.integer,
+            };
+            let primitive = parse_type(primitive)?;
+            let value = match value {
+                serde_json::Value::Bool(b) => quote!(#b),
+                serde_json::Value::Number(n) => number_literal(n),
+                value => parse_code::<syn::Expr>(&value.to_string())?.into_token_stream(),
+            };
+            quote!(proptest::strategy::Just::<#primitive>(#value))
+        }
+        TypeRef::Nullable(ty) if primitives.optional == "Option<{}>" => {
+            let inner = inner(ty)?;
+            quote!(proptest::option::of(#inner))
+        }
+        TypeRef::Any if primitives.any == "serde_json::Value" => {
+            quote!(proptest::strategy::Just(serde_json::Value::Null))
+        }
+        TypeRef::Array(ty) if primitives.array == "Vec<{}>" => {
+            let inner = inner(ty)?;
+            quote!(proptest::collection::vec(#inner, 0..4))
+        }
+        TypeRef::Set(ty) => match primitives.set.as_deref() {
+            Some(set) if set.ends_with("BTreeSet<{}>") => {
+                let inner = inner(ty)?;
+                quote!(proptest::collection::btree_set(#inner, 0..4))
+            }
+            Some(set) if set.ends_with("HashSet<{}>") => {
+                let inner = inner(ty)?;
+                quote!(proptest::collection::hash_set(#inner, 0..4))
+            }
+            _ => any()?,
+        },
+        TypeRef::Map(ty) if ctx.map_primitive().ends_with("BTreeMap<String, {}>") => {
+            let inner = inner(ty)?;
+            quote! {
+                proptest::collection::btree_map(proptest::arbitrary::any::<String>(), #inner, 0..4)
+            }
+        }
+        TypeRef::Tuple(items) => {
+            let items = items.iter().map(inner).collect::<io::Result<Vec<_>>>()?;
+            quote!((#(#items,)*))
+        }
+        _ => any()?,
+    })
+}
+
+/// Returns whether the values of `ty` can't be produced by `proptest::arbitrary::any`.
+fn needs_strategy(ty: &TypeRef) -> bool {
+    match ty {
+        TypeRef::Keyword(_) | TypeRef::Constant(_) | TypeRef::Any => true,
+        TypeRef::Array(inner)
+        | TypeRef::Set(inner)
+        | TypeRef::Map(inner)
+        | TypeRef::Nullable(inner) => needs_strategy(inner),
+        TypeRef::Tuple(items) => items.iter().any(needs_strategy),
+        _ => false,
+    }
+}
+
+/// Returns whether the values of `ty` may hold a value of the type named `owner`.
+fn leads_back(ctx: &Ctx, ty: &TypeRef, owner: &str) -> bool {
+    let Some(deps) = &ctx.deps else {
+        return false;
+    };
+    ty.inner_paths().into_iter().any(|path| {
+        ctx.file
+            .types
+            .get(path)
+            .is_some_and(|ty| ty.name == owner || deps.depends_on(&ty.name, owner))
+    })
+}
+
+/// Writes the inner documentation of the file, describing the document it comes from.
+fn gen_spec_docs(tokens: &mut TokenStream, ctx: &Ctx) {
+    let info = &ctx.file.info;
+    let mut lines = crate::doc::lines(&info.title);
+    lines.push(String::new());
+    if let Some(doc) = &info.documentation {
+        lines.extend(crate::doc::lines(doc));
+        lines.push(String::new());
+    }
+    lines.push(format!(
+        "Generated from version `{}` of the OpenRPC document.",
+        info.version
+    ));
+    for line in lines {
+        let line = if line.is_empty() {
+            line
+        } else {
+            format!(" {line}")
+        };
+        tokens.extend(quote!(#![doc = #line]));
+    }
+}
+
+/// Writes the constants holding the title and version of the document.
+fn gen_spec_constants(tokens: &mut TokenStream, ctx: &Ctx) {
+    let info = &ctx.file.info;
+    let title = &info.title;
+    let version = &info.version;
+    tokens.extend(quote! {
+        /// The title of the OpenRPC document this file was generated from.
+        pub const SPEC_TITLE: &str = #title;
+
+        /// The version of the OpenRPC document this file was generated from.
+        pub const SPEC_VERSION: &str = #version;
+    });
+}
+
+fn gen_method(tokens: &mut TokenStream, ctx: &mut Ctx, method: &Method) -> io::Result<()> {
+    let core = format_ident!("{}", ctx.std_mod());
+
+    let ident_base = ctx.method_ident_base(method);
+
+    if ctx.config.generation.method_name_constants {
+        if let Some(ref doc) = method.documentation {
+            tokens.extend(doc_attrs(doc));
+            tokens.extend(doc_line(""));
+        }
+        let doc = doc_line(&format!("`{}`", method.name));
+        let constant = parse_ident(&ident::screaming_snake(ident_base))?;
+        let name = &method.name;
+        tokens.extend(quote! {
+            #doc
+            pub const #constant: &str = #name;
+        });
+    }
+
+    // Notifications don't return anything, so no result type is generated for them. Anonymous
+    // results are already named like the alias.
+    let result = method.result.as_ref().filter(|result| {
+        ctx.config.generation.result_types
+            && ctx.type_ref_name(&result.ty, true) != ctx.result_type_name(method)
+    });
+    if let Some(result) = result {
+        if let Some(ref doc) = result.documentation {
+            tokens.extend(doc_attrs(doc));
+            tokens.extend(doc_line(""));
+        }
+        let doc = doc_line(&format!("Result type of `{}`.", method.name));
+        let ident = parse_ident(&ctx.result_type_name(method))?;
+        let args = generics_args(&ctx.type_ref_generics(&result.ty))?;
+        let ty = parse_type(&ctx.type_ref_name(&result.ty, true))?;
+        tokens.extend(quote! {
+            #doc
+            pub type #ident #args = #ty;
+        });
+    }
+
+    if ctx.param_types() && !ctx.has_shared_params(method) {
+        let ident = parse_ident(&ctx.params_type_name(method))?;
+        let generics = ctx.method_generics(method);
+        let decl = ctx.generics_decl(&generics)?;
+        let args = generics_args(&generics)?;
+        let by_name = matches!(
+            method.param_structure,
+            ParamStructure::ByName | ParamStructure::Either
+        );
+        let by_position = matches!(
+            method.param_structure,
+            ParamStructure::ByPosition | ParamStructure::Either
+        );
+
+        let names = method
+            .params
+            .iter()
+            .map(|param| parse_ident(&param.name))
+            .collect::<io::Result<Vec<_>>>()?;
+        let types = method
+            .params
+            .iter()
+            .map(|param| parse_type(&ctx.type_ref_name(&param.ty, param.required)))
+            .collect::<io::Result<Vec<_>>>()?;
+        let docs = method
+            .params
+            .iter()
+            .map(|param| param.documentation.as_deref().map(doc_attrs));
+        let doc = doc_line(&format!("Parameters of the `{}` method.", method.name));
+        tokens.extend(quote! {
+            #doc
+            #[derive(Debug, Clone)]
+            pub struct #ident #decl {
+                #(#docs pub #names: #types,)*
+            }
+        });
+
+        let serde_cfg = ctx.serde_cfg();
+        if ctx.config.generation.param_helpers {
+            tokens.extend(serde_cfg.clone());
+            gen_param_helpers(tokens, method, &ident, &generics, &decl)?;
+        }
+
+        let serialize = if by_name {
+            let keys = method.params.iter().map(|param| &param.name_in_json);
+            quote! {
+                let mut map = serializer.serialize_map(None)?;
+                #(map.serialize_entry(#keys, &self.#names)?;)*
+                map.end()
+            }
+        } else {
+            quote! {
+                let mut seq = serializer.serialize_seq(None)?;
+                #(seq.serialize_element(&self.#names)?;)*
+                seq.end()
+            }
+        };
+        let bounds = generics_where(&generics, quote!(Serialize))?;
+        tokens.extend(quote! {
+            #serde_cfg
+            impl #decl Serialize for #ident #args #bounds {
+                #[allow(unused_mut)]
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    #serialize
+                }
+            }
+        });
+
+        // The visitor and the helper struct are items of their own, which can't use the
+        // generic parameters of the impl block; they declare their own.
+        // Borrowed data must outlive the deserialized parameters.
+        let de = if generics.contains(&LIFETIME) {
+            format!("'de: {LIFETIME}")
+        } else {
+            String::from("'de")
+        };
+        let mut de_decl = syn::parse2::<syn::Generics>(decl.clone()).map_err(|error| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("`{decl}` is not valid Rust: {error}"),
+            )
+        })?;
+        de_decl.params.insert(0, parse_code(&de)?);
+        let de_where = generics_where(&generics, quote!(Deserialize<'de>))?;
+        let (visitor_decl, visitor) = if generics.is_empty() {
+            (
+                quote!(
+                    struct Visitor;
+                ),
+                quote!(Visitor),
+            )
+        } else {
+            let markers = generics
+                .iter()
+                .map(|param| {
+                    if param.starts_with('\'') {
+                        parse_type(&format!("&{param} ()"))
+                    } else {
+                        parse_type(param)
+                    }
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            (
+                quote!(struct Visitor #args(#core::marker::PhantomData<(#(#markers,)*)>);),
+                quote!(Visitor(#core::marker::PhantomData)),
+            )
+        };
+
+        let expecting = format!("the parameters for `{}`", method.name);
+        let mut visit = TokenStream::new();
+        if by_position {
+            let count = method.params.len();
+            let expected = format!("expected {count} parameters");
+            let lengths = (1..=count).map(Literal::usize_unsuffixed);
+            let too_many = Literal::usize_unsuffixed(count + 1);
+            visit.extend(quote! {
+                #[allow(unused_mut)]
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: serde::de::SeqAccess<'de>,
+                {
+                    #(
+                        let #names: #types = seq.next_element()?.ok_or_else(|| serde::de::Error::invalid_length(#lengths, &#expected))?;
+                    )*
+
+                    if seq.next_element::<serde::de::IgnoredAny>()?.is_some() {
+                        return Err(serde::de::Error::invalid_length(#too_many, &#expected));
+                    }
+
+                    Ok(#ident {
+                        #(#names,)*
+                    })
+                }
+            });
+        }
+
+        if by_name {
+            let mut fields = TokenStream::new();
+            for ((param, name), ty) in method.params.iter().zip(&names).zip(&types) {
+                if !param.required {
+                    fields.extend(quote!(#[serde(default)]));
+                }
+                if ctx.type_ref_generics(&param.ty).contains(&LIFETIME) {
+                    fields.extend(quote!(#[serde(borrow)]));
+                }
+                if param.name != param.name_in_json {
+                    let rename = &param.name_in_json;
+                    fields.extend(quote!(#[serde(rename = #rename)]));
+                }
+                fields.extend(quote!(#name: #ty,));
+            }
+            visit.extend(quote! {
+                #[allow(unused_variables)]
+                fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: serde::de::MapAccess<'de>,
+                {
+                    #[derive(Deserialize)]
+                    struct Helper #decl {
+                        #fields
+                    }
+
+                    let helper = Helper::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+
+                    Ok(#ident {
+                        #(#names: helper.#names,)*
+                    })
+                }
+            });
+        }
+
+        let deserialize = match method.param_structure {
+            ParamStructure::ByName => quote!(deserialize_map),
+            ParamStructure::ByPosition => quote!(deserialize_seq),
+            ParamStructure::Either => quote!(deserialize_any),
+        };
+        tokens.extend(quote! {
+            #serde_cfg
+            impl #de_decl Deserialize<'de> for #ident #args #de_where {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    #visitor_decl
+
+                    impl #de_decl serde::de::Visitor<'de> for Visitor #args #de_where {
+                        type Value = #ident #args;
+
+                        fn expecting(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                            write!(f, #expecting)
+                        }
+
+                        #visit
+                    }
+
+                    deserializer.#deserialize(#visitor)
+                }
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Writes the `to_positional` and `to_named` methods of the parameter struct `ident`.
+fn gen_param_helpers(
+    tokens: &mut TokenStream,
+    method: &Method,
+    ident: &syn::Ident,
+    generics: &[&str],
+    decl: &TokenStream,
+) -> io::Result<()> {
+    let args = generics_args(generics)?;
+    let bounds = generics_where(generics, quote!(Serialize))?;
+    let names = method
+        .params
+        .iter()
+        .map(|param| parse_ident(&param.name))
+        .collect::<io::Result<Vec<_>>>()?;
+    let to_named = if method.params.is_empty() {
+        quote!(Ok(serde_json::Map::new()))
+    } else {
+        let keys = method.params.iter().map(|param| &param.name_in_json);
+        quote! {
+            let mut map = serde_json::Map::new();
+            #(map.insert(#keys.into(), serde_json::to_value(&self.#names)?);)*
+            Ok(map)
+        }
+    };
+    tokens.extend(quote! {
+        impl #decl #ident #args #bounds {
+            /// Serializes the parameters as a JSON array, in the order declared by the document.
+            pub fn to_positional(&self) -> Result<Vec<serde_json::Value>, serde_json::Error> {
+                Ok(vec![
+                    #(serde_json::to_value(&self.#names)?,)*
+                ])
+            }
+
+            /// Serializes the parameters as a JSON object, keyed by parameter name.
+            pub fn to_named(&self) -> Result<serde_json::Map<String, serde_json::Value>, serde_json::Error> {
+                #to_named
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Writes the `RpcMethod` trait and a marker type implementing it for every method.
+fn gen_method_markers(tokens: &mut TokenStream, ctx: &mut Ctx) -> io::Result<()> {
+    tokens.extend(quote! {
+        /// A JSON-RPC method, linking its name to the types of its parameters and result.
+        pub trait RpcMethod {
+            /// The name of the method.
+            const NAME: &'static str;
+            /// The parameters of the method.
+            type Params;
+            /// The result of the method.
+            type Result;
+        }
+    });
+
+    for method in &ctx.file.methods {
+        let ident = parse_ident(&ctx.method_ident(method))?;
+        let doc = doc_line(&format!("The `{}` method.", method.name));
+        let name = ctx.method_name_expr(method)?;
+        let params = parse_type(&ctx.params_type_name(method))?;
+        let result = parse_type(&ctx.method_result_type(method))?;
+        tokens.extend(quote! {
+            #doc
+            #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+            pub struct #ident;
+
+            impl RpcMethod for #ident {
+                const NAME: &'static str = #name;
+                type Params = #params;
+                type Result = #result;
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the `#[cfg]` attribute of the generated test modules, which need serde.
+fn test_cfg(ctx: &Ctx) -> TokenStream {
+    match &ctx.config.generation.serde_feature {
+        Some(feature) => quote!(#[cfg(all(test, feature = #feature))]),
+        None => quote!(#[cfg(test)]),
+    }
+}
+
+/// Writes the test module checking that the examples of the methods round-trip through the
+/// generated types.
+fn gen_example_tests(tokens: &mut TokenStream, ctx: &mut Ctx) -> io::Result<()> {
+    let mut tests = TokenStream::new();
+    let mut names = BTreeSet::new();
+    for method in &ctx.file.methods {
+        for (i, example) in method.examples.iter().enumerate() {
+            let checks = example_checks(ctx, method, example);
+            if checks.is_empty() {
+                continue;
+            }
+
+            let base = format!(
+                "{}_{}",
+                ident::snake(ctx.method_ident_base(method)),
+                example.name.to_case(Case::Snake),
+            );
+            let name = parse_ident(&test_name(&base, i, &mut names))?;
+            let mut asserts = TokenStream::new();
+            for (ty, value) in checks {
+                let ty = parse_type(&ty)?;
+                let json = raw_string(&value.to_string());
+                asserts.extend(quote!(assert_round_trip::<#ty>(#json);));
+            }
+            tests.extend(quote! {
+                #[test]
+                fn #name() {
+                    #asserts
+                }
+            });
+        }
+    }
+
+    let cfg = test_cfg(ctx);
+    tokens.extend(quote! {
+        #cfg
+        mod example_tests {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// Removes the `null` fields of objects and the trailing `null`s of arrays, which
+            /// stand for optional values just like their absence.
+            fn normalize(value: serde_json::Value) -> serde_json::Value {
+                match value {
+                    serde_json::Value::Object(map) => serde_json::Value::Object(
+                        map.into_iter()
+                            .filter(|(_, value)| !value.is_null())
+                            .map(|(key, value)| (key, normalize(value)))
+                            .collect(),
+                    ),
+                    serde_json::Value::Array(mut values) => {
+                        while values.last().is_some_and(serde_json::Value::is_null) {
+                            values.pop();
+                        }
+                        serde_json::Value::Array(values.into_iter().map(normalize).collect())
+                    }
+                    value => value,
+                }
+            }
+
+            /// Asserts that `json` deserializes into `T`, and serializes back to an equivalent JSON.
+            fn assert_round_trip<T>(json: &str)
+            where
+                T: Serialize + for<'de> Deserialize<'de>,
+            {
+                let expected: serde_json::Value = serde_json::from_str(json).unwrap();
+                let value: T = serde_json::from_str(json).unwrap();
+                let actual = serde_json::to_value(&value).unwrap();
+                assert_eq!(normalize(actual), normalize(expected));
+            }
+
+            #tests
+        }
+    });
+
+    Ok(())
+}
+
+/// Writes the `fixture_tests` module, with one test deserializing each sample of the fixtures
+/// directory into the type named after its subdirectory.
+///
+/// The subdirectories may also be named after the `shared` types, which the file re-exports.
+fn gen_fixture_tests(
+    tokens: &mut TokenStream,
+    ctx: &mut Ctx,
+    fixtures: &crate::config::Fixtures,
+    shared: &[&str],
+) -> io::Result<()> {
+    let mut samples = Vec::new();
+    for dir in sorted_entries(&fixtures.dir)? {
+        if !dir.is_dir() {
+            continue;
+        }
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        let is_shared = shared.contains(&&*name);
+        let found = ctx.file.types.iter().find(|(_, ty)| ty.name == name);
+        let Some((path, ty)) = found else {
+            if is_shared {
+                samples.extend(
+                    json_files(&dir)?
+                        .into_iter()
+                        .map(|file| (name.to_string(), format!("{name}/{file}"))),
+                );
+                continue;
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the fixtures in `{}` are not named after a generated type",
+                    dir.display(),
+                ),
+            ));
+        };
+        if !ctx
+            .type_ref_generics(&TypeRef::Ref(path.clone()))
+            .is_empty()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the fixtures in `{}` are named after the generic type `{}`",
+                    dir.display(),
+                    ty.name,
+                ),
+            ));
+        }
+        for file in json_files(&dir)? {
+            samples.push((ty.name.clone(), format!("{name}/{file}")));
+        }
+    }
+
+    let crate_path = match &fixtures.crate_path {
+        Some(path) => path.clone(),
+        None => fixtures.dir.display().to_string(),
+    };
+
+    let mut tests = TokenStream::new();
+    let mut names = BTreeSet::new();
+    for (i, (ty, path)) in samples.iter().enumerate() {
+        let stem = path.trim_end_matches(".json");
+        let name = parse_ident(&test_name(&stem.to_case(Case::Snake), i, &mut names))?;
+        let ty = parse_type(ty)?;
+        tests.extend(quote! {
+            #[test]
+            fn #name() {
+                check_fixture::<#ty>(#path);
+            }
+        });
+    }
+
+    let cfg = test_cfg(ctx);
+    let extern_std = ctx.config.generation.no_std.then(|| {
+        quote!(
+            extern crate std;
+        )
+    });
+    tokens.extend(quote! {
+        #cfg
+        mod fixture_tests {
+            #extern_std
+
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// The directory holding the samples.
+            const DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/", #crate_path);
+
+            /// Asserts that the sample at `path`, relative to [`DIR`], deserializes into `T`.
+            ///
+            /// Samples that are complete JSON-RPC responses are checked against their `result`.
+            fn check_fixture<T>(path: &str)
+            where
+                T: for<'de> Deserialize<'de>,
+            {
+                let path = std::format!("{DIR}/{path}");
+                let json = std::fs::read_to_string(&path).unwrap();
+                let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+                if value.get("jsonrpc").is_some() {
+                    value = value["result"].take();
+                }
+                if let Err(error) = serde_json::from_value::<T>(value) {
+                    panic!("`{path}` does not match the generated type: {error}");
+                }
+            }
+
+            #tests
+        }
+    });
+
+    Ok(())
+}
+
+/// Returns the names of the `.json` files of `dir`, sorted.
+fn json_files(dir: &std::path::Path) -> io::Result<Vec<String>> {
+    Ok(sorted_entries(dir)?
+        .into_iter()
+        .filter(|file| file.extension().is_some_and(|e| e == "json"))
+        .map(|file| {
+            file.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into()
+        })
+        .collect())
+}
+
+/// Returns the paths of the entries of `dir`, sorted.
+fn sorted_entries(dir: &std::path::Path) -> io::Result<Vec<std::path::PathBuf>> {
+    let with_path =
+        |error: io::Error| io::Error::new(error.kind(), format!("{}: {error}", dir.display()));
+    let mut entries = std::fs::read_dir(dir)
+        .map_err(with_path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(with_path)?;
+    entries.sort();
+    Ok(entries)
+}
+
+/// Turns `base` into the name of a test function that isn't in `names` yet, using `i` to
+/// disambiguate it if needed.
+fn test_name(base: &str, i: usize, names: &mut BTreeSet<String>) -> String {
+    let mut name: String = base
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if !names.insert(name.clone()) {
+        name = format!("{name}_{i}");
+        names.insert(name.clone());
+    }
+    name
+}
+
+/// Returns the values of `example` that can be checked against the generated types, along
+/// with the type each one should round-trip through.
+fn example_checks(
+    ctx: &Ctx,
+    method: &Method,
+    example: &crate::parse::Example,
+) -> Vec<(String, serde_json::Value)> {
+    let mut checks = Vec::new();
+    let checkable = |ty: &TypeRef| !has_module(ctx, ty) && ctx.type_ref_generics(ty).is_empty();
+
+    if ctx.param_types() {
+        let all_checkable = method.params.iter().all(|p| checkable(&p.ty));
+        if !example.params.is_empty() && all_checkable {
+            checks.push((
+                ctx.params_type_name(method),
+                example_params(method, example),
+            ));
+        }
+    } else {
+        for (name, value) in &example.params {
+            let Some(param) = method.params.iter().find(|p| p.name_in_json == *name) else {
+                continue;
+            };
+            if checkable(&param.ty) {
+                let ty = ctx.type_ref_name(&param.ty, param.required).into_owned();
+                checks.push((ty, value.clone()));
+            }
+        }
+    }
+
+    if let (Some(result), Some(value)) = (&method.result, &example.result) {
+        if checkable(&result.ty) {
+            checks.push((ctx.method_result_type(method).into_owned(), value.clone()));
+        }
+    }
+
+    checks
+}
+
+/// Returns whether the provided type is, or contains, a value (de)serialized through a serde
+/// `with` module, such as an integer formatted as hexadecimal.
+///
+/// Aliases are followed, since the module is only applied where they are used. Newtypes apply
+/// it themselves.
+fn has_module(ctx: &Ctx, ty: &TypeRef) -> bool {
+    match ty {
+        TypeRef::Integer { format_as_hex, .. } => *format_as_hex,
+        TypeRef::BigInteger => true,
+        TypeRef::Format(_) => ty.with_module(ctx.config, ctx.file).is_some(),
+        TypeRef::Array(inner)
+        | TypeRef::Set(inner)
+        | TypeRef::Map(inner)
+        | TypeRef::Boxed(_, inner)
+        | TypeRef::Nullable(inner) => has_module(ctx, inner),
+        TypeRef::Tuple(items) => items.iter().any(|item| has_module(ctx, item)),
+        TypeRef::Ref(path) => match ctx.file.types.get(path).map(|ty| &ty.kind) {
+            Some(TypeKind::Alias(alias)) if !ctx.config.generation.is_newtype(path) => {
+                has_module(ctx, &alias.ty)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns `s` as a raw string literal, which keeps patterns and JSON readable.
+///
+/// Strings that raw string literals can't hold are escaped instead.
+fn raw_string(s: &str) -> Literal {
+    let hashes = "#".repeat(raw_string_hashes(s));
+    format!("r{hashes}\"{s}\"{hashes}")
+        .parse()
+        .unwrap_or_else(|_| Literal::string(s))
 }
 
-impl<'a> Ctx<'a> {
-    /// Returns the name of the type referenced by the provided [`TypeRef`].
-    pub fn type_ref_name(&self, r: &'a TypeRef, required: bool) -> Cow<'a, str> {
-        if !required {
-            let inner = self.type_ref_name(r, true);
-            return Cow::Owned(self.config.primitives.optional.replace("{}", &inner));
+/// Returns the number of `#` needed for `s` to fit in a raw string literal.
+fn raw_string_hashes(s: &str) -> usize {
+    let mut hashes = 0;
+    while s.contains(&format!("\"{}", "#".repeat(hashes))) {
+        hashes += 1;
+    }
+    hashes
+}
+
+/// Returns the parameters of `example`, structured the way `method` expects them.
+fn example_params(method: &Method, example: &crate::parse::Example) -> serde_json::Value {
+    let value = |name: &str| {
+        let found = example.params.iter().find(|(n, _)| n == name);
+        found.map(|(_, value)| value.clone())
+    };
+    if method.param_structure == ParamStructure::ByPosition {
+        let values = method.params.iter().map(|p| value(&p.name_in_json));
+        serde_json::Value::Array(values.map(Option::unwrap_or_default).collect())
+    } else {
+        let values = method
+            .params
+            .iter()
+            .filter_map(|p| Some((p.name_in_json.clone(), value(&p.name_in_json)?)));
+        serde_json::Value::Object(values.collect())
+    }
+}
+
+/// Writes the `mocks` module, answering JSON-RPC requests with the results of the examples.
+fn gen_mocks(tokens: &mut TokenStream, ctx: &mut Ctx) -> io::Result<()> {
+    let mut examples = TokenStream::new();
+    for method in &ctx.file.methods {
+        for example in &method.examples {
+            let Some(result) = &example.result else {
+                continue;
+            };
+            let method_name = &method.name;
+            let name = &example.name;
+            let params = raw_string(&example_params(method, example).to_string());
+            let result = raw_string(&result.to_string());
+            examples.extend(quote! {
+                Example {
+                    method: #method_name,
+                    name: #name,
+                    params: #params,
+                    result: #result,
+                },
+            });
         }
+    }
 
-        match r {
-            TypeRef::Array(inner) => Cow::Owned(
-                self.config
-                    .primitives
-                    .array
-                    .replace("{}", &self.type_ref_name(inner, true)),
-            ),
-            TypeRef::Boolean => Cow::Borrowed(&self.config.primitives.boolean),
-            TypeRef::Integer { .. } => Cow::Borrowed(&self.config.primitives.integer),
-            TypeRef::Null => Cow::Borrowed(&self.config.primitives.null),
-            TypeRef::Number => Cow::Borrowed(&self.config.primitives.number),
-            TypeRef::String => Cow::Borrowed(&self.config.primitives.string),
-            TypeRef::Keyword(val) => {
-                Cow::Owned(format!("{} /* {} */", &self.config.primitives.string, val))
+    let cfg = ctx.serde_cfg();
+    tokens.extend(quote! {
+        #cfg
+        /// Stubs of the methods, answering with the results of the examples of the OpenRPC document.
+        pub mod mocks {
+            /// An example call of a method.
+            #[derive(Debug, Clone, Copy)]
+            pub struct Example {
+                /// The name of the method.
+                pub method: &'static str,
+                /// The name of the example.
+                pub name: &'static str,
+                /// The parameters of the call, as a JSON array or object.
+                pub params: &'static str,
+                /// The result of the call, as JSON.
+                pub result: &'static str,
+            }
+
+            /// The examples of the methods, in the order they are declared in the document.
+            pub const EXAMPLES: &[Example] = &[#examples];
+
+            /// Returns the result of the example of `method` whose parameters are `params`.
+            ///
+            /// When no example has these parameters, the result of the first example of the method
+            /// is returned instead. `None` is returned for the methods without examples.
+            pub fn result(method: &str, params: &serde_json::Value) -> Option<serde_json::Value> {
+                let mut examples = EXAMPLES.iter().filter(|example| example.method == method);
+                let first = examples.clone().next()?;
+                let example = examples
+                    .find(|example| {
+                        serde_json::from_str::<serde_json::Value>(example.params)
+                            .is_ok_and(|p| p == *params)
+                    })
+                    .unwrap_or(first);
+                serde_json::from_str(example.result).ok()
+            }
+
+            /// Answers the provided JSON-RPC request with the result of the matching example.
+            ///
+            /// Requests for the methods without examples are answered with a "Method not found"
+            /// error.
+            pub fn respond(request: &serde_json::Value) -> serde_json::Value {
+                let id = request.get("id").cloned().unwrap_or_default();
+                let method = request
+                    .get("method")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or_default();
+                let params = request.get("params").cloned().unwrap_or_default();
+                match result(method, &params) {
+                    Some(result) => serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": result,
+                    }),
+                    None => serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32601, "message": "Method not found" },
+                    }),
+                }
             }
-            TypeRef::Ref(path) => match self.file.types.get(path) {
-                Some(ty) => Cow::Borrowed(&ty.name),
-                None => Cow::Owned(format!("BrokenReference /* {path} */")),
-            },
-            TypeRef::ExternalRef(name) => Cow::Borrowed(name),
         }
-    }
+    });
+
+    Ok(())
 }
 
-/// Generates a Rust file from the provided [`crate::parse::File`] and configuration.
-pub fn gen(
-    w: &mut dyn io::Write,
-    file: &crate::parse::File,
-    config: &crate::config::Config,
-) -> io::Result<()> {
-    let mut ctx = Ctx { file, config };
-
-    writeln!(
-        w,
-        "\
-        //\n\
-        // This file was automatically generated by openrpc-gen.\n\
-        //\n\
-        // Do not edit it manually and instead edit either the source OpenRPC document,\n\
-        // the configuration file, or open an issue or pull request on the openrpc-gen\n\
-        // GitHub repository.\n\
-        // \n\
-        //     https://github.com/nils-mathieu/openrpc-gen\n\
-        //\n\
-        "
+/// Writes the `NOTIFICATIONS` constant, listing the names of the notification methods.
+fn gen_notification_names(tokens: &mut TokenStream, ctx: &mut Ctx) -> io::Result<()> {
+    let names = ctx
+        .file
+        .methods
+        .iter()
+        .filter(|m| m.is_notification())
+        .map(|method| ctx.method_name_expr(method))
+        .collect::<io::Result<Vec<_>>>()?;
+    tokens.extend(quote! {
+        /// The names of the methods that are notifications, for which no response is expected.
+        pub const NOTIFICATIONS: &[&str] = &[#(#names,)*];
+    });
+
+    Ok(())
+}
+
+/// Writes the `Transport` trait, the `Client` and `Notifier` traits and the blanket
+/// implementations linking them.
+///
+/// When subscriptions are found, the `SubscriptionTransport` and `Subscriber` traits are
+/// written as well. The methods involved in those subscriptions are not part of the `Client`
+/// and `Notifier` traits.
+fn gen_client_trait(tokens: &mut TokenStream, ctx: &mut Ctx) -> io::Result<()> {
+    let subscriptions = ctx.subscriptions();
+    let is_subscription = |m: &Method| {
+        subscriptions
+            .iter()
+            .any(|s| std::ptr::eq(s.subscribe, m) || std::ptr::eq(s.notification, m))
+    };
+    let calls = ctx
+        .file
+        .methods
+        .iter()
+        .filter(|m| !m.is_notification() && !is_subscription(m))
+        .collect::<Vec<_>>();
+    let notifications = ctx
+        .file
+        .methods
+        .iter()
+        .filter(|m| m.is_notification() && !is_subscription(m))
+        .collect::<Vec<_>>();
+
+    let send_notification = (!notifications.is_empty()).then(|| {
+        quote! {
+            /// Sends a notification for `method` with the provided parameters, without waiting for a response.
+            async fn send_notification<P>(&self, method: &str, params: P) -> Result<(), Self::Error>
+            where
+                P: Serialize;
+        }
+    });
+    tokens.extend(quote! {
+        /// A transport able to send JSON-RPC requests to a server.
+        #[allow(async_fn_in_trait)]
+        pub trait Transport {
+            /// The error returned when a request fails.
+            type Error;
+
+            /// Sends a request for `method` with the provided parameters and returns its result.
+            async fn request<P, R>(&self, method: &str, params: P) -> Result<R, Self::Error>
+            where
+                P: Serialize,
+                R: serde::de::DeserializeOwned;
+
+            #send_notification
+        }
+    });
+
+    gen_client_methods(
+        tokens,
+        ctx,
+        "Client",
+        "A client able to call the methods of the API.",
+        "request",
+        &calls,
     )?;
 
-    writeln!(w, "use serde::{{Serialize, Deserialize}};")?;
-    if ctx.config.generation.param_types && !ctx.file.methods.is_empty() {
-        writeln!(w, "use serde::ser::SerializeMap;")?;
-    }
-    for import in &ctx.config.generation.additional_imports {
-        writeln!(w, "use {import};")?;
+    if !notifications.is_empty() {
+        gen_client_methods(
+            tokens,
+            ctx,
+            "Notifier",
+            "A client able to send the notifications of the API.",
+            "send_notification",
+            &notifications,
+        )?;
     }
-    writeln!(w)?;
 
-    for ty in file.types.values() {
-        gen_type(w, &mut ctx, ty)?;
-    }
-    for method in &file.methods {
-        gen_method(w, &mut ctx, method)?;
+    if !subscriptions.is_empty() {
+        gen_subscriber_trait(tokens, ctx, &subscriptions)?;
     }
 
     Ok(())
 }
 
-/// Writes the provided type.
-fn gen_type(w: &mut dyn io::Write, ctx: &mut Ctx, ty: &TypeDef) -> io::Result<()> {
-    if ctx.config.debug_path {
-        writeln!(w, "// {}", ty.path)?;
+/// Writes the `SubscriptionTransport` and `Subscriber` traits, and the blanket implementation
+/// linking them.
+fn gen_subscriber_trait(
+    tokens: &mut TokenStream,
+    ctx: &mut Ctx,
+    subscriptions: &[Subscription],
+) -> io::Result<()> {
+    tokens.extend(quote! {
+        /// A transport able to manage subscriptions.
+        #[allow(async_fn_in_trait)]
+        pub trait SubscriptionTransport: Transport {
+            /// The stream of items returned for a subscription, usually implementing
+            /// `futures::Stream<Item = Result<T, Self::Error>>`.
+            type Subscription<T>;
+
+            /// Subscribes by calling `subscribe` with the provided parameters, and returns a stream of
+            /// the items received through the `notification` method.
+            ///
+            /// The `unsubscribe` method should be called once the subscription is no longer needed.
+            async fn subscribe<P, T>(&self, subscribe: &str, params: P, notification: &str, unsubscribe: &str) -> Result<Self::Subscription<T>, Self::Error>
+            where
+                P: Serialize,
+                T: serde::de::DeserializeOwned;
+        }
+    });
+
+    let signature = |ctx: &Ctx, sub: &Subscription| -> io::Result<TokenStream> {
+        let name = parse_ident(&format!("subscribe_{}", sub.name.to_case(Case::Snake)))?;
+        let names = sub
+            .subscribe
+            .params
+            .iter()
+            .map(|param| parse_ident(&param.name))
+            .collect::<io::Result<Vec<_>>>()?;
+        let types = sub
+            .subscribe
+            .params
+            .iter()
+            .map(|param| parse_type(&ctx.type_ref_name(&param.ty, param.required)))
+            .collect::<io::Result<Vec<_>>>()?;
+        let payload = parse_type(&ctx.type_ref_name(&sub.payload.ty, sub.payload.required))?;
+        Ok(quote! {
+            async fn #name(&self, #(#names: #types),*) -> Result<Self::Subscription<#payload>, Self::Error>
+        })
+    };
+
+    let mut decls = TokenStream::new();
+    let mut impls = TokenStream::new();
+    for sub in subscriptions {
+        if let Some(ref doc) = sub.subscribe.documentation {
+            decls.extend(doc_attrs(doc));
+            decls.extend(doc_line(""));
+        }
+        let doc = doc_line(&format!(
+            "Calls the `{}` method and returns a stream of the items sent through `{}`.",
+            sub.subscribe.name, sub.notification.name
+        ));
+        let signature = signature(ctx, sub)?;
+        decls.extend(quote! {
+            #doc
+            #signature;
+        });
+
+        let subscribe = ctx.method_name_expr(sub.subscribe)?;
+        let params = parse_path(&ctx.params_type_name(sub.subscribe))?;
+        let names = sub
+            .subscribe
+            .params
+            .iter()
+            .map(|param| parse_ident(&param.name))
+            .collect::<io::Result<Vec<_>>>()?;
+        let notification = ctx.method_name_expr(sub.notification)?;
+        let unsubscribe = ctx.method_name_expr(sub.unsubscribe)?;
+        impls.extend(quote! {
+            #signature {
+                self.subscribe(#subscribe, #params { #(#names),* }, #notification, #unsubscribe).await
+            }
+        });
     }
-    if let Some(doc) = &ty.documentation {
-        writeln!(w, "/// {}", doc)?;
+
+    tokens.extend(quote! {
+        /// A client able to subscribe to the notifications of the API.
+        ///
+        /// This trait is implemented for every type implementing [`SubscriptionTransport`].
+        #[allow(async_fn_in_trait)]
+        pub trait Subscriber {
+            /// The error returned when subscribing fails.
+            type Error;
+            /// The stream of items returned for a subscription.
+            type Subscription<T>;
+
+            #decls
+        }
+
+        impl<T: SubscriptionTransport> Subscriber for T {
+            type Error = T::Error;
+            type Subscription<U> = T::Subscription<U>;
+
+            #impls
+        }
+    });
+
+    Ok(())
+}
+
+/// Writes a client trait named `ident` for the provided methods, and its blanket implementation
+/// forwarding calls to `Transport::<transport_fn>`.
+fn gen_client_methods(
+    tokens: &mut TokenStream,
+    ctx: &mut Ctx,
+    ident: &str,
+    doc: &str,
+    transport_fn: &str,
+    methods: &[&Method],
+) -> io::Result<()> {
+    let ident = format_ident!("{ident}");
+    let transport_fn = format_ident!("{transport_fn}");
+    let doc = doc_line(doc);
+
+    let mut decls = TokenStream::new();
+    let mut impls = TokenStream::new();
+    for method in methods {
+        if let Some(ref doc) = method.documentation {
+            decls.extend(doc_attrs(doc));
+            decls.extend(doc_line(""));
+        }
+        let doc = doc_line(&format!("Calls the `{}` method.", method.name));
+        let signature = method_signature(ctx, method)?;
+        decls.extend(quote! {
+            #doc
+            #signature;
+        });
+
+        let name = ctx.method_name_expr(method)?;
+        let params = parse_path(&ctx.params_type_name(method))?;
+        let names = method
+            .params
+            .iter()
+            .map(|param| parse_ident(&param.name))
+            .collect::<io::Result<Vec<_>>>()?;
+        impls.extend(quote! {
+            #signature {
+                self.#transport_fn(#name, #params { #(#names),* }).await
+            }
+        });
     }
-    match &ty.kind {
-        TypeKind::Alias(alias) => {
-            writeln!(
-                w,
-                "pub type {} = {};",
-                ty.name,
-                ctx.type_ref_name(&alias.ty, true)
-            )?;
+
+    tokens.extend(quote! {
+        #doc
+        ///
+        /// This trait is implemented for every type implementing [`Transport`].
+        #[allow(async_fn_in_trait)]
+        pub trait #ident {
+            /// The error returned when a call fails.
+            type Error;
+
+            #decls
         }
-        TypeKind::Struct(s) => {
-            writeln!(w, "#[derive(Debug, Clone, Serialize, Deserialize)]")?;
-            writeln!(w, "pub struct {} {{", ty.name)?;
-            for field in s.fields.values() {
-                if ctx.config.debug_path {
-                    writeln!(w, "    // {}", field.path)?;
-                }
-                if let Some(doc) = &field.documentation {
-                    writeln!(w, "    /// {}", doc)?;
-                }
-                let name = ctx.type_ref_name(&field.ty, field.required);
-                if !field.required {
-                    writeln!(w, "    #[serde(default)]")?;
-                }
-                if field.flatten {
-                    writeln!(w, "    #[serde(flatten)]")?;
-                }
-                if field.name != field.name_in_json {
-                    writeln!(w, "    #[serde(rename = \"{}\")]", field.name_in_json)?;
-                }
-                for attr in field.ty.attributes(ctx.config, ctx.file) {
-                    writeln!(w, "    {}", attr)?;
+
+        impl<T: Transport> #ident for T {
+            type Error = T::Error;
+
+            #impls
+        }
+    });
+
+    Ok(())
+}
+
+/// Returns the signature of the method generated for `method` in the `Client` and `RpcServer`
+/// traits.
+fn method_signature(ctx: &Ctx, method: &Method) -> io::Result<TokenStream> {
+    let name = parse_ident(&ctx.method_fn_name(method))?;
+    let names = method
+        .params
+        .iter()
+        .map(|param| parse_ident(&param.name))
+        .collect::<io::Result<Vec<_>>>()?;
+    let types = method
+        .params
+        .iter()
+        .map(|param| parse_type(&ctx.type_ref_name(&param.ty, param.required)))
+        .collect::<io::Result<Vec<_>>>()?;
+    let result = parse_type(&ctx.method_result_type(method))?;
+    Ok(quote! {
+        async fn #name(&self, #(#names: #types),*) -> Result<#result, Self::Error>
+    })
+}
+
+/// Writes the `RpcServer` trait and the `dispatch` function calling its methods.
+fn gen_server_trait(tokens: &mut TokenStream, ctx: &mut Ctx) -> io::Result<()> {
+    let mut decls = TokenStream::new();
+    let mut arms = TokenStream::new();
+    for method in &ctx.file.methods {
+        if let Some(ref doc) = method.documentation {
+            decls.extend(doc_attrs(doc));
+            decls.extend(doc_line(""));
+        }
+        let doc = doc_line(&format!("Handles the `{}` method.", method.name));
+        let signature = method_signature(ctx, method)?;
+        decls.extend(quote! {
+            #doc
+            #signature;
+        });
+
+        let name = ctx.method_name_expr(method)?;
+        // Methods without parameters still validate them, but don't use the result.
+        let (default, params_var) = if method.params.is_empty() {
+            let empty = match method.param_structure {
+                ParamStructure::ByName => quote!(serde_json::Value::Object(Default::default())),
+                ParamStructure::ByPosition | ParamStructure::Either => {
+                    quote!(serde_json::Value::Array(Vec::new()))
                 }
-                writeln!(w, "    pub {}: {},", field.name, name)?;
+            };
+            (
+                Some(quote!(let params = if params.is_null() { #empty } else { params };)),
+                quote!(_params),
+            )
+        } else {
+            (None, quote!(params))
+        };
+        let params = parse_type(&ctx.params_type_name(method))?;
+        let function = parse_ident(&ctx.method_fn_name(method))?;
+        let names = method
+            .params
+            .iter()
+            .map(|param| parse_ident(&param.name))
+            .collect::<io::Result<Vec<_>>>()?;
+        arms.extend(quote! {
+            #name => {
+                #default
+                let #params_var: #params = serde_json::from_value(params).map_err(DispatchError::InvalidParams)?;
+                let result = server.#function(#(params.#names),*).await.map_err(DispatchError::Server)?;
+                serde_json::to_value(result).map_err(DispatchError::InvalidResult)
             }
-            writeln!(w, "}}")?;
+        });
+    }
+
+    tokens.extend(quote! {
+        /// The methods of the API, as implemented by a server.
+        #[allow(async_fn_in_trait)]
+        pub trait RpcServer {
+            /// The error returned when a method fails.
+            type Error;
+
+            #decls
         }
-        TypeKind::Enum(e) => {
-            writeln!(w, "#[derive(Serialize, Deserialize)]")?;
-            if e.copy {
-                writeln!(w, "#[derive(Copy, PartialEq, Eq, Hash)]")?;
+
+        /// An error that might occur while dispatching a request to an [`RpcServer`].
+        #[derive(Debug)]
+        pub enum DispatchError<E> {
+            /// The requested method does not exist.
+            MethodNotFound,
+            /// The parameters of the request are invalid.
+            InvalidParams(serde_json::Error),
+            /// The result of the method could not be serialized.
+            InvalidResult(serde_json::Error),
+            /// The method itself failed.
+            Server(E),
+        }
+
+        /// Deserializes `params`, calls the method of `server` named `method` and serializes its result.
+        ///
+        /// `params` is `null` when the request has none, which is accepted by the methods without parameters.
+        pub async fn dispatch<S: RpcServer + ?Sized>(server: &S, method: &str, params: serde_json::Value) -> Result<serde_json::Value, DispatchError<S::Error>> {
+            match method {
+                #arms
+                _ => Err(DispatchError::MethodNotFound),
             }
-            for global_derive in &ctx.config.generation.global_derives {
-                writeln!(w, "#[derive({global_derive})]")?;
+        }
+    });
+
+    Ok(())
+}
+
+/// Writes the `Api` trait, to be expanded by jsonrpsee's `rpc` attribute.
+fn gen_jsonrpsee_trait(tokens: &mut TokenStream, ctx: &mut Ctx) -> io::Result<()> {
+    let mut methods = TokenStream::new();
+    for method in &ctx.file.methods {
+        if let Some(ref doc) = method.documentation {
+            methods.extend(doc_attrs(doc));
+        }
+        let name = &method.name;
+        methods.extend(match method.param_structure {
+            ParamStructure::ByName => quote!(#[method(name = #name, param_kind = map)]),
+            ParamStructure::ByPosition | ParamStructure::Either => {
+                quote!(#[method(name = #name)])
             }
-            if let Some(derives) = ctx.config.generation.derives.get(&*ty.path) {
-                for derive in derives {
-                    writeln!(w, "#[derive({derive})]")?;
+        });
+        let mut params = Vec::new();
+        for param in &method.params {
+            let rename = (param.name != param.name_in_json).then(|| {
+                let rename = &param.name_in_json;
+                quote!(#[argument(rename = #rename)])
+            });
+            let ident = parse_ident(&param.name)?;
+            let ty = parse_type(&ctx.type_ref_name(&param.ty, param.required))?;
+            params.push(quote!(#rename #ident: #ty));
+        }
+        let function = parse_ident(&ctx.method_fn_name(method))?;
+        let result = parse_type(&ctx.method_result_type(method))?;
+        methods.extend(quote! {
+            async fn #function(&self, #(#params),*) -> jsonrpsee::core::RpcResult<#result>;
+        });
+    }
+
+    tokens.extend(quote! {
+        /// The methods of the API.
+        #[jsonrpsee::proc_macros::rpc(server, client)]
+        pub trait Api {
+            #methods
+        }
+    });
+
+    Ok(())
+}
+
+/// Writes the JSON-RPC 2.0 envelope types and a request constructor per method.
+fn gen_envelopes(tokens: &mut TokenStream, ctx: &mut Ctx) -> io::Result<()> {
+    tokens.extend(quote! {
+        /// The version of the JSON-RPC protocol, always serialized as `"2.0"`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+        pub enum JsonRpcVersion {
+            /// JSON-RPC 2.0.
+            #[default]
+            #[serde(rename = "2.0")]
+            V2,
+        }
+
+        /// The identifier of a JSON-RPC request.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(untagged)]
+        pub enum JsonRpcId {
+            /// A numeric identifier.
+            Number(i64),
+            /// A string identifier.
+            String(String),
+        }
+
+        /// A JSON-RPC 2.0 request.
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct JsonRpcRequest<T> {
+            /// The version of the protocol.
+            pub jsonrpc: JsonRpcVersion,
+            /// The name of the called method.
+            pub method: String,
+            /// The parameters of the method.
+            pub params: T,
+            /// The identifier of the request.
+            ///
+            /// When `None`, the request is a notification and no response is expected.
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub id: Option<JsonRpcId>,
+        }
+
+        impl<T> JsonRpcRequest<T> {
+            /// Creates a new request expecting a response.
+            pub fn new(id: JsonRpcId, method: impl Into<String>, params: T) -> Self {
+                Self {
+                    jsonrpc: JsonRpcVersion::V2,
+                    method: method.into(),
+                    params,
+                    id: Some(id),
                 }
             }
-            match &e.tag {
-                EnumTag::Normal => (),
-                EnumTag::Tagged(tag) => {
-                    writeln!(w, "#[serde(tag = \"{}\")]", tag)?;
-                }
-                EnumTag::Untagged => {
-                    writeln!(w, "#[serde(untagged)]")?;
+
+            /// Creates a new notification, for which no response is expected.
+            pub fn notification(method: impl Into<String>, params: T) -> Self {
+                Self {
+                    jsonrpc: JsonRpcVersion::V2,
+                    method: method.into(),
+                    params,
+                    id: None,
                 }
             }
-            writeln!(w, "pub enum {} {{", ty.name)?;
-            for variant in e.variants.values() {
-                if ctx.config.debug_path {
-                    writeln!(w, "    // {}", variant.path)?;
-                }
-                if let Some(doc) = &variant.documentation {
-                    writeln!(w, "    /// {}", doc)?;
+        }
+
+        /// The error object of a failed JSON-RPC call.
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct JsonRpcError {
+            /// The error code.
+            pub code: i64,
+            /// A short description of the error.
+            pub message: String,
+            /// Additional information about the error.
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub data: Option<serde_json::Value>,
+        }
+
+        /// The outcome of a JSON-RPC call, either a result or an error.
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        pub enum JsonRpcOutcome<T> {
+            /// The call succeeded.
+            Result(T),
+            /// The call failed.
+            Error(JsonRpcError),
+        }
+
+        /// A JSON-RPC 2.0 response.
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct JsonRpcResponse<T> {
+            /// The version of the protocol.
+            pub jsonrpc: JsonRpcVersion,
+            /// The identifier of the request this response answers.
+            ///
+            /// This is `None` when the request's identifier could not be determined.
+            pub id: Option<JsonRpcId>,
+            /// The outcome of the call.
+            #[serde(flatten)]
+            pub outcome: JsonRpcOutcome<T>,
+        }
+
+        impl<T> JsonRpcResponse<T> {
+            /// Converts the response into a [`Result`].
+            pub fn into_result(self) -> Result<T, JsonRpcError> {
+                match self.outcome {
+                    JsonRpcOutcome::Result(result) => Ok(result),
+                    JsonRpcOutcome::Error(error) => Err(error),
                 }
-                if let Some(name_in_json) = &variant.name_in_json {
-                    if name_in_json != &variant.name {
-                        writeln!(w, "    #[serde(rename = \"{}\")]", name_in_json)?;
-                    }
+            }
+        }
+    });
+
+    for method in &ctx.file.methods {
+        let params = parse_type(&ctx.params_type_name(method))?;
+        let fn_name = parse_ident(&ctx.envelope_fn_name(method))?;
+        let name = ctx.method_name_expr(method)?;
+        let constructor = if method.is_notification() {
+            let doc = doc_line(&format!(
+                "Creates a notification for the `{}` method.",
+                method.name
+            ));
+            quote! {
+                #doc
+                pub fn #fn_name(params: #params) -> Self {
+                    Self::notification(#name, params)
                 }
-                if let Some(inner) = &variant.ty {
-                    writeln!(
-                        w,
-                        "    {}({}),",
-                        variant.name,
-                        ctx.type_ref_name(inner, true)
-                    )?;
-                } else {
-                    writeln!(w, "    {},", variant.name)?;
+            }
+        } else {
+            let doc = doc_line(&format!(
+                "Creates a request for the `{}` method.",
+                method.name
+            ));
+            quote! {
+                #doc
+                pub fn #fn_name(id: JsonRpcId, params: #params) -> Self {
+                    Self::new(id, #name, params)
                 }
             }
-            writeln!(w, "}}")?;
-        }
+        };
+        tokens.extend(quote! {
+            impl JsonRpcRequest<#params> {
+                #constructor
+            }
+        });
     }
-    writeln!(w)?;
 
     Ok(())
 }
 
-fn gen_method(
-    w: &mut dyn io::Write,
-    ctx: &mut Ctx,
-    method: &crate::parse::Method,
-) -> io::Result<()> {
-    let std_mod = if ctx.config.generation.use_core {
-        "core"
-    } else {
-        "std"
-    };
+/// Writes the `BatchRequest` and `BatchResponse` types, along with their `BatchHandle` and
+/// `BatchError` helpers.
+///
+/// Those rely on the envelope types and on the `RpcMethod` trait.
+fn gen_batch(tokens: &mut TokenStream, ctx: &mut Ctx) {
+    let core = format_ident!("{}", ctx.std_mod());
+    tokens.extend(quote! {
+        /// A handle to a call pushed into a [`BatchRequest`], used to retrieve its typed result from
+        /// the matching [`BatchResponse`].
+        #[derive(Debug)]
+        pub struct BatchHandle<M> {
+            id: i64,
+            _method: #core::marker::PhantomData<fn() -> M>,
+        }
 
-    let ident_base = if let Some(ref prefix) = ctx.config.generation.method_name_prefix {
-        method.name.strip_prefix(prefix).unwrap_or(&method.name)
-    } else {
-        &method.name
-    };
+        impl<M> BatchHandle<M> {
+            /// Returns the identifier of the request in the batch.
+            pub fn id(&self) -> i64 {
+                self.id
+            }
+        }
 
-    if ctx.config.generation.method_name_constants {
-        writeln!(w, "/// `{}`", method.name)?;
-        writeln!(
-            w,
-            "pub const {}: &str = \"{}\";",
-            ident_base.to_case(Case::ScreamingSnake),
-            method.name
-        )?;
-        writeln!(w)?;
-    }
+        impl<M> Clone for BatchHandle<M> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
 
-    if ctx.config.generation.result_types {
-        let mut ident = ident_base.to_case(Case::Pascal);
-        ident.push_str("Result");
-        if let Some(ref result) = method.result {
-            if let Some(ref doc) = result.documentation {
-                writeln!(w, "/// {doc}")?;
-                writeln!(w, "///")?;
-            }
-            writeln!(w, "/// Result type of `{}`.", method.name)?;
-            writeln!(
-                w,
-                "pub type {} = {};",
-                ident,
-                ctx.type_ref_name(&result.ty, true)
-            )?;
-            writeln!(w)?;
-        } else {
-            writeln!(
-                w,
-                "/// Result type of `{}`. This method does not return anything.",
-                method.name
-            )?;
-            writeln!(w, "pub type {} = ();", ident_base.to_case(Case::Pascal))?;
-            writeln!(w)?;
+        impl<M> Copy for BatchHandle<M> {}
+
+        /// A batch of JSON-RPC calls, serialized as a JSON array of requests.
+        #[derive(Debug, Clone, Default)]
+        pub struct BatchRequest {
+            requests: Vec<JsonRpcRequest<serde_json::Value>>,
         }
-    }
 
-    if ctx.config.generation.param_types {
-        let mut ident = ident_base.to_case(Case::Pascal);
-        ident.push_str("Params");
+        impl BatchRequest {
+            /// Creates a new empty batch.
+            pub fn new() -> Self {
+                Self::default()
+            }
 
-        writeln!(w, "/// Parameters of the `{}` method.", method.name)?;
-        writeln!(w, "#[derive(Debug, Clone)]")?;
-        writeln!(w, "pub struct {} {{", ident)?;
-        for param in &method.params {
-            if let Some(ref doc) = param.documentation {
-                writeln!(w, "    /// {doc}")?;
+            /// Adds a call to the batch, returning a handle to retrieve its result from the response.
+            ///
+            /// Requests are numbered in the order they are pushed, starting from `0`.
+            pub fn push<M: RpcMethod>(
+                &mut self,
+                params: M::Params,
+            ) -> Result<BatchHandle<M>, serde_json::Error>
+            where
+                M::Params: Serialize,
+            {
+                let id = self.requests.len() as i64;
+                let params = serde_json::to_value(params)?;
+                self.requests
+                    .push(JsonRpcRequest::new(JsonRpcId::Number(id), M::NAME, params));
+                Ok(BatchHandle {
+                    id,
+                    _method: #core::marker::PhantomData,
+                })
             }
-            let param_ident = ctx.type_ref_name(&param.ty, param.required);
-            writeln!(w, "    pub {}: {},", param.name, param_ident)?;
-        }
-        writeln!(w, "}}")?;
-        writeln!(w)?;
 
-        writeln!(w, "impl Serialize for {ident} {{")?;
-        writeln!(w, "        #[allow(unused_mut)]")?;
-        writeln!(
-            w,
-            "    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>"
-        )?;
-        writeln!(w, "    where")?;
-        writeln!(w, "        S: serde::Serializer,")?;
-        writeln!(w, "    {{")?;
+            /// Returns the number of calls in the batch.
+            pub fn len(&self) -> usize {
+                self.requests.len()
+            }
 
-        if matches!(
-            method.param_structure,
-            ParamStructure::ByName | ParamStructure::Either
-        ) {
-            writeln!(w, "        let mut map = serializer.serialize_map(None)?;")?;
-            for param in &method.params {
-                writeln!(
-                    w,
-                    "        map.serialize_entry(\"{}\", &self.{})?;",
-                    param.name_in_json, param.name
-                )?;
-            }
-            writeln!(w, "        map.end()")?;
-        } else {
-            writeln!(w, "        let mut seq = serializer.serialize_seq(None)?;")?;
-            for param in &method.params {
-                writeln!(w, "        seq.serialize_element(&self.{})?;", param.name)?;
+            /// Returns whether the batch contains no calls.
+            pub fn is_empty(&self) -> bool {
+                self.requests.is_empty()
+            }
+
+            /// Returns the requests of the batch.
+            pub fn requests(&self) -> &[JsonRpcRequest<serde_json::Value>] {
+                &self.requests
             }
-            writeln!(w, "        seq.end()")?;
         }
 
-        writeln!(w, "    }}")?;
-        writeln!(w, "}}")?;
-        writeln!(w)?;
+        impl Serialize for BatchRequest {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.requests.serialize(serializer)
+            }
+        }
 
-        writeln!(w, "impl<'de> Deserialize<'de> for {ident} {{")?;
-        writeln!(
-            w,
-            "    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>"
-        )?;
-        writeln!(w, "    where")?;
-        writeln!(w, "        D: serde::Deserializer<'de>,")?;
-        writeln!(w, "    {{")?;
-
-        writeln!(w, "        struct Visitor;")?;
-        writeln!(w)?;
-        writeln!(
-            w,
-            "        impl<'de> serde::de::Visitor<'de> for Visitor {{"
-        )?;
-        writeln!(w, "            type Value = {ident};",)?;
-        writeln!(w)?;
-        writeln!(
-            w,
-            "            fn expecting(&self, f: &mut {std_mod}::fmt::Formatter) -> {std_mod}::fmt::Result {{"
-        )?;
-        writeln!(
-            w,
-            "                write!(f, \"the parameters for `{}`\")",
-            method.name
-        )?;
-        writeln!(w, "            }}")?;
-        writeln!(w)?;
+        /// An error occurring while retrieving the result of a call from a [`BatchResponse`].
+        #[derive(Debug)]
+        pub enum BatchError {
+            /// The response contains no entry for the call.
+            Missing,
+            /// The server returned an error for the call.
+            Rpc(JsonRpcError),
+            /// The result of the call could not be deserialized into the expected type.
+            Deserialize(serde_json::Error),
+        }
 
-        if matches!(
-            method.param_structure,
-            ParamStructure::ByPosition | ParamStructure::Either
-        ) {
-            writeln!(w, "            #[allow(unused_mut)]")?;
-            writeln!(
-                w,
-                "            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>"
-            )?;
-            writeln!(w, "            where")?;
-            writeln!(w, "                A: serde::de::SeqAccess<'de>,")?;
-            writeln!(w, "            {{")?;
-            for (i, param) in method.params.iter().enumerate() {
-                writeln!(
-                    w,
-                    "                let {}: {} = seq.next_element()?.ok_or_else(|| serde::de::Error::invalid_length({}, &\"expected {} parameters\"))?;",
-                    param.name, ctx.type_ref_name(&param.ty, param.required), i + 1, method.params.len(),
-                )?;
-            }
-            writeln!(w)?;
-            writeln!(
-                w,
-                "                if seq.next_element::<serde::de::IgnoredAny>()?.is_some() {{"
-            )?;
-            writeln!(w, "                    return Err(serde::de::Error::invalid_length({}, &\"expected {} parameters\"));", method.params.len() + 1, method.params.len())?;
-            writeln!(w, "                }}")?;
-            writeln!(w)?;
-            writeln!(w, "                Ok({ident} {{")?;
-            for param in &method.params {
-                writeln!(w, "                    {},", param.name)?;
+        impl #core::fmt::Display for BatchError {
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                match self {
+                    Self::Missing => f.write_str("no response for the call in the batch"),
+                    Self::Rpc(error) => write!(f, "error {}: {}", error.code, error.message),
+                    Self::Deserialize(error) => write!(f, "invalid result: {error}"),
+                }
             }
-            writeln!(w, "                }})")?;
-            writeln!(w, "            }}")?;
-            writeln!(w)?;
         }
 
-        if matches!(
-            method.param_structure,
-            ParamStructure::ByName | ParamStructure::Either
-        ) {
-            writeln!(w, "            #[allow(unused_variables)]")?;
-            writeln!(
-                w,
-                "            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>"
-            )?;
-            writeln!(w, "            where")?;
-            writeln!(w, "                A: serde::de::MapAccess<'de>,")?;
-            writeln!(w, "            {{")?;
-            writeln!(w, "                #[derive(Deserialize)]")?;
-            writeln!(w, "                struct Helper {{")?;
-            for param in &method.params {
-                if !param.required {
-                    writeln!(w, "                        #[serde(default)]")?;
-                }
-                writeln!(
-                    w,
-                    "                    {}: {},",
-                    param.name,
-                    ctx.type_ref_name(&param.ty, param.required)
-                )?;
+        impl #core::error::Error for BatchError {}
+
+        /// The response to a [`BatchRequest`], deserialized from a JSON array of responses.
+        ///
+        /// The server may return the responses in any order; they are matched with their calls by
+        /// identifier.
+        #[derive(Debug, Clone, Default, Deserialize)]
+        #[serde(transparent)]
+        pub struct BatchResponse {
+            responses: Vec<JsonRpcResponse<serde_json::Value>>,
+        }
+
+        impl BatchResponse {
+            /// Returns the raw responses of the batch.
+            pub fn responses(&self) -> &[JsonRpcResponse<serde_json::Value>] {
+                &self.responses
             }
-            writeln!(w, "                }}")?;
-            writeln!(w)?;
-            writeln!(w, "                let helper = Helper::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;")?;
-            writeln!(w)?;
-            writeln!(w, "                Ok({ident} {{")?;
-            for param in &method.params {
-                writeln!(
-                    w,
-                    "                    {}: helper.{},",
-                    param.name, param.name
-                )?;
+
+            /// Returns the typed result of the call identified by `handle`.
+            pub fn get<M: RpcMethod>(&self, handle: &BatchHandle<M>) -> Result<M::Result, BatchError>
+            where
+                M::Result: serde::de::DeserializeOwned,
+            {
+                let response = self
+                    .responses
+                    .iter()
+                    .find(|r| r.id == Some(JsonRpcId::Number(handle.id)))
+                    .ok_or(BatchError::Missing)?;
+                match &response.outcome {
+                    JsonRpcOutcome::Result(result) => {
+                        M::Result::deserialize(result).map_err(BatchError::Deserialize)
+                    }
+                    JsonRpcOutcome::Error(error) => Err(BatchError::Rpc(error.clone())),
+                }
             }
-            writeln!(w, "                }})")?;
-            writeln!(w, "            }}")?;
-            writeln!(w)?;
         }
+    });
+}
+
+/// Writes an enum named `ident` listing the provided errors.
+fn gen_error_enum(
+    tokens: &mut TokenStream,
+    ctx: &mut Ctx,
+    ident: &str,
+    doc: &str,
+    errors: &[&ErrorDef],
+) -> io::Result<()> {
+    let core = format_ident!("{}", ctx.std_mod());
+    let ident = parse_ident(ident)?;
+    let has_data = errors.iter().any(|e| e.data.is_some());
+    // The errors that are created from a code, which is the first one declared with it.
+    let mut codes = BTreeSet::new();
+    let by_code: Vec<&ErrorDef> = errors
+        .iter()
+        .copied()
+        .filter(|error| codes.insert(error.code))
+        .collect();
+
+    let mut variants = TokenStream::new();
+    let mut code_arms = TokenStream::new();
+    let mut message_arms = TokenStream::new();
+    for error in errors {
+        let name = parse_ident(&error.name)?;
+        variants.extend(debug_path(ctx, &error.path));
+        variants.extend(doc_attrs(&error.message));
+        variants.extend(match &error.data {
+            Some(data) => {
+                let data = parse_type(&ctx.type_ref_name(data, true))?;
+                quote!(#name(#data),)
+            }
+            None => quote!(#name,),
+        });
+        // A pattern matching the variant of the error.
+        let pattern = match error.data {
+            Some(_) => quote!(Self::#name(..)),
+            None => quote!(Self::#name),
+        };
+        let code = int_literal(error.code.into());
+        let message = &error.message;
+        code_arms.extend(quote!(#pattern => #code,));
+        message_arms.extend(quote!(#pattern => #message,));
+    }
 
-        writeln!(w, "        }}")?;
-        writeln!(w)?;
+    let mut from_code = TokenStream::new();
+    for error in &by_code {
+        let name = parse_ident(&error.name)?;
+        let code = int_literal(error.code.into());
+        from_code.extend(if !has_data {
+            quote!(#code => Ok(Self::#name),)
+        } else if error.data.is_some() {
+            quote!(#code => Deserialize::deserialize(data?).ok().map(Self::#name),)
+        } else {
+            quote!(#code => Some(Self::#name),)
+        });
+    }
 
-        match method.param_structure {
-            ParamStructure::ByName => {
-                writeln!(w, "        deserializer.deserialize_map(Visitor)")?;
+    let doc = doc_line(doc);
+    let derive = if has_data {
+        quote!(#[derive(Debug, Clone)])
+    } else {
+        quote!(#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)])
+    };
+    let from_code_and_data = has_data.then(|| {
+        let cfg = ctx.serde_cfg();
+        quote! {
+            /// Creates an error from its JSON-RPC code and the data that accompanies it.
+            ///
+            /// Returns `None` if the code is unknown or if the data is missing or invalid.
+            #cfg
+            pub fn from_code_and_data(code: i64, data: Option<&serde_json::Value>) -> Option<Self> {
+                match code {
+                    #from_code
+                    _ => None,
+                }
             }
-            ParamStructure::ByPosition => {
-                writeln!(w, "        deserializer.deserialize_seq(Visitor)")?;
+        }
+    });
+    tokens.extend(quote! {
+        #doc
+        #derive
+        pub enum #ident {
+            #variants
+        }
+
+        impl #ident {
+            /// Returns the JSON-RPC error code of this error.
+            pub fn code(&self) -> i64 {
+                match self {
+                    #code_arms
+                }
             }
-            ParamStructure::Either => {
-                writeln!(w, "        deserializer.deserialize_any(Visitor)")?;
+
+            /// Returns the message associated with this error.
+            pub fn message(&self) -> &'static str {
+                match self {
+                    #message_arms
+                }
             }
+
+            #from_code_and_data
         }
+    });
 
-        writeln!(w, "    }}")?;
-        writeln!(w, "}}")?;
-        writeln!(w)?;
+    // Errors carrying data can't be created from their code alone.
+    if !has_data {
+        tokens.extend(quote! {
+            impl TryFrom<i64> for #ident {
+                type Error = i64;
+
+                fn try_from(code: i64) -> Result<Self, i64> {
+                    match code {
+                        #from_code
+                        _ => Err(code),
+                    }
+                }
+            }
+        });
     }
 
+    tokens.extend(quote! {
+        impl #core::fmt::Display for #ident {
+            fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                f.write_str(self.message())
+            }
+        }
+
+        impl #core::error::Error for #ident {}
+    });
+
     Ok(())
 }
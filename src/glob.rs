@@ -0,0 +1,41 @@
+//! Matching of paths against the glob patterns used in the configuration file.
+//!
+//! Paths and patterns are split into `/`-separated segments. Within a segment, `*` matches any
+//! sequence of characters and `?` matches exactly one character. A segment made of `**` alone
+//! matches any number of segments, including none.
+
+/// Returns whether `pattern` contains wildcards.
+pub fn is_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Returns whether `path` matches `pattern`.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.split('/').collect::<Vec<_>>();
+    let path = path.split('/').collect::<Vec<_>>();
+    matches_segments(&pattern, &path)
+}
+
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|i| matches_segments(rest, &path[i..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path)) => {
+                let first = first.chars().collect::<Vec<_>>();
+                let segment = segment.chars().collect::<Vec<_>>();
+                matches_segment(&first, &segment) && matches_segments(rest, path)
+            }
+            None => false,
+        },
+    }
+}
+
+fn matches_segment(pattern: &[char], segment: &[char]) -> bool {
+    match pattern.split_first() {
+        None => segment.is_empty(),
+        Some(('*', rest)) => (0..=segment.len()).any(|i| matches_segment(rest, &segment[i..])),
+        Some(('?', rest)) => !segment.is_empty() && matches_segment(rest, &segment[1..]),
+        Some((c, rest)) => segment.first() == Some(c) && matches_segment(rest, &segment[1..]),
+    }
+}
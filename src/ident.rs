@@ -0,0 +1,99 @@
+//! Turns names into valid Rust identifiers.
+
+use std::collections::BTreeMap;
+
+/// The keywords of Rust 2021, strict and reserved, which can't be used as identifiers as-is.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// The keywords that can't be written as raw identifiers either.
+const NOT_RAW: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Returns a valid identifier for `name`.
+///
+/// Keywords are replaced as configured in `renames`, or written as raw identifiers such as
+/// `r#match`. The few keywords that can't be raw identifiers get an underscore suffix instead.
+pub fn escape(name: &str, renames: &BTreeMap<String, String>) -> String {
+    if let Some(rename) = renames.get(name) {
+        rename.clone()
+    } else if NOT_RAW.contains(&name) {
+        format!("{name}_")
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.into()
+    }
+}
+
+/// Returns `ident` without its `r#` prefix, to build other identifiers from it.
+pub fn unraw(ident: &str) -> &str {
+    ident.strip_prefix("r#").unwrap_or(ident)
+}
+
+/// Returns `name` in PascalCase, such as `L1GasPrice` for `l1_gas_price`.
+///
+/// The words found in `acronyms`, compared without case, are written as they appear there, such
+/// as `DAMode` for `DA_MODE` when `DA` is listed.
+pub fn pascal(name: &str, acronyms: &[String]) -> String {
+    let mut out = String::with_capacity(name.len());
+    for word in words(name) {
+        if let Some(acronym) = acronyms.iter().find(|a| a.eq_ignore_ascii_case(word)) {
+            out.push_str(acronym);
+            continue;
+        }
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(&chars.as_str().to_lowercase());
+        }
+    }
+    out
+}
+
+/// Returns `name` in snake_case, such as `contract_address` for `contractAddress`.
+pub fn snake(name: &str) -> String {
+    let words: Vec<String> = words(name).map(str::to_lowercase).collect();
+    words.join("_")
+}
+
+/// Returns `name` in SCREAMING_SNAKE_CASE, such as `GET_BLOCK` for `getBlock`.
+pub fn screaming_snake(name: &str) -> String {
+    let words: Vec<String> = words(name).map(str::to_uppercase).collect();
+    words.join("_")
+}
+
+/// Splits `name` into words.
+///
+/// Words are separated by characters that are not alphanumeric, by a lowercase letter followed
+/// by an uppercase one, by the last letter of a run of uppercase ones followed by a lowercase
+/// one, as in `HTTPServer`, and by a digit followed by a letter. Digits stay with the letters
+/// before them, so that `l1`, `u256` and `erc20` are single words.
+fn words(name: &str) -> impl Iterator<Item = &str> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .flat_map(|part| {
+            let chars: Vec<(usize, char)> = part.char_indices().collect();
+            let mut bounds = vec![0];
+            for i in 1..chars.len() {
+                let prev = chars[i - 1].1;
+                let (pos, c) = chars[i];
+                let next = chars.get(i + 1).map(|&(_, c)| c);
+                if (prev.is_lowercase() && c.is_uppercase())
+                    || (prev.is_numeric() && c.is_alphabetic())
+                    || (prev.is_uppercase()
+                        && c.is_uppercase()
+                        && next.is_some_and(char::is_lowercase))
+                {
+                    bounds.push(pos);
+                }
+            }
+            bounds.push(part.len());
+            let words: Vec<&str> = bounds.windows(2).map(|w| &part[w[0]..w[1]]).collect();
+            words
+        })
+        .filter(|word| !word.is_empty())
+}
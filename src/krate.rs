@@ -0,0 +1,118 @@
+//! The files of the crate generated with `--crate`, other than the generated modules.
+
+use std::io;
+
+use crate::config::Config;
+
+/// Writes the `Cargo.toml` file of the crate named `name`.
+///
+/// The dependencies are the ones the generated code relies on given the configuration.
+pub fn manifest(w: &mut dyn io::Write, config: &Config, name: &str) -> io::Result<()> {
+    let krate = &config.krate;
+    let generation = &config.generation;
+
+    writeln!(w, "[package]")?;
+    writeln!(w, "name = {name:?}")?;
+    writeln!(w, "version = {:?}", krate.version)?;
+    writeln!(w, "edition = \"2021\"")?;
+    if let Some(description) = &krate.description {
+        writeln!(w, "description = {description:?}")?;
+    }
+    if let Some(license) = &krate.license {
+        writeln!(w, "license = {license:?}")?;
+    }
+    writeln!(w)?;
+
+    let arbitrary_feature = generation
+        .arbitrary_feature
+        .as_ref()
+        .filter(|_| generation.arbitrary);
+    if generation.serde_feature.is_some() || arbitrary_feature.is_some() {
+        writeln!(w, "[features]")?;
+        if let Some(feature) = &generation.serde_feature {
+            writeln!(w, "default = [{feature:?}]")?;
+            if generation.from_json {
+                writeln!(
+                    w,
+                    "{feature} = [\"dep:serde\", \"dep:serde_path_to_error\"]"
+                )?;
+            } else {
+                writeln!(w, "{feature} = [\"dep:serde\"]")?;
+            }
+        }
+        if let Some(feature) = arbitrary_feature {
+            writeln!(w, "{feature} = [\"dep:proptest\"]")?;
+        }
+        writeln!(w)?;
+    }
+
+    let mut serde_features = vec!["\"derive\""];
+    let mut serde_json = String::from("\"1\"");
+    let mut serde_extra = String::new();
+    if generation.no_std {
+        serde_features.push("\"alloc\"");
+        serde_extra.push_str(", default-features = false");
+        serde_json =
+            String::from("{ version = \"1\", default-features = false, features = [\"alloc\"] }");
+    }
+    if generation.serde_feature.is_some() {
+        serde_extra.push_str(", optional = true");
+    }
+
+    writeln!(w, "[dependencies]")?;
+    writeln!(
+        w,
+        "serde = {{ version = \"1\"{serde_extra}, features = [{}] }}",
+        serde_features.join(", ")
+    )?;
+    writeln!(w, "serde_json = {serde_json}")?;
+    if generation.from_json {
+        let optional = match generation.serde_feature {
+            Some(_) => ", optional = true",
+            None => "",
+        };
+        writeln!(w, "serde_path_to_error = {{ version = \"0.1\"{optional} }}")?;
+    }
+    if generation.arbitrary {
+        let optional = match arbitrary_feature {
+            Some(_) => ", optional = true",
+            None => "",
+        };
+        writeln!(w, "proptest = {{ version = \"1\"{optional} }}")?;
+    }
+    if generation.pattern_newtypes
+        || generation.pattern_newtype_types.values().any(|yes| *yes)
+        || generation.validation
+    {
+        writeln!(w, "regex = \"1\"")?;
+    }
+    if generation.jsonrpsee {
+        writeln!(
+            w,
+            "jsonrpsee = {{ version = {:?}, features = [\"macros\", \"server\", \"client\"] }}",
+            krate.jsonrpsee_version
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes the `lib.rs` file of the crate, declaring the provided modules.
+pub fn lib_rs(w: &mut dyn io::Write, config: &Config, modules: &[String]) -> io::Result<()> {
+    writeln!(
+        w,
+        "//! This crate was automatically generated by openrpc-gen."
+    )?;
+    if config.generation.no_std {
+        writeln!(w)?;
+        writeln!(w, "#![no_std]")?;
+        writeln!(w)?;
+        writeln!(w, "extern crate alloc;")?;
+    }
+    writeln!(w)?;
+    for module in modules {
+        writeln!(w, "pub mod {module};")?;
+    }
+
+    Ok(())
+}
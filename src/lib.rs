@@ -0,0 +1,1165 @@
+//! A library to generate Rust files from OpenRPC documents.
+//!
+//! Generation happens in three passes:
+//!
+//! 1. [`parse`] converts an OpenRPC document into our own representation of the output file.
+//! 2. [`fix`] applies the fixes requested in the configuration file to that representation.
+//! 3. [`gen`] writes the resulting Rust code.
+//!
+//! The [`Generator`] type runs the whole pipeline at once.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use convert_case::{Case, Casing};
+use serde::Deserialize;
+
+pub mod changes;
+pub mod common;
+pub mod config;
+pub mod deps;
+pub mod diagnostics;
+mod diff;
+mod doc;
+mod external;
+pub mod fix;
+mod fuzz;
+pub mod gen;
+mod glob;
+mod ident;
+mod krate;
+pub mod lint;
+mod merge;
+pub mod parse;
+pub mod verify;
+
+pub use open_rpc;
+
+use self::config::Config;
+use self::diagnostics::{Diagnostic, Severity};
+
+/// An error that might occur while generating a Rust file.
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O error occured while accessing a file.
+    Io {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+    /// The configuration file is not valid.
+    Config {
+        /// The path of the configuration file.
+        path: PathBuf,
+        /// The underlying error.
+        error: toml::de::Error,
+    },
+    /// The OpenRPC document is not valid.
+    Document {
+        /// The path of the document.
+        path: PathBuf,
+        /// The underlying error.
+        error: serde_json::Error,
+    },
+    /// An external reference of the OpenRPC document could not be resolved.
+    Reference {
+        /// The reference, resolved relative to the document holding it.
+        reference: String,
+        /// What went wrong.
+        message: String,
+    },
+    /// The OpenRPC document could not be parsed.
+    Parse(Vec<Diagnostic>),
+    /// Some of the fixes requested in the configuration could not be applied.
+    Fix(Vec<Diagnostic>),
+    /// The configuration requests code that can't be generated for the document, such as
+    /// options that don't work together.
+    Unsupported(Vec<Diagnostic>),
+    /// An error occured while writing the generated code.
+    Gen(io::Error),
+    /// `rustfmt` could not be run on the generated file.
+    Rustfmt(io::Error),
+    /// `cargo` could not be run on the generated code.
+    Cargo(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, error } => write!(f, "`{}`: {}", path.display(), error),
+            Error::Config { path, error } => write!(f, "`{}`: {}", path.display(), error),
+            Error::Document { path, error } => write!(f, "`{}`: {}", path.display(), error),
+            Error::Reference { reference, message } => write!(f, "`{}` {}", reference, message),
+            Error::Parse(errs) | Error::Fix(errs) | Error::Unsupported(errs) => {
+                for (i, err) in errs.iter().enumerate() {
+                    if i != 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", err)?;
+                }
+                Ok(())
+            }
+            Error::Gen(error) => write!(f, "{}", error),
+            Error::Rustfmt(error) => write!(f, "rustfmt: {}", error),
+            Error::Cargo(error) => write!(f, "cargo: {}", error),
+        }
+    }
+}
+
+impl Error {
+    /// Returns the diagnostics describing the error.
+    ///
+    /// Errors that are not about specific items of the document are described by a single
+    /// diagnostic without a path.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            Error::Parse(errs) | Error::Fix(errs) | Error::Unsupported(errs) => errs.clone(),
+            err => vec![Diagnostic::error(err.to_string())],
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { error, .. } => Some(error),
+            Error::Config { error, .. } => Some(error),
+            Error::Document { error, .. } => Some(error),
+            Error::Gen(error) | Error::Rustfmt(error) | Error::Cargo(error) => Some(error),
+            Error::Reference { .. } | Error::Parse(_) | Error::Fix(_) | Error::Unsupported(_) => {
+                None
+            }
+        }
+    }
+}
+
+/// Generates Rust files from OpenRPC documents using a specific configuration.
+///
+/// # Examples
+///
+/// ```no_run
+/// use openrpc_gen::Generator;
+///
+/// let generator = Generator::from_config_file("openrpc-gen.toml")?;
+/// generator.generate_file("api.json", "src/api.rs")?;
+/// # Ok::<(), openrpc_gen::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Generator {
+    config: Config,
+    trace_fixes: bool,
+}
+
+impl Generator {
+    /// Creates a new [`Generator`] using the provided configuration.
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            trace_fixes: false,
+        }
+    }
+
+    /// Creates a new [`Generator`] by loading the configuration file at `path`.
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        config::load(path.as_ref()).map(Self::new)
+    }
+
+    /// Sets whether the changes made by the fixes should be traced.
+    ///
+    /// When enabled, the [`fix::Log`] returned by the generation functions describes every
+    /// change made by the fixes requested in the configuration.
+    pub fn trace_fixes(mut self, yes: bool) -> Self {
+        self.trace_fixes = yes;
+        self
+    }
+
+    /// Returns the configuration used by this generator.
+    #[inline]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Parses and fixes the provided document, returning the representation of the file that
+    /// would be generated from it.
+    ///
+    /// Warnings emitted while fixing the file, and the trace of the fixes when enabled, are
+    /// pushed to `log`.
+    pub fn prepare(&self, document: &Document, log: &mut fix::Log) -> Result<parse::File, Error> {
+        log.warnings.extend(document.warnings.iter().cloned());
+        let mut file = parse::parse(document, &self.config).map_err(Error::Parse)?;
+        fix::fix(&mut file, &self.config, log).map_err(Error::Fix)?;
+        if self.config.strict {
+            let broken = fix::broken_references(&file);
+            if !broken.is_empty() {
+                return Err(Error::Parse(broken));
+            }
+        }
+        let (errs, warnings) = gen::check(&file, &self.config)
+            .into_iter()
+            .partition::<Vec<_>, _>(|d| d.severity == Severity::Error);
+        log.warnings.extend(warnings);
+        if !errs.is_empty() {
+            return Err(Error::Unsupported(errs));
+        }
+        Ok(file)
+    }
+
+    /// Generates Rust code for the provided document, formats it, and writes it to `w`.
+    ///
+    /// On success, the warnings emitted during generation are returned, along with the trace of
+    /// the fixes when enabled.
+    pub fn generate(&self, document: &Document, w: &mut dyn io::Write) -> Result<fix::Log, Error> {
+        let mut log = self.new_log();
+        let code = self.gen_code(document, &mut log)?;
+        let code = self.finish(code, &mut log)?;
+        w.write_all(code.as_bytes()).map_err(Error::Gen)?;
+        Ok(log)
+    }
+
+    /// Generates the syntax tree of the Rust code for the provided document, which can be
+    /// transformed before being written out with `quote::ToTokens`.
+    ///
+    /// The tree is built directly, so it holds neither the header of the generated files nor
+    /// the comments of `debug-path`.
+    pub fn generate_syntax(&self, document: &Document) -> Result<(syn::File, fix::Log), Error> {
+        let mut log = self.new_log();
+        let file = self.prepare(document, &mut log)?;
+        let mut config = self.config.clone();
+        config.debug_path = false;
+        let syntax = gen::gen(&file, &config).map_err(Error::Gen)?;
+        Ok((syntax, log))
+    }
+
+    /// Writes the representation of the file that would be generated from the provided
+    /// document to `w`, as JSON.
+    ///
+    /// This is the state of the file after fixes were applied, which is useful to find the
+    /// paths to use in the configuration. On success, the warnings emitted while fixing the
+    /// file are returned, along with the trace of the fixes when enabled.
+    pub fn generate_ir(
+        &self,
+        document: &Document,
+        w: &mut dyn io::Write,
+    ) -> Result<fix::Log, Error> {
+        let mut log = self.new_log();
+        let file = self.prepare(document, &mut log)?;
+        serde_json::to_writer_pretty(&mut *w, &file).map_err(|error| Error::Gen(error.into()))?;
+        writeln!(w).map_err(Error::Gen)?;
+        Ok(log)
+    }
+
+    /// Writes the intermediate representation of the OpenRPC document at `input` to `output`.
+    ///
+    /// See [`Generator::generate_ir`].
+    pub fn generate_ir_file(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> Result<fix::Log, Error> {
+        let output = output.as_ref();
+
+        let document = self.load(input.as_ref())?;
+        let mut code = Vec::new();
+        let log = self.generate_ir(&document, &mut code)?;
+        std::fs::write(output, code).map_err(|error| Error::Io {
+            path: output.to_path_buf(),
+            error,
+        })?;
+
+        Ok(log)
+    }
+
+    /// Generates the Rust file at `output` from the OpenRPC document at `input`.
+    ///
+    /// The generated file is formatted with `prettyplease`, or with `rustfmt` when requested by
+    /// the configuration. On success, the warnings emitted during generation are returned, along
+    /// with the trace of the fixes when enabled.
+    pub fn generate_file(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> Result<fix::Log, Error> {
+        let output = output.as_ref();
+
+        let (code, log) = self.render(input.as_ref())?;
+        std::fs::write(output, code).map_err(|error| Error::Io {
+            path: output.to_path_buf(),
+            error,
+        })?;
+
+        Ok(log)
+    }
+
+    /// Checks whether the Rust file at `output` is up to date with the OpenRPC document at
+    /// `input`, without writing anything.
+    ///
+    /// A missing `output` file is considered empty.
+    pub fn check_file(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> Result<Check, Error> {
+        let output = output.as_ref();
+
+        let (code, log) = self.render(input.as_ref())?;
+        let existing = read_existing(output)?;
+
+        let name = output.display().to_string();
+        let diff = diff::unified(&existing, &code, &name, &format!("{name} (generated)"));
+        Ok(Check { log, diff })
+    }
+
+    /// Generates a single Rust file at `output` from several OpenRPC documents, merged into one.
+    ///
+    /// The methods and the components of the documents are unioned. Components defined
+    /// differently by several documents are renamed after the file they come from, which is
+    /// reported as a warning in the returned log.
+    pub fn generate_merged_file(
+        &self,
+        inputs: &[PathBuf],
+        output: impl AsRef<Path>,
+    ) -> Result<fix::Log, Error> {
+        let output = output.as_ref();
+        let (code, log) = self.render_merged(inputs)?;
+        std::fs::write(output, code).map_err(|error| Error::Io {
+            path: output.to_path_buf(),
+            error,
+        })?;
+        Ok(log)
+    }
+
+    /// Checks whether the Rust file at `output` is up to date with the OpenRPC documents at
+    /// `inputs`, merged as by [`Generator::generate_merged_file`].
+    pub fn check_merged_file(
+        &self,
+        inputs: &[PathBuf],
+        output: impl AsRef<Path>,
+    ) -> Result<Check, Error> {
+        let output = output.as_ref();
+        let (code, log) = self.render_merged(inputs)?;
+        let existing = read_existing(output)?;
+        let name = output.display().to_string();
+        let diff = diff::unified(&existing, &code, &name, &format!("{name} (generated)"));
+        Ok(Check { log, diff })
+    }
+
+    /// Generates a single Rust file at `output` from several versions of an OpenRPC document.
+    ///
+    /// Each document is generated to its own module, named after the major and minor
+    /// components of its `info.version`, such as `v0_7` for version `0.7.1`.
+    pub fn generate_versioned_file(
+        &self,
+        inputs: &[PathBuf],
+        output: impl AsRef<Path>,
+    ) -> Result<fix::Log, Error> {
+        let output = output.as_ref();
+        let (code, log) = self.render_versioned(inputs)?;
+        std::fs::write(output, code).map_err(|error| Error::Io {
+            path: output.to_path_buf(),
+            error,
+        })?;
+        Ok(log)
+    }
+
+    /// Checks whether the Rust file at `output` is up to date with the OpenRPC documents at
+    /// `inputs`, generated as by [`Generator::generate_versioned_file`].
+    pub fn check_versioned_file(
+        &self,
+        inputs: &[PathBuf],
+        output: impl AsRef<Path>,
+    ) -> Result<Check, Error> {
+        let output = output.as_ref();
+        let (code, log) = self.render_versioned(inputs)?;
+        let existing = read_existing(output)?;
+        let name = output.display().to_string();
+        let diff = diff::unified(&existing, &code, &name, &format!("{name} (generated)"));
+        Ok(Check { log, diff })
+    }
+
+    /// Generates the Rust files of several OpenRPC documents at once, moving the types they
+    /// share into the file at `common`.
+    ///
+    /// Each `(input, output)` pair of `documents` is generated as by
+    /// [`Generator::generate_file`], except that the shared types are re-exported from the module
+    /// named by the `common-module` option instead of being defined again. See
+    /// [`common::extract`] for which types are shared.
+    pub fn generate_files_with_common(
+        &self,
+        documents: &[(PathBuf, PathBuf)],
+        common: impl AsRef<Path>,
+    ) -> Result<fix::Log, Error> {
+        let (outputs, log) = self.render_with_common(documents, common.as_ref())?;
+        write_outputs(outputs)?;
+        Ok(log)
+    }
+
+    /// Checks whether the files generated by [`Generator::generate_files_with_common`] are up
+    /// to date, without writing anything.
+    ///
+    /// The differences of all the files are concatenated into a single diff.
+    pub fn check_files_with_common(
+        &self,
+        documents: &[(PathBuf, PathBuf)],
+        common: impl AsRef<Path>,
+    ) -> Result<Check, Error> {
+        let (outputs, log) = self.render_with_common(documents, common.as_ref())?;
+        check_outputs(&outputs, log)
+    }
+
+    /// Generates a complete Cargo crate in `dir` from the OpenRPC documents at `inputs`.
+    ///
+    /// Each document is generated to a module named after its file, declared by the `lib.rs`
+    /// of the crate. When `common` is set, the types shared between the documents are moved
+    /// into a `common` module, as by [`Generator::generate_files_with_common`]. The
+    /// `Cargo.toml` file of the crate is described by the `[crate]` section of the
+    /// configuration.
+    pub fn generate_crate(
+        &self,
+        inputs: &[PathBuf],
+        dir: impl AsRef<Path>,
+        common: bool,
+    ) -> Result<fix::Log, Error> {
+        let (outputs, log) = self.render_crate(inputs, dir.as_ref(), common)?;
+        write_outputs(outputs)?;
+        Ok(log)
+    }
+
+    /// Checks whether the crate generated by [`Generator::generate_crate`] is up to date,
+    /// without writing anything.
+    pub fn check_crate(
+        &self,
+        inputs: &[PathBuf],
+        dir: impl AsRef<Path>,
+        common: bool,
+    ) -> Result<Check, Error> {
+        let (outputs, log) = self.render_crate(inputs, dir.as_ref(), common)?;
+        check_outputs(&outputs, log)
+    }
+
+    /// Generates a cargo-fuzz crate in `dir`, with one target deserializing each struct and
+    /// enum generated from the OpenRPC documents at `inputs`.
+    ///
+    /// `in_crate` tells whether the documents are generated with [`Generator::generate_crate`],
+    /// which changes the default module of the types. The crate is described by the `[fuzz]`
+    /// section of the configuration.
+    pub fn generate_fuzz_crate(
+        &self,
+        inputs: &[PathBuf],
+        dir: impl AsRef<Path>,
+        in_crate: bool,
+    ) -> Result<fix::Log, Error> {
+        let (outputs, log) = self.render_fuzz_crate(inputs, dir.as_ref(), in_crate)?;
+        write_outputs(outputs)?;
+        Ok(log)
+    }
+
+    /// Checks whether the crate generated by [`Generator::generate_fuzz_crate`] is up to date,
+    /// without writing anything.
+    pub fn check_fuzz_crate(
+        &self,
+        inputs: &[PathBuf],
+        dir: impl AsRef<Path>,
+        in_crate: bool,
+    ) -> Result<Check, Error> {
+        let (outputs, log) = self.render_fuzz_crate(inputs, dir.as_ref(), in_crate)?;
+        check_outputs(&outputs, log)
+    }
+
+    /// Creates the log passed to the fixes.
+    fn new_log(&self) -> fix::Log {
+        fix::Log {
+            tracing: self.trace_fixes,
+            ..Default::default()
+        }
+    }
+
+    /// Checks the OpenRPC document at `input` for likely mistakes, as done by [`lint::lint`],
+    /// then for the errors and warnings of the generator.
+    ///
+    /// The document is only handed to the generator once it has no errors of its own, so that
+    /// they are not reported twice.
+    pub fn lint_file(&self, input: impl AsRef<Path>) -> Result<Vec<Diagnostic>, Error> {
+        let document = self.load(input.as_ref())?;
+        let mut diagnostics = lint::lint(&document);
+        if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+            return Ok(diagnostics);
+        }
+        let mut log = self.new_log();
+        match self.prepare(&document, &mut log) {
+            Ok(_) => diagnostics.append(&mut log.warnings),
+            Err(err) => diagnostics.extend(err.diagnostics()),
+        }
+        Ok(diagnostics)
+    }
+
+    /// Loads the OpenRPC document at `input`, resolving its external references as configured.
+    fn load(&self, input: &Path) -> Result<Document, Error> {
+        load_document_with(input, &self.config.refs)
+    }
+
+    /// Generates and formats the Rust code for the OpenRPC document at `input`.
+    fn render(&self, input: &Path) -> Result<(String, fix::Log), Error> {
+        let document = self.load(input)?;
+        let mut log = self.new_log();
+        let code = self.gen_code(&document, &mut log)?;
+        Ok((self.finish(code, &mut log)?, log))
+    }
+
+    /// Generates the syntax tree of the Rust code for the provided document, before it is
+    /// formatted.
+    fn gen_code(&self, document: &Document, log: &mut fix::Log) -> Result<syn::File, Error> {
+        let file = self.prepare(document, log)?;
+        gen::gen(&file, &self.config).map_err(Error::Gen)
+    }
+
+    /// Generates the files of the crate described by [`Generator::generate_crate`], along with
+    /// the paths they should be written to.
+    fn render_crate(
+        &self,
+        inputs: &[PathBuf],
+        dir: &Path,
+        common: bool,
+    ) -> Result<(Vec<(PathBuf, String)>, fix::Log), Error> {
+        let src = dir.join("src");
+        let mut modules = Vec::new();
+        if common {
+            modules.push(String::from("common"));
+        }
+        let mut documents = Vec::new();
+        for input in inputs {
+            let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+            let module = stem.to_case(Case::Snake);
+            if modules.contains(&module) {
+                return Err(Error::Gen(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("several documents would be generated to the `{module}` module"),
+                )));
+            }
+            documents.push((input.clone(), src.join(format!("{module}.rs"))));
+            modules.push(module);
+        }
+
+        let (mut outputs, log) = if common {
+            self.render_with_common(&documents, &src.join("common.rs"))?
+        } else {
+            let mut log = self.new_log();
+            let mut outputs = Vec::new();
+            for (input, output) in &documents {
+                let (code, file_log) = self.render(input)?;
+                log.warnings.extend(file_log.warnings);
+                log.trace.extend(file_log.trace);
+                outputs.push((output.clone(), code));
+            }
+            (outputs, log)
+        };
+
+        let name = match &self.config.krate.name {
+            Some(name) => name.clone(),
+            None => std::path::absolute(dir)
+                .ok()
+                .and_then(|dir| Some(dir.file_name()?.to_string_lossy().to_case(Case::Kebab)))
+                .unwrap_or_else(|| String::from("api")),
+        };
+        let mut manifest = Vec::new();
+        krate::manifest(&mut manifest, &self.config, &name).map_err(Error::Gen)?;
+        let mut lib = Vec::new();
+        krate::lib_rs(&mut lib, &self.config, &modules).map_err(Error::Gen)?;
+        outputs.push((
+            dir.join("Cargo.toml"),
+            String::from_utf8_lossy(&manifest).into(),
+        ));
+        outputs.push((src.join("lib.rs"), String::from_utf8_lossy(&lib).into()));
+
+        Ok((outputs, log))
+    }
+
+    /// Generates the files of the crate described by [`Generator::generate_fuzz_crate`], along
+    /// with the paths they should be written to.
+    fn render_fuzz_crate(
+        &self,
+        inputs: &[PathBuf],
+        dir: &Path,
+        in_crate: bool,
+    ) -> Result<(Vec<(PathBuf, String)>, fix::Log), Error> {
+        let fuzz = &self.config.fuzz;
+        let name = match &self.config.krate.name {
+            Some(name) => name.clone(),
+            None => {
+                // The fuzz crate may not exist yet, so `..` can't be resolved by the system.
+                let mut path = dir.to_path_buf();
+                for component in Path::new(&fuzz.crate_path).components() {
+                    match component {
+                        std::path::Component::ParentDir if path.pop() => (),
+                        component => path.push(component),
+                    }
+                }
+                package_name(&path.join("Cargo.toml"))?
+            }
+        };
+        let module = match &fuzz.module {
+            Some(module) => module.as_str(),
+            None if in_crate => "{}",
+            None => "",
+        };
+
+        let mut log = self.new_log();
+        let mut outputs = Vec::new();
+        let mut targets = Vec::new();
+        for input in inputs {
+            let document = self.load(input)?;
+            let file = self.prepare(&document, &mut log)?;
+            let generics = gen::type_generics(&file, &self.config);
+
+            let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+            let stem = stem.to_case(Case::Snake);
+            let mut prefix = name.replace('-', "_");
+            let module = module.replace("{}", &stem);
+            if !module.is_empty() {
+                prefix = format!("{prefix}::{module}");
+            }
+
+            for ty in file.types.values() {
+                let generated = matches!(
+                    ty.kind,
+                    parse::TypeKind::Struct(_) | parse::TypeKind::Enum(_)
+                );
+                if !generated || generics.contains_key(&ty.name) {
+                    continue;
+                }
+                let mut target = ty.name.to_case(Case::Snake);
+                if inputs.len() > 1 {
+                    target = format!("{stem}_{target}");
+                }
+                let mut code = Vec::new();
+                fuzz::target(&mut code, &format!("{prefix}::{}", ty.name)).map_err(Error::Gen)?;
+                outputs.push((
+                    dir.join("fuzz_targets").join(format!("{target}.rs")),
+                    String::from_utf8_lossy(&code).into(),
+                ));
+                targets.push(target);
+            }
+        }
+
+        let mut manifest = Vec::new();
+        fuzz::manifest(&mut manifest, &name, &fuzz.crate_path, &targets).map_err(Error::Gen)?;
+        outputs.push((
+            dir.join("Cargo.toml"),
+            String::from_utf8_lossy(&manifest).into(),
+        ));
+
+        Ok((outputs, log))
+    }
+
+    /// Generates the Rust code for the merge of the OpenRPC documents at `inputs`.
+    fn render_merged(&self, inputs: &[PathBuf]) -> Result<(String, fix::Log), Error> {
+        let mut documents = Vec::new();
+        for input in inputs {
+            documents.push(self.load(input)?);
+        }
+        let labels: Vec<_> = inputs
+            .iter()
+            .map(|input| input.file_stem().unwrap_or_default().to_string_lossy())
+            .collect();
+        let labeled: Vec<_> = labels.iter().map(|l| &**l).zip(&documents).collect();
+        let (document, warnings) = merge::merge(&labeled).map_err(|error| Error::Document {
+            path: inputs.last().cloned().unwrap_or_default(),
+            error,
+        })?;
+
+        let mut log = self.new_log();
+        log.warnings = warnings;
+        let file = self.prepare(&document, &mut log)?;
+        let code = gen::gen(&file, &self.config).map_err(Error::Gen)?;
+        Ok((self.finish(code, &mut log)?, log))
+    }
+
+    /// Generates the Rust code for the OpenRPC documents at `inputs`, one module per version.
+    fn render_versioned(&self, inputs: &[PathBuf]) -> Result<(String, fix::Log), Error> {
+        let mut log = self.new_log();
+        let mut files = Vec::new();
+        let mut modules: Vec<String> = Vec::new();
+        for input in inputs {
+            let document = self.load(input)?;
+            let file = self.prepare(&document, &mut log)?;
+            let module = version_module(&file.info.version);
+            if modules.contains(&module) {
+                return Err(Error::Gen(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "several documents would be generated to the `{module}` module (`{}` has version `{}`)",
+                        input.display(),
+                        file.info.version,
+                    ),
+                )));
+            }
+            modules.push(module);
+            files.push(file);
+        }
+
+        let versions: Vec<_> = modules.iter().map(String::as_str).zip(&files).collect();
+        let code = gen::gen_modules(&versions, &self.config).map_err(Error::Gen)?;
+        Ok((self.finish(code, &mut log)?, log))
+    }
+
+    /// Generates the Rust code of the provided documents and of the file holding the types
+    /// they share, along with the paths it should be written to.
+    fn render_with_common(
+        &self,
+        documents: &[(PathBuf, PathBuf)],
+        common: &Path,
+    ) -> Result<(Vec<(PathBuf, String)>, fix::Log), Error> {
+        let mut log = self.new_log();
+        let mut files = Vec::new();
+        for (input, _) in documents {
+            let document = self.load(input)?;
+            files.push(self.prepare(&document, &mut log)?);
+        }
+        let shared = common::extract(&mut files, &self.config);
+
+        // The shared types are external to the other files, whose analyses must know which
+        // traits they implement.
+        let mut config = self.config.clone();
+        let shared_deps = deps::TypeDeps::new(&shared, &self.config);
+        config.deps.default_types.extend(
+            shared_deps
+                .default_types(&self.config.deps.default_types)
+                .into_iter()
+                .map(str::to_owned),
+        );
+        config.deps.eq_types.extend(
+            shared_deps
+                .eq_types(&self.config.deps.eq_types)
+                .into_iter()
+                .map(str::to_owned),
+        );
+
+        // The common file only holds types, and doesn't come from a single document.
+        let mut common_config = config.clone();
+        let generation = &mut common_config.generation;
+        generation.client_trait = false;
+        generation.server_trait = false;
+        generation.jsonrpsee = false;
+        generation.envelopes = false;
+        generation.batch = false;
+        generation.method_markers = false;
+        generation.error_enums = config::ErrorEnums::None;
+        generation.spec_info = false;
+        generation.expected_spec_version = None;
+        generation.mocks = false;
+        generation.fixtures = None;
+        let code = gen::gen(&shared, &common_config).map_err(Error::Gen)?;
+        let mut outputs = vec![(common.to_path_buf(), self.finish(code, &mut log)?)];
+
+        for (file, (_, output)) in files.iter().zip(documents) {
+            let imports = common::imports(file, &shared);
+            let code = gen::gen_with_shared(file, &config, &imports).map_err(Error::Gen)?;
+            outputs.push((output.clone(), self.finish(code, &mut log)?));
+        }
+
+        Ok((outputs, log))
+    }
+
+    /// Formats the generated code, with `rustfmt` when requested by the configuration and with
+    /// `prettyplease` otherwise.
+    ///
+    /// When `rustfmt` is not installed, the `prettyplease` output is kept and a warning is added
+    /// to `log`.
+    fn finish(&self, syntax: syn::File, log: &mut fix::Log) -> Result<String, Error> {
+        let code = gen::render(&syntax);
+        if self.config.run_rustfmt {
+            match run_rustfmt(code.as_bytes()) {
+                Ok(formatted) => {
+                    return String::from_utf8(formatted).map_err(|error| {
+                        Error::Gen(io::Error::new(io::ErrorKind::InvalidData, error))
+                    })
+                }
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                    let warning = Diagnostic::warning(
+                        "`rustfmt` is not installed, the generated code is formatted with \
+                        `prettyplease` instead",
+                    );
+                    if !log.warnings.contains(&warning) {
+                        log.warnings.push(warning);
+                    }
+                }
+                Err(error) => return Err(Error::Rustfmt(error)),
+            }
+        }
+        Ok(code)
+    }
+}
+
+/// The outcome of [`Generator::check_file`].
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// The warnings emitted during generation, and the trace of the fixes when enabled.
+    pub log: fix::Log,
+    /// A unified diff from the existing file to the generated code, or `None` if the file is
+    /// up to date.
+    pub diff: Option<String>,
+}
+
+/// An OpenRPC document.
+///
+/// Some of the JSON Schema keywords used by OpenRPC documents are not modeled by
+/// [`open_rpc`], so the raw JSON of the document is kept along with its typed representation.
+#[derive(Debug, Clone)]
+pub struct Document {
+    /// The typed representation of the document.
+    pub spec: open_rpc::OpenRpc,
+    /// The raw JSON of the document.
+    pub raw: serde_json::Value,
+    /// The text the document was read from, used to report where errors are found.
+    pub source: Option<Source>,
+    /// The warnings emitted while loading the document, such as the use of the cached copy of
+    /// a reference that failed to download.
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// The text an OpenRPC document was read from.
+#[derive(Debug, Clone)]
+pub struct Source {
+    /// The path or URL of the document.
+    pub name: String,
+    /// The contents of the document.
+    pub text: String,
+}
+
+impl Document {
+    /// Creates a new [`Document`] from its raw JSON.
+    pub fn from_json(mut raw: serde_json::Value) -> Result<Self, serde_json::Error> {
+        normalize_schemas(&mut raw);
+        let spec = open_rpc::OpenRpc::deserialize(&for_open_rpc(&raw))?;
+        Ok(Self {
+            spec,
+            raw,
+            source: None,
+            warnings: Vec::new(),
+        })
+    }
+}
+
+impl From<open_rpc::OpenRpc> for Document {
+    /// Creates a new [`Document`] from its typed representation.
+    ///
+    /// The keywords that are not modeled by [`open_rpc`] are lost in the process.
+    fn from(spec: open_rpc::OpenRpc) -> Self {
+        let raw = serde_json::to_value(&spec).unwrap_or_default();
+        Self {
+            spec,
+            raw,
+            source: None,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// Returns a copy of `raw` that [`open_rpc`] can deserialize.
+///
+/// [`open_rpc`] can't deserialize the `value` of example objects, so examples are only read
+/// from the raw JSON. It also rejects the string formats it does not know, which are removed so
+/// that the strings are generated as `String`, and the schemas accepting any value, which are
+/// replaced as described in [`replace_any_schemas`].
+fn for_open_rpc(raw: &serde_json::Value) -> serde_json::Value {
+    let mut raw = raw.clone();
+    strip_unknown_formats(&mut raw);
+    replace_any_schemas(&mut raw);
+    if let Some(methods) = raw.get_mut("methods").and_then(|m| m.as_array_mut()) {
+        for method in methods.iter_mut().filter_map(|m| m.as_object_mut()) {
+            method.remove("examples");
+        }
+    }
+    if let Some(components) = raw.get_mut("components").and_then(|c| c.as_object_mut()) {
+        components.remove("examples");
+        components.remove("examplePairingObjects");
+    }
+    raw
+}
+
+/// Returns a copy of the schema `raw` that [`open_rpc`] can deserialize, as done for whole
+/// documents by [`for_open_rpc`].
+pub(crate) fn schema_for_open_rpc(raw: &serde_json::Value) -> serde_json::Value {
+    let mut schema = raw.clone();
+    strip_unknown_formats(&mut schema);
+    replace_any_schema(&mut schema);
+    replace_any_schemas(&mut schema);
+    schema
+}
+
+/// The keywords that describe a schema without constraining its values.
+const ANNOTATIONS: &[&str] = &["title", "description", "default", "examples", "deprecated"];
+
+/// Returns whether the schema `raw` accepts any value, such as `{}` or `true`.
+///
+/// Schemas made only of annotations, comments and `x-` extensions accept any value too.
+pub(crate) fn is_any_schema(raw: &serde_json::Value) -> bool {
+    match raw {
+        serde_json::Value::Bool(accepted) => *accepted,
+        serde_json::Value::Object(map) => map.keys().all(|key| {
+            ANNOTATIONS.contains(&key.as_str()) || key == "$comment" || key.starts_with("x-")
+        }),
+        _ => false,
+    }
+}
+
+/// Replaces the schema `value` with an empty `anyOf` if it accepts any value, which
+/// [`open_rpc`] can't deserialize.
+///
+/// The raw JSON keeps the original schema, which is recognized with [`is_any_schema`].
+fn replace_any_schema(value: &mut serde_json::Value) {
+    if !is_any_schema(value) {
+        return;
+    }
+    if !value.is_object() {
+        *value = serde_json::Value::Object(Default::default());
+    }
+    if let Some(map) = value.as_object_mut() {
+        map.insert("anyOf".into(), serde_json::Value::Array(Vec::new()));
+    }
+}
+
+/// Applies [`replace_any_schema`] to the schemas found in `value`.
+pub(crate) fn replace_any_schemas(value: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                match (key.as_str(), &mut *child) {
+                    ("schema" | "items" | "additionalProperties", child) => {
+                        replace_any_schema(child)
+                    }
+                    ("properties" | "schemas", Value::Object(schemas)) => {
+                        schemas.values_mut().for_each(replace_any_schema)
+                    }
+                    ("allOf" | "anyOf" | "oneOf" | "prefixItems", Value::Array(schemas)) => {
+                        schemas.iter_mut().for_each(replace_any_schema)
+                    }
+                    _ => (),
+                }
+                replace_any_schemas(child);
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(replace_any_schemas),
+        _ => (),
+    }
+}
+
+/// Removes the string formats that [`open_rpc`] does not know from the schemas of `value`.
+pub(crate) fn strip_unknown_formats(value: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => {
+            let unknown = map.get("format").is_some_and(|format| {
+                open_rpc::StringFormat::deserialize(format).is_err()
+                    && map.get("type").and_then(Value::as_str) == Some("string")
+            });
+            if unknown {
+                map.remove("format");
+            }
+            map.values_mut().for_each(strip_unknown_formats);
+        }
+        Value::Array(values) => values.iter_mut().for_each(strip_unknown_formats),
+        _ => (),
+    }
+}
+
+/// Fills in the keywords that [`open_rpc`] requires but that JSON Schema allows to omit.
+///
+/// - Object schemas without `properties`, common for maps described by `additionalProperties`,
+///   are given an empty `properties` keyword.
+/// - Schemas made of a single `const` are given the `type` of their value.
+/// - Schemas with several types, such as `"type": ["string", "null"]`, are turned into a
+///   `oneOf` with one schema per type.
+/// - Positional `items`, from drafts older than 2020-12, are renamed to `prefixItems`, and
+///   `additionalItems` to `items`. `items: false` is dropped from tuples, as they are closed.
+fn normalize_schemas(value: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(types)) = map.get("type") {
+                let types = types.clone();
+                if let [ty] = types.as_slice() {
+                    map.insert("type".into(), ty.clone());
+                } else if types.iter().all(Value::is_string) {
+                    // The annotations stay on the schema, rather than being copied to each type.
+                    let mut schema = std::mem::take(map);
+                    for keyword in ANNOTATIONS {
+                        if let Some(annotation) = schema.remove(*keyword) {
+                            map.insert((*keyword).into(), annotation);
+                        }
+                    }
+                    let branches = types
+                        .into_iter()
+                        .map(|ty| {
+                            let mut branch = match ty.as_str() {
+                                Some("null") => serde_json::Map::new(),
+                                _ => schema.clone(),
+                            };
+                            branch.insert("type".into(), ty);
+                            Value::Object(branch)
+                        })
+                        .collect();
+                    map.insert("oneOf".into(), Value::Array(branches));
+                }
+            }
+            if map.get("items").is_some_and(Value::is_array) {
+                if let Some(items) = map.remove("items") {
+                    map.insert("prefixItems".into(), items);
+                }
+                if let Some(additional) = map.remove("additionalItems") {
+                    map.insert("items".into(), additional);
+                }
+            }
+            if map.contains_key("prefixItems") && map.get("items") == Some(&Value::Bool(false)) {
+                map.remove("items");
+            }
+            if !map.contains_key("type") {
+                let ty = match map.get("const") {
+                    Some(Value::String(_)) => Some("string"),
+                    Some(Value::Bool(_)) => Some("boolean"),
+                    Some(Value::Number(n)) if n.is_f64() => Some("number"),
+                    Some(Value::Number(_)) => Some("integer"),
+                    Some(Value::Null) => Some("null"),
+                    _ => None,
+                };
+                if let Some(ty) = ty {
+                    map.insert("type".into(), ty.into());
+                }
+            }
+            if map.get("type").and_then(|ty| ty.as_str()) == Some("object") {
+                map.entry("properties")
+                    .or_insert_with(|| Value::Object(Default::default()));
+            }
+            map.values_mut().for_each(normalize_schemas);
+        }
+        Value::Array(values) => values.iter_mut().for_each(normalize_schemas),
+        _ => (),
+    }
+}
+
+/// Loads the OpenRPC document at the provided path.
+///
+/// The path may also be an HTTP(S) URL, in which case the document is downloaded. References
+/// to other local files, and to the URLs next to a downloaded document, are resolved, but other
+/// URLs are rejected. Use [`load_document_with`] to allow them.
+pub fn load_document(path: impl AsRef<Path>) -> Result<Document, Error> {
+    load_document_with(path, &config::Refs::default())
+}
+
+/// Loads the OpenRPC document at the provided path, resolving its external references as
+/// configured by `refs`.
+pub fn load_document_with(path: impl AsRef<Path>, refs: &config::Refs) -> Result<Document, Error> {
+    let path = path.as_ref();
+    let (raw, text, warnings) = external::load(path, refs)?;
+    let mut document = Document::from_json(raw).map_err(|error| Error::Document {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    document.source = Some(Source {
+        name: path.display().to_string(),
+        text,
+    });
+    document.warnings = warnings;
+    Ok(document)
+}
+
+/// Returns the name of the module holding the code generated for version `version` of a
+/// document, made of its major and minor components.
+fn version_module(version: &str) -> String {
+    let version = version.trim_start_matches(['v', 'V']);
+    let mut components = version.split(['.', '-', '+']).take(2);
+    let mut module = String::from("v");
+    module.push_str(components.next().unwrap_or_default());
+    for component in components {
+        module.push('_');
+        module.push_str(component);
+    }
+    module
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Reads the name of the package described by the `Cargo.toml` file at `path`.
+fn package_name(path: &Path) -> Result<String, Error> {
+    let manifest = std::fs::read_to_string(path).map_err(|error| Error::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    let name = toml::from_str::<toml::Table>(&manifest)
+        .ok()
+        .and_then(|manifest| Some(manifest.get("package")?.get("name")?.as_str()?.to_owned()));
+    name.ok_or_else(|| {
+        Error::Gen(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("`{}` doesn't name a package", path.display()),
+        ))
+    })
+}
+
+/// Writes the provided files, creating their parent directories when needed.
+fn write_outputs(outputs: Vec<(PathBuf, String)>) -> Result<(), Error> {
+    for (output, code) in outputs {
+        let result = match output.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => std::fs::create_dir_all(parent),
+            _ => Ok(()),
+        };
+        result
+            .and_then(|()| std::fs::write(&output, code))
+            .map_err(|error| Error::Io {
+                path: output,
+                error,
+            })?;
+    }
+    Ok(())
+}
+
+/// Compares the provided files to the existing ones, concatenating their differences into a
+/// single diff.
+fn check_outputs(outputs: &[(PathBuf, String)], log: fix::Log) -> Result<Check, Error> {
+    let mut diff: Option<String> = None;
+    for (output, code) in outputs {
+        let existing = read_existing(output)?;
+        let name = output.display().to_string();
+        let generated = format!("{name} (generated)");
+        if let Some(file_diff) = diff::unified(&existing, code, &name, &generated) {
+            diff.get_or_insert_with(String::new).push_str(&file_diff);
+        }
+    }
+    Ok(Check { log, diff })
+}
+
+/// Reads the file at `path`, considering a missing file empty.
+fn read_existing(path: &Path) -> Result<String, Error> {
+    match std::fs::read_to_string(path) {
+        Ok(existing) => Ok(existing),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(error) => Err(Error::Io {
+            path: path.to_path_buf(),
+            error,
+        }),
+    }
+}
+
+/// Runs `rustfmt` on the provided code, returning the formatted code.
+fn run_rustfmt(code: &[u8]) -> io::Result<Vec<u8>> {
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    // rustfmt reads its whole input before writing anything, so this can't deadlock.
+    child.stdin.take().unwrap().write_all(code)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("rustfmt failed"));
+    }
+    Ok(output.stdout)
+}
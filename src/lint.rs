@@ -0,0 +1,179 @@
+//! Checks of OpenRPC documents for likely mistakes, which don't prevent them from being
+//! generated.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use crate::diagnostics::Diagnostic;
+use crate::ident;
+use crate::Document;
+
+/// Checks the provided document for likely mistakes.
+///
+/// The following are reported:
+///
+/// - references to items that don't exist, as errors;
+/// - methods defined several times, as errors;
+/// - schemas or methods whose names are the same once converted to PascalCase, as errors;
+/// - schemas that are not used by any method, as warnings;
+/// - methods without a result or a description, as warnings.
+pub fn lint(document: &Document) -> Vec<Diagnostic> {
+    let raw = &document.raw;
+    let mut diagnostics = Vec::new();
+
+    let components = raw["components"].as_object().into_iter().flatten();
+    let targets: Vec<String> = components
+        .flat_map(|(kind, items)| {
+            let names = items.as_object().into_iter().flatten();
+            names.map(move |(name, _)| format!("#/components/{kind}/{name}"))
+        })
+        .collect();
+    let mut refs = Vec::new();
+    collect_refs(raw, &mut String::from("#"), &mut refs);
+    for (path, reference) in &refs {
+        let target = reference
+            .strip_prefix('#')
+            .map(|pointer| raw.pointer(pointer));
+        if target == Some(None) {
+            diagnostics.push(
+                Diagnostic::error(format!("reference `{reference}` points to nothing"))
+                    .at(path)
+                    .did_you_mean(reference, targets.iter().map(String::as_str)),
+            );
+        }
+    }
+
+    let methods: Vec<&Value> = raw["methods"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|method| resolve(raw, method))
+        .collect();
+    let mut names = BTreeSet::new();
+    for (index, method) in methods.iter().enumerate() {
+        let Some(name) = method["name"].as_str() else {
+            continue;
+        };
+        if !names.insert(name) {
+            // The name would point to the first definition.
+            diagnostics.push(
+                Diagnostic::error(format!("method `{name}` is defined several times"))
+                    .at(format!("#/methods/{index}")),
+            );
+            continue;
+        }
+        let path = format!("#/methods/{name}");
+        if method.get("result").is_none() {
+            diagnostics.push(
+                Diagnostic::warning("method has no result, so it is generated as a notification")
+                    .at(&path),
+            );
+        }
+        if method.get("description").is_none() && method.get("summary").is_none() {
+            diagnostics.push(
+                Diagnostic::warning("method has neither a description nor a summary").at(&path),
+            );
+        }
+    }
+
+    let method_names = names
+        .iter()
+        .map(|name| (format!("#/methods/{name}"), *name));
+    collisions(method_names, &mut diagnostics);
+    let schemas = raw["components"]["schemas"].as_object();
+    let schema_names = schemas
+        .into_iter()
+        .flatten()
+        .map(|(name, _)| (format!("#/components/schemas/{name}"), name.as_str()));
+    collisions(schema_names, &mut diagnostics);
+
+    let used = used_items(raw, &methods);
+    for (name, _) in schemas.into_iter().flatten() {
+        let path = format!("#/components/schemas/{name}");
+        if !used.contains(&path) {
+            diagnostics.push(Diagnostic::warning("schema is not used by any method").at(path));
+        }
+    }
+
+    crate::parse::locate_diagnostics(document, &mut diagnostics);
+    diagnostics
+}
+
+/// Reports the items of `names`, given as `(path, name)` pairs, whose names are the same once
+/// converted to PascalCase.
+fn collisions<'a>(
+    names: impl Iterator<Item = (String, &'a str)>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut seen = BTreeMap::new();
+    for (path, name) in names {
+        let converted = ident::pascal(name, &[]);
+        match seen.get(&converted) {
+            Some(other) => diagnostics.push(
+                Diagnostic::error(format!(
+                    "`{name}` is generated as `{converted}`, like `{other}`"
+                ))
+                .at(path),
+            ),
+            None => {
+                seen.insert(converted, path);
+            }
+        }
+    }
+}
+
+/// Returns the paths of the items referenced, directly or not, by the provided methods.
+fn used_items(raw: &Value, methods: &[&Value]) -> BTreeSet<String> {
+    let mut used = BTreeSet::new();
+    let mut to_visit: Vec<&Value> = methods.to_vec();
+    while let Some(value) = to_visit.pop() {
+        let mut refs = Vec::new();
+        collect_refs(value, &mut String::from("#"), &mut refs);
+        for (_, reference) in refs {
+            let Some(target) = reference.strip_prefix('#').and_then(|p| raw.pointer(p)) else {
+                continue;
+            };
+            if used.insert(reference) {
+                to_visit.push(target);
+            }
+        }
+    }
+    used
+}
+
+/// Returns the item referenced by `value` if it is an internal reference, or `value` itself.
+fn resolve<'a>(raw: &'a Value, value: &'a Value) -> &'a Value {
+    value["$ref"]
+        .as_str()
+        .and_then(|reference| raw.pointer(reference.strip_prefix('#')?))
+        .unwrap_or(value)
+}
+
+/// Collects the `$ref`s found in `value`, along with the JSON pointer of the object holding
+/// them, `path` being the pointer of `value`.
+fn collect_refs(value: &Value, path: &mut String, refs: &mut Vec<(String, String)>) {
+    let len = path.len();
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get("$ref") {
+                refs.push((path.clone(), reference.clone()));
+            }
+            for (key, child) in map {
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                collect_refs(child, path, refs);
+                path.truncate(len);
+            }
+        }
+        Value::Array(values) => {
+            for (i, child) in values.iter().enumerate() {
+                path.push('/');
+                path.push_str(&i.to_string());
+                collect_refs(child, path, refs);
+                path.truncate(len);
+            }
+        }
+        _ => (),
+    }
+}
@@ -1,103 +1,430 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::io::Write;
-use std::path::Path;
+use std::path::PathBuf;
 use std::process::ExitCode;
 
+use openrpc_gen::changes::Changes;
+use openrpc_gen::diagnostics::{Diagnostic, Severity};
+use openrpc_gen::{verify, Error, Generator};
+
 mod command_line;
-mod config;
-mod fix;
-mod gen;
-mod parse;
+mod watch;
+
+use self::command_line::{Command, Emit, LintArgs, MessageFormat, Reporting};
 
 fn main() -> ExitCode {
-    let cmd = command_line::from_env();
-    let config = match config::load(&cmd.config) {
-        Ok(config) => config,
-        Err(err) => {
-            let _ = print_error(format_args!("`{}`: {}", cmd.config.display(), err));
-            return ExitCode::FAILURE;
-        }
+    let cli = command_line::from_env();
+    let cmd = match (cli.command, cli.generate) {
+        (Some(Command::Lint(args)), _) => return run_lint(&args),
+        (Some(Command::Watch(cmd)), _) => watch::watch(&cmd, || {
+            run_and_summarize(&cmd);
+        }),
+        (None, Some(cmd)) => cmd,
+        // The arguments of the generator are required without a command.
+        (None, None) => unreachable!(),
     };
-    let document = match load_document(&cmd.document) {
-        Ok(document) => document,
+    run_and_summarize(&cmd)
+}
+
+/// Runs the generator once, then writes the summary of the changes to the outputs when
+/// requested.
+fn run_and_summarize(cmd: &command_line::CommandLineArgs) -> ExitCode {
+    let Some(path) = &cmd.changes else {
+        return run(cmd);
+    };
+    let before = read_files(&cmd.written_files());
+    let status = run(cmd);
+    let after = read_files(&cmd.written_files());
+
+    let mut summary = Vec::new();
+    let _ = writeln!(summary, "# Changes");
+    let _ = writeln!(summary);
+    let mut unchanged = true;
+    for path in before.keys().chain(after.keys()).collect::<BTreeSet<_>>() {
+        let old = before.get(path).map_or("", String::as_str);
+        let new = after.get(path).map_or("", String::as_str);
+        let changes = match Changes::between(old, new) {
+            Ok(changes) => changes,
+            Err(err) => {
+                let _ = print_error(
+                    &cmd.reporting,
+                    format_args!("`{}`: {}", path.display(), err),
+                );
+                return ExitCode::FAILURE;
+            }
+        };
+        if !changes.is_empty() {
+            unchanged = false;
+            let _ = changes.write_markdown(&mut summary, &format!("`{}`", path.display()));
+        }
+    }
+    if unchanged {
+        let _ = writeln!(summary, "No generated item changed.");
+    }
+    if let Err(err) = std::fs::write(path, summary) {
+        let _ = print_error(
+            &cmd.reporting,
+            format_args!("`{}`: {}", path.display(), err),
+        );
+        return ExitCode::FAILURE;
+    }
+    status
+}
+
+/// Reads the provided files, skipping the ones that can't be read.
+fn read_files(paths: &[PathBuf]) -> BTreeMap<PathBuf, String> {
+    paths
+        .iter()
+        .filter_map(|path| Some((path.clone(), std::fs::read_to_string(path).ok()?)))
+        .collect()
+}
+
+/// Runs the generator once, for all the documents provided on the command line.
+fn run(cmd: &command_line::CommandLineArgs) -> ExitCode {
+    let generator = match Generator::from_config_file(&cmd.config) {
+        Ok(generator) => generator.trace_fixes(cmd.trace_fixes),
         Err(err) => {
-            let _ = print_error(format_args!("`{}`: {}", cmd.document.display(), err));
+            report(&cmd.reporting, &err);
             return ExitCode::FAILURE;
         }
     };
-    let mut document = match parse::parse(&document) {
-        Ok(document) => document,
-        Err(errs) => {
-            for err in errs {
-                let _ = print_error(format_args!("`{}`: {}", err.path, err.message));
+
+    let mut status = run_outputs(cmd, &generator);
+    if let Some(dir) = &cmd.fuzz {
+        // The fuzz crate may need the manifest of the generated crate.
+        if status == ExitCode::SUCCESS {
+            status = run_fuzz(cmd, &generator, dir);
+        }
+    }
+    if cmd.verify && status == ExitCode::SUCCESS {
+        status = run_verify(cmd, &generator);
+    }
+    status
+}
+
+/// Generates the outputs requested on the command line.
+fn run_outputs(cmd: &command_line::CommandLineArgs, generator: &Generator) -> ExitCode {
+    if let Some(common) = &cmd.common {
+        return run_with_common(cmd, generator, common);
+    }
+    if cmd.merge || cmd.versioned {
+        return run_merged(cmd, generator);
+    }
+    if let Some(dir) = &cmd.krate {
+        return run_crate(cmd, generator, dir);
+    }
+
+    let mut status = ExitCode::SUCCESS;
+    for (input, output) in cmd.documents() {
+        let result = if cmd.check {
+            generator.check_file(input, output).map(|check| {
+                if let Some(diff) = check.diff {
+                    let _ = print_error(
+                        &cmd.reporting,
+                        format_args!("`{}` is out of date", output.display()),
+                    );
+                    print!("{diff}");
+                    status = ExitCode::FAILURE;
+                }
+                check.log
+            })
+        } else {
+            match cmd.emit {
+                Emit::Rust => generator.generate_file(input, output),
+                Emit::Ir => generator.generate_ir_file(input, output),
             }
-            return ExitCode::FAILURE;
+        };
+        if !print_log(&cmd.reporting, result) {
+            status = ExitCode::FAILURE;
         }
+    }
+    status
+}
+
+/// Runs the generator once for all the documents provided on the command line, moving the
+/// types they share to `common`.
+fn run_with_common(
+    cmd: &command_line::CommandLineArgs,
+    generator: &Generator,
+    common: &std::path::Path,
+) -> ExitCode {
+    let documents: Vec<_> = cmd
+        .documents()
+        .map(|(input, output)| (input.clone(), output.clone()))
+        .collect();
+    let mut status = ExitCode::SUCCESS;
+    let result = if cmd.check {
+        generator
+            .check_files_with_common(&documents, common)
+            .map(|check| {
+                if let Some(diff) = check.diff {
+                    let _ =
+                        print_error(&cmd.reporting, format_args!("the outputs are out of date"));
+                    print!("{diff}");
+                    status = ExitCode::FAILURE;
+                }
+                check.log
+            })
+    } else {
+        generator.generate_files_with_common(&documents, common)
     };
-    match fix::fix(&mut document, &config) {
-        Ok(_) => {}
-        Err(errs) => {
-            for err in errs {
-                let _ = print_error(format_args!("{}", err));
+    if !print_log(&cmd.reporting, result) {
+        status = ExitCode::FAILURE;
+    }
+    status
+}
+
+/// Runs the generator once, merging all the documents provided on the command line into the
+/// only output, or generating them to one module per version with `--versioned`.
+fn run_merged(cmd: &command_line::CommandLineArgs, generator: &Generator) -> ExitCode {
+    let output = &cmd.outputs[0];
+    let mut status = ExitCode::SUCCESS;
+    let result = if cmd.check {
+        let check = if cmd.versioned {
+            generator.check_versioned_file(&cmd.inputs, output)
+        } else {
+            generator.check_merged_file(&cmd.inputs, output)
+        };
+        check.map(|check| {
+            if let Some(diff) = check.diff {
+                let _ = print_error(
+                    &cmd.reporting,
+                    format_args!("`{}` is out of date", output.display()),
+                );
+                print!("{diff}");
+                status = ExitCode::FAILURE;
             }
-            return ExitCode::FAILURE;
-        }
+            check.log
+        })
+    } else if cmd.versioned {
+        generator.generate_versioned_file(&cmd.inputs, output)
+    } else {
+        generator.generate_merged_file(&cmd.inputs, output)
+    };
+    if !print_log(&cmd.reporting, result) {
+        status = ExitCode::FAILURE;
     }
-    let mut output = match std::fs::File::create(&cmd.output) {
-        Ok(output) => std::io::BufWriter::new(output),
-        Err(err) => {
-            let _ = print_error(format_args!("`{}`: {}", cmd.output.display(), err));
-            return ExitCode::FAILURE;
+    status
+}
+
+/// Runs the generator once, generating a crate in `dir` from all the documents provided on the
+/// command line.
+fn run_crate(
+    cmd: &command_line::CommandLineArgs,
+    generator: &Generator,
+    dir: &std::path::Path,
+) -> ExitCode {
+    let common = cmd.inputs.len() > 1;
+    let mut status = ExitCode::SUCCESS;
+    let result = if cmd.check {
+        generator
+            .check_crate(&cmd.inputs, dir, common)
+            .map(|check| {
+                if let Some(diff) = check.diff {
+                    let _ = print_error(
+                        &cmd.reporting,
+                        format_args!("`{}` is out of date", dir.display()),
+                    );
+                    print!("{diff}");
+                    status = ExitCode::FAILURE;
+                }
+                check.log
+            })
+    } else {
+        generator.generate_crate(&cmd.inputs, dir, common)
+    };
+    if !print_log(&cmd.reporting, result) {
+        status = ExitCode::FAILURE;
+    }
+    status
+}
+
+/// Runs the generator once, generating a cargo-fuzz crate in `dir` for all the documents
+/// provided on the command line.
+fn run_fuzz(
+    cmd: &command_line::CommandLineArgs,
+    generator: &Generator,
+    dir: &std::path::Path,
+) -> ExitCode {
+    let in_crate = cmd.krate.is_some();
+    let mut status = ExitCode::SUCCESS;
+    let result = if cmd.check {
+        generator
+            .check_fuzz_crate(&cmd.inputs, dir, in_crate)
+            .map(|check| {
+                if let Some(diff) = check.diff {
+                    let _ = print_error(
+                        &cmd.reporting,
+                        format_args!("`{}` is out of date", dir.display()),
+                    );
+                    print!("{diff}");
+                    status = ExitCode::FAILURE;
+                }
+                // The warnings were already printed when generating the outputs.
+                openrpc_gen::fix::Log::default()
+            })
+    } else {
+        generator
+            .generate_fuzz_crate(&cmd.inputs, dir, in_crate)
+            .map(|_| openrpc_gen::fix::Log::default())
+    };
+    if !print_log(&cmd.reporting, result) {
+        status = ExitCode::FAILURE;
+    }
+    status
+}
+
+/// Compiles the outputs with `cargo check`, reporting the errors it finds.
+fn run_verify(cmd: &command_line::CommandLineArgs, generator: &Generator) -> ExitCode {
+    let config = &generator.config().verify;
+    let result = match &cmd.krate {
+        Some(dir) => verify::verify_crate(config, &dir.join("Cargo.toml")),
+        None => {
+            let files: Vec<_> = cmd.outputs.iter().chain(&cmd.common).cloned().collect();
+            verify::verify_files(config, &files)
         }
     };
-    match gen::gen(&mut output, &document, &config) {
-        Ok(_) => {}
+    let errors = match result {
+        Ok(errors) => errors,
         Err(err) => {
-            let _ = print_error(format_args!("{}", err));
+            report(&cmd.reporting, &err);
             return ExitCode::FAILURE;
         }
+    };
+
+    for error in &errors {
+        let diagnostic = match &error.schema_path {
+            Some(path) => Diagnostic::error(&error.message).at(path),
+            None => Diagnostic::error(format!(
+                "`{}:{}:{}`: {}",
+                error.file.display(),
+                error.line,
+                error.column,
+                error.message,
+            )),
+        };
+        let _ = print(&cmd.reporting, &diagnostic);
     }
-    drop(output);
-    if config.run_rustfmt {
-        if let Err(err) = run_rustmft(&cmd.output) {
-            let _ = print_error(format_args!("{}", err));
-            return ExitCode::FAILURE;
+    if errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Checks the documents provided to the `lint` command, printing the diagnostics found.
+fn run_lint(args: &LintArgs) -> ExitCode {
+    let reporting = &args.reporting;
+    let generator = match &args.config {
+        Some(path) => match Generator::from_config_file(path) {
+            Ok(generator) => generator,
+            Err(err) => {
+                report(reporting, &err);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Generator::new(Default::default()),
+    };
+
+    let mut status = ExitCode::SUCCESS;
+    for input in &args.inputs {
+        let diagnostics = match generator.lint_file(input) {
+            Ok(diagnostics) => diagnostics,
+            Err(err) => {
+                report(reporting, &err);
+                status = ExitCode::FAILURE;
+                continue;
+            }
+        };
+        for mut diagnostic in diagnostics {
+            if reporting.deny_warnings && diagnostic.severity == Severity::Warning {
+                diagnostic.severity = Severity::Error;
+            }
+            if diagnostic.severity == Severity::Error {
+                status = ExitCode::FAILURE;
+            }
+            let _ = print(reporting, &diagnostic);
         }
     }
-    ExitCode::SUCCESS
+    status
+}
+
+/// Prints the trace and warnings of a successful generation, or reports its error.
+///
+/// Returns whether the generation succeeded, which is not the case when warnings were emitted
+/// with `--deny-warnings`.
+fn print_log(reporting: &Reporting, result: Result<openrpc_gen::fix::Log, Error>) -> bool {
+    match result {
+        Ok(log) => {
+            for line in log.trace {
+                let _ = match reporting.message_format {
+                    MessageFormat::Human => {
+                        print_diagnostic("\x1B[36mtrace\x1B[0m", format_args!("{}", line))
+                    }
+                    MessageFormat::Json => print(reporting, &Diagnostic::new(Severity::Note, line)),
+                };
+            }
+            let denied = reporting.deny_warnings && !log.warnings.is_empty();
+            for mut warning in log.warnings {
+                if reporting.deny_warnings {
+                    warning.severity = Severity::Error;
+                }
+                let _ = print(reporting, &warning);
+            }
+            !denied
+        }
+        Err(err) => {
+            report(reporting, &err);
+            false
+        }
+    }
+}
+
+/// Reports an error to the user.
+///
+/// Errors that carry multiple diagnostics are printed one by one.
+fn report(reporting: &Reporting, err: &Error) {
+    for diagnostic in err.diagnostics() {
+        let _ = print(reporting, &diagnostic);
+    }
 }
 
 /// Print an error message to the standard error stream.
-fn print_error(args: std::fmt::Arguments) -> std::io::Result<()> {
+fn print_error(reporting: &Reporting, args: std::fmt::Arguments) -> std::io::Result<()> {
+    print(reporting, &Diagnostic::error(args.to_string()))
+}
+
+/// Print a diagnostic to the standard error stream, in the format requested on the command
+/// line.
+fn print(reporting: &Reporting, diagnostic: &Diagnostic) -> std::io::Result<()> {
+    match reporting.message_format {
+        MessageFormat::Human => {
+            let level = match diagnostic.severity {
+                Severity::Error => "\x1B[31merror\x1B[0m",
+                Severity::Warning => "\x1B[33mwarning\x1B[0m",
+                Severity::Note => "\x1B[36mnote\x1B[0m",
+            };
+            print_diagnostic(level, format_args!("{}", diagnostic))
+        }
+        MessageFormat::Json => {
+            let stderr = std::io::stderr();
+            let mut stderr = stderr.lock();
+            serde_json::to_writer(&mut stderr, diagnostic)?;
+            stderr.write_all(b"\n")?;
+            stderr.flush()
+        }
+    }
+}
+
+/// Print a message to the standard error stream, prefixed with `level`.
+fn print_diagnostic(level: &str, args: std::fmt::Arguments) -> std::io::Result<()> {
     let stderr = std::io::stderr();
     let mut stderr = stderr.lock();
 
-    stderr.write_all(b"\x1B[31merror\x1B[0m: ")?;
+    write!(stderr, "{level}: ")?;
     stderr.write_fmt(args)?;
     stderr.write_all(b"\n")?;
     stderr.flush()?;
 
     Ok(())
 }
-
-/// Loads the document from the provided path.
-fn load_document(path: &Path) -> Result<open_rpc::OpenRpc, String> {
-    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
-    let buf = std::io::BufReader::new(file);
-    let document = serde_json::from_reader(buf).map_err(|e| e.to_string())?;
-    Ok(document)
-}
-
-/// Runs `rustfmt` on the provided path.
-fn run_rustmft(path: &Path) -> std::io::Result<()> {
-    let status = std::process::Command::new("rustfmt")
-        .arg(path)
-        .status()
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-    if !status.success() {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "rustfmt failed",
-        ));
-    }
-    Ok(())
-}
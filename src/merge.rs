@@ -0,0 +1,165 @@
+//! Merging of several OpenRPC documents into a single one.
+
+use convert_case::{Case, Casing};
+use serde_json::{Map, Value};
+
+use crate::diagnostics::Diagnostic;
+use crate::Document;
+
+/// Merges the provided documents into a single one, returning it along with the warnings
+/// emitted in the process.
+///
+/// Each document comes with a label, usually the stem of the file it was loaded from. The
+/// methods of all the documents are concatenated, and their components are unioned. Components
+/// defined identically by several documents are only kept once, while a component defined
+/// differently by a later document is renamed by prefixing its name with the label of that
+/// document. References to the renamed components are updated accordingly.
+///
+/// A method defined differently by several documents is only kept from the first one.
+pub fn merge(
+    documents: &[(&str, &Document)],
+) -> Result<(Document, Vec<Diagnostic>), serde_json::Error> {
+    let mut warnings = Vec::new();
+    let mut merged = Map::new();
+    let mut methods: Vec<Value> = Vec::new();
+    let mut components: Map<String, Value> = Map::new();
+
+    for (label, document) in documents {
+        warnings.extend(document.warnings.iter().cloned());
+        let mut raw = document.raw.clone();
+
+        // Renaming a component changes the references held by the others, which may then
+        // conflict in turn.
+        loop {
+            let renames = conflicts(&components, &raw, label, &mut warnings);
+            if renames.is_empty() {
+                break;
+            }
+            for (kind, from, to) in &renames {
+                let entries = raw["components"][kind].as_object_mut().unwrap();
+                let value = entries.remove(from).unwrap();
+                entries.insert(to.clone(), value);
+                rename_refs(
+                    &mut raw,
+                    &format!("#/components/{kind}/{from}"),
+                    &format!("#/components/{kind}/{to}"),
+                );
+            }
+        }
+
+        let Value::Object(mut raw) = raw else {
+            continue;
+        };
+        if let Some(Value::Object(kinds)) = raw.remove("components") {
+            for (kind, entries) in kinds {
+                let Value::Object(entries) = entries else {
+                    continue;
+                };
+                let target = components
+                    .entry(kind)
+                    .or_insert_with(|| Value::Object(Map::new()))
+                    .as_object_mut()
+                    .unwrap();
+                for (name, value) in entries {
+                    target.entry(name).or_insert(value);
+                }
+            }
+        }
+        if let Some(Value::Array(new_methods)) = raw.remove("methods") {
+            for method in new_methods {
+                let name = method.get("name");
+                match methods.iter().find(|m| name.is_some() && m.get("name") == name) {
+                    Some(existing) if existing != &method => warnings.push(Diagnostic::warning(format!(
+                        "method `{}` is defined differently by `{label}`, only the first definition is kept",
+                        name.and_then(Value::as_str).unwrap_or_default(),
+                    ))),
+                    Some(_) => (),
+                    None => methods.push(method),
+                }
+            }
+        }
+
+        // The other fields, such as `info`, are taken from the first document.
+        for (key, value) in raw {
+            merged.entry(key).or_insert(value);
+        }
+    }
+
+    merged.insert("methods".into(), Value::Array(methods));
+    if !components.is_empty() {
+        merged.insert("components".into(), Value::Object(components));
+    }
+    let document = Document::from_json(Value::Object(merged))?;
+    Ok((document, warnings))
+}
+
+/// Returns the components of `raw`, the document labeled `label`, that are defined differently
+/// by `components`, along with the names they should be renamed to.
+fn conflicts(
+    components: &Map<String, Value>,
+    raw: &Value,
+    label: &str,
+    warnings: &mut Vec<Diagnostic>,
+) -> Vec<(String, String, String)> {
+    let mut renames = Vec::new();
+    let Some(Value::Object(kinds)) = raw.get("components") else {
+        return renames;
+    };
+    for (kind, entries) in kinds {
+        let (Some(existing), Value::Object(entries)) =
+            (components.get(kind).and_then(Value::as_object), entries)
+        else {
+            continue;
+        };
+        for (name, value) in entries {
+            if existing.get(name).is_some_and(|other| other != value) {
+                let taken = |candidate: &str| {
+                    existing.contains_key(candidate) || entries.contains_key(candidate)
+                };
+                let new_name = fresh_name(taken, label, name);
+                warnings.push(
+                    Diagnostic::warning(format!(
+                        "defined differently by `{label}`, renamed to `{new_name}`"
+                    ))
+                    .at(format!("#/components/{kind}/{name}")),
+                );
+                renames.push((kind.clone(), name.clone(), new_name));
+            }
+        }
+    }
+    renames
+}
+
+/// Returns a name for the component `name` of the document labeled `label` that is not
+/// already `taken`.
+pub fn fresh_name(taken: impl Fn(&str) -> bool, label: &str, name: &str) -> String {
+    let prefix = label.to_case(Case::ScreamingSnake);
+    let base = format!("{prefix}_{name}");
+    let mut candidate = base.clone();
+    let mut i = 2;
+    while taken(&candidate) {
+        candidate = format!("{base}_{i}");
+        i += 1;
+    }
+    candidate
+}
+
+/// Replaces the `$ref`s pointing to `from`, or inside of it, by references to `to`.
+fn rename_refs(value: &mut Value, from: &str, to: &str) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get_mut("$ref") {
+                let rest = reference.strip_prefix(from);
+                if let Some(rest) = rest.filter(|rest| rest.is_empty() || rest.starts_with('/')) {
+                    *reference = format!("{to}{rest}");
+                }
+            }
+            map.values_mut()
+                .for_each(|value| rename_refs(value, from, to));
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| rename_refs(value, from, to)),
+        _ => (),
+    }
+}
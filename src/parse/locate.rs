@@ -0,0 +1,264 @@
+//! Finds the items of a document in the text it was read from, to report the line and column of
+//! the errors found in them.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::diagnostics::{Diagnostic, Location};
+use crate::Document;
+
+/// Sets the location of the `diagnostics` about items of `doc`, when it was read from a file.
+pub(crate) fn locate_diagnostics(doc: &Document, diagnostics: &mut [Diagnostic]) {
+    let Some(source) = &doc.source else {
+        return;
+    };
+    if diagnostics.is_empty() {
+        return;
+    }
+    let positions = Positions::new(&source.text);
+    for diagnostic in diagnostics {
+        let path = diagnostic.path.as_deref().unwrap_or("#");
+        let position = positions.locate(&doc.raw, path);
+        diagnostic.location = position.map(|(line, column)| Location {
+            file: source.name.clone(),
+            line,
+            column,
+        });
+    }
+}
+
+/// The positions of the values of a JSON document in the text it was read from, recorded in a
+/// single pass over the text.
+struct Positions<'a> {
+    /// The text of the document.
+    text: &'a str,
+    /// Maps the segments of the JSON pointer of every value to the byte offset it starts at.
+    offsets: HashMap<Vec<String>, usize>,
+    /// The byte offsets the lines of the text start at.
+    lines: Vec<usize>,
+}
+
+impl<'a> Positions<'a> {
+    /// Records the positions of the values of `text`.
+    ///
+    /// The values found before a syntax error are recorded, but not the ones following it.
+    fn new(text: &'a str) -> Self {
+        let mut offsets = HashMap::new();
+        let bytes = text.as_bytes();
+        record(
+            bytes,
+            skip_whitespace(bytes, 0),
+            &mut Vec::new(),
+            &mut offsets,
+        );
+        let lines = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            text,
+            offsets,
+            lines,
+        }
+    }
+
+    /// Returns the line and column, starting at 1, of the item at `path`.
+    ///
+    /// `path` is a path of the parser, resolved in `raw`, the JSON parsed from the text. The
+    /// location of the closest parent that can be found is returned when the item itself can't,
+    /// such as when it was imported from another document.
+    fn locate(&self, raw: &Value, path: &str) -> Option<(usize, usize)> {
+        let mut pointer = pointer(raw, path);
+        loop {
+            if let Some(&offset) = self.offsets.get(&pointer) {
+                return Some(self.line_column(offset));
+            }
+            pointer.pop()?;
+        }
+    }
+
+    /// Returns the line and column, starting at 1, of the byte at `offset`.
+    fn line_column(&self, offset: usize) -> (usize, usize) {
+        let line = self.lines.partition_point(|&start| start <= offset);
+        let start = self.lines[line - 1];
+        (line, self.text[start..offset].chars().count() + 1)
+    }
+}
+
+/// Returns the segments of the JSON pointer of the item at `path`, a path of the parser, in
+/// `raw`.
+///
+/// The paths of the parser name methods and parameters instead of indexing them, number the
+/// schemas of `oneOf` and `allOf` as `variantN` and `fieldN`, and omit the `properties` and
+/// `schema` keys. JSON pointers are resolved as well. Segments that can't be resolved are
+/// ignored along with the following ones.
+fn pointer(raw: &Value, path: &str) -> Vec<String> {
+    let mut pointer = Vec::new();
+    let mut node = raw;
+    for segment in path.trim_start_matches('#').split('/').skip(1) {
+        match step(node, segment) {
+            Some((keys, child)) => {
+                pointer.extend(keys);
+                node = child;
+            }
+            None => break,
+        }
+    }
+    pointer
+}
+
+/// Resolves one segment of a path of the parser from `node`, returning the keys it stands for
+/// along with the value it points to.
+fn step<'a>(node: &'a Value, segment: &str) -> Option<(Vec<String>, &'a Value)> {
+    let indexed = |key: &str, prefix: &str| {
+        let index: usize = segment.strip_prefix(prefix)?.parse().ok()?;
+        let child = node.get(key)?.get(index)?;
+        Some((vec![key.to_owned(), index.to_string()], child))
+    };
+
+    if segment == "_anon" {
+        return Some((Vec::new(), node));
+    }
+    match node {
+        Value::Array(items) => {
+            if let Some(index) = items.iter().position(|item| item["name"] == segment) {
+                return Some((vec![index.to_string()], &items[index]));
+            }
+            // JSON pointers index the items directly.
+            let index = segment.strip_prefix("error").unwrap_or(segment);
+            let index: usize = index.parse().ok()?;
+            Some((vec![index.to_string()], items.get(index)?))
+        }
+        Value::Object(map) => {
+            if let Some(found) = indexed("oneOf", "variant")
+                .or_else(|| indexed("anyOf", "variant"))
+                .or_else(|| indexed("allOf", "field"))
+            {
+                return Some(found);
+            }
+            if let Some(child) = map.get("properties").and_then(|p| p.get(segment)) {
+                return Some((vec!["properties".into(), segment.into()], child));
+            }
+            if let Some(child) = map.get(segment) {
+                return Some((vec![segment.into()], child));
+            }
+            if let Some(Value::Array(values)) = map.get("enum") {
+                if let Some(index) = values.iter().position(|value| value == segment) {
+                    return Some((vec!["enum".into(), index.to_string()], &values[index]));
+                }
+            }
+            let schema = map.get("schema")?;
+            let (mut keys, child) = step(schema, segment)?;
+            keys.insert(0, "schema".into());
+            Some((keys, child))
+        }
+        _ => None,
+    }
+}
+
+/// Records the offset of the value starting at `pos`, whose JSON pointer is made of the segments
+/// of `pointer`, and of the values it holds, returning the position right after it.
+fn record(
+    bytes: &[u8],
+    pos: usize,
+    pointer: &mut Vec<String>,
+    offsets: &mut HashMap<Vec<String>, usize>,
+) -> Option<usize> {
+    offsets.insert(pointer.clone(), pos);
+    let (close, is_object) = match bytes.get(pos)? {
+        b'{' => (b'}', true),
+        b'[' => (b']', false),
+        b'"' => return skip_string(bytes, pos),
+        _ => {
+            let len = bytes[pos..]
+                .iter()
+                .position(|b| b.is_ascii_whitespace() || matches!(b, b',' | b'}' | b']'))
+                .unwrap_or(bytes.len() - pos);
+            return Some(pos + len);
+        }
+    };
+    let mut pos = skip_whitespace(bytes, pos + 1);
+    if bytes.get(pos) == Some(&close) {
+        return Some(pos + 1);
+    }
+    let mut index = 0;
+    loop {
+        let segment = if is_object {
+            let end = skip_string(bytes, pos)?;
+            let key = std::str::from_utf8(&bytes[pos..end]).ok()?;
+            let key: String = serde_json::from_str(key).ok()?;
+            pos = skip_whitespace(bytes, end);
+            if bytes.get(pos) != Some(&b':') {
+                return None;
+            }
+            pos = skip_whitespace(bytes, pos + 1);
+            key
+        } else {
+            index.to_string()
+        };
+        index += 1;
+        pointer.push(segment);
+        let end = record(bytes, pos, pointer, offsets);
+        pointer.pop();
+        pos = skip_whitespace(bytes, end?);
+        match bytes.get(pos)? {
+            b',' => pos = skip_whitespace(bytes, pos + 1),
+            b if *b == close => return Some(pos + 1),
+            _ => return None,
+        }
+    }
+}
+
+/// Returns the position right after the string starting at `pos`.
+fn skip_string(bytes: &[u8], mut pos: usize) -> Option<usize> {
+    if bytes.get(pos) != Some(&b'"') {
+        return None;
+    }
+    pos += 1;
+    loop {
+        match bytes.get(pos)? {
+            b'\\' => pos += 2,
+            b'"' => return Some(pos + 1),
+            _ => pos += 1,
+        }
+    }
+}
+
+/// Returns the position of the first non-whitespace byte at or after `pos`.
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"{
+  "methods": [
+    { "name": "get", "params": [] }
+  ],
+  "components": { "schemas": { "k\"é": { "type": "string" } } }
+}"#;
+
+    fn locate(path: &str) -> Option<(usize, usize)> {
+        let raw = serde_json::from_str(TEXT).unwrap();
+        Positions::new(TEXT).locate(&raw, path)
+    }
+
+    #[test]
+    fn locates_items_by_parser_path() {
+        assert_eq!(locate("#"), Some((1, 1)));
+        assert_eq!(locate("#/methods/get"), Some((3, 5)));
+        assert_eq!(locate("#/methods/get/params"), Some((3, 32)));
+        assert_eq!(locate("#/components/schemas/k\"é/type"), Some((5, 50)));
+    }
+
+    #[test]
+    fn locates_the_closest_parent() {
+        assert_eq!(locate("#/methods/get/result"), Some((3, 5)));
+        assert_eq!(locate("#/components/schemas/MISSING"), Some((5, 30)));
+    }
+}
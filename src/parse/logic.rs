@@ -2,13 +2,31 @@
 
 use std::collections::BTreeMap;
 
-use convert_case::{Case, Casing};
 use open_rpc as rpc;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
 
 use super::{
-    AliasDef, EnumDef, EnumTag, EnumVariant, File, Method, MethodParameter, MethodResult,
-    ParsingError, Path, StructDef, StructField, TypeDef, TypeKind, TypeRef, TypeSource,
+    Access, AliasDef, Constraints, Discriminator, EnumDef, EnumTag, EnumVariant, ErrorDef, Example,
+    File, Info, Method, MethodParameter, MethodResult, Path, StructDef, StructField, Tag, TypeDef,
+    TypeKind, TypeRef, TypeSource,
 };
+use crate::config::{Config, FieldType, Generation};
+use crate::diagnostics::Diagnostic;
+use crate::ident;
+use crate::Document;
+
+/// The raw JSON of the item being parsed, used to read the keywords that are not modeled by
+/// [`open_rpc`].
+///
+/// This is `None` when the raw JSON is not available.
+type Raw<'a> = Option<&'a Value>;
+
+/// Returns the raw JSON of the child `key` of `raw`.
+fn raw_child<'a>(raw: Raw<'a>, key: impl serde_json::value::Index) -> Raw<'a> {
+    raw?.get(key)
+}
 
 /// Some context required when parsing.
 struct Ctx<'a> {
@@ -21,22 +39,93 @@ struct Ctx<'a> {
     /// `components/schemas` section, meaning that they do not have a canonical name.
     pub anonymous_types: BTreeMap<Path, TypeDef>,
 
+    /// A list of errors that have been defined inline in methods, outside of the
+    /// `components/errors` section.
+    pub anonymous_errors: BTreeMap<Path, ErrorDef>,
+
     /// The document that is being parsed.
     pub doc: &'a rpc::OpenRpc,
 
+    /// The raw JSON of the document that is being parsed.
+    pub raw: &'a Value,
+
     /// A list of errors that have been encountered during parsing.
-    pub errors: Vec<ParsingError>,
+    pub errors: Vec<Diagnostic>,
+
+    /// Whether the schema features that are approximated should be reported as errors.
+    pub strict: bool,
+
+    /// The acronyms to keep as-is in PascalCase names.
+    pub acronyms: &'a [String],
+
+    /// Whether hexadecimal strings of more than 64 bits should be parsed as big integers.
+    pub big_integers: bool,
+
+    /// Whether arrays with `uniqueItems` should be parsed as sets.
+    pub sets: bool,
+
+    /// The string formats mapped to Rust types.
+    pub formats: &'a BTreeMap<String, FieldType>,
+
+    /// The prefix removed from method names before building names from them.
+    pub method_name_prefix: Option<&'a str>,
+
+    /// The words the names of anonymous types are built from, such as the name of the
+    /// enclosing type followed by the name of the field holding them.
+    pub names: Vec<String>,
+
+    /// The generation options, some of which are resolved per path while parsing.
+    pub generation: &'a Generation,
 }
 
 impl<'a> Ctx<'a> {
     /// Creates a new [`Ctx`] instance.
-    pub fn new(doc: &'a rpc::OpenRpc) -> Self {
+    pub fn new(doc: &'a Document, config: &'a Config) -> Self {
         Self {
             path: String::from("#"),
             anonymous_types: BTreeMap::new(),
-            doc,
+            anonymous_errors: BTreeMap::new(),
+            doc: &doc.spec,
+            raw: &doc.raw,
             errors: Vec::new(),
+            strict: config.strict,
+            acronyms: &config.generation.acronyms,
+            big_integers: config.primitives.big_integer.is_some(),
+            sets: config.primitives.set.is_some(),
+            formats: &config.primitives.formats,
+            method_name_prefix: config.generation.method_name_prefix.as_deref(),
+            names: Vec::new(),
+            generation: &config.generation,
+        }
+    }
+
+    /// Returns `name` in PascalCase, keeping the configured acronyms.
+    pub fn pascal(&self, name: &str) -> String {
+        ident::pascal(name, self.acronyms)
+    }
+
+    /// Returns the raw JSON of the item referenced by `reference`.
+    pub fn resolve_raw(&self, reference: &str) -> Raw<'a> {
+        self.raw.pointer(reference.strip_prefix('#')?)
+    }
+
+    /// Returns the name of an anonymous type defined at the current location, built from the
+    /// names of the items enclosing it.
+    pub fn anonymous_name(&self) -> String {
+        if self.names.is_empty() {
+            return "Anonymous".into();
         }
+        self.pascal(&self.names.join("_"))
+    }
+
+    /// Pushes a word to the names of anonymous types defined from now on.
+    pub fn push_name(&mut self, name: &str) {
+        self.names.push(name.into());
+    }
+
+    /// Pops the last word pushed with [`Ctx::push_name`].
+    pub fn pop_name(&mut self) {
+        self.names.pop();
     }
 
     /// Pushes a new path to the context.
@@ -65,51 +154,113 @@ impl<'a> Ctx<'a> {
         self.anonymous_types.insert(def.path.clone(), def);
     }
 
+    /// Adds an error about `reference` pointing to nothing, suggesting the closest item next
+    /// to the one it points to.
+    pub fn add_reference_error(&mut self, reference: &str) {
+        let parent = reference.rsplit_once('/').map_or("#", |(parent, _)| parent);
+        let siblings: Vec<String> = self
+            .resolve_raw(parent)
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(|(name, _)| format!("{parent}/{name}"))
+            .collect();
+        self.errors.push(
+            Diagnostic::error(format!("reference `{reference}` not found"))
+                .at(self.path.as_str())
+                .did_you_mean(reference, siblings.iter().map(String::as_str)),
+        );
+    }
+
     /// Adds a new error to the context.
     pub fn add_error(&mut self, message: impl Into<String>) {
-        self.errors.push(ParsingError {
-            path: self.current_path(),
-            message: message.into(),
-        });
+        self.errors
+            .push(Diagnostic::error(message).at(self.path.as_str()));
     }
 }
 
 /// Parses a file from an OpenRPC document.
-pub fn parse(doc: &rpc::OpenRpc) -> Result<File, Vec<ParsingError>> {
+pub fn parse(doc: &Document, config: &Config) -> Result<File, Vec<Diagnostic>> {
     let mut methods = Vec::new();
     let mut types = BTreeMap::new();
+    let mut errors = BTreeMap::new();
 
-    let mut ctx = Ctx::new(doc);
+    let mut ctx = Ctx::new(doc, config);
 
-    parse_methods(&mut ctx, &mut methods, &doc.methods);
+    let raw_methods = doc.raw.get("methods");
+    parse_methods(&mut ctx, &mut methods, &doc.spec.methods, raw_methods);
 
-    if let Some(ref components) = doc.components {
+    if let Some(ref components) = doc.spec.components {
+        let raw_schemas = doc.raw.pointer("/components/schemas");
         ctx.push_path("components");
-        parse_schemas(&mut ctx, &mut types, &components.schemas);
+        parse_schemas(&mut ctx, &mut types, &components.schemas, raw_schemas);
+        parse_errors(&mut ctx, &mut errors, &components.errors);
         ctx.pop_path();
     }
 
     assert_eq!(ctx.path, "#");
     if !ctx.errors.is_empty() {
+        super::locate::locate_diagnostics(doc, &mut ctx.errors);
         return Err(ctx.errors);
     }
 
     types.append(&mut ctx.anonymous_types);
+    errors.append(&mut ctx.anonymous_errors);
 
-    Ok(File { methods, types })
+    let info = Info {
+        title: doc.spec.info.title.clone(),
+        version: doc.spec.info.version.clone(),
+        documentation: doc.spec.info.description.clone(),
+    };
+
+    Ok(File {
+        info,
+        methods,
+        types,
+        errors,
+    })
 }
 
 /// Parse the methods specified in the OpenRPC document into a list of [`Method`]s.
-fn parse_methods(ctx: &mut Ctx, output: &mut Vec<Method>, methods: &[rpc::RefOr<rpc::Method>]) {
+fn parse_methods(
+    ctx: &mut Ctx,
+    output: &mut Vec<Method>,
+    methods: &[rpc::RefOr<rpc::Method>],
+    raw: Raw,
+) {
     ctx.push_path("methods");
 
-    for method in methods {
+    for (i, method) in methods.iter().enumerate() {
         match method {
             rpc::RefOr::Inline(method) => {
-                output.push(parse_method(ctx, method));
+                output.push(parse_method(ctx, method, raw_child(raw, i)));
             }
-            rpc::RefOr::Reference { .. } => {
-                ctx.add_error("externally defined methods are not supported");
+            rpc::RefOr::Reference { reference } => {
+                let raw = ctx.resolve_raw(reference);
+                // The examples are read from the raw JSON, since `open_rpc` can't deserialize
+                // their values.
+                let typed = raw.cloned().map(|mut method| {
+                    if let Some(method) = method.as_object_mut() {
+                        method.remove("examples");
+                    }
+                    crate::strip_unknown_formats(&mut method);
+                    crate::replace_any_schemas(&mut method);
+                    rpc::Method::deserialize(method)
+                });
+                match typed {
+                    // Every field of a method has a default value, but a method without a name
+                    // is most likely something else.
+                    Some(Ok(method)) if !method.name.is_empty() => {
+                        output.push(parse_method(ctx, &method, raw))
+                    }
+                    Some(Ok(_)) => {
+                        ctx.add_error(format!("reference `{reference}` is not a method"))
+                    }
+                    Some(Err(err)) => {
+                        ctx.add_error(format!("reference `{reference}` is not a method: {err}"))
+                    }
+                    None => ctx.add_reference_error(reference),
+                }
             }
         }
     }
@@ -118,20 +269,25 @@ fn parse_methods(ctx: &mut Ctx, output: &mut Vec<Method>, methods: &[rpc::RefOr<
 }
 
 /// Parses a method from the OpenRPC document into a [`Method`].
-fn parse_method(ctx: &mut Ctx, method: &rpc::Method) -> Method {
+fn parse_method(ctx: &mut Ctx, method: &rpc::Method, raw: Raw) -> Method {
     let mut params = Vec::new();
 
     ctx.push_path(&method.name);
+    let prefix = ctx.method_name_prefix.unwrap_or_default();
+    ctx.push_name(method.name.strip_prefix(prefix).unwrap_or(&method.name));
     let name = method.name.clone();
     let documentation = method
         .description
         .clone()
         .or_else(|| method.summary.clone());
-    let result = method
-        .result
-        .as_ref()
-        .and_then(|cd| ref_or_content_descriptor(ctx, cd, parse_method_result));
-    parse_params(ctx, &mut params, &method.params);
+    let result = method.result.as_ref().and_then(|cd| {
+        ref_or_content_descriptor(ctx, cd, raw_child(raw, "result"), parse_method_result)
+    });
+    parse_params(ctx, &mut params, &method.params, raw_child(raw, "params"));
+    let errors = parse_method_errors(ctx, &method.errors);
+    let tags = parse_method_tags(ctx, &method.tags);
+    let examples = parse_method_examples(ctx, &params, raw_child(raw, "examples"));
+    ctx.pop_name();
     ctx.pop_path();
 
     Method {
@@ -140,22 +296,229 @@ fn parse_method(ctx: &mut Ctx, method: &rpc::Method) -> Method {
         params,
         result,
         param_structure: method.param_structure,
+        errors,
+        tags,
+        examples,
+    }
+}
+
+/// Parses the tags of a method.
+fn parse_method_tags(ctx: &mut Ctx, tags: &[rpc::RefOr<rpc::Tag>]) -> Vec<Tag> {
+    let mut output = Vec::new();
+
+    for tag in tags {
+        let tag = match tag {
+            rpc::RefOr::Inline(tag) => tag,
+            rpc::RefOr::Reference { reference } => {
+                let found = reference
+                    .strip_prefix("#/components/tags/")
+                    .and_then(|name| ctx.doc.components.as_ref()?.tags.get(name));
+                match found {
+                    Some(tag) => tag,
+                    None => {
+                        ctx.add_reference_error(reference);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        output.push(Tag {
+            name: tag.name.clone(),
+            documentation: tag.description.clone().or_else(|| tag.summary.clone()),
+        });
+    }
+
+    output
+}
+
+/// Parses the example pairings of a method.
+///
+/// Example objects that only have an `externalValue` are ignored, since their value is not
+/// part of the document.
+fn parse_method_examples(ctx: &mut Ctx, params: &[MethodParameter], raw: Raw) -> Vec<Example> {
+    let mut output = Vec::new();
+    let Some(Value::Array(examples)) = raw else {
+        return output;
+    };
+
+    ctx.push_path("examples");
+
+    for (i, example) in examples.iter().enumerate() {
+        let Some(example) = resolve_ref(ctx, example) else {
+            continue;
+        };
+        let name = match example.get("name").and_then(Value::as_str) {
+            Some(name) => name.to_owned(),
+            None => format!("example{i}"),
+        };
+
+        let mut values = Vec::new();
+        let raw_params = example.get("params").and_then(Value::as_array);
+        for (j, param) in raw_params.into_iter().flatten().enumerate() {
+            let Some(param) = resolve_ref(ctx, param) else {
+                continue;
+            };
+            let Some(value) = param.get("value") else {
+                continue;
+            };
+            // Example objects are usually named after their parameter, but they may also
+            // be matched by position.
+            let name = param
+                .get("name")
+                .and_then(Value::as_str)
+                .filter(|name| params.iter().any(|p| p.name_in_json == *name))
+                .or_else(|| params.get(j).map(|p| p.name_in_json.as_str()));
+            if let Some(name) = name {
+                values.push((name.to_owned(), value.clone()));
+            }
+        }
+
+        let result = example
+            .get("result")
+            .and_then(|result| resolve_ref(ctx, result))
+            .and_then(|result| result.get("value").cloned());
+
+        output.push(Example {
+            name,
+            params: values,
+            result,
+        });
+    }
+
+    ctx.pop_path();
+
+    output
+}
+
+/// Returns the raw JSON `value`, or the one it references if it is a reference object.
+fn resolve_ref(ctx: &mut Ctx, value: &Value) -> Option<Value> {
+    let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+        return Some(value.clone());
+    };
+    let resolved = ctx.resolve_raw(reference).cloned();
+    if resolved.is_none() {
+        ctx.add_reference_error(reference);
+    }
+    resolved
+}
+
+/// Parses the errors of a method, returning their paths.
+///
+/// Errors defined inline are registered to the context.
+fn parse_method_errors(ctx: &mut Ctx, errors: &[rpc::RefOr<rpc::Error>]) -> Vec<Path> {
+    let mut output = Vec::new();
+
+    ctx.push_path("errors");
+
+    for (i, error) in errors.iter().enumerate() {
+        match error {
+            rpc::RefOr::Inline(error) => {
+                ctx.push_path(&format!("error{}", i));
+                let def = parse_error(ctx, None, error);
+                ctx.pop_path();
+
+                output.push(def.path.clone());
+                ctx.anonymous_errors.insert(def.path.clone(), def);
+            }
+            rpc::RefOr::Reference { reference } => {
+                if ctx.doc.get_error(reference).is_some() {
+                    output.push(Path::from(reference.as_str()));
+                } else {
+                    ctx.add_reference_error(reference);
+                }
+            }
+        }
+    }
+
+    ctx.pop_path();
+
+    output
+}
+
+/// Parses the errors declared in the `components/errors` section of the document.
+fn parse_errors(
+    ctx: &mut Ctx,
+    output: &mut BTreeMap<Path, ErrorDef>,
+    errors: &BTreeMap<String, rpc::Error>,
+) {
+    ctx.push_path("errors");
+
+    for (name, error) in errors {
+        ctx.push_path(name);
+        let def = parse_error(ctx, Some(name), error);
+        ctx.pop_path();
+
+        output.insert(def.path.clone(), def);
+    }
+
+    ctx.pop_path();
+}
+
+/// Parses an [`rpc::Error`] into an [`ErrorDef`] defined at the current path.
+///
+/// When the error has no name, one is derived from its code, such as `ErrorMinus32000` for
+/// `-32000`, as messages make for long names that may not even be identifiers.
+fn parse_error(ctx: &mut Ctx, name: Option<&str>, error: &rpc::Error) -> ErrorDef {
+    let path = ctx.current_path();
+    let name = match name {
+        Some(name) => ctx.pascal(name),
+        None if error.code < 0 => format!("ErrorMinus{}", error.code.unsigned_abs()),
+        None => format!("Error{}", error.code),
+    };
+    let names = std::mem::replace(&mut ctx.names, vec![name.clone(), "data".into()]);
+    let data = error
+        .data
+        .as_ref()
+        .and_then(|data| parse_error_data(ctx, data));
+    ctx.names = names;
+
+    ErrorDef {
+        path,
+        name,
+        code: error.code,
+        message: error.message.clone(),
+        data,
+    }
+}
+
+/// Parses the `data` field of an error.
+///
+/// The OpenRPC specification describes this field as an arbitrary value, but many documents
+/// use it to declare the schema of the data returned along with the error. When the value
+/// is not a valid schema, it is ignored.
+fn parse_error_data(ctx: &mut Ctx, data: &serde_json::Value) -> Option<TypeRef> {
+    let schema = rpc::Schema::deserialize(data).ok()?;
+
+    ctx.push_path("data");
+    let ty = parse_type(ctx, None, TypeSource::Declared, &schema, Some(data));
+    ctx.pop_path();
+
+    if let TypeKind::Alias(alias) = ty.kind {
+        return Some(alias.ty);
     }
+    let path = ty.path.clone();
+    ctx.register_type(ty);
+    Some(TypeRef::Ref(path))
 }
 
 /// Calls the provided function with either a dereferenced [`rpc::ContentDescriptor`] or the
-/// inline one.
+/// inline one, along with its raw JSON.
 fn ref_or_content_descriptor<R>(
     ctx: &mut Ctx,
     cd: &rpc::RefOr<rpc::ContentDescriptor>,
-    f: impl FnOnce(&mut Ctx, &rpc::ContentDescriptor) -> R,
+    raw: Raw,
+    f: impl FnOnce(&mut Ctx, &rpc::ContentDescriptor, Raw) -> R,
 ) -> Option<R> {
     match cd {
-        rpc::RefOr::Inline(cd) => Some(f(ctx, cd)),
+        rpc::RefOr::Inline(cd) => Some(f(ctx, cd, raw)),
         rpc::RefOr::Reference { reference } => match ctx.doc.get_content_descriptor(reference) {
-            Some(cd) => Some(f(ctx, cd)),
+            Some(cd) => {
+                let raw = ctx.resolve_raw(reference);
+                Some(f(ctx, cd, raw))
+            }
             None => {
-                ctx.add_error(format!("reference `{reference}` not found"));
+                ctx.add_reference_error(reference);
                 None
             }
         },
@@ -167,24 +530,34 @@ fn parse_params(
     ctx: &mut Ctx,
     output: &mut Vec<MethodParameter>,
     params: &[rpc::RefOr<rpc::ContentDescriptor>],
+    raw: Raw,
 ) {
     ctx.push_path("params");
 
-    for param in params {
-        ref_or_content_descriptor(ctx, param, |ctx, cd| output.push(parse_param(ctx, cd)));
+    for (i, param) in params.iter().enumerate() {
+        ref_or_content_descriptor(ctx, param, raw_child(raw, i), |ctx, cd, raw| {
+            output.push(parse_param(ctx, cd, raw))
+        });
     }
 
     ctx.pop_path();
 }
 
 /// Parses a method parameter.
-fn parse_param(ctx: &mut Ctx, param: &rpc::ContentDescriptor) -> MethodParameter {
+fn parse_param(ctx: &mut Ctx, param: &rpc::ContentDescriptor, raw: Raw) -> MethodParameter {
     ctx.push_path(&param.name);
+    ctx.push_name(&param.name);
     let name_in_json = param.name.clone();
-    let name = field_name(name_in_json.clone());
+    let name = field_name(&name_in_json);
     let documentation = param.description.clone().or_else(|| param.summary.clone());
-    let ty = parse_type_ref(ctx, TypeSource::Method, &param.schema);
+    let ty = parse_descriptor_type(
+        ctx,
+        &param.schema,
+        raw_child(raw, "schema"),
+        documentation.as_deref(),
+    );
     let required = param.required;
+    ctx.pop_name();
     ctx.pop_path();
 
     MethodParameter {
@@ -197,13 +570,20 @@ fn parse_param(ctx: &mut Ctx, param: &rpc::ContentDescriptor) -> MethodParameter
 }
 
 /// Parses a [`rpc::ContentDescriptor`] into a method result.
-fn parse_method_result(ctx: &mut Ctx, result: &rpc::ContentDescriptor) -> MethodResult {
+fn parse_method_result(ctx: &mut Ctx, result: &rpc::ContentDescriptor, raw: Raw) -> MethodResult {
     ctx.push_path("result");
-    let ty = parse_type_ref(ctx, TypeSource::Method, &result.schema);
+    ctx.push_name("result");
     let documentation = result
         .description
         .clone()
         .or_else(|| result.summary.clone());
+    let ty = parse_descriptor_type(
+        ctx,
+        &result.schema,
+        raw_child(raw, "schema"),
+        documentation.as_deref(),
+    );
+    ctx.pop_name();
     ctx.pop_path();
 
     MethodResult { ty, documentation }
@@ -214,11 +594,13 @@ fn parse_schemas(
     ctx: &mut Ctx,
     output: &mut BTreeMap<Path, TypeDef>,
     schemas: &BTreeMap<String, rpc::Schema>,
+    raw: Raw,
 ) {
     ctx.push_path("schemas");
 
     for (name, schema) in schemas {
-        let ty = parse_type(ctx, Some(name), TypeSource::Declared, schema);
+        let raw = raw_child(raw, name.as_str());
+        let ty = parse_type(ctx, Some(name), TypeSource::Declared, schema, raw);
         output.insert(ty.path.clone(), ty);
     }
 
@@ -231,77 +613,326 @@ fn parse_type(
     name: Option<&str>,
     source: TypeSource,
     schema: &rpc::Schema,
+    raw: Raw,
 ) -> TypeDef {
     ctx.push_path(name.unwrap_or("_anon"));
     let path = ctx.current_path();
-    let name = name
-        .or(schema.title.as_deref())
-        .unwrap_or("Anonymous")
-        .to_case(Case::Pascal);
+    let name = match name.or(schema.title.as_deref()) {
+        Some(name) => ctx.pascal(name),
+        None => ctx.anonymous_name(),
+    };
+    // The anonymous types defined inside this one are named after it.
+    let names = std::mem::replace(&mut ctx.names, vec![name.clone()]);
     let documentation = schema.description.clone();
-    let kind = parse_type_kind(ctx, &schema.contents);
+    let external_docs = raw_child(raw_child(raw, "externalDocs"), "url")
+        .and_then(Value::as_str)
+        .map(str::to_owned);
+    let mut kind = parse_type_kind(ctx, &schema.contents, raw);
+    if let TypeKind::Alias(alias) = &mut kind {
+        alias.constraints = parse_constraints(raw, &alias.ty);
+    }
+    ctx.names = names;
     ctx.pop_path();
 
     TypeDef {
         path,
         name,
         documentation,
+        external_docs,
         source,
         kind,
     }
 }
 
 /// Parses a [`rpc::Schema`] into a [`TypeInfo`].
-fn parse_type_ref(ctx: &mut Ctx, source: TypeSource, schema: &rpc::Schema) -> TypeRef {
-    let ty = parse_type(ctx, None, source, schema);
+fn parse_type_ref(ctx: &mut Ctx, source: TypeSource, schema: &rpc::Schema, raw: Raw) -> TypeRef {
+    let ty = parse_type(ctx, None, source, schema, raw);
+    if let TypeKind::Alias(alias) = ty.kind {
+        alias.ty
+    } else {
+        let path = ty.path.clone();
+        ctx.register_type(ty);
+        TypeRef::Ref(path)
+    }
+}
+
+/// Parses the schema of a content descriptor into a [`TypeRef`].
+///
+/// A type declared inline by the descriptor is documented by the descriptor when its schema
+/// has no description of its own.
+fn parse_descriptor_type(
+    ctx: &mut Ctx,
+    schema: &rpc::Schema,
+    raw: Raw,
+    documentation: Option<&str>,
+) -> TypeRef {
+    let mut ty = parse_type(ctx, None, TypeSource::Method, schema, raw);
     if let TypeKind::Alias(alias) = ty.kind {
         alias.ty
     } else {
+        if ty.documentation.is_none() {
+            ty.documentation = documentation.map(str::to_owned);
+        }
         let path = ty.path.clone();
         ctx.register_type(ty);
         TypeRef::Ref(path)
     }
 }
 
+/// Reads the constraints of the schema `raw` that apply to `ty`.
+///
+/// The constraints of a value that may be `null` are read from the branch describing the value.
+fn parse_constraints(raw: Raw, ty: &TypeRef) -> Constraints {
+    let count = |key: &str| raw_child(raw, key).and_then(Value::as_u64);
+    let bound = |key: &str| raw_child(raw, key).and_then(Value::as_number).cloned();
+    match ty {
+        TypeRef::String => Constraints {
+            min_length: count("minLength"),
+            max_length: count("maxLength"),
+            pattern: raw_child(raw, "pattern")
+                .and_then(Value::as_str)
+                .map(Into::into),
+            ..Constraints::default()
+        },
+        TypeRef::Array(_) | TypeRef::Set(_) => Constraints {
+            min_items: count("minItems"),
+            max_items: count("maxItems"),
+            ..Constraints::default()
+        },
+        TypeRef::Number
+        | TypeRef::Integer {
+            format_as_hex: false,
+            ..
+        } => Constraints {
+            minimum: bound("minimum"),
+            maximum: bound("maximum"),
+            ..Constraints::default()
+        },
+        TypeRef::Nullable(inner) => ["oneOf", "anyOf"]
+            .iter()
+            .filter_map(|key| raw_child(raw, *key)?.as_array())
+            .flatten()
+            .find(|branch| branch.get("type").and_then(Value::as_str) != Some("null"))
+            .map(|branch| parse_constraints(Some(branch), inner))
+            .unwrap_or_default(),
+        _ => Constraints::default(),
+    }
+}
+
 /// Parses the provided [`rpc::SchemaContents`] into a [`TypeKind`].
-fn parse_type_kind(ctx: &mut Ctx, contents: &rpc::SchemaContents) -> TypeKind {
+fn parse_type_kind(ctx: &mut Ctx, contents: &rpc::SchemaContents, raw: Raw) -> TypeKind {
+    if ctx.strict {
+        check_approximations(ctx, raw);
+    }
+    if raw.is_some_and(crate::is_any_schema) {
+        return TypeKind::Alias(AliasDef {
+            ty: TypeRef::Any,
+            constraints: Constraints::default(),
+        });
+    }
+    if let Some(ty) = raw_child(raw, "const").and_then(parse_const) {
+        return TypeKind::Alias(AliasDef {
+            ty,
+            constraints: Constraints::default(),
+        });
+    }
+
+    let branches = match contents {
+        rpc::SchemaContents::OneOf { one_of } => Some((one_of, "oneOf")),
+        rpc::SchemaContents::AnyOf { any_of } => Some((any_of, "anyOf")),
+        _ => None,
+    };
+    if let Some((branches, keyword)) = branches {
+        if let Some(ty) = parse_nullable(ctx, branches, raw_child(raw, keyword)) {
+            return TypeKind::Alias(AliasDef {
+                ty,
+                constraints: Constraints::default(),
+            });
+        }
+    }
+
     match contents {
         rpc::SchemaContents::Reference { reference } => TypeKind::Alias(AliasDef {
             ty: TypeRef::Ref(Path::from(reference.as_str())),
+            constraints: Constraints::default(),
         }),
-        rpc::SchemaContents::Literal(literal) => literal_to_type_kind(ctx, literal),
-        rpc::SchemaContents::AllOf { all_of } => parse_flatten_struct(ctx, true, all_of),
-        rpc::SchemaContents::AnyOf { any_of } => parse_flatten_struct(ctx, false, any_of),
-        rpc::SchemaContents::OneOf { one_of } => parse_enum(ctx, one_of),
+        rpc::SchemaContents::Literal(literal) => literal_to_type_kind(ctx, literal, raw),
+        rpc::SchemaContents::AllOf { all_of } => {
+            let path = ctx.current_path();
+            let merged = raw
+                .filter(|_| ctx.generation.is_merged_all_of(&path))
+                .and_then(|raw| merge_all_of(ctx, raw));
+            match merged {
+                Some((literal, raw)) => object_literal_to_type_kind(ctx, &literal, Some(&raw)),
+                None => parse_flatten_struct(ctx, true, all_of, raw_child(raw, "allOf")),
+            }
+        }
+        rpc::SchemaContents::AnyOf { any_of } if ctx.generation.is_any_of_enum(&ctx.path) => {
+            let discriminator = parse_discriminator(raw);
+            parse_enum(ctx, any_of, raw_child(raw, "anyOf"), discriminator)
+        }
+        rpc::SchemaContents::AnyOf { any_of } => {
+            parse_flatten_struct(ctx, false, any_of, raw_child(raw, "anyOf"))
+        }
+        rpc::SchemaContents::OneOf { one_of } => {
+            let discriminator = parse_discriminator(raw);
+            parse_enum(ctx, one_of, raw_child(raw, "oneOf"), discriminator)
+        }
+    }
+}
+
+/// Reports the keywords of the schema that are ignored or approximated by the generated code.
+fn check_approximations(ctx: &mut Ctx, raw: Raw) {
+    let Some(raw) = raw else {
+        return;
+    };
+    if raw.get("not").is_some() {
+        ctx.add_error("the `not` keyword is not supported and is ignored");
+    }
+    if ["if", "then", "else"]
+        .iter()
+        .any(|key| raw.get(key).is_some())
+    {
+        ctx.add_error("the `if`, `then` and `else` keywords are not supported and are ignored");
+    }
+    if raw.get("prefixItems").is_some() && raw.get("items").is_some() {
+        ctx.add_error("the items following `prefixItems` are not supported and are ignored");
+    }
+    if !ctx.sets && raw.get("uniqueItems") == Some(&Value::Bool(true)) {
+        ctx.add_error("`uniqueItems` is not enforced");
+    }
+    if raw.get("additionalProperties") == Some(&Value::Bool(false)) {
+        ctx.add_error("`additionalProperties: false` is not enforced");
+    }
+    if raw
+        .get("const")
+        .is_some_and(|value| parse_const(value).is_none())
+    {
+        ctx.add_error("`const` values other than strings, booleans and numbers are ignored");
+    }
+    if raw.get("type").and_then(Value::as_str) == Some("string") {
+        if let Some(format) = raw.get("format") {
+            let mapped = format
+                .as_str()
+                .is_some_and(|format| ctx.formats.contains_key(format));
+            if !mapped && rpc::StringFormat::deserialize(format).is_err() {
+                ctx.add_error(format!(
+                    "the string format {format} is unknown, the value is generated as a `String`"
+                ));
+            }
+        }
+    }
+}
+
+/// Parses the value of a `const` keyword.
+///
+/// Strings are parsed into keywords, and only booleans and numbers are supported otherwise.
+fn parse_const(value: &Value) -> Option<TypeRef> {
+    match value {
+        Value::String(s) => Some(TypeRef::Keyword(s.clone())),
+        Value::Bool(_) | Value::Number(_) => Some(TypeRef::Constant(value.clone())),
+        _ => None,
     }
 }
 
 /// Converts a [`rpc::Literal`] into a [`TypeRef`].
-fn literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::Literal) -> TypeKind {
+fn literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::Literal, raw: Raw) -> TypeKind {
     match literal {
-        rpc::Literal::String(lit) => string_literal_to_type_kind(ctx, lit),
+        rpc::Literal::String(lit) => string_literal_to_type_kind(ctx, lit, raw),
         rpc::Literal::Boolean => TypeKind::Alias(AliasDef {
             ty: TypeRef::Boolean,
+            constraints: Constraints::default(),
         }),
-        rpc::Literal::Integer(_) => TypeKind::Alias(AliasDef {
-            ty: TypeRef::Integer {
+        rpc::Literal::Integer(lit) => {
+            let ty = TypeRef::Integer {
                 format_as_hex: false,
-            },
-        }),
+                format: raw_child(raw, "format")
+                    .and_then(Value::as_str)
+                    .map(Into::into),
+                unsigned: lit.minimum.is_some_and(|minimum| minimum >= 0),
+                maximum: raw_child(raw, "maximum").and_then(Value::as_u64),
+            };
+            match parse_integer_enum(ctx, &ty, raw) {
+                Some(kind) => kind,
+                None => TypeKind::Alias(AliasDef {
+                    ty,
+                    constraints: Constraints::default(),
+                }),
+            }
+        }
         rpc::Literal::Number(_) => TypeKind::Alias(AliasDef {
             ty: TypeRef::Number,
+            constraints: Constraints::default(),
         }),
-        rpc::Literal::Null => TypeKind::Alias(AliasDef { ty: TypeRef::Null }),
-        rpc::Literal::Array(lit) => array_literal_to_type_kind(ctx, lit),
-        rpc::Literal::Object(lit) => object_literal_to_type_kind(ctx, lit),
+        rpc::Literal::Null => TypeKind::Alias(AliasDef {
+            ty: TypeRef::Null,
+            constraints: Constraints::default(),
+        }),
+        rpc::Literal::Array(lit) => array_literal_to_type_kind(ctx, lit, raw),
+        rpc::Literal::Object(lit) => object_literal_to_type_kind(ctx, lit, raw),
+    }
+}
+
+/// Parses the integer schema `raw` into a C-like enum of integers of type `ty`, when it allows
+/// several values with `enum`.
+///
+/// The variants are named after the `x-enum-varnames` extension when it is present, and after
+/// their values otherwise.
+fn parse_integer_enum(ctx: &mut Ctx, ty: &TypeRef, raw: Raw) -> Option<TypeKind> {
+    let values = raw_child(raw, "enum")?
+        .as_array()?
+        .iter()
+        .map(|value| value.as_number().filter(|n| !n.is_f64()))
+        .collect::<Option<Vec<_>>>()?;
+    if values.len() < 2 {
+        return None;
     }
+    let names = raw_child(raw, "x-enum-varnames")
+        .and_then(Value::as_array)
+        .filter(|names| names.len() == values.len());
+
+    let mut variants = BTreeMap::new();
+    for (i, value) in values.into_iter().enumerate() {
+        let value = value.to_string();
+        let name = match names.and_then(|names| names[i].as_str()) {
+            Some(name) => ctx.pascal(name),
+            None => ctx.pascal(&format!("value_{}", value.replace('-', "minus_"))),
+        };
+        ctx.push_path(&value);
+        let path = ctx.current_path();
+        ctx.pop_path();
+        variants.insert(
+            path.clone(),
+            EnumVariant {
+                path,
+                name,
+                name_in_json: Some(value),
+                documentation: None,
+                ty: None,
+                fallback: false,
+            },
+        );
+    }
+
+    Some(TypeKind::Enum(EnumDef {
+        variants,
+        tag: EnumTag::Integer(ty.clone()),
+        discriminator: None,
+        copy: true,
+    }))
 }
 
-fn string_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::StringLiteral) -> TypeKind {
+fn string_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::StringLiteral, raw: Raw) -> TypeKind {
+    // The raw format is used, as formats unknown to `open_rpc` are stripped from the document.
+    let format = raw_child(raw, "format")
+        .and_then(Value::as_str)
+        .filter(|format| ctx.formats.contains_key(*format));
+
     if let Some(ref e) = literal.enumeration {
         if e.len() == 1 {
             TypeKind::Alias(AliasDef {
                 ty: TypeRef::Keyword(e[0].clone()),
+                constraints: Constraints::default(),
             })
         } else {
             TypeKind::Enum(EnumDef {
@@ -309,7 +940,7 @@ fn string_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::StringLiteral) -> T
                     .iter()
                     .map(|e| {
                         ctx.push_path(e);
-                        let name = e.to_case(Case::Pascal);
+                        let name = ctx.pascal(e);
                         let path = ctx.current_path();
                         let out = EnumVariant {
                             path: path.clone(),
@@ -317,6 +948,7 @@ fn string_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::StringLiteral) -> T
                             name_in_json: Some(e.clone()),
                             documentation: None,
                             ty: None,
+                            fallback: false,
                         };
                         ctx.pop_path();
 
@@ -325,44 +957,105 @@ fn string_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::StringLiteral) -> T
                     .collect(),
                 copy: true,
                 tag: EnumTag::Normal,
+                discriminator: None,
             })
         }
+    } else if let Some(format) = format {
+        TypeKind::Alias(AliasDef {
+            ty: TypeRef::Format(format.into()),
+            constraints: Constraints::default(),
+        })
     } else if literal.pattern.as_deref() == Some("^0x[a-fA-F0-9]+$") {
         TypeKind::Alias(AliasDef {
             ty: TypeRef::Integer {
                 format_as_hex: true,
+                format: None,
+                unsigned: false,
+                maximum: None,
             },
+            constraints: Constraints::default(),
+        })
+    } else if ctx.big_integers
+        && literal
+            .pattern
+            .as_deref()
+            .and_then(hex_digits)
+            .is_some_and(|digits| digits > 16)
+    {
+        TypeKind::Alias(AliasDef {
+            ty: TypeRef::BigInteger,
+            constraints: Constraints::default(),
         })
     } else {
         TypeKind::Alias(AliasDef {
             ty: TypeRef::String,
+            constraints: Constraints::default(),
         })
     }
 }
 
-/// Converts an arbitrary name to a valid Rust field name.
-fn field_name(name_in_json: String) -> String {
-    if name_in_json == "type" {
-        "ty".into()
-    } else if name_in_json.contains(char::is_uppercase) {
-        name_in_json.to_lowercase()
+/// Returns the maximum number of digits of the hexadecimal numbers matched by `pattern`, such
+/// as `^0x[a-fA-F0-9]{1,64}$`.
+///
+/// Returns `None` when the pattern matches other strings, or hexadecimal numbers of any length.
+fn hex_digits(pattern: &str) -> Option<usize> {
+    // Longer numbers are considered unbounded.
+    const MAX_DIGITS: usize = 128;
+
+    let regex = Regex::new(pattern).ok()?;
+    let hex = regex.is_match("0x1") && regex.is_match("0xabcdef");
+    if !hex || ["", "1", "0xg", "hello"].iter().any(|s| regex.is_match(s)) {
+        return None;
+    }
+    let digits = (1..=MAX_DIGITS + 1)
+        .rev()
+        .find(|n| regex.is_match(&format!("0x{}", "f".repeat(*n))))?;
+    (digits <= MAX_DIGITS).then_some(digits)
+}
+
+/// Returns the direction in which the property described by `raw` is sent.
+///
+/// Properties that are both `readOnly` and `writeOnly` are sent in both directions.
+fn parse_access(raw: Raw) -> Access {
+    let flag = |key| raw_child(raw, key) == Some(&Value::Bool(true));
+    match (flag("readOnly"), flag("writeOnly")) {
+        (true, false) => Access::ReadOnly,
+        (false, true) => Access::WriteOnly,
+        _ => Access::ReadWrite,
+    }
+}
+
+/// Converts an arbitrary name to a valid Rust field name, in snake_case.
+///
+/// Names without any letter or digit are kept as-is, and reported when the output is checked.
+fn field_name(name_in_json: &str) -> String {
+    let name = ident::snake(name_in_json);
+    if name.is_empty() {
+        name_in_json.into()
     } else {
-        name_in_json
+        name
     }
 }
 
 /// Creates a new [`TypeRef`] for the provided object literal.
-fn object_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::ObjectLiteral) -> TypeKind {
+fn object_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::ObjectLiteral, raw: Raw) -> TypeKind {
     let mut fields = BTreeMap::new();
 
+    let raw_properties = raw_child(raw, "properties");
     for (name, value) in &literal.properties {
         ctx.push_path(name);
         let path = ctx.current_path();
         let documentation = value.description.clone();
-        let ty = parse_type_ref(ctx, TypeSource::Anonymous, value);
+        let raw = raw_child(raw_properties, name.as_str());
+        ctx.push_name(name);
+        let ty = parse_type_ref(ctx, TypeSource::Anonymous, value, raw);
+        ctx.pop_name();
+        let default = raw_child(raw, "default").cloned();
+        let constraints = parse_constraints(raw, &ty);
+        let access = parse_access(raw);
         let required = literal.required.contains(name);
         let name_in_json = name.clone();
-        let name = field_name(name_in_json.clone());
+        let name = field_name(&name_in_json);
         ctx.pop_path();
 
         fields.insert(
@@ -375,6 +1068,40 @@ fn object_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::ObjectLiteral) -> T
                 required,
                 flatten: false,
                 ty,
+                serde_with: None,
+                default,
+                constraints,
+                access,
+            },
+        );
+    }
+
+    if let Some(values) = parse_additional_properties(ctx, raw) {
+        if fields.is_empty() {
+            return TypeKind::Alias(AliasDef {
+                ty: TypeRef::Map(Box::new(values)),
+                constraints: Constraints::default(),
+            });
+        }
+
+        // The properties that are not declared are collected in a flattened map.
+        ctx.push_path("additionalProperties");
+        let path = ctx.current_path();
+        ctx.pop_path();
+        fields.insert(
+            path.clone(),
+            StructField {
+                path,
+                name: "additional_properties".into(),
+                name_in_json: "additional_properties".into(),
+                documentation: None,
+                required: true,
+                flatten: true,
+                ty: TypeRef::Map(Box::new(values)),
+                serde_with: None,
+                default: None,
+                constraints: Constraints::default(),
+                access: Access::ReadWrite,
             },
         );
     }
@@ -385,25 +1112,215 @@ fn object_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::ObjectLiteral) -> T
     })
 }
 
+/// Parses the `additionalProperties` keyword of an object schema into the type of the values
+/// of the map it describes.
+///
+/// Only schemas are supported, the boolean form of the keyword is ignored.
+fn parse_additional_properties(ctx: &mut Ctx, raw: Raw) -> Option<TypeRef> {
+    let raw = raw_child(raw, "additionalProperties").filter(|raw| raw.is_object())?;
+
+    ctx.push_path("additionalProperties");
+    ctx.push_name("value");
+    let ty = match rpc::Schema::deserialize(crate::schema_for_open_rpc(raw)) {
+        Ok(schema) => Some(parse_type_ref(
+            ctx,
+            TypeSource::Anonymous,
+            &schema,
+            Some(raw),
+        )),
+        Err(err) => {
+            ctx.add_error(format!("invalid `additionalProperties` schema: {err}"));
+            None
+        }
+    };
+    ctx.pop_name();
+    ctx.pop_path();
+
+    ty
+}
+
 /// Creates a new [`TypeRef`] for the provided array literal.
-fn array_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::ArrayLiteral) -> TypeKind {
+fn array_literal_to_type_kind(ctx: &mut Ctx, literal: &rpc::ArrayLiteral, raw: Raw) -> TypeKind {
+    if let Some(prefix) = raw_child(raw, "prefixItems").and_then(Value::as_array) {
+        return TypeKind::Alias(AliasDef {
+            ty: parse_tuple(ctx, prefix),
+            constraints: Constraints::default(),
+        });
+    }
     if let Some(ref items) = literal.items {
+        let unique = raw_child(raw, "uniqueItems") == Some(&Value::Bool(true));
+        let raw = raw_child(raw, "items");
+        ctx.push_name("item");
+        let items = parse_type_ref(ctx, TypeSource::Anonymous, items, raw);
+        ctx.pop_name();
+        let ty = if unique && ctx.sets && can_be_set_item(&items) {
+            TypeRef::Set(Box::new(items))
+        } else {
+            TypeRef::Array(Box::new(items))
+        };
         TypeKind::Alias(AliasDef {
-            ty: TypeRef::Array(Box::new(parse_type_ref(ctx, TypeSource::Anonymous, items))),
+            ty,
+            constraints: Constraints::default(),
         })
     } else {
         ctx.add_error("array literals without `.items` are not supported");
         TypeKind::Alias(AliasDef {
             ty: TypeRef::Array(Box::new(TypeRef::Null)),
+            constraints: Constraints::default(),
         })
     }
 }
 
+/// Returns whether values of type `ty` can be stored in a set.
+///
+/// Floats and maps can't be ordered nor hashed, and the modules formatting integers as
+/// hexadecimal strings don't handle sets.
+fn can_be_set_item(ty: &TypeRef) -> bool {
+    !matches!(
+        ty,
+        TypeRef::Number
+            | TypeRef::Map(_)
+            | TypeRef::Any
+            | TypeRef::BigInteger
+            | TypeRef::Integer {
+                format_as_hex: true,
+                ..
+            }
+    )
+}
+
+/// Parses the `prefixItems` of an array schema into a tuple.
+///
+/// [`open_rpc`] does not know the keyword, so the schemas are deserialized from the raw JSON.
+fn parse_tuple(ctx: &mut Ctx, prefix: &[Value]) -> TypeRef {
+    let mut items = Vec::with_capacity(prefix.len());
+    for (i, raw) in prefix.iter().enumerate() {
+        ctx.push_path(&format!("item{i}"));
+        match rpc::Schema::deserialize(crate::schema_for_open_rpc(raw)) {
+            Ok(schema) => {
+                ctx.push_name(&format!("item{i}"));
+                items.push(parse_type_ref(
+                    ctx,
+                    TypeSource::Anonymous,
+                    &schema,
+                    Some(raw),
+                ));
+                ctx.pop_name();
+            }
+            Err(err) => {
+                ctx.add_error(format!("invalid schema: {err}"));
+                items.push(TypeRef::Null);
+            }
+        }
+        ctx.pop_path();
+    }
+    TypeRef::Tuple(items)
+}
+
+/// The object schema resulting from the merge of the schemas combined by `allOf`.
+#[derive(Default)]
+struct MergedObject {
+    /// The properties declared by the schemas, the last declaration of a property winning.
+    properties: serde_json::Map<String, Value>,
+    /// The properties required by any of the schemas.
+    required: Vec<String>,
+    /// The last schema declared by `additionalProperties`, if any.
+    additional_properties: Option<Value>,
+}
+
+/// Merges the object schemas combined by the `allOf` of `raw` into a single object schema.
+///
+/// Returns `None` when one of the schemas doesn't describe an object.
+fn merge_all_of(ctx: &mut Ctx, raw: &Value) -> Option<(rpc::ObjectLiteral, Value)> {
+    let mut merged = MergedObject::default();
+    merge_object_schema(ctx, raw, 0, &mut merged)?;
+
+    let mut raw = serde_json::json!({
+        "type": "object",
+        "properties": merged.properties,
+        "required": merged.required,
+    });
+    if let Some(additional) = merged.additional_properties {
+        raw["additionalProperties"] = additional;
+    }
+    match rpc::Schema::deserialize(crate::schema_for_open_rpc(&raw))
+        .ok()?
+        .contents
+    {
+        rpc::SchemaContents::Literal(rpc::Literal::Object(literal)) => Some((literal, raw)),
+        _ => None,
+    }
+}
+
+/// Adds the properties of the object schema `schema` to `merged`, following its reference
+/// and its nested `allOf`.
+fn merge_object_schema(
+    ctx: &mut Ctx,
+    schema: &Value,
+    depth: usize,
+    merged: &mut MergedObject,
+) -> Option<()> {
+    // Deeper schemas are most likely cyclic, and can't be merged anyway.
+    const MAX_DEPTH: usize = 32;
+
+    let object = schema.get("type").is_none_or(|ty| ty == "object");
+    if depth > MAX_DEPTH
+        || !object
+        || schema.get("oneOf").is_some()
+        || schema.get("anyOf").is_some()
+    {
+        return None;
+    }
+    if let Some(reference) = schema.get("$ref") {
+        let target = ctx.resolve_raw(reference.as_str()?)?;
+        merge_object_schema(ctx, target, depth + 1, merged)?;
+    }
+    for schema in schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        merge_object_schema(ctx, schema, depth + 1, merged)?;
+    }
+
+    for (name, property) in schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        let previous = merged.properties.insert(name.clone(), property.clone());
+        if ctx.strict && previous.is_some_and(|previous| previous != *property) {
+            ctx.add_error(format!(
+                "the property `{name}` is declared differently by several schemas of `allOf`, \
+                 only the last declaration is used"
+            ));
+        }
+    }
+    let required = schema.get("required").and_then(Value::as_array);
+    for name in required.into_iter().flatten().filter_map(Value::as_str) {
+        if !merged.required.iter().any(|r| r == name) {
+            merged.required.push(name.into());
+        }
+    }
+    if let Some(additional) = schema.get("additionalProperties").filter(|a| a.is_object()) {
+        merged.additional_properties = Some(additional.clone());
+    }
+    Some(())
+}
+
 /// Parses the provided list of schemas into a flatten struct.
-fn parse_flatten_struct(ctx: &mut Ctx, required: bool, schemas: &[rpc::Schema]) -> TypeKind {
+fn parse_flatten_struct(
+    ctx: &mut Ctx,
+    required: bool,
+    schemas: &[rpc::Schema],
+    raw: Raw,
+) -> TypeKind {
     if schemas.len() == 1 {
         return TypeKind::Alias(AliasDef {
-            ty: parse_type_ref(ctx, TypeSource::Anonymous, &schemas[0]),
+            ty: parse_type_ref(ctx, TypeSource::Anonymous, &schemas[0], raw_child(raw, 0)),
+            constraints: Constraints::default(),
         });
     }
 
@@ -413,10 +1330,12 @@ fn parse_flatten_struct(ctx: &mut Ctx, required: bool, schemas: &[rpc::Schema])
         ctx.push_path(&format!("field{}", i));
         let path = ctx.current_path();
         let documentation = schema.description.clone();
-        let ty = parse_type_ref(ctx, TypeSource::Anonymous, schema);
+        ctx.push_name(&format!("field{}", i));
+        let ty = parse_type_ref(ctx, TypeSource::Anonymous, schema, raw_child(raw, i));
+        ctx.pop_name();
         let name = match schema.title {
-            Some(ref title) => field_name(title.to_case(Case::Snake)),
-            None => field_name(ty.name().to_case(Case::Snake)),
+            Some(ref title) => field_name(title),
+            None => field_name(ty.name()),
         };
         let name_in_json = name.clone();
         ctx.pop_path();
@@ -431,6 +1350,10 @@ fn parse_flatten_struct(ctx: &mut Ctx, required: bool, schemas: &[rpc::Schema])
                 required,
                 flatten: true,
                 ty,
+                serde_with: None,
+                default: None,
+                constraints: Constraints::default(),
+                access: Access::ReadWrite,
             },
         );
     }
@@ -442,17 +1365,50 @@ fn parse_flatten_struct(ctx: &mut Ctx, required: bool, schemas: &[rpc::Schema])
 }
 
 /// Parses the provided list of schemas into an enum.
-fn parse_enum(ctx: &mut Ctx, schemas: &[rpc::Schema]) -> TypeKind {
+/// Parses a union of a schema and `null`, as written by `oneOf` or `anyOf`, into a nullable
+/// type.
+///
+/// Returns `None` when the union is not made of exactly one schema and `null`.
+fn parse_nullable(ctx: &mut Ctx, schemas: &[rpc::Schema], raw: Raw) -> Option<TypeRef> {
+    let is_null = |schema: &rpc::Schema| {
+        matches!(
+            schema.contents,
+            rpc::SchemaContents::Literal(rpc::Literal::Null)
+        )
+    };
+    let [a, b] = schemas else {
+        return None;
+    };
+    let (i, schema) = match (is_null(a), is_null(b)) {
+        (false, true) => (0, a),
+        (true, false) => (1, b),
+        _ => return None,
+    };
+
+    ctx.push_path(&format!("variant{i}"));
+    let ty = parse_type_ref(ctx, TypeSource::Anonymous, schema, raw_child(raw, i));
+    ctx.pop_path();
+    Some(TypeRef::Nullable(Box::new(ty)))
+}
+
+fn parse_enum(
+    ctx: &mut Ctx,
+    schemas: &[rpc::Schema],
+    raw: Raw,
+    discriminator: Option<Discriminator>,
+) -> TypeKind {
     let mut variants = BTreeMap::new();
 
     for (i, schema) in schemas.iter().enumerate() {
         ctx.push_path(&format!("variant{}", i));
         let path = ctx.current_path();
         let documentation = schema.description.clone();
-        let ty = parse_type_ref(ctx, TypeSource::Anonymous, schema);
+        ctx.push_name(&format!("variant{}", i));
+        let ty = parse_type_ref(ctx, TypeSource::Anonymous, schema, raw_child(raw, i));
+        ctx.pop_name();
         let name = match schema.title {
-            Some(ref title) => title.to_case(Case::Pascal),
-            None => ty.name().to_case(Case::Pascal),
+            Some(ref title) => ctx.pascal(title),
+            None => ctx.pascal(ty.name()),
         };
         ctx.pop_path();
 
@@ -464,13 +1420,57 @@ fn parse_enum(ctx: &mut Ctx, schemas: &[rpc::Schema]) -> TypeKind {
                 name,
                 documentation,
                 ty: Some(ty),
+                fallback: false,
             },
         );
     }
 
+    // String constants are told apart by their values, which an untagged enum can't do.
+    let mut keywords = 0;
+    for variant in variants.values_mut() {
+        if let Some(TypeRef::Keyword(keyword)) = &variant.ty {
+            variant.name_in_json = Some(keyword.clone());
+            variant.ty = None;
+            keywords += 1;
+        }
+    }
+    let copy = keywords == variants.len();
+    let tag = match keywords {
+        0 => EnumTag::Untagged,
+        _ if copy => EnumTag::Normal,
+        _ => EnumTag::Mixed,
+    };
+
     TypeKind::Enum(EnumDef {
         variants,
-        tag: EnumTag::Untagged,
-        copy: false,
+        tag,
+        discriminator,
+        copy,
+    })
+}
+
+/// Parses the `discriminator` of the schema `raw`, if any.
+///
+/// The values of `mapping` may name a schema of `components/schemas` instead of referencing it.
+fn parse_discriminator(raw: Raw) -> Option<Discriminator> {
+    let discriminator = raw_child(raw, "discriminator")?;
+    let property = discriminator.get("propertyName")?.as_str()?;
+    let mapping = discriminator
+        .get("mapping")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(value, target)| {
+            let target = target.as_str()?;
+            let path = match target.starts_with('#') {
+                true => Path::from(target),
+                false => Path::from(format!("#/components/schemas/{target}")),
+            };
+            Some((value.clone(), path))
+        })
+        .collect();
+    Some(Discriminator {
+        property: property.into(),
+        mapping,
     })
 }
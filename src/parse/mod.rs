@@ -1,35 +1,47 @@
 //! Defines the data model we want to target with our parser.
 
+mod locate;
 mod logic;
 
 use std::collections::BTreeMap;
 
 use open_rpc::ParamStructure;
+use serde::Serialize;
 
-use crate::config::Config;
+use crate::config::{Config, Pointer};
 
+pub(crate) use self::locate::locate_diagnostics;
 pub use self::logic::parse;
 
-/// An error that occurred during parsing.
-#[derive(Debug, Clone)]
-pub struct ParsingError {
-    /// The path at which the error occured.
-    pub path: Path,
-    /// A message describing the error.
-    pub message: String,
-}
-
 /// The output file we want to generate.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct File {
+    /// The metadata of the OpenRPC document.
+    pub info: Info,
     /// The list of methods defined in the OpenRPC document.
     pub methods: Vec<Method>,
     /// The list of types defined in the OpenRPC document.
     pub types: BTreeMap<Path, TypeDef>,
+    /// The list of errors defined in the OpenRPC document.
+    ///
+    /// This includes both the errors declared in `components/errors` and the ones declared
+    /// inline in methods.
+    pub errors: BTreeMap<Path, ErrorDef>,
+}
+
+/// The metadata of an OpenRPC document.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Info {
+    /// The title of the API.
+    pub title: String,
+    /// The version of the document, as opposed to the version of OpenRPC it uses.
+    pub version: String,
+    /// Some documentation about the API.
+    pub documentation: Option<String>,
 }
 
 /// An OpenRPC method.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Method {
     /// The name of the method, as defined in the OpenRPC document.
     pub name: String,
@@ -43,13 +55,64 @@ pub struct Method {
     ///
     /// If `None`, the method is intended to be used as a notification.
     pub result: Option<MethodResult>,
+    /// The paths of the errors that the method might return.
+    pub errors: Vec<Path>,
+    /// The examples of calls to the method.
+    pub examples: Vec<Example>,
+    /// The tags used to group the method with others.
+    pub tags: Vec<Tag>,
+}
+
+/// An example of a call to an OpenRPC method, pairing parameters with the expected result.
+#[derive(Debug, Clone, Serialize)]
+pub struct Example {
+    /// The name of the example.
+    pub name: String,
+    /// The values of the parameters, along with their names in JSON.
+    pub params: Vec<(String, serde_json::Value)>,
+    /// The value of the result, if provided.
+    pub result: Option<serde_json::Value>,
+}
+
+/// A tag attached to an OpenRPC method.
+#[derive(Debug, Clone, Serialize)]
+pub struct Tag {
+    /// The name of the tag.
+    pub name: String,
+    /// Some documentation about the tag.
+    pub documentation: Option<String>,
+}
+
+impl Method {
+    /// Whether the method is a notification, meaning that it does not return anything and
+    /// that no response is expected.
+    #[inline]
+    pub fn is_notification(&self) -> bool {
+        self.result.is_none()
+    }
+}
+
+/// An error that might be returned by OpenRPC methods.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorDef {
+    /// The path at which the error is defined.
+    pub path: Path,
+    /// The name of the error.
+    pub name: String,
+    /// The JSON-RPC error code.
+    pub code: i64,
+    /// The message associated with the error.
+    pub message: String,
+    /// The type of the additional data carried by the error, if the document declares a schema
+    /// for it.
+    pub data: Option<TypeRef>,
 }
 
 /// A path to a resource defined in an OpenRPC document.
 pub type Path = std::rc::Rc<str>;
 
 /// The source of type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum TypeSource {
     /// The type is used as a method parameter or result.
     Method,
@@ -60,7 +123,7 @@ pub enum TypeSource {
 }
 
 /// A reference to an existing type.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum TypeRef {
     /// A reference to an existing type defined elsewhere in the document.
     ///
@@ -76,10 +139,18 @@ pub enum TypeRef {
     ///
     /// This usually translates to `String` or `Box<str>`.
     String,
+    /// A string with a `format` mapped to a Rust type in the configuration.
+    ///
+    /// This holds the name of the format and translates to the configured type.
+    Format(String),
     /// A keyword.
     ///
     /// This is a type of string that can only take one value.
     Keyword(String),
+    /// A boolean or a number that can only take one value.
+    ///
+    /// String constants are represented as [`TypeRef::Keyword`]s instead.
+    Constant(serde_json::Value),
     /// An integer.
     ///
     /// This usually translates to `i64` or `i32`.
@@ -88,7 +159,18 @@ pub enum TypeRef {
         ///
         /// i.e. `0xDEADBEEF`
         format_as_hex: bool,
+        /// The `format` of the integer, such as `int32` or `uint64`.
+        format: Option<String>,
+        /// Whether the `minimum` of the integer is not negative.
+        unsigned: bool,
+        /// The `maximum` of the integer, when it is not negative.
+        maximum: Option<u64>,
     },
+    /// An integer too large for the configured integer type, formatted as an hexadecimal
+    /// string.
+    ///
+    /// This usually translates to a type such as `U256`.
+    BigInteger,
     /// A number.
     ///
     /// This usually translates to `f64` or `f32`.
@@ -98,10 +180,35 @@ pub enum TypeRef {
     /// This usually translates to `Vec<T>` or `Box<[T]>` and does not require a type
     /// definition.
     Array(Box<TypeRef>),
+    /// An array whose items are unique, described by `uniqueItems`.
+    ///
+    /// This usually translates to `BTreeSet<T>` or `HashSet<T>`, and is only used when a type
+    /// is configured for sets.
+    Set(Box<TypeRef>),
+    /// A map from strings to values.
+    ///
+    /// This usually translates to `BTreeMap<String, T>` and does not require a type definition.
+    Map(Box<TypeRef>),
+    /// A value stored on the heap, behind the provided pointer.
+    ///
+    /// This usually translates to `Box<T>` and is used to break the cycles of recursive types.
+    Boxed(Pointer, Box<TypeRef>),
+    /// A fixed number of values of the provided types, described by `prefixItems`.
+    ///
+    /// This usually translates to a tuple `(A, B, C)`.
+    Tuple(Vec<TypeRef>),
+    /// A value that may be `null`.
+    ///
+    /// This usually translates to `Option<T>`.
+    Nullable(Box<TypeRef>),
     /// An empty value.
     ///
     /// Usually translates to `()`.
     Null,
+    /// Any value, described by a schema without constraints such as `{}`.
+    ///
+    /// This usually translates to `serde_json::Value`.
+    Any,
 }
 
 impl TypeRef {
@@ -111,51 +218,103 @@ impl TypeRef {
             TypeRef::Ref(path) => path.rsplit('/').next().unwrap_or("value"),
             TypeRef::ExternalRef(name) => name,
             TypeRef::Boolean => "boolean",
-            TypeRef::String => "string",
+            TypeRef::String | TypeRef::Format(_) => "string",
             TypeRef::Keyword(val) => val.as_str(),
-            TypeRef::Integer { .. } => "integer",
+            TypeRef::Constant(_) => "constant",
+            TypeRef::Integer { .. } | TypeRef::BigInteger => "integer",
             TypeRef::Number => "number",
             TypeRef::Array(_) => "array",
+            TypeRef::Set(_) => "set",
+            TypeRef::Tuple(_) => "tuple",
+            TypeRef::Map(_) => "map",
+            TypeRef::Boxed(_, inner) | TypeRef::Nullable(inner) => inner.name(),
             TypeRef::Null => "null",
+            TypeRef::Any => "value",
         }
     }
 
-    /// Returns the path of the referenced type, if any.
-    pub fn inner_path(&self) -> Option<&Path> {
+    /// Returns the paths of the referenced types.
+    pub fn inner_paths(&self) -> Vec<&Path> {
         match self {
-            TypeRef::Array(inner) => inner.inner_path(),
-            TypeRef::Ref(path) => Some(path),
-            _ => None,
+            TypeRef::Array(inner)
+            | TypeRef::Set(inner)
+            | TypeRef::Map(inner)
+            | TypeRef::Boxed(_, inner)
+            | TypeRef::Nullable(inner) => inner.inner_paths(),
+            TypeRef::Tuple(items) => items.iter().flat_map(TypeRef::inner_paths).collect(),
+            TypeRef::Ref(path) => vec![path],
+            _ => Vec::new(),
         }
     }
 
-    /// A collection of attributes to add to the type.
-    pub fn attributes(&self, config: &Config, file: &File) -> Vec<String> {
+    /// Returns the module used to (de)serialize the type, if it needs one.
+    pub fn with_module<'a>(&self, config: &'a Config, file: &File) -> Option<&'a str> {
         match self {
-            TypeRef::Ref(r) => {
-                if let Some(ty) = file.types.get(r) {
-                    if let TypeKind::Alias(a) = &ty.kind {
-                        return a.ty.attributes(config, file);
-                    }
+            TypeRef::Ref(r) => match file.types.get(r).map(|ty| &ty.kind) {
+                // Newtypes apply the module to their own field.
+                Some(TypeKind::Alias(a)) if !config.generation.is_newtype(r) => {
+                    a.ty.with_module_at(config, file, r)
                 }
-            }
+                _ => None,
+            },
             TypeRef::Integer {
                 format_as_hex: true,
-            } => {
-                return vec![format!(
-                    "#[serde(with = \"{}\")]",
-                    config.formatters.num_as_hex,
-                )]
-            }
-            _ => (),
+                ..
+            } => Some(&config.formatters.num_as_hex),
+            TypeRef::BigInteger => Some(&config.formatters.big_num_as_hex),
+            // Modules handle `Option`s, as they are used for optional fields.
+            TypeRef::Nullable(inner) => inner.with_module(config, file),
+            // The generated module also handles arrays.
+            TypeRef::Array(inner) if config.generation.hex_helpers => inner
+                .with_module(config, file)
+                .filter(|module| *module == config.formatters.num_as_hex),
+            TypeRef::Format(format) => config.primitives.formats.get(format)?.with(),
+            _ => None,
         }
+    }
 
-        Vec::new()
+    /// Like [`TypeRef::with_module`], for the type of the item at `path`, whose hexadecimal
+    /// formatter may be overridden.
+    pub fn with_module_at<'a>(
+        &self,
+        config: &'a Config,
+        file: &File,
+        path: &str,
+    ) -> Option<&'a str> {
+        match config.formatters.num_as_hex_override(path) {
+            Some(module) if self.is_hex(config, file) => Some(module),
+            _ => self.with_module(config, file),
+        }
+    }
+
+    /// Returns whether the type is an integer formatted as an hexadecimal string, possibly
+    /// through aliases.
+    fn is_hex(&self, config: &Config, file: &File) -> bool {
+        match self {
+            TypeRef::Integer { format_as_hex, .. } => *format_as_hex,
+            TypeRef::BigInteger => true,
+            TypeRef::Nullable(inner) => inner.is_hex(config, file),
+            TypeRef::Ref(r) => match file.types.get(r).map(|ty| &ty.kind) {
+                Some(TypeKind::Alias(a)) if !config.generation.is_newtype(r) => {
+                    a.ty.is_hex(config, file)
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// A collection of attributes to add to the type.
+    pub fn attributes(&self, config: &Config, file: &File) -> Vec<String> {
+        self.with_module(config, file)
+            .map(|module| format!("#[serde(with = \"{module}\")]"))
+            .into_iter()
+            .collect()
     }
 }
 
 /// The result of an OpenRPC method.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MethodResult {
     /// The type of the result.
     pub ty: TypeRef,
@@ -164,7 +323,7 @@ pub struct MethodResult {
 }
 
 /// A parameter of an OpenRPC method.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MethodParameter {
     /// The name of the parameter.
     pub name: String,
@@ -181,7 +340,7 @@ pub struct MethodParameter {
 /// A type definition.
 ///
 /// A list of type definitions is provided by the OpenRPC document.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TypeDef {
     /// The path at which the type is defined.
     pub path: Path,
@@ -189,6 +348,8 @@ pub struct TypeDef {
     pub name: String,
     /// Some documentation associated with the type.
     pub documentation: Option<String>,
+    /// The URL of the external documentation of the type, declared by `externalDocs`.
+    pub external_docs: Option<String>,
     /// The source of this type.
     pub source: TypeSource,
     /// The kind of the type.
@@ -196,7 +357,7 @@ pub struct TypeDef {
 }
 
 /// The kind of a type.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum TypeKind {
     /// A struct.
     Struct(StructDef),
@@ -207,7 +368,7 @@ pub enum TypeKind {
 }
 
 /// A struct definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StructDef {
     /// A collection of tags that have been found in this struct, but that have been previously
     /// removed from the document by a fix.
@@ -219,7 +380,7 @@ pub struct StructDef {
 }
 
 /// A field of a struct.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StructField {
     /// The path of the struct field.
     pub path: Path,
@@ -238,25 +399,59 @@ pub struct StructField {
     pub flatten: bool,
     /// The type of the field.
     pub ty: TypeRef,
+    /// The module used to (de)serialize the field, overriding the attributes implied by its
+    /// type.
+    pub serde_with: Option<String>,
+    /// The value used when the field is missing, as declared by the `default` keyword.
+    pub default: Option<serde_json::Value>,
+    /// The constraints declared next to the type of the field.
+    pub constraints: Constraints,
+    /// Whether the field is only sent in one direction.
+    pub access: Access,
     /// The original name of the field, eventually required to rename the field
     /// with `#[serde(rename = "...")]`.`
     pub name_in_json: String,
 }
 
+/// The direction in which a field is sent, as declared by `readOnly` and `writeOnly`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum Access {
+    /// The field is sent in both requests and responses.
+    #[default]
+    ReadWrite,
+    /// The field is only sent in responses, declared by `readOnly`.
+    ReadOnly,
+    /// The field is only sent in requests, declared by `writeOnly`.
+    WriteOnly,
+}
+
 /// An enum definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EnumDef {
     /// The variants of the enum.
     pub variants: BTreeMap<Path, EnumVariant>,
     /// Describes how the enum is represented in JSON.
     pub tag: EnumTag,
+    /// The `discriminator` declared next to the schemas of the enum, if any.
+    pub discriminator: Option<Discriminator>,
     /// Whether the enumeration is a "simple" enum that should implement
     /// [`Copy`], [`PartialEq`], [`Eq`] and [`Hash`].
     pub copy: bool,
 }
 
+/// Names the property that tells the variants of an enum apart, as declared by a
+/// `discriminator`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Discriminator {
+    /// The name of the property in JSON, declared by `propertyName`.
+    pub property: String,
+    /// Maps the values of the property to the paths of the schemas they select, declared by
+    /// `mapping`.
+    pub mapping: BTreeMap<String, Path>,
+}
+
 /// Describes how an enum is represented in JSON.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum EnumTag {
     /// The enum is not tagged.
     ///
@@ -264,13 +459,30 @@ pub enum EnumTag {
     Untagged,
     /// The enum is tagged with a specific property.
     Tagged(String),
+    /// The enum is tagged with a specific property, and the content of the variants is held
+    /// by another property.
+    Adjacent {
+        /// The property holding the name of the variant.
+        tag: String,
+        /// The property holding the content of the variant.
+        content: String,
+    },
     /// If the enum contains content, it is tagged with object properties. Otherwise,
     /// it is tagged as a string.
     Normal,
+    /// The variants without content are tagged as strings, and the others are untagged.
+    ///
+    /// This is how a `oneOf` mixing string constants with other schemas is represented.
+    Mixed,
+    /// The enum is represented by the integer values of its variants, held by their
+    /// `name_in_json`, whose type is the provided integer.
+    ///
+    /// This usually translates to a C-like enum with a `#[repr]` attribute.
+    Integer(TypeRef),
 }
 
 /// A variant of an enum.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EnumVariant {
     /// The path of the variant.
     pub path: Path,
@@ -285,11 +497,38 @@ pub struct EnumVariant {
     pub documentation: Option<String>,
     /// The type associated with the variant, if any.
     pub ty: Option<TypeRef>,
+    /// Whether the variant catches the values that match none of the other variants.
+    ///
+    /// Such a variant is always generated last.
+    pub fallback: bool,
 }
 
 /// An alias definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AliasDef {
     /// The aliased type.
     pub ty: TypeRef,
+    /// The constraints the values of the alias must satisfy.
+    pub constraints: Constraints,
+}
+
+/// The constraints of a schema that are not enforced by its type, such as the length of strings.
+///
+/// Only the constraints that apply to the type of the schema are kept.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Constraints {
+    /// The minimum number of characters of a string, declared by `minLength`.
+    pub min_length: Option<u64>,
+    /// The maximum number of characters of a string, declared by `maxLength`.
+    pub max_length: Option<u64>,
+    /// The regular expression a string must match, declared by `pattern`.
+    pub pattern: Option<String>,
+    /// The minimum number of items of an array, declared by `minItems`.
+    pub min_items: Option<u64>,
+    /// The maximum number of items of an array, declared by `maxItems`.
+    pub max_items: Option<u64>,
+    /// The inclusive lower bound of a number, declared by `minimum`.
+    pub minimum: Option<serde_json::Number>,
+    /// The inclusive upper bound of a number, declared by `maximum`.
+    pub maximum: Option<serde_json::Number>,
 }
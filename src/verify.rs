@@ -0,0 +1,205 @@
+//! Compiles the generated code with `cargo check`, reporting the errors along with the schema
+//! they come from.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use convert_case::{Case, Casing};
+
+use crate::config::Verify;
+use crate::Error;
+
+/// An error reported by the compiler in the generated code.
+#[derive(Debug, Clone)]
+pub struct CompileError {
+    /// The message of the compiler.
+    pub message: String,
+    /// The file the error is in.
+    pub file: PathBuf,
+    /// The line the error is at, starting at 1.
+    pub line: usize,
+    /// The column the error is at, starting at 1.
+    pub column: usize,
+    /// The path of the schema the error comes from, read from the comments written when
+    /// `debug-path` is enabled.
+    pub schema_path: Option<String>,
+}
+
+/// Compiles the provided generated files, each one being copied into a module of a scratch
+/// crate named after its file.
+///
+/// The scratch crate is described by the `[verify]` section of the configuration, and lives in
+/// the temporary directory so that its dependencies are only built once. The errors are
+/// reported in the provided files.
+pub fn verify_files(config: &Verify, files: &[PathBuf]) -> Result<Vec<CompileError>, Error> {
+    let dir = std::env::temp_dir().join("openrpc-gen-verify");
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |error| Error::Io { path, error }
+    };
+
+    let mut manifest = toml::Table::new();
+    let mut package = toml::Table::new();
+    package.insert("name".into(), "openrpc-gen-verify".into());
+    package.insert("version".into(), "0.0.0".into());
+    package.insert("edition".into(), "2021".into());
+    package.insert("publish".into(), false.into());
+    manifest.insert("package".into(), package.into());
+    manifest.insert("dependencies".into(), config.dependencies.clone().into());
+    // The scratch crate must not be picked up by a workspace around the temporary directory.
+    manifest.insert("workspace".into(), toml::Table::new().into());
+    let manifest = toml::to_string(&manifest)
+        .map_err(|error| Error::Gen(io::Error::new(io::ErrorKind::InvalidData, error)))?;
+
+    let src = dir.join("src");
+    std::fs::create_dir_all(&src).map_err(io_error(&src))?;
+
+    let mut lib = String::from("#![allow(dead_code, unused_imports)]\n\n");
+    lib.push_str(&config.preamble);
+    lib.push('\n');
+    // The copies of the files, along with the file they come from and the line at which the
+    // import of the preamble was inserted.
+    let mut copies = BTreeMap::new();
+    for file in files {
+        let source = std::fs::read_to_string(file).map_err(io_error(file))?;
+        let module = file
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_case(Case::Snake);
+        // The generated file is a module of its own rather than being included, as it may
+        // start with inner doc comments. It finds the items of the preamble, such as the
+        // formatters, as if it was written at the root of the crate.
+        let (source, line) = import_preamble(&source);
+        let copy = src.join(format!("{module}.rs"));
+        std::fs::write(&copy, source).map_err(io_error(&copy))?;
+        lib.push_str(&format!("\npub mod {module};\n"));
+        copies.insert(copy, (file.clone(), line));
+    }
+
+    let manifest_path = dir.join("Cargo.toml");
+    std::fs::write(&manifest_path, manifest).map_err(io_error(&manifest_path))?;
+    let lib_path = src.join("lib.rs");
+    std::fs::write(&lib_path, lib).map_err(io_error(&lib_path))?;
+
+    let mut errors = cargo_check(config, &manifest_path, &dir.join("target"))?;
+    for error in &mut errors {
+        let Some((file, line)) = copies.get(&error.file) else {
+            continue;
+        };
+        if error.line == *line {
+            error.column = error.column.saturating_sub(IMPORT_PREAMBLE.len()).max(1);
+        }
+        error.file = file.clone();
+    }
+    Ok(errors)
+}
+
+/// The import inserted in the generated files to bring the items of the preamble in scope.
+const IMPORT_PREAMBLE: &str = "use super::*; ";
+
+/// Inserts [`IMPORT_PREAMBLE`] at the start of the first line of `source` that is not a
+/// comment or an inner attribute, returning the new source and the number of that line.
+///
+/// The import is written on an existing line so that the errors keep their line numbers.
+fn import_preamble(source: &str) -> (String, usize) {
+    let mut lines: Vec<&str> = source.lines().collect();
+    let index = lines
+        .iter()
+        .position(|line| {
+            let line = line.trim_start();
+            !line.is_empty() && !line.starts_with("//") && !line.starts_with("#!")
+        })
+        .unwrap_or(lines.len());
+    let first = format!(
+        "{IMPORT_PREAMBLE}{}",
+        lines.get(index).copied().unwrap_or_default()
+    );
+    match lines.get_mut(index) {
+        Some(line) => *line = &first,
+        None => lines.push(&first),
+    }
+    let mut source = lines.join("\n");
+    source.push('\n');
+    (source, index + 1)
+}
+
+/// Compiles the crate whose manifest is at `manifest_path`, such as one generated with
+/// `--crate`.
+pub fn verify_crate(config: &Verify, manifest_path: &Path) -> Result<Vec<CompileError>, Error> {
+    let target = std::env::temp_dir()
+        .join("openrpc-gen-verify")
+        .join("target");
+    cargo_check(config, manifest_path, &target)
+}
+
+/// Runs `cargo check` on the crate whose manifest is at `manifest_path`, and collects the
+/// errors it reports.
+fn cargo_check(
+    config: &Verify,
+    manifest_path: &Path,
+    target: &Path,
+) -> Result<Vec<CompileError>, Error> {
+    let output = Command::new("cargo")
+        .arg("check")
+        .arg("--message-format=json")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .arg("--target-dir")
+        .arg(target)
+        .args(&config.cargo_args)
+        .output()
+        .map_err(Error::Cargo)?;
+
+    let root = manifest_path.parent().unwrap_or(Path::new("."));
+    let mut sources = BTreeMap::new();
+    let mut errors = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if message["reason"] != "compiler-message" || message["message"]["level"] != "error" {
+            continue;
+        }
+        let message = &message["message"];
+        let spans = message["spans"].as_array().into_iter().flatten();
+        // Summaries such as "aborting due to previous error" have no location.
+        let Some(span) = spans.into_iter().find(|span| span["is_primary"] == true) else {
+            continue;
+        };
+
+        let file = root.join(span["file_name"].as_str().unwrap_or_default());
+        let line = span["line_start"].as_u64().unwrap_or_default() as usize;
+        let column = span["column_start"].as_u64().unwrap_or_default() as usize;
+        let source = sources
+            .entry(file.clone())
+            .or_insert_with(|| std::fs::read_to_string(&file).unwrap_or_default());
+        errors.push(CompileError {
+            message: message["message"].as_str().unwrap_or_default().into(),
+            schema_path: schema_path_at(source, line),
+            file,
+            line,
+            column,
+        });
+    }
+
+    if errors.is_empty() && !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Cargo(io::Error::other(stderr.trim().to_owned())));
+    }
+    Ok(errors)
+}
+
+/// Returns the path written in the closest `debug-path` comment at or before `line`.
+fn schema_path_at(source: &str, line: usize) -> Option<String> {
+    source
+        .lines()
+        .take(line)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix("// #/"))
+        .map(|path| format!("#/{path}"))
+}
@@ -0,0 +1,45 @@
+//! Regenerates the outputs whenever their sources change.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::command_line::CommandLineArgs;
+
+/// How often the sources are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Runs `regenerate` once, then again every time one of the sources of `cmd` is modified.
+///
+/// Sources are polled rather than watched through OS notifications, which is enough for a
+/// handful of files and works the same way on every platform. This function never returns.
+pub fn watch(cmd: &CommandLineArgs, mut regenerate: impl FnMut()) -> ! {
+    let mut last = modification_times(cmd);
+    regenerate();
+    eprintln!("watching for changes...");
+
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let current = modification_times(cmd);
+        if current == last {
+            continue;
+        }
+        last = current;
+
+        eprintln!();
+        eprintln!("change detected, regenerating...");
+        regenerate();
+        eprintln!("watching for changes...");
+    }
+}
+
+/// Returns the last modification time of every source of `cmd`.
+///
+/// Files that can't be read are reported as `None`, so that their reappearance is noticed.
+fn modification_times(cmd: &CommandLineArgs) -> Vec<(PathBuf, Option<SystemTime>)> {
+    cmd.sources()
+        .map(|path| {
+            let time = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+            (path.clone(), time)
+        })
+        .collect()
+}
@@ -0,0 +1,102 @@
+//! Checks the code generated for small documents with various configurations.
+
+use openrpc_gen::config::Config;
+use openrpc_gen::fix::Log;
+use openrpc_gen::{Document, Error, Generator};
+
+/// Generates the code of a document holding the provided schemas, with a single method
+/// returning the first of them.
+fn try_generate(config: &str, schemas: serde_json::Value) -> Result<(String, Log), Error> {
+    let first = schemas.as_object().unwrap().keys().next().unwrap().clone();
+    let document = Document::from_json(serde_json::json!({
+        "openrpc": "1.2.6",
+        "info": { "title": "Test", "version": "1.0.0" },
+        "methods": [
+            {
+                "name": "get",
+                "params": [],
+                "result": {
+                    "name": "result",
+                    "schema": { "$ref": format!("#/components/schemas/{first}") }
+                }
+            }
+        ],
+        "components": { "schemas": schemas }
+    }))
+    .unwrap();
+    let config: Config = toml::from_str(config).unwrap();
+    let mut out = Vec::new();
+    let log = Generator::new(config).generate(&document, &mut out)?;
+    Ok((String::from_utf8(out).unwrap(), log))
+}
+
+/// Generates the code of a document like [`try_generate`], expecting it to succeed.
+fn generate(config: &str, schemas: serde_json::Value) -> String {
+    try_generate(config, schemas).unwrap().0
+}
+
+#[test]
+fn debug_paths_are_written_as_comments() {
+    let code = generate(
+        "debug-path = true",
+        serde_json::json!({
+            "TXN": {
+                "type": "object",
+                "properties": { "kind": { "type": "string", "const": "A */ B /* C" } },
+                "required": ["kind"]
+            }
+        }),
+    );
+    assert!(code.contains("\n// #/components/schemas/TXN\n"), "{code}");
+    assert!(
+        code.contains("    // #/components/schemas/TXN/kind\n    pub kind: String,"),
+        "{code}"
+    );
+    assert!(!code.contains("openrpc_gen_debug_path"), "{code}");
+}
+
+#[test]
+fn pattern_newtypes_reject_unsupported_patterns() {
+    let result = try_generate(
+        "generation = { pattern-newtypes = true }",
+        serde_json::json!({
+            "NAME": { "type": "string", "pattern": "^(?!0x)[a-z]+$" }
+        }),
+    );
+    let Err(Error::Unsupported(errs)) = result else {
+        panic!("expected the pattern to be rejected");
+    };
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].path.as_deref(), Some("#/components/schemas/NAME"));
+    assert!(
+        errs[0].message.contains("look-around"),
+        "{}",
+        errs[0].message
+    );
+}
+
+#[test]
+fn validation_skips_unsupported_patterns() {
+    let (code, log) = try_generate(
+        "generation = { validation = true }",
+        serde_json::json!({
+            "BLOCK": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "pattern": "^(?!0x)[a-z]+$" },
+                    "hash": { "type": "string", "pattern": "^0x[0-9a-f]+$" }
+                },
+                "required": ["name", "hash"]
+            }
+        }),
+    )
+    .unwrap();
+    assert_eq!(log.warnings.len(), 1, "{:?}", log.warnings);
+    assert_eq!(
+        log.warnings[0].path.as_deref(),
+        Some("#/components/schemas/BLOCK/name")
+    );
+    assert!(!code.contains("(?!0x)"), "{code}");
+    assert!(code.contains("^0x[0-9a-f]+$"), "{code}");
+    assert!(!code.contains("expect("), "{code}");
+}
@@ -0,0 +1,162 @@
+//! Compiles the code generated with `no-std` in a `#![no_std]` crate.
+//!
+//! This runs `cargo check` on the generated crate, which downloads its dependencies when they
+//! are not already available.
+
+use openrpc_gen::config::Config;
+use openrpc_gen::{verify, Error, Generator};
+
+/// A document using the constructs whose generated code refers to `std` by default.
+const DOCUMENT: &str = r##"{
+    "openrpc": "1.2.6",
+    "info": { "title": "No std", "version": "1.0.0" },
+    "methods": [
+        {
+            "name": "getBlock",
+            "params": [
+                { "name": "block_id", "required": true, "schema": { "$ref": "#/components/schemas/BLOCK_ID" } },
+                { "name": "verbose", "schema": { "type": "boolean" } }
+            ],
+            "result": { "name": "result", "schema": { "$ref": "#/components/schemas/BLOCK" } },
+            "errors": [{ "$ref": "#/components/errors/BLOCK_NOT_FOUND" }]
+        }
+    ],
+    "components": {
+        "schemas": {
+            "BLOCK_ID": { "type": "string" },
+            "STATUS": { "type": "string", "enum": ["PENDING", "ACCEPTED"] },
+            "BLOCK": {
+                "type": "object",
+                "properties": {
+                    "number": { "type": "integer" },
+                    "status": { "$ref": "#/components/schemas/STATUS" },
+                    "transactions": { "type": "array", "items": { "type": "string" } },
+                    "extra": { "type": "object", "additionalProperties": { "type": "string" } }
+                },
+                "required": ["number", "status", "transactions"]
+            }
+        },
+        "errors": {
+            "BLOCK_NOT_FOUND": { "code": 24, "message": "Block not found" }
+        }
+    }
+}"##;
+
+const CONFIG: &str = r#"
+[generation]
+no-std = true
+param-types = true
+result-types = true
+error-enums = "method"
+enum-strings = true
+"#;
+
+/// Generates a `no-std` crate from `document` in the directory `name`, and returns the errors
+/// reported when compiling it.
+fn compile_crate(name: &str, document: &str, config: &str) -> Vec<verify::CompileError> {
+    let dir = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("api.json");
+    std::fs::write(&input, document).unwrap();
+
+    let config: Config = toml::from_str(config).unwrap();
+    let generator = Generator::new(config.clone());
+    let krate = dir.join("krate");
+    generator.generate_crate(&[input], &krate, false).unwrap();
+
+    let lib = std::fs::read_to_string(krate.join("src/lib.rs")).unwrap();
+    assert!(lib.contains("#![no_std]"));
+    verify::verify_crate(&config.verify, &krate.join("Cargo.toml")).unwrap()
+}
+
+#[test]
+fn no_std_output_compiles() {
+    let errors = compile_crate("openrpc-gen-no-std", DOCUMENT, CONFIG);
+    assert!(
+        errors.is_empty(),
+        "the generated crate does not compile: {errors:#?}"
+    );
+}
+
+#[test]
+fn no_std_manual_deserialize_compiles() {
+    // The implementations of untagged enums format the error of each variant.
+    let document = r##"{
+        "openrpc": "1.2.6",
+        "info": { "title": "No std", "version": "1.0.0" },
+        "methods": [
+            {
+                "name": "getTransaction",
+                "params": [],
+                "result": { "name": "result", "schema": { "$ref": "#/components/schemas/TXN" } }
+            },
+            {
+                "name": "getId",
+                "params": [],
+                "result": { "name": "result", "schema": { "$ref": "#/components/schemas/ID" } }
+            }
+        ],
+        "components": {
+            "schemas": {
+                "INVOKE": {
+                    "type": "object",
+                    "properties": { "type": { "const": "INVOKE" }, "fee": { "type": "integer" } },
+                    "required": ["type", "fee"]
+                },
+                "DEPLOY": {
+                    "type": "object",
+                    "properties": { "type": { "const": "DEPLOY" }, "hash": { "type": "string" } },
+                    "required": ["type", "hash"]
+                },
+                "TXN": {
+                    "oneOf": [
+                        { "$ref": "#/components/schemas/INVOKE" },
+                        { "$ref": "#/components/schemas/DEPLOY" }
+                    ],
+                    "discriminator": { "propertyName": "type" }
+                },
+                "ID": { "oneOf": [{ "type": "integer" }, { "type": "string" }] }
+            }
+        }
+    }"##;
+    let config = r#"
+        fixes = { auto-tag-discriminators = true }
+        generation = { no-std = true, manual-deserialize = true }
+    "#;
+
+    let errors = compile_crate("openrpc-gen-no-std-manual", document, config);
+    assert!(
+        errors.is_empty(),
+        "the generated crate does not compile: {errors:#?}"
+    );
+}
+
+#[test]
+fn no_std_rejects_pattern_newtypes() {
+    let document = openrpc_gen::Document::from_json(serde_json::json!({
+        "openrpc": "1.2.6",
+        "info": { "title": "No std", "version": "1.0.0" },
+        "methods": [
+            {
+                "name": "getName",
+                "params": [],
+                "result": { "name": "result", "schema": { "$ref": "#/components/schemas/NAME" } }
+            }
+        ],
+        "components": {
+            "schemas": {
+                "NAME": { "type": "string", "pattern": "^[a-z]+$" }
+            }
+        }
+    }))
+    .unwrap();
+    let config: Config =
+        toml::from_str("generation = { no-std = true, pattern-newtypes = true }").unwrap();
+    let result = Generator::new(config).generate(&document, &mut Vec::new());
+    let Err(Error::Unsupported(errs)) = result else {
+        panic!("expected the pattern newtype to be rejected");
+    };
+    assert_eq!(errs.len(), 1);
+    assert_eq!(errs[0].path.as_deref(), Some("#/components/schemas/NAME"));
+}
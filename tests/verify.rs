@@ -0,0 +1,262 @@
+//! Compiles generated files in the scratch crate of `--verify`.
+//!
+//! This runs `cargo check`, which downloads the dependencies of the scratch crate when they are
+//! not already available.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use openrpc_gen::config::Config;
+use openrpc_gen::verify::{self, CompileError};
+use openrpc_gen::Generator;
+
+/// Serializes the tests, which share the scratch crate.
+static SCRATCH: Mutex<()> = Mutex::new(());
+
+/// Generates the file `{name}.rs` from `document` with the provided configuration, and compiles
+/// it along with `others`.
+fn compile(
+    name: &str,
+    config: &str,
+    document: serde_json::Value,
+    others: &[PathBuf],
+) -> (PathBuf, Vec<CompileError>) {
+    let dir = std::env::temp_dir().join("openrpc-gen-verify-files");
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join(format!("{name}.json"));
+    std::fs::write(&input, document.to_string()).unwrap();
+    let output = dir.join(format!("{name}.rs"));
+
+    let config: Config = toml::from_str(config).unwrap();
+    Generator::new(config.clone())
+        .generate_file(&input, &output)
+        .unwrap();
+    let files = std::iter::once(output.clone())
+        .chain(others.iter().cloned())
+        .collect::<Vec<_>>();
+    let _guard = SCRATCH.lock().unwrap_or_else(|error| error.into_inner());
+    let errors = verify::verify_files(&config.verify, &files).unwrap();
+    (output, errors)
+}
+
+#[test]
+fn verify_files_accepts_inner_docs() {
+    let document = serde_json::json!({
+        "openrpc": "1.2.6",
+        "info": { "title": "Verify", "version": "1.0.0", "description": "A document to verify." },
+        "methods": [
+            {
+                "name": "getBlock",
+                "params": [],
+                "result": { "name": "result", "schema": { "$ref": "#/components/schemas/BLOCK" } }
+            }
+        ],
+        "components": {
+            "schemas": {
+                "HASH": { "type": "string" },
+                "BLOCK": {
+                    "type": "object",
+                    "properties": { "hash": { "$ref": "#/components/schemas/HASH" } },
+                    "required": ["hash"]
+                }
+            }
+        }
+    });
+    // Enables the inner doc comments, and relies on a type of the preamble.
+    let config = r##"
+        debug-path = true
+
+        [fixes]
+        replace = { "#/components/schemas/HASH" = "Hash" }
+
+        [generation]
+        spec-info = true
+
+        [verify]
+        preamble = "pub type Hash = String;"
+    "##;
+
+    let (output, errors) = compile("inner_docs", config, document.clone(), &[]);
+    assert!(std::fs::read_to_string(&output).unwrap().contains("//! "));
+    assert!(
+        errors.is_empty(),
+        "the generated file does not compile: {errors:#?}"
+    );
+
+    // The errors are reported in the verified file rather than in its copy.
+    let broken = output.with_file_name("broken.rs");
+    std::fs::write(&broken, "//! Broken.\n\npub const X: u8 = \"x\";\n").unwrap();
+    let (_, errors) = compile(
+        "inner_docs",
+        config,
+        document,
+        std::slice::from_ref(&broken),
+    );
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    assert_eq!(errors[0].file, broken);
+    assert_eq!((errors[0].line, errors[0].column), (3, 19));
+}
+
+#[test]
+fn default_functions_of_generic_structs_compile() {
+    let document = serde_json::json!({
+        "openrpc": "1.2.6",
+        "info": { "title": "Verify", "version": "1.0.0" },
+        "methods": [
+            {
+                "name": "getBlock",
+                "params": [],
+                "result": { "name": "result", "schema": { "$ref": "#/components/schemas/BLOCK" } }
+            }
+        ],
+        "components": {
+            "schemas": {
+                "FELT": { "type": "string" },
+                "BLOCK": {
+                    "type": "object",
+                    "properties": {
+                        "hash": { "$ref": "#/components/schemas/FELT" },
+                        "count": { "type": "integer", "default": 5 },
+                        "label": { "type": "string", "default": "none" }
+                    },
+                    "required": ["hash"]
+                }
+            }
+        }
+    });
+    let config = r##"
+        [fixes]
+        replace = { "#/components/schemas/FELT" = "F" }
+
+        [generation]
+        generics = { F = "Default" }
+    "##;
+
+    let (output, errors) = compile("generic_defaults", config, document, &[]);
+    let code = std::fs::read_to_string(&output).unwrap();
+    assert!(
+        code.contains("fn default_block_count() -> Option<i64>"),
+        "{code}"
+    );
+    assert!(
+        errors.is_empty(),
+        "the generated file does not compile: {errors:#?}"
+    );
+}
+
+#[test]
+fn builders_of_generic_structs_compile() {
+    let document = serde_json::json!({
+        "openrpc": "1.2.6",
+        "info": { "title": "Verify", "version": "1.0.0" },
+        "methods": [
+            {
+                "name": "getBlock",
+                "params": [],
+                "result": { "name": "result", "schema": { "$ref": "#/components/schemas/BLOCK" } }
+            }
+        ],
+        "components": {
+            "schemas": {
+                "FELT": { "type": "string" },
+                "BLOCK": {
+                    "type": "object",
+                    "properties": {
+                        "hash": { "$ref": "#/components/schemas/FELT" },
+                        "name": { "type": "string" },
+                        "count": { "type": "integer", "default": 5 },
+                        "parent": { "$ref": "#/components/schemas/FELT" }
+                    },
+                    "required": ["hash", "name"]
+                }
+            }
+        }
+    });
+    // The preamble uses the builder, which takes the lifetime and the parameter of the struct.
+    let config = r##"
+        [fixes]
+        replace = { "#/components/schemas/FELT" = "F" }
+
+        [generation]
+        borrowed = true
+        generics = { F = "Default" }
+        builders = ["#/components/schemas/*"]
+
+        [verify]
+        preamble = """
+        pub fn build() -> generic_builders::Block<'static, u8> {
+            generic_builders::Block::builder()
+                .name("block".into())
+                .parent(2)
+                .hash(1)
+                .build()
+        }
+        """
+    "##;
+
+    let (_, errors) = compile("generic_builders", config, document, &[]);
+    assert!(
+        errors.is_empty(),
+        "the generated file does not compile: {errors:#?}"
+    );
+}
+
+#[test]
+fn borrowed_parameters_compile() {
+    let document = serde_json::json!({
+        "openrpc": "1.2.6",
+        "info": { "title": "Verify", "version": "1.0.0" },
+        "methods": [
+            {
+                "name": "getBlock",
+                "params": [
+                    { "name": "hash", "required": true, "schema": { "type": "string" } },
+                    { "name": "tag", "schema": { "type": "string" } }
+                ],
+                "result": { "name": "result", "schema": { "type": "integer" } }
+            }
+        ]
+    });
+    let config = r##"
+        [generation]
+        borrowed = true
+        param-types = true
+    "##;
+
+    let (_, errors) = compile("borrowed_params", config, document, &[]);
+    assert!(
+        errors.is_empty(),
+        "the generated file does not compile: {errors:#?}"
+    );
+}
+
+#[test]
+fn integer_enums_with_container_options_compile() {
+    let document = serde_json::json!({
+        "openrpc": "1.2.6",
+        "info": { "title": "Verify", "version": "1.0.0" },
+        "methods": [
+            {
+                "name": "getCode",
+                "params": [],
+                "result": { "name": "result", "schema": { "$ref": "#/components/schemas/CODE" } }
+            }
+        ],
+        "components": {
+            "schemas": {
+                "CODE": { "type": "integer", "enum": [1, 2] }
+            }
+        }
+    });
+    let config = r##"
+        [generation.serde]
+        rename-all = "snake_case"
+        deny-unknown-fields = true
+    "##;
+
+    let (_, errors) = compile("integer_enums", config, document, &[]);
+    assert!(
+        errors.is_empty(),
+        "the generated file does not compile: {errors:#?}"
+    );
+}